@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::access;
+
+// Any text, however malformed, reads as a lobby access list or an error
+fuzz_target!(|text: &str| {
+    let _ = access::parse(text);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::archive::ArchiveReader;
+
+// The input is compressed first, so the fuzzer explores the archive's
+// entries rather than the zstd framing
+fuzz_target!(|bytes: &[u8]| {
+    let Ok(compressed) = zstd::encode_all(bytes, 1) else { return };
+    let Ok(reader) = ArchiveReader::from_reader(Cursor::new(compressed)) else { return };
+    for game in reader {
+        if game.is_err() {
+            break;
+        }
+    }
+});
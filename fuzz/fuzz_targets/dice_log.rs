@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::fair_dice;
+
+// Any text, however malformed, reads as a dice log or an error
+fuzz_target!(|text: &str| {
+    let _ = fair_dice::parse_log(text);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::game_record::GameRecord;
+
+// Any text, however malformed, reads as a game record or an error
+fuzz_target!(|text: &str| {
+    let _ = GameRecord::from_text(text);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::layout_file;
+
+// Any text, however malformed, reads as a board layout or an error
+fuzz_target!(|text: &str| {
+    let _ = layout_file::parse(text);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::position_code;
+
+fuzz_target!(|code: &str| {
+    // A code that decodes names a real position, which encodes back to itself
+    if let Ok(game) = position_code::decode(code) {
+        assert_eq!(position_code::decode(&position_code::encode(&game)).unwrap(), game);
+    }
+});
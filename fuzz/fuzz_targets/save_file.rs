@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::save::SavedGame;
+
+// Any text, however malformed, reads as a saved game or an error
+fuzz_target!(|text: &str| {
+    let _ = SavedGame::from_text(text);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ur_fuzz::tile_notation;
+
+// Any text, however malformed, reads as a game in tile notation or an error
+fuzz_target!(|text: &str| {
+    let _ = tile_notation::parse_game(text);
+});
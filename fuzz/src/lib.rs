@@ -0,0 +1,82 @@
+//! The game's parsers built as a library for the fuzz targets. The game is a
+//! single binary, so its modules are included here by path, with every module
+//! the parsers reach, and keep their `crate::` paths.
+
+// The game's unit tests come in with its modules and may use modules not
+// included here; they run in the game's own package
+#![cfg(not(test))]
+// Lints on a library's public API, which the game as a binary does not have
+#![allow(clippy::len_without_is_empty, clippy::new_without_default)]
+
+#[path = "../../src/access.rs"]
+pub mod access;
+#[path = "../../src/ai.rs"]
+pub mod ai;
+#[path = "../../src/ai_helpers.rs"]
+pub mod ai_helpers;
+#[path = "../../src/archive.rs"]
+pub mod archive;
+#[path = "../../src/args.rs"]
+pub mod args;
+#[path = "../../src/autotune.rs"]
+pub mod autotune;
+#[path = "../../src/board.rs"]
+pub mod board;
+#[path = "../../src/cancel.rs"]
+pub mod cancel;
+#[path = "../../src/dice.rs"]
+pub mod dice;
+#[path = "../../src/error.rs"]
+pub mod error;
+#[path = "../../src/expectimax.rs"]
+pub mod expectimax;
+#[path = "../../src/fair_dice.rs"]
+pub mod fair_dice;
+#[path = "../../src/fast_stats.rs"]
+pub mod fast_stats;
+#[path = "../../src/game_record.rs"]
+pub mod game_record;
+#[path = "../../src/history.rs"]
+pub mod history;
+#[path = "../../src/input.rs"]
+pub mod input;
+#[path = "../../src/layout_file.rs"]
+pub mod layout_file;
+#[path = "../../src/leaderboard.rs"]
+pub mod leaderboard;
+#[path = "../../src/luck.rs"]
+pub mod luck;
+#[path = "../../src/opponents.rs"]
+pub mod opponents;
+#[path = "../../src/optimized_game.rs"]
+pub mod optimized_game;
+#[path = "../../src/position_code.rs"]
+pub mod position_code;
+#[path = "../../src/presets.rs"]
+pub mod presets;
+#[path = "../../src/resources.rs"]
+pub mod resources;
+#[path = "../../src/rng.rs"]
+pub mod rng;
+#[path = "../../src/save.rs"]
+pub mod save;
+#[path = "../../src/screen.rs"]
+pub mod screen;
+#[path = "../../src/scripting.rs"]
+pub mod scripting;
+#[path = "../../src/square_stats.rs"]
+pub mod square_stats;
+#[path = "../../src/state_repr.rs"]
+pub mod state_repr;
+#[path = "../../src/stats.rs"]
+pub mod stats;
+#[path = "../../src/style.rs"]
+pub mod style;
+#[path = "../../src/tile_notation.rs"]
+pub mod tile_notation;
+#[path = "../../src/toml_file.rs"]
+pub mod toml_file;
+#[path = "../../src/transcript.rs"]
+pub mod transcript;
+#[path = "../../src/workers.rs"]
+pub mod workers;
@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+"""Example client for `ur lobby`: seeks a game, plays random legal moves and
+seeks again, until interrupted. Run the lobby, then one or more of
+    python3 scripts/lobby_client.py --name ada --time 500
+"""
+import argparse
+import json
+import secrets
+import socket
+
+parser = argparse.ArgumentParser()
+parser.add_argument("--host", default="127.0.0.1")
+parser.add_argument("--port", type=int, default=7878)
+parser.add_argument("--name")
+parser.add_argument("--token", help="the name's token, if the lobby lists it")
+parser.add_argument("--time", type=int, default=1000, help="milliseconds per move")
+options = parser.parse_args()
+
+connection = socket.create_connection((options.host, options.port))
+reader = connection.makefile("r")
+
+
+def send(message):
+    connection.sendall((json.dumps(message) + "\n").encode())
+
+
+if options.name:
+    hello = {"type": "hello", "name": options.name}
+    if options.token:
+        hello["token"] = options.token
+    send(hello)
+send({"type": "seek", "time_ms": options.time})
+
+for line in reader:
+    message = json.loads(line)
+    if message["type"] == "paired":
+        print(f"game {message['game']} against {message['opponent']}, seat {message['seat']}")
+    elif message["type"] == "move":
+        send({"move": secrets.choice(message["moves"])})
+    elif message["type"] == "chat":
+        print(f"seat {message['seat']}: {message['text']}")
+    elif message["type"] == "end":
+        print(f"winner {message['winner']}: {message['reason']}")
+        send({"type": "seek", "time_ms": options.time})
+    elif message["type"] == "error":
+        print(f"error: {message['message']}")
@@ -0,0 +1,38 @@
+#!/usr/bin/env python3
+"""Example bot for `ur match`: plays a random legal move. Run it with
+    cargo run --release -- match --p1 "python3 scripts/random_bot.py" --p2 smart
+It also takes part in fair dice (--fair-dice), committing to a fresh nonce
+before each roll and checking the other seat's nonce against its commitment,
+and can be resumed mid-game (--reconnects), as it keeps no state between moves.
+"""
+import hashlib
+import json
+import secrets
+import sys
+
+seat = None
+nonce = None
+commitments = None
+
+for line in sys.stdin:
+    message = json.loads(line)
+    if message["type"] == "start":
+        seat = message["seat"]
+    elif message["type"] == "resume":
+        seat = message["seat"]
+        history = json.dumps(message["history"], separators=(",", ":"))
+        print(json.dumps({"hash": hashlib.sha256(history.encode()).hexdigest()}), flush=True)
+    elif message["type"] == "commit":
+        nonce = secrets.token_bytes(32)
+        print(json.dumps({"commit": hashlib.sha256(nonce).hexdigest()}), flush=True)
+    elif message["type"] == "reveal":
+        commitments = message["commitments"]
+        print(json.dumps({"nonce": nonce.hex()}), flush=True)
+    elif message["type"] == "roll":
+        other = 2 - seat
+        if hashlib.sha256(bytes.fromhex(message["nonces"][other])).hexdigest() != commitments[other]:
+            print("the other seat's nonce does not match its commitment", file=sys.stderr)
+    elif message["type"] == "move":
+        print(json.dumps({"move": secrets.choice(message["moves"])}), flush=True)
+    elif message["type"] == "end":
+        break
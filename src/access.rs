@@ -0,0 +1,231 @@
+//! Who may use the lobby, and how fast: the access file passed to `ur lobby`
+//! or `ur serve` with `--access FILE`. A client listed in it proves who it is
+//! with its token in `hello`, and no one else may take its name; with
+//! `allowlist = true` only listed clients may do more than say hello. Every
+//! client may send so many move replies a second, spaced out evenly, and ask
+//! for so many analyses a minute; a listed client can be given limits of its
+//! own. Tokens are kept as their SHA-256 hashes, so the file does not give
+//! them away:
+//!
+//! ```toml
+//! allowlist = true
+//! moves_per_second = 20
+//! analyses_per_minute = 6
+//!
+//! [[client]]
+//! name = "ada"
+//! # printf %s 'the token' | sha256sum
+//! token_sha256 = "4a1f...(64 hex digits)"
+//! analyses_per_minute = 60
+//! ```
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::UrError;
+use crate::fair_dice;
+use crate::toml_file::TomlFile;
+
+pub const DEFAULT_MOVES_PER_SECOND: usize = 20;
+pub const DEFAULT_ANALYSES_PER_MINUTE: usize = 6;
+const KEYS: [&str; 4] = ["allowlist", "moves_per_second", "analyses_per_minute", "client"];
+const CLIENT_KEYS: [&str; 4] = ["name", "token_sha256", "moves_per_second", "analyses_per_minute"];
+
+/// How fast a client may go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub moves_per_second: usize,
+    pub analyses_per_minute: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { moves_per_second: DEFAULT_MOVES_PER_SECOND, analyses_per_minute: DEFAULT_ANALYSES_PER_MINUTE }
+    }
+}
+
+/// A client listed in the access file
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    token_hash: [u8; 32],
+    pub limits: Limits,
+}
+
+/// The lobby's access rules
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    /// Only listed clients may do more than say hello
+    pub allowlist: bool,
+    /// Limits for clients that are not listed, and for listed ones without their own
+    pub limits: Limits,
+    members: Vec<Member>,
+}
+
+impl Access {
+    /// The listed client called `name`, if there is one
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// The listed client called `name`, if `token` is its token
+    pub fn authenticate(&self, name: &str, token: &str) -> Option<&Member> {
+        let hash: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        self.member(name).filter(|member| member.token_hash == hash)
+    }
+
+    pub fn members(&self) -> usize {
+        self.members.len()
+    }
+}
+
+/// Read and check the access file at `path`
+pub fn load(path: &Path) -> Result<Access, UrError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Check an access file's contents
+pub fn parse(text: &str) -> Result<Access, UrError> {
+    let file = TomlFile::parse(text)?;
+    let table = &file.table;
+    if let Some(key) = table.keys().find(|key| !KEYS.contains(&key.as_str())) {
+        return Err(file.error(key, format!("unknown key '{}'", key)));
+    }
+    let allowlist = match table.get("allowlist") {
+        None => false,
+        Some(toml::Value::Boolean(allowlist)) => *allowlist,
+        Some(_) => return Err(file.error("allowlist", "'allowlist' must be true or false")),
+    };
+    let limits = limits(&file, table, "", Limits::default(), "")?;
+
+    let mut members: Vec<Member> = Vec::new();
+    let clients = match table.get("client") {
+        None => Vec::new(),
+        Some(toml::Value::Array(clients)) => clients.clone(),
+        Some(_) => return Err(file.error("client", "'client' must be a list of [[client]] tables")),
+    };
+    for (idx, client) in clients.iter().enumerate() {
+        let at = format!("client.{}", idx);
+        let client = client.as_table().ok_or_else(|| file.error(&at, format!("client {} must be a [[client]] table", idx + 1)))?;
+        if let Some(key) = client.keys().find(|key| !CLIENT_KEYS.contains(&key.as_str())) {
+            return Err(file.error(&format!("{}.{}", at, key), format!("unknown key '{}'", key)));
+        }
+        let name = client.get("name").and_then(toml::Value::as_str)
+            .ok_or_else(|| file.error(&at, format!("client {}: missing 'name'", idx + 1)))?;
+        if members.iter().any(|member| member.name == name) {
+            return Err(file.error(&format!("{}.name", at), format!("'{}' is listed twice", name)));
+        }
+        let token_hash = client.get("token_sha256").and_then(toml::Value::as_str)
+            .and_then(|hex| fair_dice::from_hex(&hex.to_ascii_lowercase()))
+            .ok_or_else(|| file.error(&format!("{}.token_sha256", at), format!("'{}' needs a token_sha256 of 64 hex digits", name)))?;
+        let limits = self::limits(&file, client, &format!("{}.", at), limits, &format!("'{}': ", name))?;
+        members.push(Member { name: name.to_string(), token_hash, limits });
+    }
+    Ok(Access { allowlist, limits, members })
+}
+
+/// The limits set in `table`, found at `path` in `file`, with the others as in `defaults`
+fn limits(file: &TomlFile, table: &toml::Table, path: &str, defaults: Limits, at: &str) -> Result<Limits, UrError> {
+    let limit = |key: &str, default: usize| match table.get(key) {
+        None => Ok(default),
+        Some(value) => value.as_integer().and_then(|n| usize::try_from(n).ok()).filter(|&n| n > 0)
+            .ok_or_else(|| file.error(&format!("{}{}", path, key), format!("{}'{}' must be a whole number above 0", at, key))),
+    };
+    Ok(Limits {
+        moves_per_second: limit("moves_per_second", defaults.moves_per_second)?,
+        analyses_per_minute: limit("analyses_per_minute", defaults.analyses_per_minute)?,
+    })
+}
+
+/// At most `limit` events in any `window`
+pub struct RateLimit {
+    limit: usize,
+    window: Duration,
+    times: VecDeque<Instant>,
+}
+
+impl RateLimit {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        RateLimit { limit, window, times: VecDeque::new() }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Change the limit, keeping the events already counted
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Count an event now, if it is within the limit
+    pub fn admit(&mut self) -> bool {
+        let now = Instant::now();
+        while self.times.front().is_some_and(|&at| now - at >= self.window) {
+            self.times.pop_front();
+        }
+        if self.times.len() >= self.limit {
+            return false;
+        }
+        self.times.push_back(now);
+        true
+    }
+
+    /// How long until an event would be within the limit
+    pub fn wait(&self) -> Duration {
+        match self.times.front() {
+            Some(&oldest) if self.times.len() >= self.limit => (oldest + self.window).saturating_duration_since(Instant::now()),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-256 of "open sesame"
+    const TOKEN_HASH: &str = "41ef4bb0b23661e66301aac36066912dac037827b4ae63a7b1165a5aa93ed4eb";
+
+    fn file(client: &str) -> String {
+        format!("allowlist = true\nanalyses_per_minute = 3\n\n[[client]]\nname = \"ada\"\ntoken_sha256 = \"{}\"\n{}", TOKEN_HASH, client)
+    }
+
+    #[test]
+    fn listed_clients_need_their_token() {
+        let access = parse(&file("")).unwrap();
+        assert!(access.allowlist);
+        assert!(access.authenticate("ada", "open sesame").is_some());
+        assert!(access.authenticate("ada", "open sesame ").is_none());
+        assert!(access.authenticate("bob", "open sesame").is_none());
+    }
+
+    #[test]
+    fn client_limits_fall_back_to_the_file_then_the_defaults() {
+        let access = parse(&file("moves_per_second = 50\n")).unwrap();
+        let ada = access.member("ada").unwrap();
+        assert_eq!(ada.limits, Limits { moves_per_second: 50, analyses_per_minute: 3 });
+        assert_eq!(access.limits, Limits { moves_per_second: DEFAULT_MOVES_PER_SECOND, analyses_per_minute: 3 });
+        let line = |text: &str| match parse(text) {
+            Err(UrError::ParseError { line, .. }) => line,
+            other => panic!("{:?} was not refused", other.map(|_| ())),
+        };
+        assert_eq!(line(&file("moves_per_second = 0\n")), 7);
+        assert_eq!(line(&file("token = \"plain\"\n")), 7);
+        assert_eq!(line(&file("[[client]]\nname = \"ada\"\n")), 8);
+        assert_eq!(line("allowlist = 1\n"), 1);
+        assert_eq!(line("\nallowlist = \n"), 2);
+    }
+
+    #[test]
+    fn rate_limit_admits_up_to_its_limit() {
+        let mut limit = RateLimit::new(2, Duration::from_secs(60));
+        assert!(limit.admit() && limit.admit());
+        assert!(!limit.admit());
+        assert!(limit.wait() > Duration::from_secs(59));
+    }
+}
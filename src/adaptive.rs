@@ -0,0 +1,64 @@
+//! Opponent that tunes its own strength to the human it is playing.
+//! It keeps an Elo estimate of the human and plays each game at a nominal
+//! rating the human should beat a little more often than not; the nominal
+//! rating maps to an MCTS budget and a rate of deliberate random moves.
+
+use crate::ai::HybridAI;
+use crate::rating::{elo_from_score, update_rating};
+
+/// Human win rate the adaptive AI aims for
+const TARGET_HUMAN_SCORE: f64 = 0.55;
+/// Rough nominal ratings of the weakest and strongest settings
+const MIN_AI_RATING: f64 = 1000.0;
+const MAX_AI_RATING: f64 = 2000.0;
+const STARTING_HUMAN_RATING: f64 = 1500.0;
+
+pub struct AdaptiveOpponent {
+    human_rating: f64,
+    games: usize,
+}
+
+impl AdaptiveOpponent {
+    pub fn new() -> Self {
+        AdaptiveOpponent {
+            human_rating: STARTING_HUMAN_RATING,
+            games: 0,
+        }
+    }
+
+    /// Nominal rating the AI plays at: just below the human so they win about TARGET_HUMAN_SCORE
+    pub fn ai_rating(&self) -> f64 {
+        (self.human_rating - elo_from_score(TARGET_HUMAN_SCORE)).clamp(MIN_AI_RATING, MAX_AI_RATING)
+    }
+
+    /// Simulations per move and blunder rate for the current nominal rating
+    pub fn settings(&self) -> (usize, f64) {
+        let strength = (self.ai_rating() - MIN_AI_RATING) / (MAX_AI_RATING - MIN_AI_RATING);
+        // Blunders fade out over the bottom half; simulations double every sixth of the range
+        let blunder_rate = (0.4 * (1.0 - strength * 2.0)).max(0.0);
+        let simulations = (100.0 * 2f64.powf(strength * 6.0)).round() as usize;
+        (simulations, blunder_rate)
+    }
+
+    /// Build the AI for the next game
+    pub fn build_ai(&self, num_threads: usize) -> HybridAI {
+        let (simulations, blunder_rate) = self.settings();
+        let mut ai = HybridAI::new_with_threads(simulations, num_threads);
+        ai.blunder_rate = blunder_rate;
+        ai
+    }
+
+    /// Update the human's rating after a game against the current settings
+    pub fn record_game(&mut self, human_won: bool) {
+        let score = if human_won { 1.0 } else { 0.0 };
+        self.human_rating = update_rating(self.human_rating, self.ai_rating(), score);
+        self.games += 1;
+    }
+
+    pub fn describe(&self) -> String {
+        let (simulations, blunder_rate) = self.settings();
+        format!("Your estimated rating: {:.0} after {} game{} | AI plays at ~{:.0} ({} simulations, {:.0}% random moves)",
+                self.human_rating, self.games, if self.games == 1 { "" } else { "s" },
+                self.ai_rating(), simulations, blunder_rate * 100.0)
+    }
+}
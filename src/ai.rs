@@ -1,10 +1,143 @@
 use std::collections::HashMap;
 use std::f64::consts::SQRT_2;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use crate::optimized_game::{FastGameState, FastPlayer};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use crate::cancel::CancelToken;
+use crate::ai_helpers::{choose_random_move_fast, choose_smart_move_fast, describe_move, order_moves};
+use crate::optimized_game::{FastGameState, FastPlayer, MoveInfo};
+use crate::state_repr::{GameStateRepr, SearchState};
+use crate::workers;
+
+/// Search effort behind a single AI move
+#[derive(Debug, Clone, Default)]
+pub struct SearchInfo {
+    /// Playouts run (MCTS) or candidate moves evaluated (heuristics)
+    pub nodes: usize,
+    /// Wall-clock time spent choosing the move
+    pub elapsed: Duration,
+    /// MCTS root statistics, most visited first; empty for other searches
+    pub candidates: Vec<CandidateMove>,
+    /// MCTS work counters; all zero for other searches
+    pub profile: SearchProfile,
+}
+
+/// Root statistics for one move considered by MCTS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CandidateMove {
+    pub piece: u8,
+    pub visits: usize,
+    pub win_rate: f64,
+}
+
+/// Work done by one MCTS search, shown with `--ai-profile`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchProfile {
+    pub playouts: usize,
+    /// Legal moves listed by move generation, at the root and in playouts
+    pub moves_generated: usize,
+    /// Moves made in playouts, counting the root move each starts with
+    pub makes: usize,
+    pub unmakes: usize,
+    /// Longest playout, in moves counting the root move
+    pub deepest: usize,
+}
+
+impl SearchProfile {
+    pub fn add(&mut self, other: &SearchProfile) {
+        self.playouts += other.playouts;
+        self.moves_generated += other.moves_generated;
+        self.makes += other.makes;
+        self.unmakes += other.unmakes;
+        self.deepest = self.deepest.max(other.deepest);
+    }
+
+    pub fn average_playout_len(&self) -> f64 {
+        self.makes as f64 / self.playouts.max(1) as f64
+    }
+
+    /// One-line summary such as "2000 playouts (avg 61.3 moves, deepest 142), 184k moves generated, 124k makes / 122k unmakes"
+    pub fn summary(&self) -> String {
+        format!("{} playouts (avg {:.1} moves, deepest {}), {:.0}k moves generated, {:.0}k makes / {:.0}k unmakes",
+                self.playouts,
+                self.average_playout_len(),
+                self.deepest,
+                self.moves_generated as f64 / 1000.0,
+                self.makes as f64 / 1000.0,
+                self.unmakes as f64 / 1000.0)
+    }
+}
+
+/// What the last MCTS search found at its root, kept by the search after
+/// `choose_move` returns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchReport {
+    /// Every legal move with its visits and estimated win rate, in the order
+    /// the moves were generated
+    pub children: Vec<CandidateMove>,
+    /// Longest playout, in moves counting the root move; 0 when there was no search
+    pub depth: usize,
+}
+
+impl SearchInfo {
+    pub fn new(nodes: usize, elapsed: Duration) -> Self {
+        SearchInfo { nodes, elapsed, candidates: Vec::new(), profile: SearchProfile::default() }
+    }
+
+    pub fn nodes_per_sec(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+
+    /// One-line summary such as "8000 nodes in 41.2 ms (194k nodes/s)"
+    pub fn summary(&self) -> String {
+        format!("{} nodes in {:.1} ms ({:.0}k nodes/s)",
+                self.nodes,
+                self.elapsed.as_secs_f64() * 1000.0,
+                self.nodes_per_sec() / 1000.0)
+    }
+}
+
+/// How far the risk preference can shift a move's win rate, per unit of volatility
+const RISK_SCALE: f64 = 0.5;
+/// With progressive widening, the nth playout of a search picks its move from
+/// the first 1 + n^WIDENING_EXPONENT root moves: 2 for the first, 3 from the
+/// 16th, all 7 from the 1296th
+const WIDENING_EXPONENT: f64 = 0.25;
+
+/// How a side picks its moves during MCTS playouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayoutPolicy {
+    /// 70% simple heuristic, 30% random; used when nothing is known about the player
+    Mixed,
+    /// Uniformly random legal moves, like the Random AI
+    Random,
+    /// The Smart AI's evaluation
+    Smart,
+}
 
-/// Optimized MCTS implementation using FastGameState with make/unmake moves
+impl PlayoutPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            PlayoutPolicy::Mixed => "mixed",
+            PlayoutPolicy::Random => "random",
+            PlayoutPolicy::Smart => "smart",
+        }
+    }
+
+    fn choose<S: GameStateRepr>(self, game_state: &S, player: FastPlayer, moves: &[u8], roll: u8, rng: &mut SmallRng) -> u8 {
+        match self {
+            PlayoutPolicy::Mixed if rng.random::<f64>() < 0.7 => {
+                // Simple heuristic: prefer moves that advance pieces furthest or finish pieces
+                MCTSAI::choose_smart_piece(game_state, player, moves, roll)
+            }
+            PlayoutPolicy::Mixed | PlayoutPolicy::Random => moves[rng.random_range(0..moves.len())],
+            PlayoutPolicy::Smart => choose_smart_move_fast(game_state, player, moves, roll),
+        }
+    }
+}
+
+/// Optimized MCTS implementation using make/unmake moves on the compile-time `SearchState` encoding
 pub struct MCTSAI {
     /// Number of simulations to run
     pub simulations: usize,
@@ -14,6 +147,61 @@ pub struct MCTSAI {
     pub max_simulation_depth: usize,
     /// Number of threads to use for parallel simulation
     pub num_threads: usize,
+    /// When set, search for this long per move instead of a fixed number of simulations
+    pub time_budget: Option<Duration>,
+    /// How the opponent is assumed to play during playouts
+    pub opponent_model: PlayoutPolicy,
+    /// Risk preference in [-1, 1]: positive seeks volatile moves when behind and avoids them
+    /// when ahead, negative does the opposite, 0 just maximizes win rate
+    pub risk: f64,
+    /// Seed for the playout dice and policies, making searches reproducible;
+    /// None draws a fresh seed for every search
+    pub seed: Option<u64>,
+    /// Split a simulation budget into this many playout streams, each seeded
+    /// from `seed` and its own number and given a fixed share of the
+    /// simulations, which the threads share out and which are merged in
+    /// order. A seeded search then finds the same move with any number of
+    /// threads; None splits the work by thread, so it only repeats with the
+    /// same number. Searches on a time budget are split by thread either way.
+    pub streams: Option<usize>,
+    /// Open the root moves to the search one by one as playouts add up, in
+    /// the order `order_moves` gives, so a small budget is spent on the
+    /// likely moves rather than spread over all of them
+    pub progressive_widening: bool,
+    /// The root statistics of the last search, for `last_report`
+    report: Mutex<Option<SearchReport>>,
+}
+
+/// The position a search starts from and when it has to stop
+struct Root<'a> {
+    state: SearchState,
+    player: FastPlayer,
+    roll: u8,
+    moves: &'a [u8],
+    /// `moves` in the order progressive widening opens them
+    ordered: Vec<u8>,
+    deadline: Option<Instant>,
+    cancel: &'a CancelToken,
+}
+
+/// Root statistics and work done by a search or one part of it
+type Searched = (HashMap<u8, MoveStats>, SearchProfile);
+
+/// What one search thread carries through its playouts
+struct Playouts {
+    /// Source of the playout dice and policy choices
+    rng: SmallRng,
+    profile: SearchProfile,
+}
+
+impl Playouts {
+    fn new(seed: Option<u64>, thread_id: usize) -> Self {
+        let rng = match seed {
+            Some(seed) => SmallRng::seed_from_u64(seed.wrapping_add(thread_id as u64)),
+            None => SmallRng::from_rng(&mut rand::rng()),
+        };
+        Playouts { rng, profile: SearchProfile::default() }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +221,17 @@ impl MoveStats {
     }
 }
 
+/// Set once a search inconsistency has been reported, so a long run reports only the first
+static REPORTED_INCONSISTENCY: AtomicBool = AtomicBool::new(false);
+
+fn missing(piece_idx: u8) -> String {
+    format!("no statistics for piece {}", piece_idx)
+}
+
+fn stats_for(move_stats: &HashMap<u8, MoveStats>, piece_idx: u8) -> Result<&MoveStats, String> {
+    move_stats.get(&piece_idx).ok_or_else(|| missing(piece_idx))
+}
+
 impl MCTSAI {
     pub fn new_with_threads(simulations: usize, exploration_constant: f64, num_threads: usize) -> Self {
         MCTSAI {
@@ -40,16 +239,59 @@ impl MCTSAI {
             exploration_constant,
             max_simulation_depth: 200,
             num_threads: num_threads.max(1),
+            time_budget: None,
+            opponent_model: PlayoutPolicy::Mixed,
+            risk: 0.0,
+            seed: None,
+            streams: None,
+            progressive_widening: false,
+            report: Mutex::new(None),
         }
     }
 
-    /// Choose the best move using optimized MCTS with make/unmake moves
-    pub fn choose_move(
+    /// The root statistics of the last move chosen, or None before the first
+    /// or after a search that went wrong. A move without alternatives reports
+    /// that move unvisited.
+    pub fn last_report(&self) -> Option<SearchReport> {
+        self.report.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    fn keep_report(&self, report: Option<SearchReport>) {
+        *self.report.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = report;
+    }
+
+    /// Whether a search that has run `done` playouts should keep going
+    fn keep_searching(done: usize, simulations: usize, deadline: Option<Instant>, cancel: &CancelToken) -> bool {
+        if cancel.is_cancelled() {
+            return false;
+        }
+        match deadline {
+            Some(deadline) => Instant::now() < deadline,
+            None => done < simulations,
+        }
+    }
+
+    /// Choose the best move using optimized MCTS with make/unmake moves,
+    /// reporting how many playouts were run and how long it took
+    pub fn choose_move_with_info(
         &self,
         game_state: &FastGameState,
         player: FastPlayer,
         roll: u8,
-    ) -> Option<u8> {
+    ) -> Option<(u8, SearchInfo)> {
+        self.choose_move_cancellable(game_state, player, roll, &CancelToken::new())
+    }
+
+    /// Like `choose_move_with_info`, but once `cancel` is cancelled the search
+    /// ends and the best move found so far is played
+    pub fn choose_move_cancellable(
+        &self,
+        game_state: &FastGameState,
+        player: FastPlayer,
+        roll: u8,
+        cancel: &CancelToken,
+    ) -> Option<(u8, SearchInfo)> {
+        let start = Instant::now();
         let moves = game_state.generate_moves(roll);
         if moves.is_empty() {
             return None;
@@ -57,153 +299,175 @@ impl MCTSAI {
 
         // For single move, just return it
         if moves.len() == 1 {
-            return Some(moves[0]);
+            let only = CandidateMove { piece: moves[0], visits: 0, win_rate: 0.0 };
+            self.keep_report(Some(SearchReport { children: vec![only], depth: 0 }));
+            return Some((moves[0], SearchInfo::new(0, start.elapsed())));
+        }
+
+        let deadline = self.time_budget.map(|budget| start + budget);
+        let mut ordered = moves.clone();
+        if self.progressive_widening {
+            order_moves(game_state, player, &mut ordered, roll);
         }
+        let root = Root { state: SearchState::from_fast(game_state), player, roll, moves: &moves, ordered, deadline, cancel };
 
         // Use multithreading for complex decisions
-        let best_piece_idx = if self.num_threads > 1 && self.simulations >= self.num_threads * 10 {
-            self.choose_move_parallel(game_state, player, roll, &moves)
-        } else {
-            self.choose_move_sequential(game_state, player, roll, &moves)
+        let worth_parallel = deadline.is_some() || self.simulations >= self.num_threads * 10;
+        let searched = match self.streams {
+            Some(streams) if deadline.is_none() => self.choose_move_streamed(&root, streams.max(1)),
+            _ if self.num_threads > 1 && worth_parallel => self.choose_move_parallel(&root),
+            _ => self.search_share(&root, self.simulations, Playouts::new(self.seed, 0)),
         };
+        let chosen = searched.and_then(|(move_stats, profile)| {
+            let nodes = move_stats.values().map(|s| s.visits).sum();
+            // A search stopped before its first playout has nothing to go on
+            let best_piece_idx = if nodes == 0 {
+                Self::choose_smart_piece(game_state, player, &moves, roll)
+            } else {
+                self.select_final_move(game_state, player, roll, &moves, &move_stats)?
+            };
+            Ok((best_piece_idx, nodes, move_stats, profile))
+        });
+        let (best_piece_idx, nodes, move_stats, mut profile) = match chosen {
+            Ok(chosen) => chosen,
+            Err(e) => {
+                // A bug in the search must not end a game or a long run
+                if !REPORTED_INCONSISTENCY.swap(true, Ordering::Relaxed) {
+                    eprintln!("⚠️  MCTS search went wrong ({}); a random move is played when it does", e);
+                }
+                self.keep_report(None);
+                return Some((choose_random_move_fast(&moves), SearchInfo::new(0, start.elapsed())));
+            }
+        };
+
+        profile.moves_generated += moves.len();
+
+        let children: Vec<CandidateMove> = moves.iter().filter_map(|&piece| {
+            let stats = move_stats.get(&piece)?;
+            let win_rate = if stats.visits > 0 { stats.wins / stats.visits as f64 } else { 0.0 };
+            Some(CandidateMove { piece, visits: stats.visits, win_rate })
+        }).collect();
+        let mut candidates = children.clone();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.visits));
+        self.keep_report(Some(SearchReport { children, depth: profile.deepest }));
 
-        Some(best_piece_idx)
+        Some((best_piece_idx, SearchInfo { nodes, elapsed: start.elapsed(), candidates, profile }))
     }
 
-    fn choose_move_parallel(
+    /// Pick the move with the best win rate, shifted by the risk preference toward
+    /// volatile moves when behind and away from them when ahead
+    fn select_final_move(
         &self,
         game_state: &FastGameState,
         player: FastPlayer,
         roll: u8,
         moves: &[u8],
-    ) -> u8 {
-        let simulations_per_thread = self.simulations / self.num_threads;
-        let extra_simulations = self.simulations % self.num_threads;
-
-        // Shared results that threads will write to
-        let combined_stats = Arc::new(Mutex::new(HashMap::<u8, MoveStats>::new()));
-
-        // Initialize combined stats
-        {
-            let mut stats = combined_stats.lock().unwrap();
-            for &piece_idx in moves {
-                stats.insert(piece_idx, MoveStats::new());
+        move_stats: &HashMap<u8, MoveStats>,
+    ) -> Result<u8, String> {
+        let mut win_rates = [0.0; 7];
+        for &piece_idx in moves {
+            let stats = stats_for(move_stats, piece_idx)?;
+            let win_rate = if stats.visits > 0 { stats.wins / stats.visits as f64 } else { 0.0 };
+            if win_rate.is_nan() {
+                return Err(format!("piece {} has a win rate of NaN", piece_idx));
             }
+            win_rates[piece_idx as usize] = win_rate;
         }
+        let win_rate = |piece_idx: u8| win_rates[piece_idx as usize];
+        let best_win_rate = moves.iter().map(|&m| win_rate(m)).fold(0.0, f64::max);
+        let lean = self.risk * (0.5 - best_win_rate) * RISK_SCALE;
 
-        let fast_state = Arc::new(*game_state);
-        let moves = Arc::new(moves.to_vec());
-
-        // Spawn worker threads
-        let mut handles = vec![];
+        let score = |piece_idx: u8| {
+            if lean == 0.0 {
+                return win_rate(piece_idx);
+            }
+            win_rate(piece_idx) + lean * describe_move(game_state, player, piece_idx, roll).volatility()
+        };
+        moves.iter().copied().max_by(|&a, &b| score(a).total_cmp(&score(b))).ok_or_else(|| "no moves to choose from".to_string())
+    }
 
-        for thread_id in 0..self.num_threads {
-            let fast_state = Arc::clone(&fast_state);
-            let moves = Arc::clone(&moves);
-            let combined_stats = Arc::clone(&combined_stats);
+    fn choose_move_parallel(&self, root: &Root<'_>) -> Result<Searched, String> {
+        let simulations_per_thread = self.simulations / self.num_threads;
+        let extra_simulations = self.simulations % self.num_threads;
 
+        // One job per worker thread; jobs that cannot get a thread run on this one
+        let jobs = (0..self.num_threads).map(|thread_id| {
             // Give some threads one extra simulation to handle remainder
-            let thread_simulations = if thread_id < extra_simulations {
-                simulations_per_thread + 1
-            } else {
-                simulations_per_thread
-            };
-
-            let exploration_constant = self.exploration_constant;
-            let max_depth = self.max_simulation_depth;
-
-            let handle = thread::spawn(move || {
-                // Run MCTS simulations for this thread
-                let mut local_stats = HashMap::<u8, MoveStats>::new();
-                for &piece_idx in moves.iter() {
-                    local_stats.insert(piece_idx, MoveStats::new());
-                }
-
-                for _ in 0..thread_simulations {
-                    // Select move using UCB1
-                    let selected_piece = Self::select_move_ucb1_static(&moves, &local_stats, exploration_constant);
-
-                    // Simulate game from this move using make/unmake
-                    let win_value = Self::simulate_move_fast(*fast_state, player, selected_piece, roll, max_depth);
-
-                    // Update local statistics
-                    let stats = local_stats.get_mut(&selected_piece).unwrap();
-                    stats.visits += 1;
-                    stats.wins += win_value;
-                }
-
-                // Merge local results into combined results
-                let mut combined = combined_stats.lock().unwrap();
-                for (piece_idx, local_stat) in local_stats {
-                    combined.get_mut(&piece_idx).unwrap().add(&local_stat);
-                }
-            });
-
-            handles.push(handle);
-        }
-
-        // Wait for all threads to complete
-        for handle in handles {
-            handle.join().unwrap();
-        }
+            let thread_simulations = simulations_per_thread + usize::from(thread_id < extra_simulations);
+            move || (thread_id, self.search_share(root, thread_simulations, Playouts::new(self.seed, thread_id)))
+        });
+        Self::merge(root.moves, workers::run_parallel("MCTS search", jobs))
+    }
 
-        // Select best move from combined results
-        let stats = combined_stats.lock().unwrap();
-        *moves.iter()
-            .max_by(|&&a, &&b| {
-                let stats_a = &stats[&a];
-                let stats_b = &stats[&b];
-                let win_rate_a = if stats_a.visits > 0 { stats_a.wins / stats_a.visits as f64 } else { 0.0 };
-                let win_rate_b = if stats_b.visits > 0 { stats_b.wins / stats_b.visits as f64 } else { 0.0 };
-                win_rate_a.partial_cmp(&win_rate_b).unwrap()
-            })
-            .unwrap()
+    /// Search `streams` fixed parts of the simulation budget, each with its own
+    /// playouts, on up to `num_threads` threads
+    fn choose_move_streamed(&self, root: &Root<'_>, streams: usize) -> Result<Searched, String> {
+        let threads = self.num_threads.min(streams);
+        let simulations_per_stream = self.simulations / streams;
+        let extra_simulations = self.simulations % streams;
+
+        // Thread t searches streams t, t + threads, ...; a stream comes out the same on any thread
+        let jobs = (0..threads).map(|first| {
+            move || {
+                (first..streams).step_by(threads).map(|stream| {
+                    let stream_simulations = simulations_per_stream + usize::from(stream < extra_simulations);
+                    (stream, self.search_share(root, stream_simulations, Playouts::new(self.seed, stream)))
+                }).collect::<Vec<_>>()
+            }
+        });
+        let searched = workers::run_parallel("MCTS search", jobs).into_iter().flatten().collect();
+        Self::merge(root.moves, searched)
     }
 
-    fn choose_move_sequential(
-        &self,
-        game_state: &FastGameState,
-        player: FastPlayer,
-        roll: u8,
-        moves: &[u8],
-    ) -> u8 {
-        // Initialize move statistics
-        let mut move_stats: HashMap<u8, MoveStats> = HashMap::new();
-        for &piece_idx in moves {
-            move_stats.insert(piece_idx, MoveStats::new());
+    /// Add up the parts of a search in the order they are numbered, so the
+    /// totals do not depend on which part finished first
+    fn merge(moves: &[u8], mut parts: Vec<(usize, Result<Searched, String>)>) -> Result<Searched, String> {
+        parts.sort_by_key(|&(idx, _)| idx);
+        let mut move_stats: HashMap<u8, MoveStats> = moves.iter().map(|&piece_idx| (piece_idx, MoveStats::new())).collect();
+        let mut profile = SearchProfile::default();
+        for (_, part) in parts {
+            let (part_stats, part_profile) = part?;
+            for (piece_idx, stats) in part_stats {
+                move_stats.get_mut(&piece_idx).ok_or_else(|| missing(piece_idx))?.add(&stats);
+            }
+            profile.add(&part_profile);
         }
+        Ok((move_stats, profile))
+    }
 
-        // Run simulations
-        for _ in 0..self.simulations {
-            // Select move using UCB1
-            let selected_piece = self.select_move_ucb1(moves, &move_stats);
+    /// Run up to `simulations` playouts from the root with statistics of their
+    /// own: a whole sequential search, or one thread's or stream's part of one
+    fn search_share(&self, root: &Root<'_>, simulations: usize, mut playouts: Playouts) -> Result<Searched, String> {
+        let mut move_stats: HashMap<u8, MoveStats> = root.moves.iter().map(|&piece_idx| (piece_idx, MoveStats::new())).collect();
+        let mut done = 0;
+        while Self::keep_searching(done, simulations, root.deadline, root.cancel) {
+            done += 1;
+
+            // Select move using UCB1, from the moves opened so far
+            let open = if self.progressive_widening {
+                1 + (done as f64).powf(WIDENING_EXPONENT) as usize
+            } else {
+                root.ordered.len()
+            };
+            let selected_piece = self.select_move_ucb1(&root.ordered[..open.min(root.ordered.len())], &move_stats)?;
 
             // Simulate game from this move using make/unmake
-            let win_value = Self::simulate_move_fast(*game_state, player, selected_piece, roll, self.max_simulation_depth);
+            let win_value = Self::simulate_move_fast(root.state, root.player, selected_piece, root.roll, self.max_simulation_depth, self.opponent_model, &mut playouts);
 
             // Update statistics
-            let stats = move_stats.get_mut(&selected_piece).unwrap();
+            let stats = move_stats.get_mut(&selected_piece).ok_or_else(|| missing(selected_piece))?;
             stats.visits += 1;
             stats.wins += win_value;
         }
 
-        // Select move with highest win rate
-        *moves.iter()
-            .max_by(|&&a, &&b| {
-                let stats_a = &move_stats[&a];
-                let stats_b = &move_stats[&b];
-                let win_rate_a = if stats_a.visits > 0 { stats_a.wins / stats_a.visits as f64 } else { 0.0 };
-                let win_rate_b = if stats_b.visits > 0 { stats_b.wins / stats_b.visits as f64 } else { 0.0 };
-                win_rate_a.partial_cmp(&win_rate_b).unwrap()
-            })
-            .unwrap()
+        Ok((move_stats, playouts.profile))
     }
 
     fn select_move_ucb1(
         &self,
         moves: &[u8],
         move_stats: &HashMap<u8, MoveStats>,
-    ) -> u8 {
+    ) -> Result<u8, String> {
         Self::select_move_ucb1_static(moves, move_stats, self.exploration_constant)
     }
 
@@ -211,20 +475,21 @@ impl MCTSAI {
         moves: &[u8],
         move_stats: &HashMap<u8, MoveStats>,
         exploration_constant: f64,
-    ) -> u8 {
+    ) -> Result<u8, String> {
         let total_visits: usize = move_stats.values().map(|s| s.visits).sum();
 
-        *moves.iter()
-            .max_by(|&&a, &&b| {
-                let stats_a = &move_stats[&a];
-                let stats_b = &move_stats[&b];
-
-                let ucb1_a = Self::calculate_ucb1_static(stats_a, total_visits, exploration_constant);
-                let ucb1_b = Self::calculate_ucb1_static(stats_b, total_visits, exploration_constant);
-
-                ucb1_a.partial_cmp(&ucb1_b).unwrap()
-            })
-            .unwrap()
+        // Ties go to the later move, as with `max_by`
+        let mut best: Option<(u8, f64)> = None;
+        for &piece_idx in moves {
+            let ucb1 = Self::calculate_ucb1_static(stats_for(move_stats, piece_idx)?, total_visits, exploration_constant);
+            if ucb1.is_nan() {
+                return Err(format!("piece {} has a UCB1 score of NaN", piece_idx));
+            }
+            if best.is_none_or(|(_, best_ucb1)| ucb1.total_cmp(&best_ucb1).is_ge()) {
+                best = Some((piece_idx, ucb1));
+            }
+        }
+        best.map(|(piece_idx, _)| piece_idx).ok_or_else(|| "no moves to choose from".to_string())
     }
 
     fn calculate_ucb1_static(stats: &MoveStats, total_visits: usize, exploration_constant: f64) -> f64 {
@@ -240,24 +505,29 @@ impl MCTSAI {
     }
 
     /// Ultra-fast simulation using make/unmake moves - NO ALLOCATIONS!
-    fn simulate_move_fast(
-        initial_state: FastGameState,
+    fn simulate_move_fast<S: GameStateRepr>(
+        initial_state: S,
         initial_player: FastPlayer,
         piece_idx: u8,
         roll: u8,
         max_depth: usize,
+        opponent_model: PlayoutPolicy,
+        playouts: &mut Playouts,
     ) -> f64 {
         let mut game_state = initial_state;
+        playouts.profile.playouts += 1;
 
         // Make the initial move
-        if let Some(_move_info) = game_state.make_move(piece_idx, roll) {
+        if let Ok(_move_info) = game_state.make_move(piece_idx, roll) {
+            playouts.profile.makes += 1;
+            playouts.profile.deepest = playouts.profile.deepest.max(1);
             // Check for immediate win
             if game_state.is_winner(initial_player) {
                 return 1.0;
             }
 
             // Simulate rest of game
-            let result = Self::simulate_game_fast(game_state, initial_player, max_depth);
+            let result = Self::simulate_game_fast(game_state, initial_player, max_depth, opponent_model, playouts);
 
             // No need to unmake the initial move since we're working with a copy
             result
@@ -266,63 +536,48 @@ impl MCTSAI {
         }
     }
 
-    fn simulate_game_fast(
-        mut game_state: FastGameState,
+    fn simulate_game_fast<S: GameStateRepr>(
+        mut game_state: S,
         initial_player: FastPlayer,
         max_depth: usize,
+        opponent_model: PlayoutPolicy,
+        playouts: &mut Playouts,
     ) -> f64 {
         let mut moves_stack = Vec::with_capacity(max_depth);
+        // Counted locally and recorded once, keeping the profile off the hot path
+        let mut generated = 0;
 
         for _ in 0..max_depth {
             let current_player = game_state.current_player();
 
             // Check for terminal state
-            if game_state.is_winner(FastPlayer::One) {
-                // Unmake all moves in reverse order
-                for (player, move_info) in moves_stack.into_iter().rev() {
-                    game_state.unmake_move(player, &move_info);
-                }
-                return if initial_player == FastPlayer::One { 1.0 } else { 0.0 };
-            }
-            if game_state.is_winner(FastPlayer::Two) {
-                // Unmake all moves in reverse order
-                for (player, move_info) in moves_stack.into_iter().rev() {
-                    game_state.unmake_move(player, &move_info);
-                }
-                return if initial_player == FastPlayer::Two { 1.0 } else { 0.0 };
+            if let Some(winner) = game_state.status().winner() {
+                Self::unmake_all(&mut game_state, moves_stack, generated, &mut playouts.profile);
+                return if initial_player == winner { 1.0 } else { 0.0 };
             }
 
-            let sim_roll = FastGameState::roll_dice();
+            let sim_roll = FastGameState::roll_dice_with(current_player, &mut playouts.rng);
             if sim_roll == 0 {
                 continue; // Game handles turn switching internally
             }
 
-            let sim_moves = game_state.generate_moves(sim_roll);
+            let sim_moves = game_state.generate_move_list(sim_roll);
+            generated += sim_moves.len();
             if sim_moves.is_empty() {
                 continue; // Game handles turn switching internally
             }
 
-            // Choose move (70% smart-ish, 30% random for variety)
-             let chosen_piece = if rand::random::<f64>() < 0.7 {
-                 // Simple heuristic: prefer moves that advance pieces furthest or finish pieces
-                 Self::choose_smart_piece(&game_state, current_player, &sim_moves, sim_roll)
-             } else {
-                 // Random move
-                 use rand::Rng;
-                 let mut rng = rand::rng();
-                 sim_moves[rng.random_range(0..sim_moves.len())]
-             };
+            // Our own moves use the mixed policy; the opponent's follow its model
+            let policy = if current_player == initial_player { PlayoutPolicy::Mixed } else { opponent_model };
+            let chosen_piece = policy.choose(&game_state, current_player, &sim_moves, sim_roll, &mut playouts.rng);
 
             // Make move
-            if let Some(move_info) = game_state.make_move(chosen_piece, sim_roll) {
+            if let Ok(move_info) = game_state.make_move(chosen_piece, sim_roll) {
                 moves_stack.push((current_player, move_info));
 
                 // Check for win after move
                 if game_state.is_winner(current_player) {
-                    // Unmake all moves in reverse order
-                    for (player, move_info) in moves_stack.into_iter().rev() {
-                        game_state.unmake_move(player, &move_info);
-                    }
+                    Self::unmake_all(&mut game_state, moves_stack, generated, &mut playouts.profile);
                     return if initial_player == current_player { 1.0 } else { 0.0 };
                 }
             } else {
@@ -330,10 +585,7 @@ impl MCTSAI {
             }
         }
 
-        // Unmake all moves in reverse order
-        for (player, move_info) in moves_stack.into_iter().rev() {
-            game_state.unmake_move(player, &move_info);
-        }
+        Self::unmake_all(&mut game_state, moves_stack, generated, &mut playouts.profile);
 
         // Evaluate final position based on progress
         let our_score = game_state.get_score(initial_player) as f64;
@@ -342,8 +594,24 @@ impl MCTSAI {
         ((our_score + (7.0 - opp_score)) / 14.0).clamp(0.0, 1.0)
     }
 
+    /// Unmake all playout moves in reverse order, recording the playout's work
+    fn unmake_all<S: GameStateRepr>(
+        game_state: &mut S,
+        moves_stack: Vec<(FastPlayer, MoveInfo)>,
+        generated: usize,
+        profile: &mut SearchProfile,
+    ) {
+        profile.moves_generated += generated;
+        profile.makes += moves_stack.len();
+        profile.unmakes += moves_stack.len();
+        profile.deepest = profile.deepest.max(moves_stack.len() + 1);
+        for (player, move_info) in moves_stack.into_iter().rev() {
+            game_state.unmake_move(player, &move_info);
+        }
+    }
+
     /// Simple heuristic for choosing good moves during simulation
-    pub fn choose_smart_piece(game_state: &FastGameState, player: FastPlayer, moves: &[u8], roll: u8) -> u8 {
+    pub fn choose_smart_piece<S: GameStateRepr>(game_state: &S, player: FastPlayer, moves: &[u8], roll: u8) -> u8 {
         let mut best_piece = moves[0];
         let mut best_score = f64::NEG_INFINITY;
 
@@ -353,11 +621,11 @@ impl MCTSAI {
 
             match pos {
                 0 => score = 10.0, // Entering is good
-                1..=14 => {
+                _ if FastGameState::is_on_board(pos) => {
                     let path_idx = pos - 1;
                     let new_path_idx = path_idx + roll;
 
-                    if new_path_idx >= 14 {
+                    if new_path_idx >= FastGameState::path_len() {
                         score = 50.0; // Finishing is excellent
                     } else {
                         score = new_path_idx as f64; // Advancing is good
@@ -369,10 +637,9 @@ impl MCTSAI {
                         }
 
                         // Check for captures
-                        if let Some(occupant) = game_state.get_occupant(target_square) {
-                            if occupant != player && !FastGameState::is_safe(target_square) {
-                                score += 8.0; // Captures are very good
-                            }
+                        if let Some(occupant) = game_state.get_occupant(target_square)
+                            && occupant != player && !FastGameState::is_safe(target_square) {
+                            score += 8.0; // Captures are very good
                         }
                     }
                 }
@@ -390,6 +657,9 @@ impl MCTSAI {
 
     /// Get information about the threading configuration
     pub fn get_thread_info(&self) -> String {
+        if let Some(budget) = self.time_budget {
+            return format!("FastMCTS: {} threads, {} ms per move", self.num_threads, budget.as_millis());
+        }
         format!("FastMCTS: {} threads, {} simulations ({} per thread)",
                 self.num_threads,
                 self.simulations,
@@ -401,6 +671,10 @@ impl MCTSAI {
 pub struct HybridAI {
     pub mcts: MCTSAI,
     pub use_mcts_threshold: usize, // Use MCTS only if there are this many or more moves
+    /// Chance of playing a random legal move instead of searching (used to weaken the AI on purpose)
+    pub blunder_rate: f64,
+    /// Preset the AI was built from; used as its name in output
+    pub name: Option<String>,
 }
 
 impl HybridAI {
@@ -408,39 +682,287 @@ impl HybridAI {
         HybridAI {
             mcts: MCTSAI::new_with_threads(mcts_simulations, SQRT_2, num_threads),
             use_mcts_threshold: 2,
+            blunder_rate: 0.0,
+            name: None,
         }
     }
 
-    /// Choose the best move using hybrid approach
-    pub fn choose_move(
+    /// Set the risk preference, clamped to [-1, 1]
+    pub fn with_risk(mut self, risk: f64) -> Self {
+        self.mcts.risk = risk.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Assume the opponent plays like `model` during playouts
+    pub fn with_opponent_model(mut self, model: PlayoutPolicy) -> Self {
+        self.mcts.opponent_model = model;
+        self
+    }
+
+    /// MCTS that searches for `budget` per move rather than a fixed simulation count
+    pub fn new_with_time_budget(budget: Duration, num_threads: usize) -> Self {
+        let mut ai = Self::new_with_threads(0, num_threads);
+        ai.mcts.time_budget = Some(budget);
+        ai
+    }
+
+    /// Choose the best move using hybrid approach, reporting the search effort
+    /// behind it. A search in progress ends once `cancel` is cancelled and plays
+    /// the best move found so far.
+    pub fn choose_move_cancellable(
         &self,
         game_state: &FastGameState,
         player: FastPlayer,
         roll: u8,
-    ) -> Option<u8> {
+        cancel: &CancelToken,
+    ) -> Option<(u8, SearchInfo)> {
+        let start = Instant::now();
         let moves = game_state.generate_moves(roll);
         if moves.is_empty() {
             return None;
         }
 
+        // Moves chosen without a search leave no report, rather than the last one
         if moves.len() == 1 {
-            return Some(moves[0]);
+            self.mcts.keep_report(None);
+            return Some((moves[0], SearchInfo::new(0, start.elapsed())));
+        }
+
+        if self.blunder_rate > 0.0 && rand::random::<f64>() < self.blunder_rate {
+            self.mcts.keep_report(None);
+            let piece_idx = moves[rand::rng().random_range(0..moves.len())];
+            return Some((piece_idx, SearchInfo::new(0, start.elapsed())));
         }
 
         if moves.len() >= self.use_mcts_threshold {
             // Use optimized MCTS for complex decisions
-            self.mcts.choose_move(game_state, player, roll)
+            self.mcts.choose_move_cancellable(game_state, player, roll, cancel)
         } else {
             // Use simple depth-1 evaluation for simple decisions
-            Some(MCTSAI::choose_smart_piece(game_state, player, &moves, roll))
+            self.mcts.keep_report(None);
+            let piece_idx = MCTSAI::choose_smart_piece(game_state, player, &moves, roll);
+            Some((piece_idx, SearchInfo::new(moves.len(), start.elapsed())))
         }
     }
 
+    /// Short name for this configuration: the preset name, or e.g. "mcts-800-risk+0.50"
+    /// with settings at their defaults left out
+    pub fn config_label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let mut label = match self.mcts.time_budget {
+            Some(budget) => format!("mcts-{}ms", budget.as_millis()),
+            None => format!("mcts-{}", self.mcts.simulations),
+        };
+        if self.mcts.exploration_constant != SQRT_2 {
+            label.push_str(&format!("-c{:.3}", self.mcts.exploration_constant));
+        }
+        if self.mcts.risk != 0.0 {
+            label.push_str(&format!("-risk{:+.2}", self.mcts.risk));
+        }
+        if self.mcts.opponent_model != PlayoutPolicy::Mixed {
+            label.push_str(&format!("-model-{}", self.mcts.opponent_model.label()));
+        }
+        if self.mcts.progressive_widening {
+            label.push_str("-widening");
+        }
+        if self.blunder_rate > 0.0 {
+            label.push_str(&format!("-blunder{:.2}", self.blunder_rate));
+        }
+        label
+    }
+
+    /// The AI a label in the form `config_label` writes names, searching on
+    /// `num_threads` threads; the inverse of `config_label` for AIs without a preset
+    pub fn from_label(label: &str, num_threads: usize) -> Result<Self, String> {
+        let settings = label.strip_prefix("mcts-").ok_or_else(|| format!("'{}' is not an MCTS label", label))?;
+        let mut words = settings.split('-');
+        let budget = words.next().unwrap_or_default();
+        let mut ai = match budget.strip_suffix("ms") {
+            Some(ms) => ms.parse().map(|ms| Self::new_with_time_budget(Duration::from_millis(ms), num_threads)),
+            None => budget.parse().map(|simulations| Self::new_with_threads(simulations, num_threads)),
+        }.map_err(|_| format!("'{}' is not a budget such as 5000 or 250ms", budget))?;
+
+        let number = |text: &str| text.parse::<f64>().ok().filter(|n| n.is_finite())
+            .ok_or_else(|| format!("'{}' is not a number in '{}'", text, label));
+        while let Some(word) = words.next() {
+            // A negative value is split off its name by the dash before it
+            let mut value = |prefix: &str| match &word[prefix.len()..] {
+                "" => number(&format!("-{}", words.next().unwrap_or_default())),
+                value => number(value),
+            };
+            match word {
+                "widening" => ai.mcts.progressive_widening = true,
+                "model" => {
+                    ai.mcts.opponent_model = match words.next() {
+                        Some("mixed") => PlayoutPolicy::Mixed,
+                        Some("random") => PlayoutPolicy::Random,
+                        Some("smart") => PlayoutPolicy::Smart,
+                        _ => return Err(format!("'{}' needs model-random, model-smart or model-mixed", label)),
+                    }
+                }
+                risk if risk.starts_with("risk") => ai.mcts.risk = value("risk")?.clamp(-1.0, 1.0),
+                blunder if blunder.starts_with("blunder") => ai.blunder_rate = value("blunder")?.clamp(0.0, 1.0),
+                c if c.starts_with('c') => ai.mcts.exploration_constant = value("c")?,
+                _ => return Err(format!("unknown MCTS setting '{}' in '{}'", word, label)),
+            }
+        }
+        Ok(ai)
+    }
+
     /// Get information about the MCTS configuration
     pub fn get_info(&self) -> String {
-        format!("HybridAI: {}, MCTS threshold: {} moves",
+        let preset = self.name.as_ref().map(|name| format!("preset {}, ", name)).unwrap_or_default();
+        format!("HybridAI: {}{}, MCTS threshold: {} moves",
+                preset,
                 self.mcts.get_thread_info(),
                 self.use_mcts_threshold)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::GameRng;
+
+    /// Simulations per MCTS move in the strength check
+    const STRENGTH_SIMULATIONS: usize = 400;
+    /// Dice seeds in the strength check; each is played with the MCTS AI on either side
+    const STRENGTH_SEEDS: u64 = 100;
+    /// Lowest share of the games the MCTS AI may win, in percent. It won 82%
+    /// when this was set; over 200 games that is about four standard deviations
+    /// of slack.
+    const MIN_STRENGTH_WIN_PERCENT: f64 = 70.0;
+
+    /// Positions with several moves to choose from, from a seeded Smart AI game
+    fn decisions() -> Vec<(FastGameState, u8)> {
+        let mut dice = GameRng::from_seed(11);
+        let mut game = FastGameState::new();
+        let mut decisions = Vec::new();
+        while decisions.len() < 6 && !game.status().is_over() {
+            let roll = dice.roll_dice(game.current_player());
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            if moves.len() > 2 {
+                decisions.push((game, roll));
+            }
+            game.make_move(choose_smart_move_fast(&game, game.current_player(), &moves, roll), roll).expect("generated moves are legal");
+        }
+        decisions
+    }
+
+    /// The move and root statistics a search finds in each position
+    fn search(engine: &MCTSAI) -> Vec<(u8, Vec<CandidateMove>)> {
+        decisions().iter().map(|(game, roll)| {
+            let (piece, info) = engine.choose_move_with_info(game, game.current_player(), *roll).expect("there are moves");
+            (piece, info.candidates)
+        }).collect()
+    }
+
+    fn seeded(threads: usize, streams: Option<usize>) -> MCTSAI {
+        let mut engine = MCTSAI::new_with_threads(400, SQRT_2, threads);
+        engine.seed = Some(42);
+        engine.streams = streams;
+        engine
+    }
+
+    #[test]
+    fn seeded_parallel_search_repeats() {
+        assert_eq!(search(&seeded(3, None)), search(&seeded(3, None)));
+    }
+
+    #[test]
+    fn search_reports_its_root_after_returning() {
+        let engine = seeded(2, Some(4));
+        assert_eq!(engine.last_report(), None);
+        let (game, roll) = decisions()[0];
+        let (piece, info) = engine.choose_move_with_info(&game, game.current_player(), roll).expect("there are moves");
+
+        let report = engine.last_report().expect("a search was made");
+        assert_eq!(report.children.iter().map(|child| child.piece).collect::<Vec<_>>(), game.generate_moves(roll));
+        assert_eq!(report.children.iter().map(|child| child.visits).sum::<usize>(), 400);
+        assert!(report.children.iter().any(|child| child.piece == piece && child.visits > 0));
+        assert!(report.depth > 1 && report.depth == info.profile.deepest);
+    }
+
+    #[test]
+    fn widening_opens_the_ordered_moves_in_turn() {
+        let mut engine = seeded(1, None);
+        engine.simulations = 15;
+        engine.progressive_widening = true;
+        for (game, roll) in decisions() {
+            let player = game.current_player();
+            let mut ordered = game.generate_moves(roll);
+            order_moves(&game, player, &mut ordered, roll);
+            engine.choose_move_with_info(&game, player, roll).expect("there are moves");
+            // Fifteen playouts open only the first two
+            let report = engine.last_report().expect("a search was made");
+            let visited: Vec<u8> = ordered.iter().copied().filter(|&piece| report.children.iter().any(|c| c.piece == piece && c.visits > 0)).collect();
+            assert_eq!(visited, ordered[..2]);
+        }
+    }
+
+    #[test]
+    fn streamed_search_is_the_same_with_any_thread_count() {
+        let single = search(&seeded(1, Some(6)));
+        for threads in [2, 3, 4, 7] {
+            assert_eq!(search(&seeded(threads, Some(6))), single, "{} threads", threads);
+        }
+    }
+
+    #[test]
+    fn labels_build_the_ai_they_name() {
+        for label in ["mcts-5000", "mcts-250ms-c0.800-risk-0.50-model-smart", "mcts-400-risk+1.00-widening-blunder0.20"] {
+            assert_eq!(HybridAI::from_label(label, 2).unwrap().config_label(), label);
+        }
+        let ai = HybridAI::from_label("mcts-800-risk-0.25-model-random", 3).unwrap();
+        assert_eq!((ai.mcts.simulations, ai.mcts.num_threads, ai.mcts.risk, ai.mcts.opponent_model), (800, 3, -0.25, PlayoutPolicy::Random));
+        for bad in ["mcts", "mcts-lots", "mcts-400-risky", "mcts-400-model-wild", "smart-400"] {
+            assert!(HybridAI::from_label(bad, 1).is_err(), "{}", bad);
+        }
+    }
+
+    /// Play one game with the dice from `seed`; whether the MCTS AI, as `mcts_side`, won
+    fn mcts_wins(ai: &HybridAI, mcts_side: FastPlayer, seed: u64) -> bool {
+        let mut dice = GameRng::from_seed(seed);
+        let mut game = FastGameState::new();
+        let cancel = CancelToken::new();
+        while !game.status().is_over() {
+            let player = game.current_player();
+            let roll = dice.roll_dice(player);
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            let piece = if player == mcts_side {
+                ai.choose_move_cancellable(&game, player, roll, &cancel).expect("there are moves").0
+            } else {
+                choose_smart_move_fast(&game, player, &moves, roll)
+            };
+            game.make_move(piece, roll).expect("generated moves are legal");
+        }
+        game.status().winner() == Some(mcts_side)
+    }
+
+    /// Catches search changes that weaken play. Run with
+    /// `cargo test --release -- --ignored strength`
+    #[test]
+    #[ignore]
+    fn strength_against_smart() {
+        let mut ai = HybridAI::new_with_threads(STRENGTH_SIMULATIONS, std::thread::available_parallelism().map_or(1, |n| n.get()));
+        ai.mcts.seed = Some(7);
+        ai.mcts.streams = Some(8);
+        let wins = (0..STRENGTH_SEEDS)
+            .flat_map(|seed| [FastPlayer::One, FastPlayer::Two].map(|side| mcts_wins(&ai, side, seed)))
+            .filter(|&won| won)
+            .count();
+        let win_percent = wins as f64 * 100.0 / (2 * STRENGTH_SEEDS) as f64;
+        println!("MCTS ({} simulations) won {:.1}% of {} games against the Smart AI", STRENGTH_SIMULATIONS, win_percent, 2 * STRENGTH_SEEDS);
+        assert!(win_percent >= MIN_STRENGTH_WIN_PERCENT, "MCTS won only {:.1}% against the Smart AI, below {}%", win_percent, MIN_STRENGTH_WIN_PERCENT);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::dice;
 use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::GameStateRepr;
 
 /// Fast AI functions that work directly with FastGameState
 pub fn choose_random_move_fast(moves: &[u8]) -> u8 {
@@ -7,12 +9,118 @@ pub fn choose_random_move_fast(moves: &[u8]) -> u8 {
     moves[rng.random_range(0..moves.len())]
 }
 
-pub fn choose_smart_move_fast(game: &FastGameState, player: FastPlayer, moves: &[u8], roll: u8) -> u8 {
+/// Weights for the Smart AI's one-ply move evaluation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalWeights {
+    /// Bonus for bringing a new piece onto the board
+    pub enter: f64,
+    /// Bonus per path square of the destination
+    pub advancement: f64,
+    /// Bonus for landing on a rosette (extra turn)
+    pub rosette: f64,
+    /// Bonus for capturing an opponent piece
+    pub capture: f64,
+    /// Extra capture bonus per path square the captured piece had travelled
+    pub capture_progress: f64,
+    /// Bonus for bearing a piece off
+    pub finish: f64,
+    /// Bonus when bearing off wins the game
+    pub win: f64,
+    /// Penalty scaled by the chance an opponent can hit the destination next turn
+    pub vulnerability: f64,
+}
+
+impl EvalWeights {
+    /// The weights the Smart AI has always used (no vulnerability term)
+    pub const DEFAULT: EvalWeights = EvalWeights {
+        enter: 50.0,
+        advancement: 10.0,
+        rosette: 200.0,
+        capture: 150.0,
+        capture_progress: 5.0,
+        finish: 1000.0,
+        win: 10000.0,
+        vulnerability: 0.0,
+    };
+}
+
+impl EvalWeights {
+    /// "smart" followed by every weight that differs from the default, e.g. "smart-rosette0-vulnerability200"
+    pub fn label(&self) -> String {
+        let fields = [
+            ("enter", self.enter, Self::DEFAULT.enter),
+            ("advancement", self.advancement, Self::DEFAULT.advancement),
+            ("rosette", self.rosette, Self::DEFAULT.rosette),
+            ("capture", self.capture, Self::DEFAULT.capture),
+            ("capture_progress", self.capture_progress, Self::DEFAULT.capture_progress),
+            ("finish", self.finish, Self::DEFAULT.finish),
+            ("win", self.win, Self::DEFAULT.win),
+            ("vulnerability", self.vulnerability, Self::DEFAULT.vulnerability),
+        ];
+        let mut label = String::from("smart");
+        for (name, value, default) in fields {
+            if value != default {
+                label.push_str(&format!("-{}{}", name, value));
+            }
+        }
+        label
+    }
+
+    /// The weights a label in the form `label` writes names
+    pub fn from_label(label: &str) -> Result<EvalWeights, String> {
+        let settings = label.strip_prefix("smart").filter(|settings| settings.is_empty() || settings.starts_with('-'))
+            .ok_or_else(|| format!("'{}' is not a Smart AI label", label))?;
+        let mut weights = Self::DEFAULT;
+        let mut words = settings.split('-').skip(1);
+        while let Some(word) = words.next() {
+            let split = word.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(word.len());
+            let (name, value) = word.split_at(split);
+            // A negative weight is split off its name by the dash before it
+            let value = match value {
+                "" => format!("-{}", words.next().unwrap_or_default()),
+                value => value.to_string(),
+            };
+            let value: f64 = value.parse().ok().filter(|v: &f64| v.is_finite())
+                .ok_or_else(|| format!("'{}' is not a number in '{}'", value, label))?;
+            let weight = match name {
+                "enter" => &mut weights.enter,
+                "advancement" => &mut weights.advancement,
+                "rosette" => &mut weights.rosette,
+                "capture" => &mut weights.capture,
+                "capture_progress" => &mut weights.capture_progress,
+                "finish" => &mut weights.finish,
+                "win" => &mut weights.win,
+                "vulnerability" => &mut weights.vulnerability,
+                _ => return Err(format!("unknown weight '{}' in '{}'", name, label)),
+            };
+            *weight = value;
+        }
+        Ok(weights)
+    }
+
+    /// Default weights plus a vulnerability penalty; used to judge human moves
+    pub const COACH: EvalWeights = EvalWeights {
+        vulnerability: 200.0,
+        ..EvalWeights::DEFAULT
+    };
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub fn choose_smart_move_fast<S: GameStateRepr>(game: &S, player: FastPlayer, moves: &[u8], roll: u8) -> u8 {
+    choose_weighted_move(game, player, moves, roll, &EvalWeights::DEFAULT)
+}
+
+pub fn choose_weighted_move<S: GameStateRepr>(game: &S, player: FastPlayer, moves: &[u8], roll: u8, weights: &EvalWeights) -> u8 {
     let mut best_move = moves[0];
     let mut best_score = f64::NEG_INFINITY;
 
     for &piece_idx in moves {
-        let score = evaluate_move_fast(game, player, piece_idx, roll);
+        let score = evaluate_move_weighted(game, player, piece_idx, roll, weights);
         if score > best_score {
             best_score = score;
             best_move = piece_idx;
@@ -22,62 +130,229 @@ pub fn choose_smart_move_fast(game: &FastGameState, player: FastPlayer, moves: &
     best_move
 }
 
-pub fn evaluate_move_fast(game: &FastGameState, player: FastPlayer, piece_idx: u8, roll: u8) -> f64 {
+pub fn evaluate_move_weighted<S: GameStateRepr>(game: &S, player: FastPlayer, piece_idx: u8, roll: u8, weights: &EvalWeights) -> f64 {
     let pos = game.get_piece_pos(player, piece_idx);
     let mut score = 0.0;
 
     match pos {
         0 => {
             // Entering the board
-            score += 50.0;
+            score += weights.enter;
             // Check if we land on a rosette
             let target_square = FastGameState::path_to_global(player, 0);
             if FastGameState::is_rosette(target_square) {
-                score += 200.0; // Extra turn bonus
+                score += weights.rosette; // Extra turn bonus
             }
         }
-        1..=14 => {
+        _ if FastGameState::is_on_board(pos) => {
             let path_idx = pos - 1;
             let new_path_idx = path_idx + roll;
 
-            if new_path_idx >= 14 {
+            if new_path_idx >= FastGameState::path_len() {
                 // Finishing a piece
-                score += 1000.0;
+                score += weights.finish;
                 // Bonus if this wins the game
                 if game.get_score(player) == 6 {
-                    score += 10000.0;
+                    score += weights.win;
                 }
             } else {
                 // Moving on board
-                score += new_path_idx as f64 * 10.0; // Advancement bonus
+                score += new_path_idx as f64 * weights.advancement; // Advancement bonus
 
                 let target_square = FastGameState::path_to_global(player, new_path_idx);
 
                 // Rosette bonus
                 if FastGameState::is_rosette(target_square) {
-                    score += 200.0;
+                    score += weights.rosette;
                 }
 
                 // Capture bonus
-                if let Some(occupant) = game.get_occupant(target_square) {
-                    if occupant != player && !FastGameState::is_safe(target_square) {
-                        // Find the piece being captured to get its advancement bonus
-                        for i in 0..7 {
-                            let opp_pos = game.get_piece_pos(occupant, i);
-                            if opp_pos >= 1 && opp_pos <= 14 {
-                                let opp_square = FastGameState::path_to_global(occupant, opp_pos - 1);
-                                if opp_square == target_square {
-                                    score += 150.0 + ((opp_pos - 1) as f64 * 5.0);
-                                    break;
-                                }
+                if let Some(occupant) = game.get_occupant(target_square)
+                    && occupant != player && !FastGameState::is_safe(target_square) {
+                    // Find the piece being captured to get its advancement bonus
+                    for i in 0..7 {
+                        let opp_pos = game.get_piece_pos(occupant, i);
+                        if FastGameState::is_on_board(opp_pos) {
+                            let opp_square = FastGameState::path_to_global(occupant, opp_pos - 1);
+                            if opp_square == target_square {
+                                score += weights.capture + ((opp_pos - 1) as f64 * weights.capture_progress);
+                                break;
                             }
                         }
                     }
                 }
+
+                // Vulnerability penalty
+                if weights.vulnerability != 0.0 && !FastGameState::is_safe(target_square) {
+                    score -= weights.vulnerability * hit_probability(game, player.opposite(), target_square);
+                }
             }
         }
         _ => {}
     }
 
     score
-}
\ No newline at end of file
+}
+
+/// Chance that at least one of `attacker`'s board pieces can land on `square` with its next roll
+fn hit_probability<S: GameStateRepr>(game: &S, attacker: FastPlayer, square: u8) -> f64 {
+    let dice = dice::model_for(attacker);
+    // Bit r set when a roll of r hits
+    let mut hitting_rolls = 0u32;
+    for i in 0..7 {
+        let pos = game.get_piece_pos(attacker, i);
+        if !FastGameState::is_on_board(pos) {
+            continue;
+        }
+        for roll in 1..=dice.max_roll() {
+            let path_idx = pos - 1 + roll;
+            if path_idx < FastGameState::path_len() && FastGameState::path_to_global(attacker, path_idx) == square {
+                hitting_rolls |= 1 << roll;
+            }
+        }
+    }
+
+    (1..=dice.max_roll())
+        .filter(|roll| hitting_rolls & (1 << roll) != 0)
+        .map(|roll| dice.probability(roll))
+        .sum()
+}
+
+/// Kinds of move in the order searches try them: bearing off, captures that
+/// cannot be hit back, rosettes, advances out of the opponent's reach, and
+/// last moves that leave a piece where it can be hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveClass {
+    Exit,
+    SafeCapture,
+    Rosette,
+    RiskyCapture,
+    SafeAdvance,
+    RiskyAdvance,
+}
+
+/// Which kind of move moving `piece_idx` by `roll` is; the move must be legal
+pub fn classify_move<S: GameStateRepr>(game: &S, player: FastPlayer, piece_idx: u8, roll: u8) -> MoveClass {
+    let path_idx = match game.get_piece_pos(player, piece_idx) {
+        0 => 0,
+        pos => pos - 1 + roll,
+    };
+    if path_idx >= FastGameState::path_len() {
+        return MoveClass::Exit;
+    }
+    let square = FastGameState::path_to_global(player, path_idx);
+    let opponent = player.opposite();
+    let attackable = !FastGameState::is_safe(square) && hit_probability(game, opponent, square) > 0.0;
+    let captures = game.get_occupant(square) == Some(opponent);
+    if captures && !attackable {
+        MoveClass::SafeCapture
+    } else if FastGameState::is_rosette(square) {
+        MoveClass::Rosette
+    } else if captures {
+        MoveClass::RiskyCapture
+    } else if !attackable {
+        MoveClass::SafeAdvance
+    } else {
+        MoveClass::RiskyAdvance
+    }
+}
+
+/// Sort `moves` into the order a search should try them: by kind, then by the
+/// Smart AI's evaluation, best first. Moves alike in both keep their order.
+pub fn order_moves<S: GameStateRepr>(game: &S, player: FastPlayer, moves: &mut [u8], roll: u8) {
+    let mut keys = [(MoveClass::Exit, 0.0); 7];
+    for &piece_idx in moves.iter() {
+        keys[piece_idx as usize] = (classify_move(game, player, piece_idx, roll),
+                                    evaluate_move_weighted(game, player, piece_idx, roll, &EvalWeights::DEFAULT));
+    }
+    moves.sort_by(|&a, &b| {
+        let ((class_a, score_a), (class_b, score_b)) = (keys[a as usize], keys[b as usize]);
+        class_a.cmp(&class_b).then(score_b.total_cmp(&score_a))
+    });
+}
+
+/// What a single move does, shared by move explanations and the evaluation's reasoning
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveDescription {
+    pub piece: u8,
+    /// Path index before the move; `None` when entering
+    pub from: Option<u8>,
+    /// Path index after the move; `None` when bearing off
+    pub to: Option<u8>,
+    /// Global destination square; `None` when bearing off
+    pub square: Option<u8>,
+    pub rosette: bool,
+    pub captures: bool,
+    /// Bears off the last piece
+    pub wins: bool,
+    /// Chance the opponent can hit the piece at its destination next turn
+    pub exposure: f64,
+    /// Chance the opponent could have hit the piece where it stood before moving
+    pub threat_escaped: f64,
+}
+
+impl MoveDescription {
+    pub fn enters(&self) -> bool {
+        self.from.is_none()
+    }
+
+    pub fn finishes(&self) -> bool {
+        self.to.is_none()
+    }
+
+    /// Short reasons this move might be attractive, most important first
+    pub fn reasons(&self) -> Vec<&'static str> {
+        let mut reasons = Vec::new();
+        if self.wins {
+            reasons.push("wins the game");
+        } else if self.finishes() {
+            reasons.push("exits a piece");
+        }
+        if self.captures {
+            reasons.push("captures");
+        }
+        if self.rosette {
+            reasons.push("rosette, extra roll");
+        }
+        if self.threat_escaped > 0.0 && self.exposure < self.threat_escaped {
+            reasons.push("escapes a threat");
+        }
+        reasons
+    }
+
+    /// Rough size of the swing this move invites: a capture plus the chance of being hit back
+    pub fn volatility(&self) -> f64 {
+        self.exposure + if self.captures { 0.5 } else { 0.0 }
+    }
+}
+
+/// Describe moving `piece_idx` by `roll`; the move must be legal
+pub fn describe_move(game: &FastGameState, player: FastPlayer, piece_idx: u8, roll: u8) -> MoveDescription {
+    let pos = game.get_piece_pos(player, piece_idx);
+    let opponent = player.opposite();
+    let from = FastGameState::is_on_board(pos).then(|| pos - 1);
+    let to = match from {
+        None => Some(0),
+        Some(path_idx) if path_idx + roll < FastGameState::path_len() => Some(path_idx + roll),
+        Some(_) => None,
+    };
+    let square = to.map(|path_idx| FastGameState::path_to_global(player, path_idx));
+
+    let exposure_at = |square: u8| {
+        if FastGameState::is_safe(square) { 0.0 } else { hit_probability(game, opponent, square) }
+    };
+
+    MoveDescription {
+        piece: piece_idx,
+        from,
+        to,
+        square,
+        rosette: square.is_some_and(FastGameState::is_rosette),
+        captures: square.is_some_and(|sq| {
+            game.get_occupant(sq) == Some(opponent) && !FastGameState::is_safe(sq)
+        }),
+        wins: to.is_none() && game.get_score(player) == 6,
+        exposure: square.map_or(0.0, exposure_at),
+        threat_escaped: from.map_or(0.0, |path_idx| exposure_at(FastGameState::path_to_global(player, path_idx))),
+    }
+}
@@ -0,0 +1,336 @@
+//! `ur analyze-db`: replay a directory of recorded games, rate every decision
+//! with MCTS and summarize how accurately each player chose.
+//! A move's loss is how much estimated win probability it gave up compared with
+//! the engine's best move; forced moves are not counted. A player's efficiency
+//! is the win probability they kept on average: 100 less their average loss in
+//! points. Games finished interactively are rated the same way. Verdicts are
+//! kept in the analysis cache, so a game rated before is not searched again.
+
+use std::collections::BTreeMap;
+use std::f64::consts::SQRT_2;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::ai::MCTSAI;
+use crate::analysis_cache::{Analysis, AnalysisCache, DEFAULT_CACHE_ENTRIES, DEFAULT_CACHE_FILE};
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::game_record::{load_dir, GameRecord, RecordedMove};
+use crate::openings::run_openings;
+use crate::optimized_game::FastGameState;
+use crate::presets::{self, Preset};
+use crate::screen::{Align, Screen, Span, Table};
+
+pub struct AnalyzeOptions {
+    pub dir: PathBuf,
+    pub simulations: usize,
+    pub threads: usize,
+    /// MCTS preset to rate moves with instead of `simulations` and `threads`
+    pub preset: Option<&'static Preset>,
+    /// Win probability a move must lose to count as a blunder, as a fraction
+    pub blunder_threshold: f64,
+    pub csv_path: String,
+    /// Only games recorded with this board and dice are analyzed
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    /// Show the opening explorer instead of rating moves
+    pub openings: bool,
+    /// Analysis cache file, None with `--no-cache`, and the most verdicts it keeps
+    pub cache: Option<PathBuf>,
+    pub cache_size: usize,
+}
+
+impl AnalyzeOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--dir", "--sims", "--threads", "--blunder", "--csv", "--board", "--dice", "--preset", "--cache", "--cache-size"],
+                                 &["--openings", "--no-cache"])?;
+        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let blunder: f64 = flags.get("--blunder", 10.0)?;
+        Ok(AnalyzeOptions {
+            dir: PathBuf::from(flags.get_str("--dir").unwrap_or("games")),
+            simulations: flags.get("--sims", 2000)?,
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            preset: flags.get_str("--preset").map(presets::lookup).transpose().map_err(|e| e.to_string())?,
+            blunder_threshold: blunder / 100.0,
+            csv_path: flags.get_str("--csv").unwrap_or("analysis.csv").to_string(),
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            openings: flags.has("--openings"),
+            cache: (!flags.has("--no-cache")).then(|| PathBuf::from(flags.get_str("--cache").unwrap_or(DEFAULT_CACHE_FILE))),
+            cache_size: flags.get("--cache-size", DEFAULT_CACHE_ENTRIES)?.max(1),
+        })
+    }
+}
+
+/// Decision quality of one player across the database, or in one game
+#[derive(Debug, Default, Clone)]
+pub struct Accuracy {
+    games: usize,
+    /// Moves with a real choice
+    pub decisions: usize,
+    total_loss: f64,
+    pub blunders: usize,
+    /// Decisions where the engine's choice was played
+    best_moves: usize,
+}
+
+impl Accuracy {
+    /// Percentage of decisions counted by `count`
+    pub fn rate(&self, count: usize) -> f64 {
+        count as f64 / self.decisions.max(1) as f64 * 100.0
+    }
+
+    /// Best-move percentage
+    pub fn best_move_rate(&self) -> f64 {
+        self.rate(self.best_moves)
+    }
+
+    /// Average win probability lost per decision, in points
+    pub fn average_loss(&self) -> f64 {
+        self.total_loss / self.decisions.max(1) as f64 * 100.0
+    }
+
+    /// Average win probability kept per decision, in points out of 100; None
+    /// without a decision to judge
+    pub fn efficiency(&self) -> Option<f64> {
+        (self.decisions > 0).then(|| 100.0 - self.average_loss())
+    }
+
+    fn add(&mut self, judgement: &Judgement, blunder_threshold: f64) {
+        self.decisions += 1;
+        self.total_loss += judgement.loss;
+        if judgement.loss >= blunder_threshold {
+            self.blunders += 1;
+        }
+        if judgement.played_best {
+            self.best_moves += 1;
+        }
+    }
+}
+
+/// The engine's verdict on one move
+#[derive(Debug, Clone, Copy)]
+struct Judgement {
+    best_piece: u8,
+    /// Estimated win probability after the move played, and after the best
+    played: f64,
+    best: f64,
+    loss: f64,
+    played_best: bool,
+}
+
+/// The engine moves are rated with, and the verdicts it has reached before
+struct Rater {
+    engine: MCTSAI,
+    /// Board, dice and engine label the cache keeps verdicts under
+    setup: String,
+    cache: AnalysisCache,
+}
+
+impl Rater {
+    /// Rate with `engine`, called `label` in the cache, on the current board and dice
+    fn new(engine: MCTSAI, label: &str, cache: AnalysisCache) -> Self {
+        let setup = format!("{} {} {}", board::variant().label(), dice::variant().label(), label);
+        Rater { engine, setup, cache }
+    }
+
+    /// The engine's search of `position` with `mv`'s roll, from the cache if
+    /// it holds one at least as deep
+    fn analyze(&mut self, position: &FastGameState, mv: RecordedMove) -> Option<Analysis> {
+        if let Some(analysis) = self.cache.lookup(&self.setup, position, mv.roll, self.engine.simulations) {
+            return Some(analysis);
+        }
+        let (best_piece, info) = self.engine.choose_move_with_info(position, mv.player, mv.roll)?;
+        // Moves to the same position are one choice; the most searched speaks for it
+        let mut outcomes: Vec<(u64, f64)> = Vec::new();
+        for candidate in &info.candidates {
+            let next = outcome(position, candidate.piece, mv.roll);
+            if !outcomes.iter().any(|&(key, _)| key == next) {
+                outcomes.push((next, candidate.win_rate));
+            }
+        }
+        let best = outcome(position, best_piece, mv.roll);
+        if let Some(candidate) = info.candidates.iter().find(|c| c.piece == best_piece)
+            && let Some(entry) = outcomes.iter_mut().find(|(key, _)| *key == best) {
+            entry.1 = candidate.win_rate;
+        }
+        let analysis = Analysis { best, outcomes, simulations: info.nodes };
+        self.cache.store(&self.setup, position, mv.roll, analysis.clone());
+        Some(analysis)
+    }
+
+    /// Rate `mv`, played from `position`, against the engine's best move. None
+    /// when it was forced: moves that lead to the same position (e.g. entering
+    /// any of several waiting pieces) are one choice, and with only one there is
+    /// nothing to judge.
+    fn judge(&mut self, position: &FastGameState, mv: RecordedMove) -> Option<Judgement> {
+        let analysis = self.analyze(position, mv)?;
+        if analysis.outcomes.len() < 2 {
+            return None;
+        }
+        let best_piece = position.generate_move_list(mv.roll).iter().copied()
+            .find(|&piece| outcome(position, piece, mv.roll) == analysis.best)?;
+        let best = analysis.win_rate(analysis.best);
+        let played_best = outcome(position, mv.piece, mv.roll) == analysis.best;
+        let played = if played_best { best } else { analysis.win_rate(outcome(position, mv.piece, mv.roll)) };
+        Some(Judgement { best_piece, played, best, loss: (best - played).max(0.0), played_best })
+    }
+}
+
+/// Canonical key of the position moving `piece` with `roll` leads to, the
+/// same whichever of a player's pieces stands where
+fn outcome(position: &FastGameState, piece: u8, roll: u8) -> u64 {
+    let mut next = *position;
+    next.make_move(piece, roll).expect("candidate moves are legal");
+    next.canonical_key()
+}
+
+/// Simulations per decision when rating a game just finished
+const GAME_END_SIMULATIONS: usize = 400;
+/// Win probability lost that counts as a blunder at game end, as in `analyze-db`
+const GAME_END_BLUNDER: f64 = 0.10;
+
+/// Rate every decision of a finished game for both sides, on all cores
+pub fn rate_game(record: &GameRecord) -> [Accuracy; 2] {
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let engine = MCTSAI::new_with_threads(GAME_END_SIMULATIONS, SQRT_2, threads);
+    // The cache only saves time, so a rating goes ahead without one that cannot be read or written
+    let path = PathBuf::from(DEFAULT_CACHE_FILE);
+    let cache = AnalysisCache::load(&path, DEFAULT_CACHE_ENTRIES).unwrap_or_else(|_| AnalysisCache::new(DEFAULT_CACHE_ENTRIES));
+    let mut rater = Rater::new(engine, "mcts", cache);
+    let mut accuracy = [Accuracy::default(), Accuracy::default()];
+    // The game was legal as played, so its moves replay
+    for (position, mv) in record.positions().unwrap_or_default() {
+        if let Some(judgement) = rater.judge(&position, mv) {
+            accuracy[mv.player as usize].add(&judgement, GAME_END_BLUNDER);
+        }
+    }
+    let _ = rater.cache.save(&path);
+    accuracy
+}
+
+pub fn run_analyze_db(options: &AnalyzeOptions) -> io::Result<()> {
+    board::select(options.board);
+    dice::select(options.dice);
+    if options.openings {
+        return run_openings(options);
+    }
+    let (engine, label, engine_desc) = match options.preset {
+        Some(preset) => (preset.build(options.threads).mcts, preset.name.as_str(), format!("preset {} ({})", preset.name, preset.describe())),
+        None => (MCTSAI::new_with_threads(options.simulations, SQRT_2, options.threads), "mcts",
+                 format!("MCTS {} simulations, {} threads", options.simulations, options.threads)),
+    };
+    let cache = match &options.cache {
+        Some(path) => AnalysisCache::load(path, options.cache_size)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?,
+        None => AnalysisCache::new(options.cache_size),
+    };
+    let mut rater = Rater::new(engine, label, cache);
+
+    println!("=== GAME DATABASE ANALYSIS ===");
+    println!("Directory: {} | {} | blunder = {:.0}+ points of win probability lost",
+             options.dir.display(), engine_desc, options.blunder_threshold * 100.0);
+    if let Some(path) = &options.cache {
+        println!("Cache: {} ({} verdicts{})", path.display(), rater.cache.size(), match rater.cache.discarded {
+            0 => String::new(),
+            discarded => format!(", {} from another engine version discarded", discarded),
+        });
+    }
+    println!();
+
+    let mut players: BTreeMap<String, Accuracy> = BTreeMap::new();
+    let mut csv = String::from("game,move,player,side,roll,piece,best_piece,win_rate,best_win_rate,loss\n");
+    let (mut analyzed, mut skipped) = (0, 0);
+
+    for (path, record) in load_dir(&options.dir)? {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let record = match record {
+            Ok(record) if record.board == options.board && record.dice == options.dice => record,
+            Ok(record) => {
+                println!("  {}: skipped, recorded with --board {} --dice {}", name, record.board.label(), record.dice.label());
+                skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                println!("  {}: skipped, {}", name, e);
+                skipped += 1;
+                continue;
+            }
+        };
+        let positions = match record.positions() {
+            Ok(positions) => positions,
+            Err(e) => {
+                println!("  {}: skipped, {}", name, e);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        print!("  {}: {} vs {}, {} moves... ", name, record.players[0], record.players[1], positions.len());
+        io::stdout().flush()?;
+        let mut game_loss = [0.0; 2];
+        let mut game_decisions = [0; 2];
+
+        for (move_no, (position, mv)) in positions.iter().enumerate() {
+            let Some(judgement) = rater.judge(position, *mv) else {
+                continue;
+            };
+            let side = mv.player as usize;
+            let label = &record.players[side];
+            players.entry(label.clone()).or_default().add(&judgement, options.blunder_threshold);
+            game_loss[side] += judgement.loss;
+            game_decisions[side] += 1;
+
+            let Judgement { best_piece, played, best, loss, .. } = judgement;
+            csv.push_str(&format!("{},{},{},{},{},{},{},{:.4},{:.4},{:.4}\n",
+                                  name, move_no + 1, label, side + 1, mv.roll, mv.piece, best_piece, played, best, loss));
+        }
+
+        for (side, label) in record.players.iter().enumerate() {
+            // Both sides may share a label (e.g. smart vs smart); count the game once
+            if side == 1 && record.players[0] == *label {
+                continue;
+            }
+            players.entry(label.clone()).or_default().games += 1;
+        }
+        let average = |side: usize| game_loss[side] / game_decisions[side].max(1) as f64 * 100.0;
+        println!("average loss {:.1} / {:.1} points", average(0), average(1));
+        analyzed += 1;
+    }
+
+    println!();
+    if analyzed == 0 {
+        println!("No games to analyze in {} ({} skipped). Record some with --record {}.",
+                 options.dir.display(), skipped, options.dir.display());
+        return Ok(());
+    }
+
+    println!("PLAYER ACCURACY ({} games analyzed, {} skipped):", analyzed, skipped);
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right, Align::Right, Align::Right, Align::Right, Align::Right], 2);
+    table.row(["Player", "Games", "Decisions", "Avg loss", "Efficiency", "Blunders", "Best move"].map(Span::plain).to_vec());
+    for (label, accuracy) in &players {
+        table.row(vec![
+            Span::plain(label.as_str()),
+            Span::plain(accuracy.games.to_string()),
+            Span::plain(accuracy.decisions.to_string()),
+            Span::plain(format!("{:.1}%", accuracy.average_loss())),
+            Span::plain(accuracy.efficiency().map_or("-".to_string(), |efficiency| format!("{:.1}", efficiency))),
+            Span::plain(format!("{:.1}%", accuracy.rate(accuracy.blunders))),
+            Span::plain(format!("{:.1}%", accuracy.best_move_rate())),
+        ]);
+    }
+    let mut screen = Screen::new();
+    screen.table(&table, 2);
+    screen.print();
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Per-move evaluations written to {}", options.csv_path);
+    if let Some(path) = &options.cache {
+        rater.cache.save(path)?;
+        println!("{} positions searched, {} taken from the cache", rater.cache.misses, rater.cache.hits);
+    }
+    Ok(())
+}
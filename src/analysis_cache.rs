@@ -0,0 +1,219 @@
+//! Engine verdicts kept between runs, so `ur analyze-db` run again over the
+//! same games, and the rating at the end of a game, do not redo searches
+//! already made. A verdict is kept per board, dice, engine setup, position
+//! (by its canonical key, so the numbering of the pieces does not matter) and
+//! roll, with the positions each move leads to rather than the pieces moved.
+//! It is reused by any search that would run no more simulations than it did.
+//! The file keeps the most recently used `limit` verdicts, and is discarded
+//! whole when it was written by another `ENGINE_VERSION`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::error::UrError;
+use crate::optimized_game::FastGameState;
+
+pub const DEFAULT_CACHE_FILE: &str = "ur_analysis_cache.txt";
+pub const DEFAULT_CACHE_ENTRIES: usize = 200_000;
+/// Raised whenever the search or its playouts change in a way that changes
+/// verdicts, so that every one cached before is thrown away
+pub const ENGINE_VERSION: u32 = 1;
+
+const CACHE_HEADER: &str = "# Royal Game of Ur analysis cache";
+const CACHE_VERSION: u32 = 1;
+
+/// The engine's search of one position and roll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// Canonical key of the position the engine's best move leads to
+    pub best: u64,
+    /// Each position a legal move leads to, with the estimated win
+    /// probability there for the side that moved; empty when only one move
+    /// was legal
+    pub outcomes: Vec<(u64, f64)>,
+    /// Simulations the search ran
+    pub simulations: usize,
+}
+
+impl Analysis {
+    /// The estimated win probability after a move to `outcome`
+    pub fn win_rate(&self, outcome: u64) -> f64 {
+        self.outcomes.iter().find(|&&(key, _)| key == outcome).map_or(0.0, |&(_, rate)| rate)
+    }
+}
+
+/// Where a verdict applies: the board, dice and engine setup as one label,
+/// the position's canonical key and the roll
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    setup: String,
+    position: u64,
+    roll: u8,
+}
+
+#[derive(Debug)]
+pub struct AnalysisCache {
+    /// Each verdict with when it was last used
+    entries: HashMap<Key, (Analysis, u64)>,
+    clock: u64,
+    limit: usize,
+    /// Verdicts thrown away on loading, as another engine version's
+    pub discarded: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl AnalysisCache {
+    /// An empty cache keeping at most `limit` verdicts
+    pub fn new(limit: usize) -> Self {
+        AnalysisCache { entries: HashMap::new(), clock: 0, limit, discarded: 0, hits: 0, misses: 0 }
+    }
+
+    /// Read a cache file; a missing file is an empty cache
+    pub fn load(path: &Path, limit: usize) -> Result<Self, UrError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text, limit),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new(limit)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Verdicts held
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The verdict on `position` with `roll` under `setup`, if one was made
+    /// with at least `simulations` simulations or there was no choice to make
+    pub fn lookup(&mut self, setup: &str, position: &FastGameState, roll: u8, simulations: usize) -> Option<Analysis> {
+        let key = Key { setup: setup.to_string(), position: position.canonical_key(), roll };
+        self.clock += 1;
+        match self.entries.get_mut(&key) {
+            Some((analysis, used)) if analysis.simulations >= simulations || analysis.outcomes.len() < 2 => {
+                *used = self.clock;
+                self.hits += 1;
+                Some(analysis.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn store(&mut self, setup: &str, position: &FastGameState, roll: u8, analysis: Analysis) {
+        let key = Key { setup: setup.to_string(), position: position.canonical_key(), roll };
+        self.clock += 1;
+        self.entries.insert(key, (analysis, self.clock));
+    }
+
+    /// The cache as text, least recently used first, cut to its limit
+    pub fn to_text(&self) -> String {
+        let mut entries: Vec<(&Key, &(Analysis, u64))> = self.entries.iter().collect();
+        entries.sort_by_key(|(_, (_, used))| *used);
+        let kept = &entries[entries.len().saturating_sub(self.limit)..];
+
+        let mut out = String::new();
+        out.push_str(CACHE_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", CACHE_VERSION));
+        out.push_str(&format!("engine {}\n", ENGINE_VERSION));
+        for (key, (analysis, _)) in kept {
+            out.push_str(&format!("entry {} {:x} {} {} {:x}", key.setup, key.position, key.roll, analysis.simulations, analysis.best));
+            for (outcome, rate) in &analysis.outcomes {
+                out.push_str(&format!(" {:x}={:.6}", outcome, rate));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse a cache; its entries count as used in the order they are listed
+    pub fn from_text(text: &str, limit: usize) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut cache = Self::new(limit);
+        let mut stale = false;
+
+        for (idx, raw) in text.lines().enumerate() {
+            let err = |message: String| UrError::ParseError { line: idx + 1, message };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["version", v] => {
+                    let v: u32 = v.parse().map_err(|_| err("expected a version number".to_string()))?;
+                    if v != CACHE_VERSION {
+                        return Err(err(format!("unsupported cache version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                ["engine", v] => stale = v.parse() != Ok(ENGINE_VERSION),
+                ["entry", ..] if stale => cache.discarded += 1,
+                ["entry", board, dice, engine, position, roll, simulations, best, outcomes @ ..] => {
+                    let hex = |field: &str| u64::from_str_radix(field, 16).map_err(|_| err(format!("'{}' is not a position key", field)));
+                    let outcomes = outcomes.iter().map(|outcome| {
+                        let (key, rate) = outcome.split_once('=').ok_or_else(|| err(format!("expected KEY=RATE, got '{}'", outcome)))?;
+                        Ok((hex(key)?, rate.parse().map_err(|_| err(format!("'{}' is not a win rate", rate)))?))
+                    }).collect::<Result<_, UrError>>()?;
+                    let (Ok(roll), Ok(simulations)) = (roll.parse(), simulations.parse()) else {
+                        return Err(err("roll and simulations must be numbers".to_string()));
+                    };
+                    let setup = format!("{} {} {}", board, dice, engine);
+                    cache.clock += 1;
+                    cache.entries.insert(Key { setup, position: hex(position)?, roll },
+                                         (Analysis { best: hex(best)?, outcomes, simulations }, cache.clock));
+                }
+                _ => return Err(err(format!("unrecognized line '{}'", line))),
+            }
+        }
+
+        version.ok_or_else(|| UrError::parse("missing 'version'"))?;
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETUP: &str = "standard tetrahedra mcts";
+
+    fn analysis(simulations: usize) -> Analysis {
+        Analysis { best: 0x21, outcomes: vec![(0x21, 0.625), (0x1_0000_0003, 0.25)], simulations }
+    }
+
+    #[test]
+    fn keeps_the_most_recently_used_verdicts() {
+        let start = FastGameState::new();
+        let mut after = start;
+        after.make_move(0, 2).expect("entering is legal");
+
+        let mut cache = AnalysisCache::new(2);
+        cache.store(SETUP, &start, 2, analysis(2000));
+        cache.store(SETUP, &after, 3, analysis(400));
+        cache.store(SETUP, &start, 4, analysis(2000));
+        assert_eq!(cache.lookup(SETUP, &start, 2, 400), Some(analysis(2000)));
+        // Too shallow for this search, and another setup's
+        assert_eq!(cache.lookup(SETUP, &after, 3, 2000), None);
+        assert_eq!(cache.lookup("standard d4 mcts", &start, 2, 400), None);
+
+        // The least recently used verdict is the one left out
+        let mut read = AnalysisCache::from_text(&cache.to_text(), 10).unwrap();
+        assert_eq!(read.size(), 2);
+        assert!(read.lookup(SETUP, &start, 2, 2000).is_some());
+        assert!(read.lookup(SETUP, &start, 4, 2000).is_some());
+        assert!(read.lookup(SETUP, &after, 3, 0).is_none());
+
+        let stale = cache.to_text().replace(&format!("engine {}", ENGINE_VERSION), "engine 0");
+        let stale = AnalysisCache::from_text(&stale, 10).unwrap();
+        assert_eq!((stale.size(), stale.discarded), (0, 2));
+    }
+}
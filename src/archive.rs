@@ -0,0 +1,499 @@
+//! Compact binary archive of many games, for self-play corpora too large for
+//! one `.urg` text file per game. An archive is a single zstd stream: a header
+//! naming the board, dice and dice generator, then one entry per game with its players, start,
+//! result and every turn packed into one byte (the side, the roll and the
+//! piece moved, or none for a lost turn) followed by a byte of the rolling state
+//! hash after it, so a damaged archive, or one read under other rules, fails
+//! at the turn that went wrong. Writer and reader both stream, so an
+//! archive of millions of games never has to fit in memory. `ur selfplay`
+//! writes them and `ur archive-stats` summarizes them.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::game_record::{GameRecord, RecordedMove, StateHash};
+use crate::optimized_game::{EndReason, FastGameState, FastPlayer, GameStatus};
+use crate::resources::ResourceMeter;
+use crate::rng::{self, GameRng, RngAlgorithm};
+use crate::state_repr::GameStateRepr;
+use crate::stats::{play_silent_game, StatsAgent};
+use crate::workers;
+
+const ARCHIVE_MAGIC: &[u8; 4] = b"URAR";
+/// Version 2 added the dice generator to the header, version 3 the state hash bytes
+const ARCHIVE_VERSION: u8 = 3;
+/// zstd level; higher levels barely shrink the turn bytes further
+const COMPRESSION_LEVEL: i32 = 9;
+/// Piece number that marks a turn lost to a zero roll or a blocked position
+const NO_MOVE: u8 = 7;
+/// Entry flag bits; an adjudicated or drawn game keeps its `EndReason`
+/// (as an index into `EndReason::ALL`) in the three bits from `REASON_SHIFT`
+const FLAG_P2_WON: u8 = 1;
+const FLAG_CUSTOM_START: u8 = 2;
+const FLAG_ADJUDICATED: u8 = 4;
+const FLAG_DRAWN: u8 = 8;
+const REASON_SHIFT: u8 = 4;
+
+/// One turn: the side to move, its roll and the piece it moved, if it could
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    pub player: FastPlayer,
+    pub roll: u8,
+    pub piece: Option<u8>,
+}
+
+impl Turn {
+    fn pack(self) -> u8 {
+        (self.player as u8) << 7 | self.roll << 3 | self.piece.unwrap_or(NO_MOVE)
+    }
+
+    fn unpack(byte: u8) -> Self {
+        let piece = byte & 7;
+        Turn {
+            player: if byte >> 7 == 0 { FastPlayer::One } else { FastPlayer::Two },
+            roll: (byte >> 3) & 15,
+            piece: (piece != NO_MOVE).then_some(piece),
+        }
+    }
+}
+
+/// A whole game as stored in an archive. Unlike a `GameRecord` it keeps lost
+/// turns, so turn counts and the dice sequence survive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedGame {
+    pub players: [String; 2],
+    pub start: FastGameState,
+    /// How the game ended; never `InProgress`
+    pub status: GameStatus,
+    pub turns: Vec<Turn>,
+}
+
+impl ArchivedGame {
+    /// The game as a text record, e.g. to open it in `ur analyze-db`
+    pub fn to_record(&self, board: BoardVariant, dice: DiceVariant) -> GameRecord {
+        let moves = self.turns.iter()
+            .filter_map(|turn| turn.piece.map(|piece| RecordedMove { player: turn.player, roll: turn.roll, piece }))
+            .collect();
+        GameRecord { players: self.players.clone(), board, dice, start: self.start, moves, result: Some(self.status), unassisted: false,
+                     rng: RngAlgorithm::default(), seed: None, hashes: Vec::new() }
+    }
+}
+
+/// Flag bits recording how a game ended
+fn status_flags(status: GameStatus) -> io::Result<u8> {
+    let reason = |reason: EndReason| (EndReason::ALL.iter().position(|&r| r == reason).unwrap_or(0) as u8) << REASON_SHIFT;
+    match status {
+        GameStatus::Won(winner) => Ok(winner as u8 * FLAG_P2_WON),
+        GameStatus::Adjudicated(winner, why) => Ok((winner as u8 * FLAG_P2_WON) | FLAG_ADJUDICATED | reason(why)),
+        GameStatus::Drawn(why) => Ok(FLAG_DRAWN | reason(why)),
+        GameStatus::InProgress => Err(invalid("only finished games can be archived")),
+    }
+}
+
+/// How a game ended, from its flag bits
+fn flags_status(flags: u8) -> GameStatus {
+    let winner = if flags & FLAG_P2_WON != 0 { FastPlayer::Two } else { FastPlayer::One };
+    let reason = EndReason::ALL.get(usize::from((flags >> REASON_SHIFT) & 7)).copied().unwrap_or(EndReason::ALL[0]);
+    if flags & FLAG_DRAWN != 0 {
+        GameStatus::Drawn(reason)
+    } else if flags & FLAG_ADJUDICATED != 0 {
+        GameStatus::Adjudicated(winner, reason)
+    } else {
+        GameStatus::Won(winner)
+    }
+}
+
+/// The state hash after each of `game`'s turns, stepping through them on the current board
+fn turn_hashes(game: &ArchivedGame) -> Result<Vec<StateHash>, String> {
+    let mut state = game.start;
+    let mut hash = StateHash::START;
+    game.turns.iter().enumerate().map(|(idx, turn)| {
+        let player = state.current_player();
+        if turn.player != player {
+            return Err(format!("turn {}: {} to move, not {}", idx + 1, player.name(), turn.player.name()));
+        }
+        match turn.piece {
+            None => state.pass_turn(),
+            Some(piece) => {
+                state.play(piece, turn.roll).map_err(|e| format!("turn {}: {}", idx + 1, e))?;
+            }
+        }
+        hash = hash.after(&state);
+        Ok(hash)
+    }).collect()
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn write_varint(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        out.write_all(&[value as u8 | 0x80])?;
+        value >>= 7;
+    }
+    out.write_all(&[value as u8])
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(input)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn write_str(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write_varint(out, text.len() as u64)?;
+    out.write_all(text.as_bytes())
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let len = read_varint(input)?;
+    if len > 1024 {
+        return Err(invalid(format!("string of {} bytes", len)));
+    }
+    let mut bytes = vec![0; len as usize];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid("string is not UTF-8"))
+}
+
+/// Streams games into an archive file
+pub struct ArchiveWriter {
+    encoder: zstd::Encoder<'static, File>,
+    games: usize,
+}
+
+impl ArchiveWriter {
+    /// Start an archive of games played with `board`, `dice` and `rng`, replacing any file at `path`
+    pub fn create(path: &Path, board: BoardVariant, dice: DiceVariant, rng: RngAlgorithm) -> io::Result<Self> {
+        let mut encoder = zstd::Encoder::new(File::create(path)?, COMPRESSION_LEVEL)?;
+        encoder.write_all(ARCHIVE_MAGIC)?;
+        encoder.write_all(&[ARCHIVE_VERSION])?;
+        write_str(&mut encoder, board.label())?;
+        write_str(&mut encoder, dice.label())?;
+        write_str(&mut encoder, rng.label())?;
+        Ok(ArchiveWriter { encoder, games: 0 })
+    }
+
+    pub fn write(&mut self, game: &ArchivedGame) -> io::Result<()> {
+        let custom_start = game.start != FastGameState::new();
+        let mut flags = status_flags(game.status)?;
+        if custom_start {
+            flags |= FLAG_CUSTOM_START;
+        }
+        let out = &mut self.encoder;
+        out.write_all(&[flags])?;
+        write_str(out, &game.players[0])?;
+        write_str(out, &game.players[1])?;
+        if custom_start {
+            for player in [FastPlayer::One, FastPlayer::Two] {
+                let pieces: [u8; 7] = std::array::from_fn(|idx| game.start.get_piece_pos(player, idx as u8));
+                out.write_all(&pieces)?;
+            }
+            out.write_all(&[game.start.current_player() as u8])?;
+        }
+        write_varint(out, game.turns.len() as u64)?;
+        let packed: Vec<u8> = game.turns.iter().map(|turn| turn.pack()).collect();
+        out.write_all(&packed)?;
+        let hashes: Vec<u8> = turn_hashes(game).map_err(invalid)?.into_iter().map(StateHash::low_byte).collect();
+        out.write_all(&hashes)?;
+        self.games += 1;
+        Ok(())
+    }
+
+    /// Flush the compressed stream and close the file; returns the number of games written
+    pub fn finish(self) -> io::Result<usize> {
+        self.encoder.finish()?.sync_all()?;
+        Ok(self.games)
+    }
+}
+
+/// Streams games back out of an archive file, one per `next()`
+pub struct ArchiveReader {
+    decoder: zstd::Decoder<'static, io::BufReader<Box<dyn Read + Send>>>,
+    version: u8,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    pub rng: RngAlgorithm,
+}
+
+impl ArchiveReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Read an archive from any byte stream, such as one already in memory
+    pub fn from_reader(input: impl Read + Send + 'static) -> io::Result<Self> {
+        let input: Box<dyn Read + Send> = Box::new(input);
+        let mut decoder = zstd::Decoder::new(input)?;
+        let mut magic = [0; 4];
+        decoder.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(invalid("not a game archive"));
+        }
+        let version = read_byte(&mut decoder)?;
+        if !(1..=ARCHIVE_VERSION).contains(&version) {
+            return Err(invalid(format!("unsupported archive version {}", version)));
+        }
+        let board = read_str(&mut decoder)?;
+        let board = BoardVariant::from_label(&board).ok_or_else(|| invalid(format!("unknown board '{}'", board)))?;
+        let dice = read_str(&mut decoder)?;
+        let dice = DiceVariant::from_label(&dice).ok_or_else(|| invalid(format!("unknown dice '{}'", dice)))?;
+        // Version 1 archives were all rolled with xoshiro
+        let rng = if version == 1 {
+            RngAlgorithm::Xoshiro
+        } else {
+            let rng = read_str(&mut decoder)?;
+            RngAlgorithm::from_label(&rng).ok_or_else(|| invalid(format!("unknown rng '{}'", rng)))?
+        };
+        Ok(ArchiveReader { decoder, version, board, dice, rng })
+    }
+
+    /// The rest of a game entry whose flags byte has been read. From version
+    /// 3 its turns are replayed on the current board against their state
+    /// hashes, so the archive's board and dice must be selected first.
+    fn read_game(&mut self, flags: u8) -> io::Result<ArchivedGame> {
+        let input = &mut self.decoder;
+        let players = [read_str(input)?, read_str(input)?];
+        let start = if flags & FLAG_CUSTOM_START != 0 {
+            let mut pieces = [[0; 7]; 2];
+            input.read_exact(&mut pieces[0])?;
+            input.read_exact(&mut pieces[1])?;
+            let to_move = if read_byte(input)? == 0 { FastPlayer::One } else { FastPlayer::Two };
+            FastGameState::from_piece_positions(pieces[0], pieces[1], to_move)
+                .map_err(|e| invalid(format!("start: {}", e)))?
+        } else {
+            FastGameState::new()
+        };
+        let count = read_varint(input)?;
+        if count > 1 << 20 {
+            return Err(invalid(format!("game of {} turns", count)));
+        }
+        let mut packed = vec![0; count as usize];
+        input.read_exact(&mut packed)?;
+        let game = ArchivedGame { players, start, status: flags_status(flags), turns: packed.into_iter().map(Turn::unpack).collect() };
+        if self.version >= 3 {
+            let mut recorded = vec![0; count as usize];
+            input.read_exact(&mut recorded)?;
+            let damaged = |problem: String| invalid(format!("{}; the archive is damaged or was written under other rules", problem));
+            let hashes = turn_hashes(&game).map_err(damaged)?;
+            if let Some(idx) = hashes.iter().zip(&recorded).position(|(hash, &byte)| hash.low_byte() != byte) {
+                return Err(damaged(format!("turn {}: state hash does not match", idx + 1)));
+            }
+        }
+        Ok(game)
+    }
+}
+
+impl Iterator for ArchiveReader {
+    type Item = io::Result<ArchivedGame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The end of the stream is only clean between games
+        let mut flags = [0];
+        match self.decoder.read(&mut flags) {
+            Ok(0) => None,
+            Ok(_) => Some(self.read_game(flags[0]).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => invalid("archive ends in the middle of a game"),
+                _ => e,
+            })),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Options for `ur selfplay`
+pub struct SelfPlayOptions {
+    pub games: usize,
+    pub players: [String; 2],
+    pub out: PathBuf,
+    pub threads: usize,
+    pub seed: Option<u64>,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    pub rng: RngAlgorithm,
+}
+
+impl SelfPlayOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--p1", "--p2", "--out", "--threads", "--seed", "--board", "--dice", "--rng"], &[])?;
+        let num_cpus = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let options = SelfPlayOptions {
+            games: flags.get("--games", 10_000)?,
+            players: [flags.get("--p1", "smart".to_string())?, flags.get("--p2", "smart".to_string())?],
+            out: PathBuf::from(flags.get_str("--out").unwrap_or("selfplay.ura")),
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            seed: flags.get_opt("--seed")?,
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            rng: rng::from_flags(&flags)?.unwrap_or_default(),
+        };
+        for spec in &options.players {
+            StatsAgent::from_spec(spec, 1)?;
+        }
+        Ok(options)
+    }
+}
+
+/// Play the games with these dice seeds, handing each to `emit`
+fn play_chunk(options: &SelfPlayOptions, seeds: &[u64], mut emit: impl FnMut(ArchivedGame) -> io::Result<()>) -> io::Result<()> {
+    let [p1, p2] = options.players.each_ref().map(|spec| StatsAgent::from_spec(spec, 1).expect("checked when parsing"));
+    let players = [p1.label(), p2.label()];
+    for &seed in seeds {
+        let game = play_silent_game(&p1, &p2, &mut GameRng::from_seed(seed));
+        emit(ArchivedGame { players: players.clone(), start: FastGameState::new(), status: game.status, turns: game.log })?;
+    }
+    Ok(())
+}
+
+/// Play `options.games` silent games across threads and stream them into an archive
+pub fn run_selfplay(options: &SelfPlayOptions) -> io::Result<()> {
+    board::select(options.board);
+    dice::select(options.dice);
+    rng::select(options.rng);
+    let mut seeds = options.seed.map_or_else(GameRng::random, GameRng::from_seed);
+    let seeds: Vec<u64> = (0..options.games).map(|_| seeds.next_seed()).collect();
+
+    println!("=== SELF-PLAY ===");
+    println!("{} vs {} | {} games | {} threads | board {} | dice {} | rng {}",
+             options.players[0], options.players[1], options.games, options.threads,
+             options.board.label(), options.dice.label(), options.rng.label());
+
+    let start_time = Instant::now();
+    let mut writer = ArchiveWriter::create(&options.out, options.board, options.dice, options.rng)?;
+    let (mut turns, mut text_bytes) = (0, 0);
+    let mut meter = ResourceMeter::new();
+    let (sender, receiver) = mpsc::channel();
+    let chunk = seeds.len().div_ceil(options.threads).max(1);
+    let mut record = |game: ArchivedGame| -> io::Result<()> {
+        turns += game.turns.len();
+        text_bytes += game.to_record(options.board, options.dice).to_text().len();
+        writer.write(&game)?;
+        if writer.games % 1000 == 0 {
+            print!("\r  {} / {} games | {}   ", writer.games, options.games, meter.summary());
+            io::stdout().flush()?;
+        }
+        Ok(())
+    };
+    thread::scope(|scope| -> io::Result<()> {
+        let mut stranded = Vec::new();
+        for seeds in seeds.chunks(chunk) {
+            let sender = sender.clone();
+            let job = move || play_chunk(options, seeds, |game| sender.send(game).map_err(|_| io::ErrorKind::BrokenPipe.into()));
+            if workers::spawn_scoped(scope, "self-play", job).is_err() {
+                stranded.push(seeds);
+            }
+        }
+        drop(sender);
+        // Chunks without a thread are played here, writing out the other threads' games between them
+        for seeds in stranded {
+            play_chunk(options, seeds, |game| {
+                record(game)?;
+                while let Ok(game) = receiver.try_recv() {
+                    record(game)?;
+                }
+                Ok(())
+            })?;
+        }
+        // The receiver outlives the workers, so an early error here ends them at their next send
+        for game in receiver {
+            record(game)?;
+        }
+        Ok(())
+    })?;
+
+    let games = writer.finish()?;
+    let bytes = fs::metadata(&options.out)?.len();
+    println!();
+    println!("Wrote {} games ({} turns) to {} in {:.1}s", games, turns, options.out.display(), start_time.elapsed().as_secs_f64());
+    // Read everything back so a damaged file is caught while the games can still be replayed
+    let reader = ArchiveReader::open(&options.out)?;
+    if (reader.board, reader.dice, reader.rng) != (options.board, options.dice, options.rng) {
+        return Err(invalid("archive header does not match the games written"));
+    }
+    let mut read_back = 0;
+    for game in reader {
+        game?;
+        read_back += 1;
+    }
+    if read_back != games {
+        return Err(invalid(format!("read back {} of {} games", read_back, games)));
+    }
+    println!("Archive is {} bytes, {:.1} per game; as .urg text records they would take {} bytes ({:.0}x larger)",
+             bytes, bytes as f64 / games.max(1) as f64, text_bytes, text_bytes as f64 / bytes.max(1) as f64);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game from a custom start with Two to move, whose first turn is lost,
+    /// ended by resignation; and a drawn game from the usual start
+    fn games() -> Vec<ArchivedGame> {
+        let mut p1 = [0; 7];
+        p1[0] = 1;
+        let start = FastGameState::from_piece_positions(p1, [0; 7], FastPlayer::Two).unwrap();
+        let turn = |player, roll, piece| Turn { player, roll, piece };
+        let adjudicated = ArchivedGame {
+            players: ["Smart".to_string(), "Random".to_string()],
+            start,
+            status: GameStatus::Adjudicated(FastPlayer::One, EndReason::Resignation),
+            turns: vec![turn(FastPlayer::Two, 0, None), turn(FastPlayer::One, 2, Some(0)), turn(FastPlayer::Two, 1, Some(3))],
+        };
+        let drawn = ArchivedGame {
+            players: ["Expectimax".to_string(), "Smart".to_string()],
+            start: FastGameState::new(),
+            status: GameStatus::Drawn(EndReason::Agreement),
+            turns: vec![turn(FastPlayer::One, 3, Some(5)), turn(FastPlayer::Two, 2, Some(0))],
+        };
+        vec![adjudicated, drawn]
+    }
+
+    fn write_archive(path: &Path, games: &[ArchivedGame]) {
+        let mut writer = ArchiveWriter::create(path, BoardVariant::default(), DiceVariant::Tetrahedra, RngAlgorithm::default()).unwrap();
+        for game in games {
+            writer.write(game).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), games.len());
+    }
+
+    #[test]
+    fn games_round_trip_and_a_flipped_turn_is_damage() {
+        let path = std::env::temp_dir().join(format!("ur-archive-test-{}.ura", std::process::id()));
+        let games = games();
+        write_archive(&path, &games);
+        let reader = ArchiveReader::open(&path).unwrap();
+        assert_eq!((reader.board, reader.dice, reader.rng), (BoardVariant::default(), DiceVariant::Tetrahedra, RngAlgorithm::default()));
+        let read: Vec<ArchivedGame> = reader.collect::<io::Result<_>>().unwrap();
+        assert_eq!(read, games);
+
+        // Entering piece 2 instead of piece 3 is a legal move, so only the state hash catches it
+        let mut bytes = zstd::decode_all(File::open(&path).unwrap()).unwrap();
+        let packed: Vec<u8> = games[0].turns.iter().map(|turn| turn.pack()).collect();
+        let at = bytes.windows(packed.len()).position(|window| window == packed).unwrap();
+        bytes[at + 2] ^= 1;
+        fs::write(&path, zstd::encode_all(&bytes[..], COMPRESSION_LEVEL).unwrap()).unwrap();
+        let error = ArchiveReader::open(&path).unwrap().next().unwrap().unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(error.to_string().starts_with("turn 3: state hash does not match; the archive is damaged"), "{}", error);
+    }
+}
@@ -0,0 +1,118 @@
+//! `ur archive-stats`: the stats-mode report for games already in an archive.
+//! Each game's turns are stepped through on the board, without any AI, to get
+//! its captures and square occupancy, so one expensive self-play run can be
+//! summarized again whenever a metric is added.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::archive::{ArchiveReader, ArchivedGame};
+use crate::args::Flags;
+use crate::board;
+use crate::dice;
+use crate::luck;
+use crate::optimized_game::FastPlayer;
+use crate::square_stats::SquareTracker;
+use crate::state_repr::GameStateRepr;
+use crate::stats::{ExtraRolls, GameStatistics};
+
+pub struct ArchiveStatsOptions {
+    pub file: PathBuf,
+}
+
+impl ArchiveStatsOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--file"], &[])?;
+        Ok(ArchiveStatsOptions {
+            file: PathBuf::from(flags.get_str("--file").ok_or("--file is required")?),
+        })
+    }
+}
+
+/// What stats mode records about a game, recovered from its turns
+struct Replayed {
+    captures: [usize; 2],
+    squares: SquareTracker,
+}
+
+/// Step through `game`'s turns, checking each against the rules
+fn replay(game: &ArchivedGame) -> Result<Replayed, String> {
+    let mut state = game.start;
+    let mut captures = [0; 2];
+    let mut squares = SquareTracker::new();
+    for (idx, turn) in game.turns.iter().enumerate() {
+        let player = state.current_player();
+        if turn.player != player {
+            return Err(format!("turn {}: {} to move, not {}", idx + 1, player.name(), turn.player.name()));
+        }
+        squares.observe(&state);
+        squares.observe_roll(&state, turn.roll);
+        let moves = if turn.roll == 0 { Default::default() } else { state.generate_move_list(turn.roll) };
+        match turn.piece {
+            None if moves.is_empty() => state.pass_turn(),
+            None => return Err(format!("turn {}: lost with a move available", idx + 1)),
+            Some(piece) if moves.contains(&piece) => {
+                let info = state.make_move(piece, turn.roll).expect("legal move");
+                captures[player as usize] += usize::from(info.captured_piece.is_some());
+            }
+            Some(piece) => return Err(format!("turn {}: piece {} cannot move {}", idx + 1, piece, turn.roll)),
+        }
+    }
+    Ok(Replayed { captures, squares })
+}
+
+/// Print stats-mode statistics for every pairing of players in an archive
+pub fn run_archive_stats(options: &ArchiveStatsOptions) -> io::Result<()> {
+    let reader = ArchiveReader::open(&options.file)?;
+    board::select(reader.board);
+    dice::select(reader.dice);
+    println!("=== ARCHIVE STATISTICS ===");
+    println!("Archive: {} | board {} | dice {} | rng {}", options.file.display(), reader.board.label(), reader.dice.label(), reader.rng.label());
+
+    let start_time = Instant::now();
+    // Seat 1 and seat 2 labels → their games
+    let mut pairings: BTreeMap<[String; 2], GameStatistics> = BTreeMap::new();
+    let (mut games, mut skipped) = (0, 0);
+    for game in reader {
+        let game = game.map_err(|e| io::Error::new(e.kind(), format!("game {}: {}", games + 1, e)))?;
+        games += 1;
+        match replay(&game) {
+            Ok(replayed) => {
+                let stats = pairings.entry(game.players).or_insert_with(GameStatistics::new);
+                stats.add_game(game.status, game.turns.len(), replayed.captures[0], replayed.captures[1]);
+                stats.add_extra_rolls(&ExtraRolls::from_log(&game.turns), game.status.winner());
+                stats.add_square_stats(replayed.squares, game.status.winner());
+                stats.add_luck(&luck::assess(game.start, &game.turns), false);
+            }
+            Err(e) => {
+                if skipped == 0 {
+                    println!("⚠️  Game {} skipped, {}", games, e);
+                }
+                skipped += 1;
+            }
+        }
+        if games % 100_000 == 0 {
+            print!("\r  {} games", games);
+            io::stdout().flush()?;
+        }
+    }
+    println!("\r  {} games read in {:.1}s{}", games, start_time.elapsed().as_secs_f64(),
+             if skipped > 0 { format!(", {} skipped as unplayable", skipped) } else { String::new() });
+
+    if pairings.is_empty() {
+        println!("No games to report.");
+        return Ok(());
+    }
+    for ([p1, p2], stats) in &pairings {
+        if pairings.len() > 1 {
+            println!();
+            println!("--- {} ({}) vs {} ({}) ---", p1, FastPlayer::One.name(), p2, FastPlayer::Two.name());
+        }
+        stats.display(p1, p2);
+        stats.display_luck(p1, p2);
+        stats.display_board_control(p1, p2);
+    }
+    Ok(())
+}
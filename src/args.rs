@@ -0,0 +1,57 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// Minimal `--flag value` / `--switch` parser shared by the subcommands
+pub struct Flags {
+    values: HashMap<String, String>,
+    switches: HashSet<String>,
+}
+
+impl Flags {
+    /// Parse `args`, accepting only the listed value flags and switches
+    pub fn parse(args: &[String], value_flags: &[&str], switch_flags: &[&str]) -> Result<Flags, String> {
+        let mut flags = Flags {
+            values: HashMap::new(),
+            switches: HashSet::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if value_flags.contains(&name) {
+                let value = iter.next().ok_or_else(|| format!("{} expects a value", name))?;
+                flags.values.insert(name.to_string(), value.clone());
+            } else if switch_flags.contains(&name) {
+                flags.switches.insert(name.to_string());
+            } else {
+                return Err(format!("unknown option '{}'", name));
+            }
+        }
+
+        Ok(flags)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Parsed value of a flag, if it was given
+    pub fn get_opt<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        match self.values.get(name) {
+            None => Ok(None),
+            Some(raw) => raw
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value '{}' for {}", raw, name)),
+        }
+    }
+
+    /// Parsed value of a flag, or `default` if it was not given
+    pub fn get<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        Ok(self.get_opt(name)?.unwrap_or(default))
+    }
+}
@@ -0,0 +1,84 @@
+//! Search settings chosen for the machine, so a player need not know how many
+//! threads or simulations to ask for. Interactive play searches on every
+//! core but one, leaving that one to the game's display and input, and a
+//! preset with an `auto` budget runs as many simulations as fit its think
+//! time here: a short burst of searching, made once per thread count and
+//! run, measures how many playouts the threads get through.
+
+use std::collections::HashMap;
+use std::f64::consts::SQRT_2;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::ai::MCTSAI;
+use crate::dice;
+use crate::optimized_game::FastGameState;
+use crate::state_repr::GameStateRepr;
+
+/// How long the calibration burst searches
+const CALIBRATION_TIME: Duration = Duration::from_millis(200);
+
+/// Logical cores on this machine
+pub fn cores() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Search threads for interactive play: every core but one, and at least one
+pub fn threads() -> usize {
+    cores().saturating_sub(1).max(1)
+}
+
+/// Playouts per second a search on `threads` threads runs here
+pub fn playout_rate(threads: usize) -> f64 {
+    static RATES: OnceLock<Mutex<HashMap<usize, f64>>> = OnceLock::new();
+    let mut rates = RATES.get_or_init(Default::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *rates.entry(threads).or_insert_with(|| calibrate(threads))
+}
+
+/// Simulations a search on `threads` threads runs here in about `think`
+pub fn simulations_for(think: Duration, threads: usize) -> usize {
+    ((playout_rate(threads) * think.as_secs_f64()) as usize).max(1)
+}
+
+/// The chosen setup in a line, e.g. "7 search threads (one of 8 cores left
+/// free), 10250 simulations for about 250 ms per move at 41k playouts/s"
+pub fn summary(threads: usize, think: Duration) -> String {
+    let free = cores().saturating_sub(threads);
+    let cores = match free {
+        0 if cores() == 1 => "1 core".to_string(),
+        0 => format!("{} cores", cores()),
+        1 => format!("one of {} cores left free", cores()),
+        free => format!("{} of {} cores left free", free, cores()),
+    };
+    let threads_label = if threads == 1 { "1 search thread".to_string() } else { format!("{} search threads", threads) };
+    format!("{} ({}), {} simulations for about {} ms per move at {:.0}k playouts/s",
+            threads_label, cores, simulations_for(think, threads), think.as_millis(), playout_rate(threads) / 1000.0)
+}
+
+/// Search an early position for `CALIBRATION_TIME` and count the playouts.
+/// Playouts are longest from early positions, so later searches take less
+/// time than measured, never more.
+fn calibrate(threads: usize) -> f64 {
+    // A few moves in with twos, until a roll has more than one move to search
+    let mut game = FastGameState::new();
+    let choice = |game: &FastGameState| (1..=dice::model_for(game.current_player()).max_roll())
+        .find(|&roll| game.generate_moves(roll).len() > 1);
+    for _ in 0..20 {
+        if choice(&game).is_some() {
+            break;
+        }
+        if let Some(&piece_idx) = game.generate_moves(2).first() {
+            let _ = game.make_move(piece_idx, 2);
+        } else {
+            game.pass_turn();
+        }
+    }
+    let (player, roll) = (game.current_player(), choice(&game).unwrap_or(2));
+
+    let mut search = MCTSAI::new_with_threads(0, SQRT_2, threads);
+    search.time_budget = Some(CALIBRATION_TIME);
+    search.choose_move_with_info(&game, player, roll)
+        .map_or(0.0, |(_, info)| info.nodes_per_sec())
+        .max(1.0)
+}
@@ -0,0 +1,122 @@
+//! `ur bench`: a fixed, deterministic MCTS workload for comparing engine speed
+//! across machines and checking optimizations. The positions, dice and playout
+//! seeds never change, so on the same build every run searches exactly the same
+//! trees; the signature printed at the end changes only when search behavior
+//! does, and the final nodes-per-second figure is the number to compare.
+
+use std::f64::consts::SQRT_2;
+use std::time::{Duration, Instant};
+
+use crate::ai::{SearchProfile, MCTSAI};
+use crate::ai_helpers::choose_smart_move_fast;
+use crate::args::Flags;
+use crate::optimized_game::FastGameState;
+use crate::rng::GameRng;
+use crate::state_repr::GameStateRepr;
+
+/// Games played by the Smart AI to produce the benchmark positions
+const POSITION_SEEDS: [u64; 4] = [1, 2, 3, 4];
+/// A position is taken every this many moves of each game
+const POSITION_INTERVAL: usize = 6;
+const POSITIONS_PER_GAME: usize = 4;
+const SEARCH_SEED: u64 = 0x5552_4245_4e43;
+
+pub struct BenchOptions {
+    pub simulations: usize,
+    pub threads: usize,
+    /// Split each search into this many seeded streams (`MCTSAI::streams`),
+    /// so the signature no longer depends on the thread count
+    pub streams: Option<usize>,
+    /// Print the search's work counters, totalled over all positions
+    pub profile: bool,
+}
+
+impl BenchOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--sims", "--threads", "--streams"], &["--ai-profile"])?;
+        Ok(BenchOptions {
+            simulations: flags.get("--sims", 5000)?,
+            threads: flags.get("--threads", 1)?.max(1),
+            streams: flags.get_opt::<usize>("--streams")?.map(|streams| streams.max(1)),
+            profile: flags.has("--ai-profile"),
+        })
+    }
+}
+
+/// Positions (with the roll to search) reached by Smart AI self-play from fixed seeds
+fn bench_positions() -> Vec<(FastGameState, u8)> {
+    let mut positions = Vec::new();
+    for seed in POSITION_SEEDS {
+        let mut dice = GameRng::from_seed(seed);
+        let mut game = FastGameState::new();
+        let mut moves_played = 0;
+        let mut taken = 0;
+
+        while taken < POSITIONS_PER_GAME && !game.status().is_over() {
+            let roll = dice.roll_dice(game.current_player());
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            // Only real decisions make the engine search
+            if moves.len() > 1 && moves_played > 0 && moves_played % POSITION_INTERVAL == 0 {
+                positions.push((game, roll));
+                taken += 1;
+            }
+            let player = game.current_player();
+            game.make_move(choose_smart_move_fast(&game, player, &moves, roll), roll).expect("generated moves are legal");
+            moves_played += 1;
+        }
+    }
+    positions
+}
+
+pub fn run_bench(options: &BenchOptions) {
+    let positions = bench_positions();
+    let mut engine = MCTSAI::new_with_threads(options.simulations, SQRT_2, options.threads);
+    engine.seed = Some(SEARCH_SEED);
+    engine.streams = options.streams;
+
+    println!("=== ENGINE BENCH ===");
+    println!("{} positions | {} simulations each | {} threads{}", positions.len(), options.simulations, options.threads,
+             options.streams.map_or(String::new(), |streams| format!(" | {} streams", streams)));
+    println!();
+
+    let mut nodes = 0;
+    let mut elapsed = Duration::ZERO;
+    let mut profile = SearchProfile::default();
+    // FNV-1a over every chosen move and root visit count
+    let mut signature: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut mix = |value: usize| {
+        signature ^= value as u64;
+        signature = signature.wrapping_mul(0x0100_0000_01b3);
+    };
+
+    for (idx, (game, roll)) in positions.iter().enumerate() {
+        let start = Instant::now();
+        let Some((piece, info)) = engine.choose_move_with_info(game, game.current_player(), *roll) else {
+            continue;
+        };
+        elapsed += start.elapsed();
+        nodes += info.nodes;
+        profile.add(&info.profile);
+
+        mix(piece as usize);
+        for candidate in &info.candidates {
+            mix(candidate.piece as usize);
+            mix(candidate.visits);
+        }
+        let depth = engine.last_report().map_or(0, |report| report.depth);
+        println!("  position {:>2}: roll {}, best piece {} | {} | depth {}", idx + 1, roll, piece, info.summary(), depth);
+    }
+
+    println!();
+    println!("Nodes searched: {}", nodes);
+    println!("Total time:     {:.3} s", elapsed.as_secs_f64());
+    println!("Signature:      {:016x}", signature);
+    if options.profile {
+        println!("Profile:        {}", profile.summary());
+    }
+    println!("Nodes/second:   {:.0}", nodes as f64 / elapsed.as_secs_f64().max(1e-9));
+}
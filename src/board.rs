@@ -0,0 +1,257 @@
+//! Board layouts: which squares exist, where they are drawn, the path each
+//! player's pieces travel and which squares are rosettes or safe. The layout is
+//! chosen once at startup (`--board`) and read by the game state, the AI and
+//! the display; only the variant study switches it between games, and only
+//! the house-rule explorer moves its rosettes and safe squares. Besides the
+//! built-in layouts, one custom layout per run can be loaded from a file (see
+//! `layout_file`).
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+
+use crate::args::Flags;
+use crate::layout_file;
+
+/// Most squares a layout may have; each player's occupancy bitboard is this wide
+pub const MAX_SQUARES: u8 = 32;
+
+/// Static description of one board.
+/// Squares are numbered 0.. in `coords` order; rosettes and safe squares are bitmasks over them.
+#[derive(Debug)]
+pub struct BoardLayout {
+    pub rows: usize,
+    pub cols: usize,
+    /// Grid (row, col) of each square
+    pub coords: &'static [(usize, usize)],
+    /// Squares visited by Player 1 and Player 2, in order (at most 30 each, so
+    /// positions fit `FastGameState`'s 5-bit fields)
+    pub paths: [&'static [u8]; 2],
+    /// Landing here grants an extra turn
+    pub rosettes: u32,
+    /// Pieces here cannot be captured
+    pub safe: u32,
+}
+
+impl BoardLayout {
+    /// The rosette both players' paths cross, if the layout has one
+    pub fn central_rosette(&self) -> Option<u8> {
+        (0..self.coords.len() as u8).find(|&square| {
+            (self.rosettes >> square) & 1 != 0 && self.paths.iter().all(|path| path.contains(&square))
+        })
+    }
+}
+
+/// The traditional 20-square board with a 14-square path
+const STANDARD: BoardLayout = BoardLayout {
+    rows: 3,
+    cols: 8,
+    coords: &[
+        (0, 0), (0, 1), (0, 2), (0, 3), (0, 6), (0, 7),
+        (1, 0), (1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (1, 6), (1, 7),
+        (2, 0), (2, 1), (2, 2), (2, 3), (2, 6), (2, 7),
+    ],
+    paths: [
+        &[3, 2, 1, 0, 6, 7, 8, 9, 10, 11, 12, 13, 5, 4],
+        &[17, 16, 15, 14, 6, 7, 8, 9, 10, 11, 12, 13, 19, 18],
+    ],
+    rosettes: (1 << 4) | (1 << 9) | (1 << 18),
+    safe: (1 << 0) | (1 << 4) | (1 << 9) | (1 << 14) | (1 << 18),
+};
+
+/// The standard board with the central rosette open to capture; landing there
+/// still grants an extra turn
+const OPEN_ROSETTE: BoardLayout = BoardLayout {
+    safe: STANDARD.safe & !(1 << 9),
+    ..STANDARD
+};
+
+/// The standard board with all five decorated squares as rosettes: the corner
+/// squares before the shared lane grant an extra turn as well
+const FIVE_ROSETTES: BoardLayout = BoardLayout {
+    rosettes: STANDARD.rosettes | (1 << 0) | (1 << 14),
+    ..STANDARD
+};
+
+/// 26 squares: the shared lane is two squares longer and each exit tail has four
+/// squares instead of two, for an 18-square path
+const EXTENDED: BoardLayout = BoardLayout {
+    rows: 3,
+    cols: 10,
+    coords: &[
+        (0, 0), (0, 1), (0, 2), (0, 3), (0, 6), (0, 7), (0, 8), (0, 9),
+        (1, 0), (1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (1, 6), (1, 7), (1, 8), (1, 9),
+        (2, 0), (2, 1), (2, 2), (2, 3), (2, 6), (2, 7), (2, 8), (2, 9),
+    ],
+    paths: [
+        &[3, 2, 1, 0, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 7, 6, 5, 4],
+        &[21, 20, 19, 18, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 25, 24, 23, 22],
+    ],
+    rosettes: (1 << 4) | (1 << 11) | (1 << 22),
+    safe: (1 << 0) | (1 << 4) | (1 << 11) | (1 << 18) | (1 << 22),
+};
+
+/// A layout loaded from a file, with the name it goes by
+#[derive(Debug)]
+pub struct CustomBoard {
+    pub name: String,
+    pub description: String,
+    pub layout: BoardLayout,
+}
+
+/// The custom layout loaded this run, if any
+static CUSTOM: OnceLock<CustomBoard> = OnceLock::new();
+
+fn custom() -> &'static CustomBoard {
+    CUSTOM.get().expect("the custom board is only chosen once loaded")
+}
+
+/// Selectable board layouts, encoded by label with the `serde` feature (see `encoding`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoardVariant {
+    #[default]
+    Standard,
+    Extended,
+    OpenRosette,
+    FiveRosettes,
+    /// The layout loaded from a file with `--board FILE`
+    Custom,
+}
+
+impl BoardVariant {
+    /// The built-in boards; the custom one is not listed, as it may not be loaded
+    pub const ALL: [BoardVariant; 4] =
+        [BoardVariant::Standard, BoardVariant::Extended, BoardVariant::OpenRosette, BoardVariant::FiveRosettes];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BoardVariant::Standard => "standard",
+            BoardVariant::Extended => "extended",
+            BoardVariant::OpenRosette => "open-rosette",
+            BoardVariant::FiveRosettes => "five-rosettes",
+            BoardVariant::Custom => &custom().name,
+        }
+    }
+
+    /// How the board differs from the standard one
+    pub fn description(self) -> &'static str {
+        match self {
+            BoardVariant::Standard => "the traditional board",
+            BoardVariant::Extended => "longer shared lane and exit tails",
+            BoardVariant::OpenRosette => "the central rosette is not safe",
+            BoardVariant::FiveRosettes => "the corner squares are rosettes too",
+            BoardVariant::Custom => &custom().description,
+        }
+    }
+
+    /// The board with this label: a built-in one, or the custom one if it is loaded
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|variant| variant.label() == label)
+            .or_else(|| CUSTOM.get().filter(|board| board.name == label).map(|_| BoardVariant::Custom))
+    }
+
+    pub fn layout(self) -> &'static BoardLayout {
+        match self {
+            BoardVariant::Standard => &STANDARD,
+            BoardVariant::Extended => &EXTENDED,
+            BoardVariant::OpenRosette => &OPEN_ROSETTE,
+            BoardVariant::FiveRosettes => &FIVE_ROSETTES,
+            BoardVariant::Custom => &custom().layout,
+        }
+    }
+}
+
+/// Load the layout file at `path` as this run's custom board. Only one can be
+/// loaded; loading the same one again is allowed.
+pub fn load_custom(path: &Path) -> Result<BoardVariant, String> {
+    let board = layout_file::load(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = board.name.clone();
+    // Set only if nothing was loaded before
+    let _ = CUSTOM.set(board);
+    if custom().name != name {
+        return Err(format!("the '{}' board is already loaded; only one custom board can be used at a time", custom().name));
+    }
+    Ok(BoardVariant::Custom)
+}
+
+/// The `--board` flag: a built-in board's label, or a layout file (`.toml`) to
+/// load as the custom board
+pub fn from_flags(flags: &Flags) -> Result<Option<BoardVariant>, String> {
+    match flags.get_str("--board") {
+        Some(file) if file.ends_with(".toml") => load_custom(Path::new(file)).map(Some),
+        _ => flags.get_opt("--board"),
+    }
+}
+
+impl FromStr for BoardVariant {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::from_label(s).ok_or(())
+    }
+}
+
+/// Index in `BoardVariant::ALL` of the chosen board, plus one; 0 until one is chosen
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+fn encode(variant: BoardVariant) -> u8 {
+    BoardVariant::ALL.iter().position(|&v| v == variant).unwrap_or(BoardVariant::ALL.len()) as u8 + 1
+}
+
+/// Choose the board for this run; must happen before any game is created.
+/// Returns false if a different board was already chosen.
+pub fn select(variant: BoardVariant) -> bool {
+    let code = encode(variant);
+    match SELECTED.compare_exchange(0, code, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => true,
+        Err(current) => current == code,
+    }
+}
+
+/// Change the board mid-run, for experiments that compare layouts. No game
+/// may be in progress, as positions mean different squares on another board.
+pub fn switch(variant: BoardVariant) {
+    SELECTED.store(encode(variant), Ordering::Relaxed);
+}
+
+/// The board chosen for this run (standard unless `select` said otherwise)
+#[inline]
+pub fn variant() -> BoardVariant {
+    match SELECTED.load(Ordering::Relaxed) {
+        0 => BoardVariant::default(),
+        code => BoardVariant::ALL.get(code as usize - 1).copied().unwrap_or(BoardVariant::Custom),
+    }
+}
+
+#[inline]
+pub fn layout() -> &'static BoardLayout {
+    variant().layout()
+}
+
+/// Whether `HOUSE_ROSETTES` and `HOUSE_SAFE` replace the layout's squares
+static HOUSE_RULES: AtomicBool = AtomicBool::new(false);
+static HOUSE_ROSETTES: AtomicU32 = AtomicU32::new(0);
+static HOUSE_SAFE: AtomicU32 = AtomicU32::new(0);
+
+/// Play with other rosettes and safe squares (bitmasks, as in `BoardLayout`)
+/// on the chosen board, or with its own again for None. As with `switch`, no
+/// game may be in progress.
+pub fn switch_squares(squares: Option<(u32, u32)>) {
+    let (rosettes, safe) = squares.unwrap_or_default();
+    HOUSE_ROSETTES.store(rosettes, Ordering::Relaxed);
+    HOUSE_SAFE.store(safe, Ordering::Relaxed);
+    HOUSE_RULES.store(squares.is_some(), Ordering::Relaxed);
+}
+
+/// Rosettes in play: the layout's unless `switch_squares` replaced them
+#[inline]
+pub fn rosettes() -> u32 {
+    if HOUSE_RULES.load(Ordering::Relaxed) { HOUSE_ROSETTES.load(Ordering::Relaxed) } else { layout().rosettes }
+}
+
+/// Safe squares in play: the layout's unless `switch_squares` replaced them
+#[inline]
+pub fn safe() -> u32 {
+    if HOUSE_RULES.load(Ordering::Relaxed) { HOUSE_SAFE.load(Ordering::Relaxed) } else { layout().safe }
+}
@@ -0,0 +1,204 @@
+//! Campaign mode: a single-player ladder of computer opponents, weakest first.
+//! Beating an opponent once unlocks the next one. Progress is kept in a
+//! plain-text file, so the climb carries on across runs, with boards and dice
+//! other than the standard ones tracked as ladders of their own.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crossterm::style::Color;
+
+use crate::autotune;
+use crate::error::UrError;
+use crate::input::{prompt_in_range, InputResult};
+use crate::leaderboard::config_name;
+use crate::opponents;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::presets::{self, PLAY_PRESET};
+use crate::rng::GameRng;
+use crate::screen::{Align, Border, Panel, Screen, Span, Table};
+use crate::transcript::outln;
+use crate::{play_game, record_opponent_game, AIType, PlayOptions};
+
+pub const DEFAULT_CAMPAIGN_FILE: &str = "ur_campaign.txt";
+
+const CAMPAIGN_HEADER: &str = "# Royal Game of Ur campaign progress";
+const CAMPAIGN_VERSION: u32 = 1;
+
+/// The ladder, weakest first
+fn ladder() -> [AIType; 6] {
+    let named = |key| AIType::Opponent(opponents::find(key).expect("roster opponent"));
+    [AIType::Random, AIType::Smart, named("raider"), named("scribe"), AIType::MCTS, named("oracle")]
+}
+
+/// Games won and lost against one opponent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Record {
+    wins: usize,
+    losses: usize,
+}
+
+/// Results against each opponent, keyed by its name on the current board and
+/// dice (as on the leaderboard)
+#[derive(Debug, Default, PartialEq)]
+pub struct Progress {
+    records: BTreeMap<String, Record>,
+}
+
+impl Progress {
+    /// Read a progress file; a missing file is a campaign not yet started
+    pub fn load(path: &Path) -> Result<Self, UrError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Progress::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    fn record(&self, opponent: &AIType) -> Record {
+        self.records.get(&config_name(opponent.label())).copied().unwrap_or_default()
+    }
+
+    fn add_result(&mut self, opponent: &AIType, won: bool) {
+        let record = self.records.entry(config_name(opponent.label())).or_default();
+        if won {
+            record.wins += 1;
+        } else {
+            record.losses += 1;
+        }
+    }
+
+    /// How many rungs of `ladder` are open: the first, and each one whose
+    /// predecessor has been beaten
+    fn unlocked(&self, ladder: &[AIType]) -> usize {
+        1 + ladder.iter().take_while(|opponent| self.record(opponent).wins > 0).count().min(ladder.len() - 1)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(CAMPAIGN_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", CAMPAIGN_VERSION));
+        for (name, record) in &self.records {
+            out.push_str(&format!("record {} {} {}\n", name, record.wins, record.losses));
+        }
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut progress = Progress::default();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let err = |message: String| UrError::ParseError { line: idx + 1, message };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["version", v] => {
+                    let v: u32 = v.parse().map_err(|_| err("expected a version number".to_string()))?;
+                    if v != CAMPAIGN_VERSION {
+                        return Err(err(format!("unsupported campaign version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                ["record", name, wins, losses] => {
+                    let (Ok(wins), Ok(losses)) = (wins.parse(), losses.parse()) else {
+                        return Err(err("wins and losses must be numbers".to_string()));
+                    };
+                    progress.records.insert(name.to_string(), Record { wins, losses });
+                }
+                _ => return Err(err(format!("unrecognized line '{}'", line))),
+            }
+        }
+
+        version.ok_or_else(|| UrError::parse("missing 'version'"))?;
+        Ok(progress)
+    }
+}
+
+/// Show the ladder and play the opponents the human picks, as Player 1,
+/// until they leave
+pub fn run_campaign(options: &PlayOptions) -> InputResult<()> {
+    let path = Path::new(DEFAULT_CAMPAIGN_FILE);
+    let mut progress = match Progress::load(path) {
+        Ok(progress) => progress,
+        Err(e) => {
+            outln!("❌ Could not read {}: {}", path.display(), e);
+            return Ok(());
+        }
+    };
+    let ladder = ladder();
+    let mcts_ai = presets::lookup(PLAY_PRESET).expect("built-in preset").build(autotune::threads());
+
+    loop {
+        let unlocked = progress.unlocked(&ladder);
+        show_ladder(&progress, &ladder, unlocked);
+        let choice = prompt_in_range(
+            &format!("Challenge which opponent? [1-{}, 0 to leave] (default {}): ", unlocked, unlocked),
+            0..=unlocked,
+            unlocked,
+        )?;
+        if choice == 0 {
+            return Ok(());
+        }
+
+        let opponent = &ladder[choice - 1];
+        let players = [AIType::Human, opponent.clone()];
+        outln!();
+        let Some(summary) = play_game(FastGameState::new(), players.clone(), &mcts_ai, options, GameRng::random())? else {
+            continue;
+        };
+        record_opponent_game(&players, summary.winner, &mcts_ai);
+        let won = summary.winner == Some(FastPlayer::One);
+        progress.add_result(opponent, won);
+        if let Err(e) = progress.save(path) {
+            outln!("⚠️  Could not save campaign progress to {}: {}", path.display(), e);
+        }
+
+        outln!();
+        if !won {
+            outln!("{} holds the rung. Try again when you're ready.", opponent.display_name());
+        } else if progress.unlocked(&ladder) > unlocked {
+            outln!("🔓 {} is unlocked!", ladder[unlocked].display_name());
+        } else if choice == ladder.len() {
+            outln!("🏆 You have beaten {}, the top of the ladder!", opponent.display_name());
+        }
+        outln!();
+    }
+}
+
+fn show_ladder(progress: &Progress, ladder: &[AIType], unlocked: usize) {
+    let mut table = Table::new(&[Align::Right, Align::Left, Align::Right], 2);
+    for (i, opponent) in ladder.iter().enumerate() {
+        let record = progress.record(opponent);
+        let (mark, color) = if i >= unlocked {
+            ("🔒", Color::DarkGrey)
+        } else if record.wins > 0 {
+            ("✅", Color::Green)
+        } else {
+            ("⚔️", Color::Yellow)
+        };
+        table.row(vec![
+            Span::plain(if i < unlocked { format!("{}.", i + 1) } else { String::new() }),
+            Span::colored(format!("{} {}", mark, opponent.display_name()), color),
+            Span::plain(format!("{}-{}", record.wins, record.losses)),
+        ]);
+    }
+
+    let mut panel = Panel::new(Border::Double, 39);
+    panel.centered(vec![Span::plain("🏛️  Campaign Ladder  🏛️")]);
+    panel.rule();
+    panel.table(&table);
+    let mut screen = Screen::new();
+    screen.panel(&panel);
+    screen.print();
+}
@@ -0,0 +1,36 @@
+//! Cancellation for AI searches. A search is handed a `CancelToken` and checks
+//! it before every playout; whoever holds a clone (the game UI when a key asks
+//! for the move now, the referee when a move clock runs out) can cancel it, and
+//! the search then returns the best move it has found so far.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    /// The token also counts as cancelled from this moment
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by `cancel`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that cancels itself at `deadline`
+    pub fn expiring_at(deadline: Instant) -> Self {
+        CancelToken { deadline: Some(deadline), ..Self::default() }
+    }
+
+    /// Ask every search holding this token, or a clone of it, to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
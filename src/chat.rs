@@ -0,0 +1,135 @@
+//! Talking across the board in an interactive game: chat lines, draw offers
+//! and rematch requests, typed as slash commands (`/say`, `/draw`,
+//! `/rematch`) or sent by a seated bot as JSON lines. A draw or rematch
+//! happens once both sides have asked for it. In-process AIs never take a
+//! draw but are always ready for another game. Messages are cut to
+//! `MAX_LEN` characters and each seat may send `RATE_LIMIT` of them every
+//! `RATE_WINDOW`, so a bot cannot flood the screen.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::optimized_game::FastPlayer;
+use crate::json::json_string;
+use crate::subprocess_bot::string_field;
+
+/// Longest chat message, in characters
+pub const MAX_LEN: usize = 200;
+/// Messages a seat may send within `RATE_WINDOW`
+pub const RATE_LIMIT: usize = 3;
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+/// Chat lines kept for the pane under the board
+const PANE_LINES: usize = 4;
+
+/// Something one side says to the other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Said {
+    Text(String),
+    /// Offers a draw, or accepts the one on offer
+    Draw,
+    /// Asks for another game with the same sides
+    Rematch,
+}
+
+impl Said {
+    /// A line from a bot, if it is one of `{"type":"chat","text":"..."}`,
+    /// `{"type":"draw"}` or `{"type":"rematch"}` rather than a reply
+    pub fn from_bot(line: &str) -> Option<Said> {
+        match string_field(line, "type")?.as_str() {
+            "chat" => Some(Said::Text(clean(&string_field(line, "text").unwrap_or_default()))),
+            "draw" => Some(Said::Draw),
+            "rematch" => Some(Said::Rematch),
+            _ => None,
+        }
+    }
+
+    /// The message a bot is sent when the player at `seat` says this
+    pub fn to_json(&self, seat: FastPlayer) -> String {
+        let seat = seat as usize + 1;
+        match self {
+            Said::Text(text) => format!(r#"{{"type":"chat","seat":{},"text":{}}}"#, seat, json_string(text)),
+            Said::Draw => format!(r#"{{"type":"draw","seat":{}}}"#, seat),
+            Said::Rematch => format!(r#"{{"type":"rematch","seat":{}}}"#, seat),
+        }
+    }
+}
+
+/// `text` as it may be shown: control characters become spaces and it is
+/// cut to `MAX_LEN` characters
+pub fn clean(text: &str) -> String {
+    text.trim().chars().map(|c| if c.is_control() { ' ' } else { c }).take(MAX_LEN).collect()
+}
+
+/// What became of something a seat said
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Heard {
+    /// A chat line, as it goes in the pane
+    Line(String),
+    /// The seat is over its rate limit; the message was dropped
+    TooFast,
+    /// The seat offered a draw, or asked for a rematch, for the first time
+    Asked,
+    /// The seat had already asked
+    Repeated,
+    /// Both sides now want it
+    Agreed,
+}
+
+/// Chat in one game: the latest lines for the pane, each seat's rate limit
+/// and who has asked for a draw or a rematch
+pub struct Chat {
+    lines: Vec<String>,
+    sent: [VecDeque<Instant>; 2],
+    draw: [bool; 2],
+    rematch: [bool; 2],
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Chat { lines: Vec::new(), sent: Default::default(), draw: [false; 2], rematch: [false; 2] }
+    }
+
+    /// Take in what the player at `seat`, called `name`, said
+    pub fn hear(&mut self, seat: FastPlayer, name: &str, said: &Said) -> Heard {
+        let asked = match said {
+            Said::Text(text) => {
+                let now = Instant::now();
+                let sent = &mut self.sent[seat as usize];
+                while sent.front().is_some_and(|&at| now - at >= RATE_WINDOW) {
+                    sent.pop_front();
+                }
+                if sent.len() >= RATE_LIMIT {
+                    return Heard::TooFast;
+                }
+                sent.push_back(now);
+                let line = format!("{}: {}", name, text);
+                if self.lines.len() == PANE_LINES {
+                    self.lines.remove(0);
+                }
+                self.lines.push(line.clone());
+                return Heard::Line(line);
+            }
+            Said::Draw => &mut self.draw,
+            Said::Rematch => &mut self.rematch,
+        };
+        if asked[seat.opposite() as usize] {
+            asked[seat as usize] = true;
+            Heard::Agreed
+        } else if asked[seat as usize] {
+            Heard::Repeated
+        } else {
+            asked[seat as usize] = true;
+            Heard::Asked
+        }
+    }
+
+    /// The latest chat lines, oldest first
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Whether both sides asked for a rematch
+    pub fn rematch_agreed(&self) -> bool {
+        self.rematch == [true; 2]
+    }
+}
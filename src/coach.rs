@@ -0,0 +1,92 @@
+//! Post-move feedback for learning players: spot a clearly inferior human move
+//! and explain it in one line from the two moves' descriptions, or summarize
+//! the candidates an MCTS move was chosen from.
+
+use crate::ai::SearchInfo;
+use crate::ai_helpers::{describe_move, evaluate_move_weighted, EvalWeights, MoveDescription};
+use crate::display::global_to_coord;
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+/// How far below the best move (in evaluation points) a move must score to be called weak
+const WEAK_MOVE_MARGIN: f64 = 100.0;
+
+/// A one-line explanation if `chosen` is clearly worse than the best move in `moves`
+pub fn explain_weak_move(game: &FastGameState, player: FastPlayer, chosen: u8, moves: &[u8], roll: u8) -> Option<String> {
+    let score = |piece_idx: u8| evaluate_move_weighted(game, player, piece_idx, roll, &EvalWeights::COACH);
+    let best = moves.iter().copied().max_by(|&a, &b| score(a).total_cmp(&score(b)))?;
+    if score(best) - score(chosen) < WEAK_MOVE_MARGIN {
+        return None;
+    }
+
+    let chosen = describe_move(game, player, chosen, roll);
+    let best = describe_move(game, player, best, roll);
+    let alternative = action(&best);
+
+    Some(match advantage(&best, &chosen) {
+        Some(advantage) if chosen.exposure > best.exposure + 0.1 => {
+            let (r, c) = global_to_coord(chosen.square.unwrap_or_default());
+            format!("This left piece {} exposed on grid ({}, {}) ({:.0}% chance of capture); {} {}",
+                    chosen.piece, r, c, chosen.exposure * 100.0, alternative, advantage)
+        }
+        Some(advantage) => format!("This passed up {}, which {}", alternative, advantage),
+        None => format!("This passed up a stronger move: {}", alternative),
+    })
+}
+
+/// Candidates shown by `explain_candidates`
+const TOP_CANDIDATES: usize = 3;
+
+/// One line per most-visited MCTS candidate: visit share, win rate and reasons.
+/// Empty when the search kept no root statistics (heuristic AIs, forced moves).
+pub fn explain_candidates(game: &FastGameState, player: FastPlayer, roll: u8, info: &SearchInfo) -> Vec<String> {
+    let total = info.nodes.max(1) as f64;
+    info.candidates.iter().take(TOP_CANDIDATES).map(|candidate| {
+        let description = describe_move(game, player, candidate.piece, roll);
+        let reasons = description.reasons();
+        let reasons = if reasons.is_empty() { String::new() } else { format!(" — {}", reasons.join(", ")) };
+        format!("{}: {:.0}% of visits, {:.1}% wins{}",
+                candidate_label(&description), candidate.visits as f64 / total * 100.0,
+                candidate.win_rate * 100.0, reasons)
+    }).collect()
+}
+
+/// A candidate move, naming the piece even when it enters
+fn candidate_label(mv: &MoveDescription) -> String {
+    if mv.enters() {
+        format!("piece {} enters", mv.piece)
+    } else if mv.finishes() {
+        format!("piece {} exits", mv.piece)
+    } else {
+        let (r, c) = global_to_coord(mv.square.unwrap_or_default());
+        format!("piece {} → grid ({}, {})", mv.piece, r, c)
+    }
+}
+
+/// A move, as a noun phrase
+fn action(mv: &MoveDescription) -> String {
+    if mv.enters() {
+        "entering a new piece".to_string()
+    } else if mv.finishes() {
+        format!("bearing piece {} off", mv.piece)
+    } else {
+        let (r, c) = global_to_coord(mv.square.unwrap_or_default());
+        format!("moving piece {} to grid ({}, {})", mv.piece, r, c)
+    }
+}
+
+/// Why the better move is better, if one of its features explains it
+fn advantage(best: &MoveDescription, chosen: &MoveDescription) -> Option<&'static str> {
+    if best.wins {
+        Some("would have won the game")
+    } else if best.captures && !chosen.captures {
+        Some("would have captured")
+    } else if best.rosette && !chosen.rosette {
+        Some("would have earned an extra roll")
+    } else if best.exposure + 0.1 < chosen.exposure {
+        Some("was safer")
+    } else if best.threat_escaped > chosen.threat_escaped + 0.1 {
+        Some("would have moved a threatened piece out of danger")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,155 @@
+//! Side-by-side evaluations for `ur demo --compare` and `ur disagree`. Before each move, both
+//! engines rate the position: the one to move and the one waiting, which
+//! answers as though it were its turn. The viewer sees what each would play
+//! and how much it thinks the choice matters. An MCTS engine's numbers come
+//! from its own search. Any other engine still picks its own move, but it has
+//! no win estimate of its own, so its moves are rated by the race model that
+//! `luck` uses. When the engines pick different moves, the disagreement is how
+//! much the other engine's pick costs, measured by whichever engine's numbers
+//! give the larger cost.
+
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use std::io;
+
+use crate::AIType;
+use crate::ai::{HybridAI, SearchReport};
+use crate::luck;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::stats::StatsAgent;
+use crate::style;
+
+/// Points of win probability a disagreement must reach to be highlighted
+pub const DEFAULT_THRESHOLD: f64 = 5.0;
+/// Alternatives listed after an engine's pick
+const ALTERNATIVES: usize = 2;
+
+/// One engine's view of a roll: the move it picks and the win probability,
+/// for the player to move, it gives each move it looked at
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub choice: u8,
+    pub values: Vec<(u8, f64)>,
+    /// Whether the values are the engine's own search rather than the race model
+    pub searched: bool,
+}
+
+impl Evaluation {
+    /// The win probability given to `piece`, if the engine looked at it
+    pub fn value(&self, piece: u8) -> Option<f64> {
+        self.values.iter().find(|&&(p, _)| p == piece).map(|&(_, value)| value)
+    }
+}
+
+/// Ask `ai_type` for its move and its numbers for the position
+pub fn evaluate(ai_type: &AIType, game: &FastGameState, moves: &[u8], roll: u8, mcts_ai: &HybridAI) -> Evaluation {
+    if let AIType::Tuned(tuned) = ai_type {
+        return evaluate_agent(tuned.agent(), game, moves, roll);
+    }
+    let choice = ai_type.choose_move(game, moves, roll, mcts_ai);
+    let report = match ai_type {
+        AIType::MCTS | AIType::Adaptive => mcts_ai.mcts.last_report(),
+        _ => None,
+    };
+    rate(game, moves, roll, choice, report)
+}
+
+/// Ask a player spec's agent for its move and its numbers for the position
+pub fn evaluate_agent(agent: &StatsAgent, game: &FastGameState, moves: &[u8], roll: u8) -> Evaluation {
+    let (choice, _) = agent.choose_move_with_info(game, moves, roll);
+    let report = match agent {
+        StatsAgent::MCTS(ai) => ai.mcts.last_report(),
+        _ => None,
+    };
+    rate(game, moves, roll, choice, report)
+}
+
+/// The numbers behind `choice`: the search's, if it searched the move, or the race model's
+fn rate(game: &FastGameState, moves: &[u8], roll: u8, choice: u8, report: Option<SearchReport>) -> Evaluation {
+    let searched: Vec<(u8, f64)> = report.map_or_else(Vec::new, |report| {
+        report.children.iter().filter(|child| child.visits > 0).map(|child| (child.piece, child.win_rate)).collect()
+    });
+    if searched.iter().any(|&(piece, _)| piece == choice) {
+        return Evaluation { choice, values: searched, searched: true };
+    }
+    let player = game.current_player();
+    let values = moves.iter().map(|&piece| (piece, race_value(game, player, piece, roll))).collect();
+    Evaluation { choice, values, searched: false }
+}
+
+/// The race model's win probability for `player` after moving `piece`
+fn race_value(game: &FastGameState, player: FastPlayer, piece: u8, roll: u8) -> f64 {
+    let mut next = *game;
+    match next.make_move(piece, roll) {
+        Ok(_) => luck::win_probability(&next, player),
+        Err(_) => 0.0,
+    }
+}
+
+/// Whether moving `a` and moving `b` leave the same position, as entering
+/// either of two waiting pieces does
+fn same_move(game: &FastGameState, roll: u8, a: u8, b: u8) -> bool {
+    let after = |piece: u8| {
+        let mut next = *game;
+        next.make_move(piece, roll).ok().map(|_| next)
+    };
+    a == b || after(a).is_some_and(|next| after(b) == Some(next))
+}
+
+/// Points of win probability the engines' disagreement is worth: what each
+/// thinks the other's pick gives up, whichever is more; 0 when they agree
+pub fn disagreement(game: &FastGameState, roll: u8, a: &Evaluation, b: &Evaluation) -> f64 {
+    if same_move(game, roll, a.choice, b.choice) {
+        return 0.0;
+    }
+    let cost = |judge: &Evaluation, other: &Evaluation| match (judge.value(judge.choice), judge.value(other.choice)) {
+        (Some(own), Some(theirs)) => own - theirs,
+        _ => 0.0,
+    };
+    cost(a, b).max(cost(b, a)).max(0.0) * 100.0
+}
+
+/// Whether the engines pick different moves, counting moves to the same
+/// position as one
+pub fn differ(game: &FastGameState, roll: u8, a: &Evaluation, b: &Evaluation) -> bool {
+    !same_move(game, roll, a.choice, b.choice)
+}
+
+/// Print both seats' evaluations, marking the one to move, and how far apart
+/// they are, highlighted at `threshold` points or more
+pub fn show(game: &FastGameState, roll: u8, evals: &[Evaluation; 2], seats: [&AIType; 2], threshold: f64) {
+    let mover = game.current_player();
+    for (seat, (eval, ai_type)) in [FastPlayer::One, FastPlayer::Two].into_iter().zip(evals.iter().zip(seats)) {
+        let marker = if seat == mover { "▶" } else { " " };
+        let mut line = format!("  {} {:<14} piece {} ", marker, ai_type.display_name(), eval.choice);
+        if let Some(value) = eval.value(eval.choice) {
+            line.push_str(&format!("{:>3.0}%", value * 100.0));
+        }
+        let mut others: Vec<_> = eval.values.iter().filter(|&&(piece, _)| piece != eval.choice).collect();
+        others.sort_by(|a, b| b.1.total_cmp(&a.1));
+        if !others.is_empty() {
+            let listed: Vec<String> = others.iter().take(ALTERNATIVES)
+                .map(|&&(piece, value)| format!("{} {:.0}%", piece, value * 100.0))
+                .collect();
+            line.push_str(&format!("  (else {})", listed.join(", ")));
+        }
+        line.push_str(if eval.searched { "  by its search" } else { "  by the race model" });
+        let _ = execute!(io::stdout(), SetForegroundColor(style::of(seat).color), Print(line), ResetColor, Print("\n"));
+    }
+
+    let gap = disagreement(game, roll, &evals[0], &evals[1]);
+    if !differ(game, roll, &evals[0], &evals[1]) {
+        println!("    they agree");
+    } else if gap >= threshold {
+        let _ = execute!(
+            io::stdout(),
+            SetForegroundColor(Color::Yellow),
+            Print(format!("  ⚡ They disagree by {:.1} points\n", gap)),
+            ResetColor
+        );
+    } else {
+        println!("    they differ by {:.1} points", gap);
+    }
+}
@@ -0,0 +1,113 @@
+//! The daily challenge: one game a day against Scribe on the standard board,
+//! with dice seeded from the UTC date so every player gets the same rolls, and
+//! a one-line result to share.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::input::InputResult;
+use crate::opponents;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::presets::{self, PLAY_PRESET};
+use crate::rng::{self, GameRng, RngAlgorithm};
+use crate::session::GameSummary;
+use crate::transcript::outln;
+use crate::{play_game, record_opponent_game, AIType, PlayOptions};
+
+/// The opponent everyone faces
+const OPPONENT: &str = "scribe";
+
+/// A calendar date in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Today's date in UTC
+    pub fn today() -> Self {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self::from_days((secs / 86_400) as i64)
+    }
+
+    /// The date `days` days after 1970-01-01 (Howard Hinnant's civil_from_days)
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// The dice seed for this date: the date written as YYYYMMDD, so the
+    /// challenge can be replayed by entering it as the seed in game setup
+    pub fn seed(self) -> u64 {
+        (self.year * 10_000 + i64::from(self.month) * 100 + i64::from(self.day)) as u64
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Play today's challenge as Player 1 and print the result to share
+pub fn run_daily(options: &PlayOptions) -> InputResult<()> {
+    if !board::select(BoardVariant::default()) || !dice::select(DiceVariant::default()) {
+        outln!("❌ The daily challenge is played on the standard board with the standard dice; run it without --board and --dice.");
+        return Ok(());
+    }
+    if dice::is_biased() {
+        outln!("❌ The daily challenge is played with fair dice; run it without --p1-pip-chance and --p2-pip-chance.");
+        return Ok(());
+    }
+    if rng::algorithm() != RngAlgorithm::default() {
+        outln!("❌ Everyone's daily dice come from the same seed and generator; run it without --rng.");
+        return Ok(());
+    }
+    let date = Date::today();
+    let opponent = opponents::find(OPPONENT).expect("roster opponent");
+    let players = [AIType::Human, AIType::Opponent(opponent)];
+
+    outln!("📅 Daily challenge for {} (UTC)", date);
+    outln!("Everyone playing today gets the same dice. You are Player 1 against {}, who plays {}.", opponent.name, opponent.style);
+    outln!();
+
+    // Never consulted, as neither player is an MCTS AI
+    let mcts_ai = presets::lookup(PLAY_PRESET).expect("built-in preset").build(1);
+    let Some(summary) = play_game(FastGameState::new(), players.clone(), &mcts_ai, options, GameRng::from_seed(date.seed()))? else {
+        return Ok(());
+    };
+    record_opponent_game(&players, summary.winner, &mcts_ai);
+
+    outln!();
+    outln!("📤 Share your result:");
+    outln!("{}", share_string(date, opponent.name, &summary));
+    Ok(())
+}
+
+/// One line summing up a daily game for Player 1, e.g.
+/// "Ur daily 2026-10-16 vs Scribe: 🏆 won 7-5 in 48 turns, 3 captures, efficiency 91.4"
+pub fn share_string(date: Date, opponent: &str, summary: &GameSummary) -> String {
+    let [mine, theirs] = summary.scores;
+    let result = match summary.winner {
+        Some(FastPlayer::One) => "🏆 won",
+        Some(FastPlayer::Two) => "💀 lost",
+        None => "🤝 drew",
+    };
+    let captures = summary.tally.captures[0];
+    let efficiency = summary.efficiency[0].map_or(String::new(), |efficiency| format!(", efficiency {:.1}", efficiency));
+    format!(
+        "Ur daily {} vs {}: {} {}-{} in {} turns, {} capture{}{}",
+        date, opponent, result, mine, theirs, summary.tally.turns, captures, if captures == 1 { "" } else { "s" }, efficiency,
+    )
+}
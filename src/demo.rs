@@ -0,0 +1,317 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::{thread, time::Duration};
+use crossterm::{
+    execute,
+    style::{Print, ResetColor, SetForegroundColor},
+};
+
+use crate::AIType;
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::ai::HybridAI;
+use crate::compare;
+use crate::presets::{self, Preset, DEMO_PRESET};
+use crate::frontend::ordinal;
+use crate::display::{move_squares, print_score, redraw_board, show_winner};
+use crate::game_record::GameRecord;
+use crate::history::GameHistory;
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::screen::{Align, Border, Panel, Screen, Span, Table};
+use crate::scripting;
+use crate::state_repr::GameStateRepr;
+use crate::stats::{ExtraRolls, StatsAgent, TunedAI};
+use crate::style::{self, Styles};
+
+/// Matchups the demo cycles through; a script AI adds `SCRIPT_MATCHUPS`
+const DEMO_MATCHUPS: [(AIType, AIType); 4] = [
+    (AIType::Smart, AIType::Random),
+    (AIType::MCTS, AIType::Smart),
+    (AIType::Random, AIType::MCTS),
+    (AIType::Smart, AIType::Smart),
+];
+const SCRIPT_MATCHUPS: [(AIType, AIType); 2] = [
+    (AIType::Script, AIType::Smart),
+    (AIType::MCTS, AIType::Script),
+];
+/// A capture stays on screen this many move delays
+const CAPTURE_DELAYS: u32 = 3;
+
+/// Options for the attract mode, parsed from `ur demo [--games N] [--delay MS]`
+pub struct DemoOptions {
+    /// Stop after this many games (None = run forever)
+    pub max_games: Option<usize>,
+    /// Pause after each move
+    pub move_delay: Duration,
+    /// Board layout to play on
+    pub board: BoardVariant,
+    /// Dice to roll
+    pub dice: DiceVariant,
+    /// Directory to write a record of every game to
+    pub record: Option<PathBuf>,
+    /// MCTS preset for the MCTS player
+    pub preset: &'static Preset,
+    /// The one matchup to play, from `--p1` and `--p2`, instead of the cycle
+    pub matchup: Option<(AIType, AIType)>,
+    /// Rhai script to add to the matchups as an AI
+    pub script: Option<PathBuf>,
+    /// Player colors, piece glyphs and board orientation
+    pub styles: Styles,
+    /// Show both engines' evaluations every move, highlighting disagreements
+    /// of at least this many points, from `--compare [--threshold PTS]`
+    pub compare: Option<f64>,
+}
+
+impl DemoOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(
+            args,
+            &["--games", "--delay", "--board", "--dice", "--record", "--preset", "--script", "--p1", "--p2",
+              "--p1-color", "--p2-color", "--pieces", "--view", "--threshold"],
+            &["--compare"],
+        )?;
+        // A player spec given for either seat plays the Smart AI unless both are
+        let matchup = match (flags.get_str("--p1"), flags.get_str("--p2")) {
+            (None, None) => None,
+            (p1, p2) => {
+                let threads = thread::available_parallelism().map_or(1, |n| n.get());
+                let seat = |spec: Option<&str>| StatsAgent::from_spec(spec.unwrap_or("smart"), threads).map(|agent| AIType::Tuned(TunedAI::new(agent)));
+                Some((seat(p1)?, seat(p2)?))
+            }
+        };
+        let threshold = flags.get_opt("--threshold")?;
+        let compare = match (flags.has("--compare"), threshold) {
+            (false, Some(_)) => return Err("--threshold needs --compare".to_string()),
+            (false, None) => None,
+            (true, threshold) => Some(threshold.unwrap_or(compare::DEFAULT_THRESHOLD)),
+        };
+        Ok(DemoOptions {
+            max_games: flags.get_opt("--games")?,
+            move_delay: Duration::from_millis(flags.get("--delay", 700)?),
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            record: flags.get_str("--record").map(PathBuf::from),
+            preset: presets::lookup(flags.get_str("--preset").unwrap_or(DEMO_PRESET)).map_err(|e| e.to_string())?,
+            script: flags.get_str("--script").map(PathBuf::from),
+            matchup,
+            styles: Styles::from_flags(&flags)?,
+            compare,
+        })
+    }
+}
+
+/// Rolling win/loss record for each AI type shown between games
+struct Leaderboard {
+    entries: Vec<(String, usize, usize)>, // (name, wins, games)
+}
+
+impl Leaderboard {
+    fn new() -> Self {
+        Leaderboard { entries: Vec::new() }
+    }
+
+    fn record(&mut self, name: &str, won: bool) {
+        let idx = match self.entries.iter().position(|(n, _, _)| n == name) {
+            Some(idx) => idx,
+            None => {
+                self.entries.push((name.to_string(), 0, 0));
+                self.entries.len() - 1
+            }
+        };
+        let entry = &mut self.entries[idx];
+        entry.2 += 1;
+        if won {
+            entry.1 += 1;
+        }
+    }
+
+    fn display(&self, games_played: usize) {
+        let mut ranked = self.entries.clone();
+        ranked.sort_by(|a, b| {
+            let rate_a = a.1 as f64 / a.2.max(1) as f64;
+            let rate_b = b.1 as f64 / b.2.max(1) as f64;
+            rate_b.total_cmp(&rate_a)
+        });
+
+        let mut table = Table::new(&[Align::Right, Align::Left, Align::Right, Align::Left, Align::Right], 1);
+        for (rank, (name, wins, games)) in ranked.iter().enumerate() {
+            let pct = *wins as f64 / (*games).max(1) as f64 * 100.0;
+            table.row(vec![
+                Span::plain(format!("{}.", rank + 1)),
+                Span::plain(name.clone()),
+                Span::plain(wins.to_string()),
+                Span::plain(format!("/ {}", games)),
+                Span::plain(format!("{:.1}%", pct)),
+            ]);
+        }
+
+        let mut panel = Panel::new(Border::Single, 37);
+        panel.line(vec![Span::plain(format!("🏆 DEMO LEADERBOARD ({} games)", games_played))]);
+        panel.rule();
+        panel.table(&table);
+        let mut screen = Screen::new();
+        screen.blank();
+        screen.panel(&panel);
+        screen.print();
+    }
+}
+
+/// Play AI-vs-AI games forever (or until `max_games`), cycling matchups
+pub fn run_demo(options: &DemoOptions) {
+    board::select(options.board);
+    dice::select(options.dice);
+    style::select(options.styles);
+    let mut matchups = DEMO_MATCHUPS.to_vec();
+    if let Some(path) = &options.script {
+        if let Err(e) = scripting::load(path) {
+            eprintln!("Error: {}", e);
+            return;
+        }
+        matchups.extend(SCRIPT_MATCHUPS);
+    }
+    if let Some(matchup) = &options.matchup {
+        matchups = vec![matchup.clone()];
+    }
+    let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let mcts_ai = options.preset.build(num_cpus);
+    let mut leaderboard = Leaderboard::new();
+    let mut games_played = 0;
+
+    for (p1, p2) in matchups.iter().cycle() {
+        if options.max_games.is_some_and(|max| games_played >= max) {
+            break;
+        }
+        games_played += 1;
+
+        let (winner, history) = play_demo_game(games_played, p1, p2, &mcts_ai, options.move_delay, options.compare);
+        if let Some(dir) = &options.record {
+            let record = GameRecord::from_history(&history, [p1.label().to_string(), p2.label().to_string()], Some(GameStatus::Won(winner)));
+            if let Err(e) = record.write_to_dir(dir) {
+                println!("❌ Could not record the game: {}", e);
+            }
+        }
+        leaderboard.record(p1.display_name(), winner == FastPlayer::One);
+        leaderboard.record(p2.display_name(), winner == FastPlayer::Two);
+
+        leaderboard.display(games_played);
+        if options.max_games != Some(games_played) {
+            println!("\nNext game starting soon... (ctrl-C to exit)");
+            thread::sleep(options.move_delay * 6);
+        }
+    }
+}
+
+fn play_demo_game(
+    game_number: usize,
+    p1: &AIType,
+    p2: &AIType,
+    mcts_ai: &HybridAI,
+    move_delay: Duration,
+    compare: Option<f64>,
+) -> (FastPlayer, GameHistory) {
+    let mut game = FastGameState::new();
+    let mut history = GameHistory::new(game);
+    let mut turn = 0;
+    let mut captures = [0; 2];
+    let mut extra_rolls = ExtraRolls::default();
+    // Decisions with a real choice, those the engines split on, and those over the threshold
+    let mut compared = [0; 3];
+    let caption = format!("🎬 DEMO — Game {}: {} vs {}", game_number, p1.display_name(), p2.display_name());
+
+    loop {
+        if let Some(winner) = game.status().winner() {
+            show_winner(GameStatus::Won(winner), &game);
+            if let (Some(threshold), [decisions, split, wide]) = (compare, compared) {
+                println!("The engines disagreed on {} of {} decisions, {} by {:.0} points or more",
+                         split, decisions, wide, threshold);
+            }
+            return (winner, history);
+        }
+
+        redraw_board(&game, &caption, &[]);
+        print_score(&game, Some(captures));
+
+        let player = game.current_player();
+        let ai_type = match player {
+            FastPlayer::One => p1,
+            FastPlayer::Two => p2,
+        };
+
+        let roll = animate_roll(player, ai_type);
+        turn += 1;
+        extra_rolls.turn(player);
+
+        let moves = game.generate_moves(roll);
+        if moves.is_empty() {
+            println!("No moves available. Turn passes.");
+            game.pass_turn();
+            thread::sleep(move_delay);
+            continue;
+        }
+
+        // With --compare both engines weigh in and the mover plays what it said
+        let piece = match compare {
+            Some(threshold) if moves.len() > 1 => {
+                let evals = [p1, p2].map(|seat| compare::evaluate(seat, &game, &moves, roll, mcts_ai));
+                compare::show(&game, roll, &evals, [p1, p2], threshold);
+                let gap = compare::disagreement(&game, roll, &evals[0], &evals[1]);
+                compared[0] += 1;
+                compared[1] += usize::from(compare::differ(&game, roll, &evals[0], &evals[1]));
+                compared[2] += usize::from(gap >= threshold);
+                evals[player as usize].choice
+            }
+            _ => ai_type.choose_move(&game, &moves, roll, mcts_ai),
+        };
+        let mut delay = move_delay;
+        if let Ok(info) = game.make_move(piece, roll) {
+            history.record(turn, player, roll, info, game);
+            // Show the capture on the board, with the squares it moved between
+            // highlighted, and leave it up longer
+            if info.captured_piece.is_some() {
+                captures[player as usize] += 1;
+                redraw_board(&game, &caption, &move_squares(player, info.from_pos, info.to_pos));
+                print_score(&game, Some(captures));
+                delay = move_delay * CAPTURE_DELAYS;
+            }
+            let action = match (info.from_pos, info.to_pos) {
+                (0, _) => format!("enters piece {}", piece),
+                (_, to) if to == FastGameState::finished_pos() => format!("bears off piece {}", piece),
+                (_, to) => format!("moves piece {} to path {}", piece, to - 1),
+            };
+            print!("{} {}", player.name(), action);
+            if let Some(captured) = info.captured_piece {
+                print!(" 💥 Piece sent home! ({}'s piece {})", player.opposite().name(), captured);
+            }
+            if info.extra_turn {
+                match extra_rolls.chain() + 1 {
+                    1 => print!(" ⭐ extra roll!"),
+                    chain => print!(" ⭐ {} extra roll in a row!", ordinal(chain)),
+                }
+            }
+            println!();
+        }
+
+        thread::sleep(delay);
+    }
+}
+
+/// Tumble the dice briefly before revealing the real roll
+fn animate_roll(player: FastPlayer, ai_type: &AIType) -> u8 {
+    let _ = execute!(
+        io::stdout(),
+        SetForegroundColor(style::of(player).color),
+        Print(format!("{} ({}) rolls ", player.name(), ai_type.display_name())),
+        ResetColor
+    );
+
+    for _ in 0..6 {
+        print!("{}\u{8}", FastGameState::roll_dice(player));
+        let _ = io::stdout().flush();
+        thread::sleep(Duration::from_millis(60));
+    }
+
+    let roll = FastGameState::roll_dice(player);
+    println!("🎲 {} {}", roll, dice::model().render(roll));
+    roll
+}
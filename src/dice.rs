@@ -0,0 +1,316 @@
+//! Dice models: how a roll is drawn, how likely each roll is, and how it is drawn
+//! on screen. The model is chosen once at startup (`--dice`); dice rolls, the AI's
+//! hit-probability estimates and the roll display all read it. Binary dice can
+//! also be biased per player (`--p1-pip-chance`, `--p2-pip-chance`), as a
+//! handicap or to study how much the game turns on luck; rolls and the AI's
+//! estimates then use `model_for` the player rolling.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+use rand::RngCore;
+
+use crate::args::Flags;
+use crate::optimized_game::FastPlayer;
+
+/// A way of producing movement rolls
+pub trait DiceModel: Send + Sync {
+    /// Plain-English name for the rules summary
+    fn description(&self) -> &'static str;
+
+    /// Largest possible roll
+    fn max_roll(&self) -> u8;
+
+    /// Chance of rolling exactly `roll`
+    fn probability(&self, roll: u8) -> f64;
+
+    fn roll(&self, rng: &mut dyn RngCore) -> u8;
+
+    /// Picture of a roll for the terminal
+    fn render(&self, roll: u8) -> String;
+
+    /// Smallest roll with a nonzero chance
+    fn min_roll(&self) -> u8 {
+        (0..=self.max_roll()).find(|&roll| self.probability(roll) > 0.0).unwrap_or(0)
+    }
+}
+
+/// `count` two-sided dice (the tetrahedra have two marked tips out of four);
+/// the roll is the number that land marked side up
+pub struct BinaryDice {
+    pub count: u8,
+}
+
+impl DiceModel for BinaryDice {
+    fn description(&self) -> &'static str {
+        match self.count {
+            3 => "3 binary dice",
+            _ => "4 binary dice",
+        }
+    }
+
+    fn max_roll(&self) -> u8 {
+        self.count
+    }
+
+    fn probability(&self, roll: u8) -> f64 {
+        if roll > self.count {
+            return 0.0;
+        }
+        // C(count, roll) / 2^count
+        let ways = (0..roll).fold(1.0, |acc, i| acc * (self.count - i) as f64 / (i + 1) as f64);
+        ways / (1u32 << self.count) as f64
+    }
+
+    /// One random bit per die
+    #[inline]
+    fn roll(&self, rng: &mut dyn RngCore) -> u8 {
+        (rng.next_u32() & ((1 << self.count) - 1)).count_ones() as u8
+    }
+
+    fn render(&self, roll: u8) -> String {
+        (0..self.count).map(|i| if i < roll { '▲' } else { '△' }).collect()
+    }
+}
+
+/// Binary dice that each land pip side up with chance `pip` rather than one in two
+pub struct BiasedBinaryDice {
+    pub count: u8,
+    pub pip: f64,
+}
+
+impl DiceModel for BiasedBinaryDice {
+    fn description(&self) -> &'static str {
+        match self.count {
+            3 => "3 biased binary dice",
+            _ => "4 biased binary dice",
+        }
+    }
+
+    fn max_roll(&self) -> u8 {
+        self.count
+    }
+
+    fn probability(&self, roll: u8) -> f64 {
+        if roll > self.count {
+            return 0.0;
+        }
+        // C(count, roll) p^roll (1 - p)^(count - roll)
+        let ways = (0..roll).fold(1.0, |acc, i| acc * (self.count - i) as f64 / (i + 1) as f64);
+        ways * self.pip.powi(roll as i32) * (1.0 - self.pip).powi((self.count - roll) as i32)
+    }
+
+    fn roll(&self, rng: &mut dyn RngCore) -> u8 {
+        let threshold = (self.pip * u32::MAX as f64) as u32;
+        (0..self.count).filter(|_| rng.next_u32() < threshold).count() as u8
+    }
+
+    fn render(&self, roll: u8) -> String {
+        (0..self.count).map(|i| if i < roll { '▲' } else { '△' }).collect()
+    }
+}
+
+/// A single four-sided die numbered 1-4: no zero rolls, flat distribution
+pub struct D4;
+
+impl DiceModel for D4 {
+    fn description(&self) -> &'static str {
+        "a four-sided die"
+    }
+
+    fn max_roll(&self) -> u8 {
+        4
+    }
+
+    fn probability(&self, roll: u8) -> f64 {
+        if (1..=4).contains(&roll) { 0.25 } else { 0.0 }
+    }
+
+    fn roll(&self, rng: &mut dyn RngCore) -> u8 {
+        (rng.next_u32() % 4) as u8 + 1
+    }
+
+    fn render(&self, roll: u8) -> String {
+        format!("[{}]", roll)
+    }
+}
+
+/// A single knucklebone (astragalus): four faces worth 1, 3, 4 and 6 that do not
+/// land equally often, since the broad sides come up far more than the narrow ones
+pub struct Knucklebones;
+
+impl Knucklebones {
+    /// (value, chance, traditional name) per face
+    const FACES: [(u8, f64, &'static str); 4] = [
+        (1, 0.1, "chios"),
+        (3, 0.4, "hyption"),
+        (4, 0.4, "pranes"),
+        (6, 0.1, "coan"),
+    ];
+}
+
+impl DiceModel for Knucklebones {
+    fn description(&self) -> &'static str {
+        "a knucklebone"
+    }
+
+    fn max_roll(&self) -> u8 {
+        6
+    }
+
+    fn probability(&self, roll: u8) -> f64 {
+        Self::FACES.iter().find(|face| face.0 == roll).map_or(0.0, |face| face.1)
+    }
+
+    fn roll(&self, rng: &mut dyn RngCore) -> u8 {
+        match rng.next_u32() % 10 {
+            0 => 1,
+            1..=4 => 3,
+            5..=8 => 4,
+            _ => 6,
+        }
+    }
+
+    fn render(&self, roll: u8) -> String {
+        let name = Self::FACES.iter().find(|face| face.0 == roll).map_or("?", |face| face.2);
+        format!("🦴 {}", name)
+    }
+}
+
+static FOUR_BINARY: BinaryDice = BinaryDice { count: 4 };
+static THREE_BINARY: BinaryDice = BinaryDice { count: 3 };
+
+/// Selectable dice models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum DiceVariant {
+    /// Four binary tetrahedra, the standard game
+    #[default]
+    Tetrahedra,
+    #[cfg_attr(feature = "serde", serde(rename = "binary3"))]
+    ThreeBinary,
+    D4,
+    Knucklebones,
+}
+
+impl DiceVariant {
+    pub const ALL: [DiceVariant; 4] = [
+        DiceVariant::Tetrahedra,
+        DiceVariant::ThreeBinary,
+        DiceVariant::D4,
+        DiceVariant::Knucklebones,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DiceVariant::Tetrahedra => "tetrahedra",
+            DiceVariant::ThreeBinary => "binary3",
+            DiceVariant::D4 => "d4",
+            DiceVariant::Knucklebones => "knucklebones",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|variant| variant.label() == label)
+    }
+
+    /// Whether the dice are binary and so can be biased
+    pub fn is_binary(self) -> bool {
+        matches!(self, DiceVariant::Tetrahedra | DiceVariant::ThreeBinary)
+    }
+
+    pub fn model(self) -> &'static dyn DiceModel {
+        match self {
+            DiceVariant::Tetrahedra => &FOUR_BINARY,
+            DiceVariant::ThreeBinary => &THREE_BINARY,
+            DiceVariant::D4 => &D4,
+            DiceVariant::Knucklebones => &Knucklebones,
+        }
+    }
+}
+
+impl FromStr for DiceVariant {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::from_label(s).ok_or(())
+    }
+}
+
+static SELECTED: OnceLock<DiceVariant> = OnceLock::new();
+
+/// Choose the dice for this run; must happen before any roll.
+/// Returns false if different dice were already chosen.
+pub fn select(variant: DiceVariant) -> bool {
+    *SELECTED.get_or_init(|| variant) == variant
+}
+
+/// The dice chosen for this run (four tetrahedra unless `select` said otherwise)
+pub fn variant() -> DiceVariant {
+    SELECTED.get().copied().unwrap_or_default()
+}
+
+#[inline]
+pub fn model() -> &'static dyn DiceModel {
+    variant().model()
+}
+
+/// Each player's biased dice, for four and for three binary dice; None for a
+/// player whose dice are fair
+static BIASED: OnceLock<[[Option<BiasedBinaryDice>; 2]; 2]> = OnceLock::new();
+
+/// Bias each player's binary dice: a die lands pip side up with that player's
+/// chance, one half being fair. Must happen before any roll. Returns false if
+/// other chances were already chosen.
+pub fn select_pip_chances(chances: [f64; 2]) -> bool {
+    let biased = BIASED.get_or_init(|| {
+        chances.map(|pip| [4, 3].map(|count| (pip != 0.5).then_some(BiasedBinaryDice { count, pip })))
+    });
+    pip_chances_of(biased) == chances
+}
+
+fn pip_chances_of(biased: &[[Option<BiasedBinaryDice>; 2]; 2]) -> [f64; 2] {
+    biased.each_ref().map(|dice| dice[0].as_ref().map_or(0.5, |dice| dice.pip))
+}
+
+/// The chance each player's binary dice land pip side up (one half unless biased)
+pub fn pip_chances() -> [f64; 2] {
+    BIASED.get().map_or([0.5; 2], pip_chances_of)
+}
+
+/// Whether either player's dice are biased; only binary dice can be
+pub fn is_biased() -> bool {
+    variant().is_binary() && pip_chances() != [0.5; 2]
+}
+
+/// The dice `player` rolls: the chosen model, biased if their dice are
+#[inline]
+pub fn model_for(player: FastPlayer) -> &'static dyn DiceModel {
+    let biased = match (BIASED.get(), variant()) {
+        (Some(biased), DiceVariant::Tetrahedra) => biased[player as usize][0].as_ref(),
+        (Some(biased), DiceVariant::ThreeBinary) => biased[player as usize][1].as_ref(),
+        _ => None,
+    };
+    match biased {
+        Some(dice) => dice,
+        None => model(),
+    }
+}
+
+/// Each player's pip chance from `--p1-pip-chance` and `--p2-pip-chance`,
+/// strictly between 0 and 1 so every roll stays possible; None when both are
+/// fair. `dice` is the `--dice` choice, which must be binary to be biased.
+pub fn pip_chances_from_flags(flags: &Flags, dice: Option<DiceVariant>) -> Result<Option<[f64; 2]>, String> {
+    let chance = |flag: &str| match flags.get(flag, 0.5)? {
+        chance if chance > 0.0 && chance < 1.0 => Ok(chance),
+        chance => Err(format!("{} must be between 0 and 1, not {}", flag, chance)),
+    };
+    let chances = [chance("--p1-pip-chance")?, chance("--p2-pip-chance")?];
+    if chances == [0.5; 2] {
+        return Ok(None);
+    }
+    if dice.is_some_and(|dice| !dice.is_binary()) {
+        return Err("pip chances need binary dice (tetrahedra or binary3)".to_string());
+    }
+    Ok(Some(chances))
+}
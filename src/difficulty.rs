@@ -0,0 +1,72 @@
+//! Difficulty ladder for the computer opponent in human-vs-AI games.
+//! MCTS levels scale the budget of the preset picked at startup, so "Hard" is
+//! exactly the MCTS AI the menu would have created; "Medium" is the Expectimax
+//! AI, which looks a few plies ahead without playouts.
+
+use crate::AIType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Beginner,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Beginner,
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "Beginner",
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        }
+    }
+
+    /// The level matching an AI picked from the main menu; the adaptive AI sets its own
+    pub fn for_ai(ai_type: &AIType) -> Option<Difficulty> {
+        match ai_type {
+            AIType::Human | AIType::Adaptive | AIType::Script | AIType::External(_) | AIType::Opponent(_) | AIType::Tuned(_) => None,
+            AIType::Random => Some(Difficulty::Beginner),
+            AIType::Smart => Some(Difficulty::Easy),
+            AIType::Expectimax => Some(Difficulty::Medium),
+            AIType::MCTS => Some(Difficulty::Hard),
+        }
+    }
+
+    pub fn ai_type(self) -> AIType {
+        match self {
+            Difficulty::Beginner => AIType::Random,
+            Difficulty::Easy => AIType::Smart,
+            Difficulty::Medium => AIType::Expectimax,
+            Difficulty::Hard | Difficulty::Expert => AIType::MCTS,
+        }
+    }
+
+    /// Multiple of the startup MCTS preset's budget at this level; `None` for heuristic levels
+    pub fn mcts_scale(self) -> Option<f64> {
+        match self {
+            Difficulty::Beginner | Difficulty::Easy | Difficulty::Medium => None,
+            Difficulty::Hard => Some(1.0),
+            Difficulty::Expert => Some(4.0),
+        }
+    }
+
+    pub fn stronger(self) -> Option<Difficulty> {
+        Self::ALL.get(self as usize + 1).copied()
+    }
+
+    pub fn weaker(self) -> Option<Difficulty> {
+        (self as usize).checked_sub(1).map(|i| Self::ALL[i])
+    }
+}
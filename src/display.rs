@@ -1,137 +1,257 @@
-use std::io;
-use crossterm::{
-    execute,
-    terminal::{Clear, ClearType},
-    style::{Color, ResetColor, SetForegroundColor, SetBackgroundColor, Print},
-    cursor::MoveTo,
-};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
-use crate::optimized_game::{FastGameState, FastPlayer};
+use crossterm::style::Color;
+
+use crate::board;
+use crate::dice;
+use crate::input::{Command, Slash};
+use crate::optimized_game::{EndReason, FastGameState, FastPlayer, GameStatus};
+use crate::rng;
+use crate::screen::{self, Align, Border, Panel, Screen, Span, Table};
+use crate::style::{self, View};
+use crate::turn_clock::TurnClock;
 
 pub fn clear_screen() {
-    let _ = execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
+    screen::clear();
 }
 
-pub fn display_board(game: &FastGameState) {
-    // Build a 3×8 grid representation with colors
-    let mut grid: [[char; 8]; 3] = [[' '; 8]; 3];
-    let mut grid_colors: [[Color; 8]; 3] = [[Color::Reset; 8]; 3];
-    let mut grid_bg_colors: [[Color; 8]; 3] = [[Color::Reset; 8]; 3];
+/// Which way up the board's grid is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    /// Grid row 0 is drawn at the bottom instead of the top
+    flipped: bool,
+}
 
-    // Initialize empty squares
-    let valid_squares = [
-        (0, 0), (0, 1), (0, 2), (0, 3), (0, 6), (0, 7),  // Top row
-        (1, 0), (1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (1, 6), (1, 7),  // Middle row
-        (2, 0), (2, 1), (2, 2), (2, 3), (2, 6), (2, 7),  // Bottom row
-    ];
+impl Orientation {
+    /// The orientation that draws `player`'s home row, where their pieces
+    /// enter, at the bottom
+    pub fn facing(player: FastPlayer) -> Self {
+        let layout = board::layout();
+        let home_row = |player: FastPlayer| layout.coords[layout.paths[player as usize][0] as usize].0;
+        Orientation { flipped: home_row(player) < home_row(player.opposite()) }
+    }
+
+    /// The orientation for a position, from `style::viewer`
+    pub fn for_position(game: &FastGameState, hot_seat: bool) -> Self {
+        Orientation::facing(style::viewer(game.current_player(), hot_seat))
+    }
+
+    /// Grid row drawn on the `line`th row of the board, counting from the top.
+    /// Mirroring is its own inverse, so this also maps grid rows to lines.
+    pub fn grid_row(self, line: usize) -> usize {
+        if self.flipped { board::layout().rows - 1 - line } else { line }
+    }
+
+    /// Where `global` square is drawn, as (row from the top, column)
+    pub fn screen_coord(self, global: u8) -> (usize, usize) {
+        let (row, col) = global_to_coord(global);
+        (self.grid_row(row), col)
+    }
+}
+
+/// Squares show the path positions of the player to move instead of the
+/// board having grid coordinates
+static PATH_LABELS: AtomicBool = AtomicBool::new(false);
+
+/// Switch between grid coordinates and path-position labels; returns whether
+/// path labels are now shown
+pub fn toggle_path_labels() -> bool {
+    !PATH_LABELS.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// Whose route the board draws: off, Player 1's, Player 2's or both
+static ROUTE: AtomicU8 = AtomicU8::new(0);
+
+/// Step the route overlay from off to Player 1's route, Player 2's, both and
+/// back to off
+pub fn cycle_route() {
+    let _ = ROUTE.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |route| Some((route + 1) % 4));
+}
+
+/// Players whose routes are drawn
+fn route_players() -> &'static [FastPlayer] {
+    match ROUTE.load(Ordering::Relaxed) {
+        1 => &[FastPlayer::One],
+        2 => &[FastPlayer::Two],
+        3 => &[FastPlayer::One, FastPlayer::Two],
+        _ => &[],
+    }
+}
+
+/// Arrow from one screen square towards the next
+fn arrow(from: (usize, usize), to: (usize, usize)) -> char {
+    let step = |a: usize, b: usize| (b as isize - a as isize).signum();
+    match (step(from.0, to.0), step(from.1, to.1)) {
+        (-1, 0) => '↑',
+        (1, 0) => '↓',
+        (0, -1) => '←',
+        (0, 1) => '→',
+        (-1, -1) => '↖',
+        (-1, 1) => '↗',
+        (1, -1) => '↙',
+        _ => '↘',
+    }
+}
+
+/// Where `player`'s route leaves each square on its path, keyed by square:
+/// an arrow to the next square, or `⌂` on the last, where pieces bear off
+fn route_steps(player: FastPlayer, orientation: Orientation) -> Vec<(u8, char)> {
+    let path = board::layout().paths[player as usize];
+    path.iter().enumerate().map(|(idx, &square)| {
+        let step = match path.get(idx + 1) {
+            Some(&next) => arrow(orientation.screen_coord(square), orientation.screen_coord(next)),
+            None => '⌂',
+        };
+        (square, step)
+    }).collect()
+}
+
+/// Add the board to `screen` as seen from `orientation`, with `caption` on the
+/// line above it. Rows keep their grid numbers whichever way up they are; with
+/// path labels on, empty squares on the mover's path show how far along it
+/// they are instead. With a route overlay on, empty squares on the chosen
+/// routes show where a piece goes next (or, with path labels, how far along
+/// the one chosen route they are). The `marked` squares are highlighted, as
+/// when a capture is shown.
+pub fn render_board(screen: &mut Screen, game: &FastGameState, caption: &str, orientation: Orientation, marked: &[u8]) {
+    // Build a rows×cols grid of squares for the selected layout, top row first
+    let layout = board::layout();
+    let path_labels = PATH_LABELS.load(Ordering::Relaxed);
+    let routes = route_players();
+    let mover = match routes {
+        [player] => *player,
+        _ => game.current_player(),
+    };
+    let label = |text: String| if path_labels { format!("{:>2}", text) } else { text };
+    let mut grid = vec![vec![None; layout.cols]; layout.rows];
 
     // Mark safe squares and rosettes with colors
-    for &(row, col) in &valid_squares {
-        let global = coord_to_global(row, col);
-        if let Some(g) = global {
-            if FastGameState::is_rosette(g) {
-                grid[row][col] = '★';
-                grid_colors[row][col] = Color::Yellow;
-                grid_bg_colors[row][col] = Color::DarkMagenta;
-            } else if FastGameState::is_safe(g) {
-                grid[row][col] = '▣';
-                grid_colors[row][col] = Color::Green;
-                grid_bg_colors[row][col] = Color::DarkGreen;
-            } else {
-                grid[row][col] = '·';
-                grid_colors[row][col] = Color::DarkGrey;
+    for g in 0..layout.coords.len() as u8 {
+        let (row, col) = orientation.screen_coord(g);
+        let (glyph, fg, bg) = if FastGameState::is_rosette(g) {
+            ('★', Color::Yellow, Color::DarkMagenta)
+        } else if FastGameState::is_safe(g) {
+            ('▣', Color::Green, Color::DarkGreen)
+        } else {
+            ('·', Color::DarkGrey, Color::Reset)
+        };
+        let text = match layout.paths[mover as usize].iter().position(|&square| square == g) {
+            Some(path_idx) if path_labels => path_idx.to_string(),
+            _ => glyph.to_string(),
+        };
+        grid[row][col] = Some((label(text), fg, bg));
+    }
+
+    // Draw the routes over the empty squares. A square on both routes gets
+    // one arrow when they leave it the same way, a two-headed one when they
+    // part in opposite directions, and otherwise the mover's.
+    if !path_labels {
+        let steps: Vec<(FastPlayer, Vec<(u8, char)>)> = routes.iter().map(|&player| (player, route_steps(player, orientation))).collect();
+        for g in 0..layout.coords.len() as u8 {
+            let here: Vec<(FastPlayer, char)> = steps.iter()
+                .filter_map(|(player, route)| route.iter().find(|&&(square, _)| square == g).map(|&(_, step)| (*player, step)))
+                .collect();
+            let (step, color) = match here[..] {
+                [] => continue,
+                [(player, step)] => (step, style::of(player).color),
+                [(_, a), (_, b)] if a == b => (a, Color::White),
+                [(_, '↑' | '↓'), (_, '↑' | '↓')] => ('↕', Color::White),
+                [(_, '←' | '→'), (_, '←' | '→')] => ('↔', Color::White),
+                _ => {
+                    let player = game.current_player();
+                    (here.iter().find(|&&(p, _)| p == player).map_or('·', |&(_, step)| step), style::of(player).color)
+                }
+            };
+            let (row, col) = orientation.screen_coord(g);
+            if let Some(square) = &mut grid[row][col] {
+                square.0 = step.to_string();
+                square.1 = color;
             }
         }
     }
 
-    // Place pieces with distinct colors
+    // Place pieces in their player's style, keeping the square's background
     for player in [FastPlayer::One, FastPlayer::Two] {
-        let (symbol, color) = match player {
-            FastPlayer::One => ('●', Color::Blue),
-            FastPlayer::Two => ('●', Color::Red),
-        };
-
+        let style = style::of(player);
         for piece_idx in 0..7 {
             let pos = game.get_piece_pos(player, piece_idx);
-            if pos >= 1 && pos <= 14 {
+            if FastGameState::is_on_board(pos) {
                 let global_square = FastGameState::path_to_global(player, pos - 1);
-                let (row, col) = global_to_coord(global_square);
-                grid[row][col] = symbol;
-                grid_colors[row][col] = color;
+                let (row, col) = orientation.screen_coord(global_square);
+                if let Some(square) = &mut grid[row][col] {
+                    square.0 = label(style.glyph(piece_idx).to_string());
+                    square.1 = style.color;
+                }
             }
         }
     }
+    for &g in marked {
+        let (row, col) = orientation.screen_coord(g);
+        if let Some(square) = &mut grid[row][col] {
+            square.2 = Color::DarkRed;
+        }
+    }
 
-    // Display the enhanced board
-    println!("\n╔═══════════════════════════════════════╗");
-    println!("║        🏛️  Royal Game of Ur  🏛️         ║");
-    println!("╠═══════════════════════════════════════╣");
-    print!("║     ");
-    for col in 0..8 {
-        print!("{} ", col);
-    }
-    println!("     ║");
-    println!("╠═══════════════════════════════════════╣");
-
-    for (row, line) in grid.iter().enumerate() {
-        print!("║  {} │ ", row);
-        for (col, &cell) in line.iter().enumerate() {
-            if valid_squares.contains(&(row, col)) {
-                let _ = execute!(
-                    io::stdout(),
-                    SetForegroundColor(grid_colors[row][col]),
-                    SetBackgroundColor(grid_bg_colors[row][col]),
-                    Print(cell),
-                    ResetColor,
-                    Print(" ")
-                );
-            } else {
-                print!("  ");
-            }
+    let mut panel = Panel::new(Border::Double, 39);
+    panel.centered(vec![Span::plain("🏛️  Royal Game of Ur  🏛️")]);
+    panel.rule();
+    if path_labels {
+        panel.centered(vec![Span::colored(format!("Path positions for {}", mover.name()), style::of(mover).color)]);
+    } else if let [player] = routes {
+        panel.centered(vec![Span::colored(format!("Route of {} (⌂ bears off)", player.name()), style::of(*player).color)]);
+    } else if !routes.is_empty() {
+        panel.centered(vec![Span::plain("Routes of both players (⌂ bears off)")]);
+    } else {
+        let columns: String = (0..layout.cols).map(|col| format!("{} ", col)).collect();
+        panel.centered(vec![Span::plain(format!("      {}   ", columns))]);
+    }
+    panel.rule();
+    for (line_idx, squares) in grid.into_iter().enumerate() {
+        let row_label = if path_labels { " ".to_string() } else { orientation.grid_row(line_idx).to_string() };
+        let mut line = vec![Span::plain(format!("  {} │ ", row_label))];
+        for square in squares {
+            line.push(match square {
+                Some((text, fg, bg)) => Span::colored(text, fg).on(bg),
+                None => Span::plain(label(" ".to_string())),
+            });
+            line.push(Span::plain(" "));
         }
-        println!("│  ║");
+        line.push(Span::plain("│  "));
+        panel.centered(line);
     }
-    println!("╚═══════════════════════════════════════╝");
-    println!();
+
+    screen.line(vec![Span::colored(caption, Color::Yellow)]);
+    screen.panel(&panel);
+    screen.blank();
 }
 
-pub fn coord_to_global(row: usize, col: usize) -> Option<u8> {
-    match (row, col) {
-        (0, 0) => Some(0),   (0, 1) => Some(1),   (0, 2) => Some(2),   (0, 3) => Some(3),
-        (0, 6) => Some(4),   (0, 7) => Some(5),
-        (1, 0) => Some(6),   (1, 1) => Some(7),   (1, 2) => Some(8),   (1, 3) => Some(9),
-        (1, 4) => Some(10),  (1, 5) => Some(11),  (1, 6) => Some(12),  (1, 7) => Some(13),
-        (2, 0) => Some(14),  (2, 1) => Some(15),  (2, 2) => Some(16),  (2, 3) => Some(17),
-        (2, 6) => Some(18),  (2, 7) => Some(19),
-        _ => None,
-    }
+/// Draw the board at the top of the terminal, with `caption` above it and the
+/// `marked` squares highlighted, and clear everything below; only the squares
+/// that changed are redrawn
+pub fn redraw_board(game: &FastGameState, caption: &str, marked: &[u8]) {
+    let mut screen = Screen::new();
+    render_board(&mut screen, game, caption, Orientation::for_position(game, false), marked);
+    screen.present();
 }
 
 pub fn global_to_coord(global: u8) -> (usize, usize) {
-    match global {
-        0 => (0, 0),   1 => (0, 1),   2 => (0, 2),   3 => (0, 3),
-        4 => (0, 6),   5 => (0, 7),
-        6 => (1, 0),   7 => (1, 1),   8 => (1, 2),   9 => (1, 3),
-        10 => (1, 4),  11 => (1, 5),  12 => (1, 6),  13 => (1, 7),
-        14 => (2, 0),  15 => (2, 1),  16 => (2, 2),  17 => (2, 3),
-        18 => (2, 6),  19 => (2, 7),
-        _ => (0, 0), // Default fallback
-    }
+    board::layout().coords.get(global as usize).copied().unwrap_or((0, 0)) // Default fallback
 }
 
-pub fn print_piece_positions(game: &FastGameState, player: FastPlayer) {
-    let (player_color, player_symbol) = match player {
-        FastPlayer::One => (Color::Blue, "🔵"),
-        FastPlayer::Two => (Color::Red, "🔴"),
-    };
+/// Squares a move by `player` from path position `from_pos` to `to_pos` went
+/// between, leaving out entering and bearing off
+pub fn move_squares(player: FastPlayer, from_pos: u8, to_pos: u8) -> Vec<u8> {
+    [from_pos, to_pos].into_iter()
+        .filter(|&pos| FastGameState::is_on_board(pos))
+        .map(|pos| FastGameState::path_to_global(player, pos - 1))
+        .collect()
+}
 
-    let _ = execute!(
-        io::stdout(),
-        SetForegroundColor(player_color),
-        Print(format!("{} {}'s pieces:", player_symbol, player.name())),
-        ResetColor
-    );
-    println!();
+/// Add a summary of where `player`'s pieces are to `screen`
+pub fn render_piece_positions(screen: &mut Screen, game: &FastGameState, player: FastPlayer) {
+    let style = style::of(player);
+    let (player_color, player_symbol) = (style.color, style.marker);
+    screen.line(vec![Span::colored(format!("{} {}'s pieces:", player_symbol, player.name()), player_color)]);
 
     let mut off_board = 0;
     let mut on_board = Vec::new();
@@ -141,8 +261,8 @@ pub fn print_piece_positions(game: &FastGameState, player: FastPlayer) {
         let pos = game.get_piece_pos(player, piece_idx);
         match pos {
             0 => off_board += 1,
-            15 => finished += 1,
-            1..=14 => {
+            _ if pos == FastGameState::finished_pos() => finished += 1,
+            _ if FastGameState::is_on_board(pos) => {
                 let path_idx = pos - 1;
                 on_board.push((piece_idx, path_idx));
             }
@@ -151,94 +271,170 @@ pub fn print_piece_positions(game: &FastGameState, player: FastPlayer) {
     }
 
     // Summary line
-    let _ = execute!(
-        io::stdout(),
-        SetForegroundColor(Color::DarkGrey),
-        Print(format!("  📊 Off board: {} | On board: {} | Finished: {}",
-               off_board, on_board.len(), finished)),
-        ResetColor
-    );
-    println!();
+    screen.line(vec![Span::colored(
+        format!("  📊 Off board: {} | On board: {} | Finished: {}", off_board, on_board.len(), finished),
+        Color::DarkGrey,
+    )]);
 
     // Details for pieces on board
     if !on_board.is_empty() {
         on_board.sort_by_key(|(_, path_idx)| *path_idx);
-        print!("  🎯 Active pieces: ");
+        let mut line = vec![Span::plain("  🎯 Active pieces: ")];
         for (i, (piece_idx, path_idx)) in on_board.iter().enumerate() {
-            if i > 0 { print!(" | "); }
-            let _ = execute!(
-                io::stdout(),
-                SetForegroundColor(player_color),
-                Print(format!("#{} at path {}", piece_idx, path_idx)),
-                ResetColor
-            );
+            if i > 0 {
+                line.push(Span::plain(" | "));
+            }
+            line.push(Span::colored(format!("#{} at path {}", piece_idx, path_idx), player_color));
         }
-        println!();
+        screen.line(line);
     }
-    println!();
+    screen.blank();
 }
 
-pub fn print_score(game: &FastGameState) {
+/// Add the score box to `screen`
+/// The score panel, with each player's captures so far when they are known
+pub fn render_score(screen: &mut Screen, game: &FastGameState, captures: Option<[usize; 2]>) {
     let p1_score = game.get_score(FastPlayer::One);
     let p2_score = game.get_score(FastPlayer::Two);
+    let score_color = |leading: bool| if leading { Color::Green } else { Color::White };
 
-    println!("╔═══════════════════════════════════════╗");
-    print!("║ 🏆 SCORE: ");
-
-    let _ = execute!(
-        io::stdout(),
-        SetForegroundColor(Color::Blue),
-        Print("🔵"),
-        ResetColor,
-        Print(format!(" {} = ", FastPlayer::One.name())),
-        SetForegroundColor(if p1_score > p2_score { Color::Green } else { Color::White }),
-        Print(format!("{}", p1_score)),
-        ResetColor,
-        Print(" | "),
-        SetForegroundColor(Color::Red),
-        Print("🔴"),
-        ResetColor,
-        Print(format!(" {} = ", FastPlayer::Two.name())),
-        SetForegroundColor(if p2_score > p1_score { Color::Green } else { Color::White }),
-        Print(format!("{}", p2_score)),
-        ResetColor
-    );
-
-    // Pad to align with box
-    let padding = 39 - 11 - FastPlayer::One.name().len() - FastPlayer::Two.name().len() - 8;
-    for _ in 0..padding {
-        print!(" ");
-    }
-    println!("║");
-    println!("╚═══════════════════════════════════════╝");
-    println!();
-}
-
-
-pub fn show_winner(winner: FastPlayer, game: &FastGameState) {
-    clear_screen();
-    display_board(game);
+    let mut panel = Panel::new(Border::Double, 39);
+    let (p1_style, p2_style) = (style::of(FastPlayer::One), style::of(FastPlayer::Two));
+    panel.centered(vec![
+        Span::plain("🏆 "),
+        Span::colored(p1_style.marker, p1_style.color),
+        Span::plain(format!(" {} = ", FastPlayer::One.name())),
+        Span::colored(p1_score.to_string(), score_color(p1_score > p2_score)),
+        Span::plain(" | "),
+        Span::colored(p2_style.marker, p2_style.color),
+        Span::plain(format!(" {} = ", FastPlayer::Two.name())),
+        Span::colored(p2_score.to_string(), score_color(p2_score > p1_score)),
+    ]);
+    if let Some([p1_captures, p2_captures]) = captures {
+        panel.centered(vec![
+            Span::plain("⚔️  Captures: "),
+            Span::colored(p1_style.marker, p1_style.color),
+            Span::plain(format!(" {} | ", p1_captures)),
+            Span::colored(p2_style.marker, p2_style.color),
+            Span::plain(format!(" {}", p2_captures)),
+        ]);
+    }
+    screen.panel(&panel);
+    screen.blank();
+}
 
-    let (winner_color, winner_symbol) = match winner {
-        FastPlayer::One => (Color::Blue, "🔵"),
-        FastPlayer::Two => (Color::Red, "🔴"),
-    };
+pub fn print_score(game: &FastGameState, captures: Option<[usize; 2]>) {
+    let mut screen = Screen::new();
+    render_score(&mut screen, game, captures);
+    screen.print();
+}
+
+/// The rules for the board, dice and view chosen for this run, one per line
+pub fn rules_summary() -> Vec<String> {
+    let layout = board::layout();
+    let dice = dice::model();
+    let mut rules = vec![
+        match style::view() {
+            Some(View::Player1) => "Two players (Player 1 = bottom row, Player 2 = top row).".to_string(),
+            Some(View::Auto) => "Two players; the board turns to put the mover's home row at the bottom.".to_string(),
+            _ => "Two players (Player 1 = top row, Player 2 = bottom row).".to_string(),
+        },
+        "Each has 7 pieces off‐board initially.".to_string(),
+    ];
+    if dice.min_roll() == 0 {
+        rules.push(format!("Roll {} => move 0..{} steps; '0' = pass turn.", dice.description(), dice.max_roll()));
+    } else {
+        rules.push(format!("Roll {} => move {}..{} steps.", dice.description(), dice.min_roll(), dice.max_roll()));
+    }
+    if dice::is_biased() {
+        let [p1, p2] = dice::pip_chances();
+        rules.push(format!("Biased dice: each die shows a pip {:.0}% of the time for Player 1, {:.0}% for Player 2.", p1 * 100.0, p2 * 100.0));
+    } else if dice::pip_chances() != [0.5; 2] {
+        rules.push("Pip chances are ignored: only binary dice can be biased.".to_string());
+    }
+    rules.push(format!("Each piece travels a {}‐square path; exact roll to exit.", FastGameState::path_len()));
+    rules.push("Capture by landing on opponent on a non‐rosette shared square.".to_string());
+    rules.push(format!("Safe squares ({} total) protect from capture.", layout.safe.count_ones()));
+    rules.push(format!("Rosettes ({} of them) give extra rolls.", layout.rosettes.count_ones()));
+    rules
+}
+
+/// Add the quick-reference card shown with `?` to `screen`: the rules, what
+/// each kind of square means, the settings this game is played with and the
+/// shortcut keys
+pub fn render_rules(screen: &mut Screen, clock: Option<TurnClock>) {
+    let mut panel = Panel::new(Border::Double, 39);
+    panel.centered(vec![Span::plain("📜 Quick Reference")]);
+    panel.rule();
+    for rule in rules_summary() {
+        panel.line(vec![Span::plain(format!("• {}", rule))]);
+    }
+
+    panel.rule();
+    let mut legend = Table::new(&[Align::Left, Align::Left], 2);
+    legend.row(vec![Span::colored("★", Color::Yellow).on(Color::DarkMagenta), Span::plain("Rosette: extra roll, and safe from capture")]);
+    legend.row(vec![Span::colored("▣", Color::Green).on(Color::DarkGreen), Span::plain("Safe square: pieces here cannot be captured")]);
+    legend.row(vec![Span::colored("·", Color::DarkGrey), Span::plain("Combat square: captures happen on the shared middle row")]);
+    panel.table(&legend);
 
-    println!("\n╔═══════════════════════════════════════╗");
-    println!("║                                       ║");
-    print!("║          🎉 VICTORY! 🎉             ║\n");
-    print!("║                                       ║\n");
-    print!("║   ");
-    let _ = execute!(
-        io::stdout(),
-        SetForegroundColor(winner_color),
-        Print(format!("{} {} WINS!", winner_symbol, winner.name())),
-        ResetColor
-    );
-    println!("                ║");
-    println!("║                                       ║");
-    println!("║     All 7 pieces successfully        ║");
-    println!("║     completed the journey! 🏁        ║");
-    println!("║                                       ║");
-    println!("╚═══════════════════════════════════════╝");
+    panel.rule();
+    let mut settings = Table::new(&[Align::Left, Align::Left], 2);
+    settings.row(vec![Span::plain("Board"), Span::plain(format!("{} ({}-square path)", board::variant().label(), FastGameState::path_len()))]);
+    settings.row(vec![Span::plain("Dice"), Span::plain(format!("{} ({})", dice::variant().label(), dice::model().description()))]);
+    if dice::is_biased() {
+        let [p1, p2] = dice::pip_chances();
+        settings.row(vec![Span::plain("Pip chance"), Span::plain(format!("P1 {:.0}%, P2 {:.0}%", p1 * 100.0, p2 * 100.0))]);
+    }
+    settings.row(vec![Span::plain("Dice RNG"), Span::plain(format!("{} ({})", rng::algorithm().label(), rng::algorithm().description()))]);
+    settings.row(vec![Span::plain("Move clock"), Span::plain(clock.map_or("none".to_string(), |clock| clock.to_string()))]);
+    panel.table(&settings);
+
+    panel.rule();
+    let mut keys = Table::new(&[Align::Left, Align::Left], 2);
+    for (key, action) in Command::HELP.iter().chain(Slash::HELP) {
+        keys.row(vec![Span::colored(*key, Color::Cyan), Span::plain(*action)]);
+    }
+    panel.table(&keys);
+    screen.panel(&panel);
+    screen.blank();
+}
+
+/// Result banner over the final position: who won and how, or the draw
+pub fn show_winner(status: GameStatus, game: &FastGameState) {
+    let mut panel = Panel::new(Border::Double, 39);
+    panel.blank();
+    match status.winner() {
+        Some(winner) => {
+            let style = style::of(winner);
+            panel.centered(vec![Span::plain("🎉 VICTORY! 🎉")]);
+            panel.blank();
+            panel.centered(vec![Span::colored(format!("{} {} WINS!", style.marker, winner.name()), style.color)]);
+        }
+        None => panel.centered(vec![Span::plain("🤝 DRAW 🤝")]),
+    }
+    panel.blank();
+    let loser = status.winner().map_or("", |winner| winner.opposite().name());
+    match status {
+        GameStatus::Adjudicated(_, EndReason::Timeout) => panel.centered(vec![Span::plain(format!("{} ran out of time! ⏰", loser))]),
+        GameStatus::Adjudicated(_, EndReason::Resignation) => panel.centered(vec![Span::plain(format!("{} resigned. 🏳️", loser))]),
+        GameStatus::Adjudicated(_, EndReason::Forfeit) => panel.centered(vec![Span::plain(format!("{} forfeited the game.", loser))]),
+        GameStatus::Adjudicated(_, EndReason::MoveLimit) => panel.centered(vec![Span::plain("Ahead at the move limit.")]),
+        GameStatus::Adjudicated(_, EndReason::Stopped) => panel.centered(vec![Span::plain("Ahead when play stopped.")]),
+        GameStatus::Drawn(EndReason::Stopped) => panel.centered(vec![Span::plain("Level when play stopped.")]),
+        GameStatus::Adjudicated(_, EndReason::Agreement) | GameStatus::Drawn(EndReason::Agreement) => {
+            panel.centered(vec![Span::plain("Agreed between the players.")])
+        }
+        GameStatus::Drawn(_) => panel.centered(vec![Span::plain("Level at the move limit.")]),
+        GameStatus::Won(_) | GameStatus::InProgress => {
+            panel.centered(vec![Span::plain("All 7 pieces successfully")]);
+            panel.centered(vec![Span::plain("completed the journey! 🏁")]);
+        }
+    }
+    panel.blank();
+
+    let mut screen = Screen::new();
+    render_board(&mut screen, game, "", Orientation::for_position(game, false), &[]);
+    screen.panel(&panel);
+    clear_screen();
+    screen.print();
 }
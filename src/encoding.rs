@@ -0,0 +1,173 @@
+//! The serde encoding of the core types, with the `serde` feature: game
+//! states, moves, game records, the variants and dice generators they name,
+//! and the statistics of a run all implement `Serialize` and `Deserialize`,
+//! so saves, messages and exports written in any serde format agree. Most
+//! derive theirs where they are declared, with the labels the command line
+//! uses; a `FastGameState` is written as its position code, which does not
+//! change with the packed layout and names the board it is on, and a board
+//! is written as its label, a custom board's being the name in its layout
+//! file. A document wraps its value in `Versioned`, so a reader can refuse
+//! an encoding it does not know. `ur export` writes a directory of game
+//! records as one such document, and `analyze-db` reads it back.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::args::Flags;
+use crate::board::BoardVariant;
+use crate::error::UrError;
+use crate::game_record::{self, GameRecord};
+use crate::optimized_game::FastGameState;
+use crate::position_code;
+use crate::toml_file;
+
+/// Version of the encoding, raised whenever a type's encoding changes
+pub const ENCODING_VERSION: u32 = 1;
+/// Exports in a record directory are files with this extension
+pub const EXPORT_EXTENSION: &str = "toml";
+
+/// A value with the version of the encoding it was written in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub value: T,
+}
+
+/// Just the version of a document, read before its value so a document in
+/// another encoding is refused as such rather than as a malformed value
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(value: T) -> Self {
+        Versioned { version: ENCODING_VERSION, value }
+    }
+}
+
+/// `value` as a TOML document in the current encoding
+pub fn to_toml<T: Serialize>(value: &T) -> Result<String, UrError> {
+    toml::to_string(&Versioned::new(value)).map_err(|e| UrError::parse(e.to_string()))
+}
+
+/// The value of a TOML document, if it was written in this version of the encoding
+pub fn from_toml<T: DeserializeOwned>(text: &str) -> Result<T, UrError> {
+    let error = |e: toml::de::Error| UrError::ParseError {
+        line: e.span().map_or(0, |span| toml_file::line_at(text, span.start)),
+        message: e.message().trim_end().to_string(),
+    };
+    let Version { version } = toml::from_str(text).map_err(error)?;
+    if version != ENCODING_VERSION {
+        return Err(UrError::parse(format!("encoding version {} is not supported (expected {})", version, ENCODING_VERSION)));
+    }
+    toml::from_str::<Versioned<T>>(text).map(|document| document.value).map_err(error)
+}
+
+/// Options for `ur export [--dir DIR] [--out FILE]`
+pub struct ExportOptions {
+    /// Game record directory to export
+    pub dir: PathBuf,
+    pub out: PathBuf,
+}
+
+impl ExportOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--dir", "--out"], &[])?;
+        Ok(ExportOptions {
+            dir: PathBuf::from(flags.get_str("--dir").unwrap_or("games")),
+            out: PathBuf::from(flags.get_str("--out").unwrap_or("games.toml")),
+        })
+    }
+}
+
+/// Write every readable game record in `options.dir` to one versioned document
+pub fn run_export(options: &ExportOptions) -> Result<(), UrError> {
+    let mut records = Vec::new();
+    for (path, record) in game_record::load_dir(&options.dir)? {
+        match record {
+            Ok(record) => records.push(record),
+            Err(e) => println!("  {}: skipped, {}", path.display(), e),
+        }
+    }
+    fs::write(&options.out, to_toml(&records)?)?;
+    println!("Exported {} games from {} to {}", records.len(), options.dir.display(), options.out.display());
+    Ok(())
+}
+
+/// The game records in an export written by `run_export`
+pub fn read_export(text: &str) -> Result<Vec<GameRecord>, UrError> {
+    from_toml(text)
+}
+
+impl Serialize for FastGameState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&position_code::encode(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for FastGameState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        position_code::decode(&code).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for BoardVariant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.label())
+    }
+}
+
+impl<'de> Deserialize<'de> for BoardVariant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = String::deserialize(deserializer)?;
+        BoardVariant::from_label(&label).ok_or_else(|| de::Error::custom(format!("unknown board '{}' (a custom board must be loaded first)", label)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::DiceVariant;
+    use crate::game_record::RecordedMove;
+    use crate::optimized_game::{EndReason, FastPlayer, GameStatus};
+    use crate::rng::RngAlgorithm;
+
+    #[test]
+    fn records_round_trip() {
+        let mut start = FastGameState::new();
+        start.make_move(0, 4).expect("entering is legal");
+        let record = GameRecord {
+            players: ["human".to_string(), "smart".to_string()],
+            board: BoardVariant::Standard,
+            dice: DiceVariant::Tetrahedra,
+            start,
+            moves: vec![RecordedMove { player: FastPlayer::Two, roll: 2, piece: 3 }],
+            result: Some(GameStatus::Adjudicated(FastPlayer::One, EndReason::Resignation)),
+            unassisted: true,
+            rng: RngAlgorithm::Pcg,
+            seed: Some(42),
+            hashes: Vec::new(),
+        };
+        let text = to_toml(&vec![record.clone()]).unwrap();
+        assert!(text.starts_with(&format!("version = {}", ENCODING_VERSION)));
+        assert!(text.contains(&position_code::encode(&start)));
+        assert!(text.contains(r#"board = "standard""#) && text.contains(r#"Adjudicated = ["one", "resignation"]"#));
+
+        let read = read_export(&text).unwrap().remove(0);
+        assert_eq!(read.start, record.start);
+        assert_eq!(read.moves, record.moves);
+        assert_eq!(read.result, record.result);
+        assert_eq!((read.rng, read.seed), (record.rng, record.seed));
+        assert_eq!(read.board, record.board);
+
+        let unknown = text.replace(r#"board = "standard""#, r#"board = "no-such-board""#);
+        assert!(read_export(&unknown).is_err());
+        let later = text.replacen(&format!("version = {}", ENCODING_VERSION), "version = 99", 1);
+        assert!(read_export(&later).unwrap_err().to_string().contains("encoding version 99"));
+    }
+}
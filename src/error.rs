@@ -0,0 +1,45 @@
+//! The crate's error type. Engine calls, file parsers and the bot protocol
+//! report what went wrong as a `UrError`, so a caller can tell an illegal
+//! move from an impossible position, a malformed file, a failed read or a
+//! peer that broke the protocol, and show the reason rather than a bare
+//! failure.
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::optimized_game::MoveError;
+
+#[derive(Debug, Error)]
+pub enum UrError {
+    /// A piece that cannot move with the roll
+    #[error("piece {piece} cannot move {roll}: {reason}")]
+    IllegalMove { piece: u8, roll: u8, #[source] reason: MoveError },
+    /// A position no game can reach: two pieces on a square, a piece off its path
+    #[error("impossible position: {0}")]
+    InvalidState(String),
+    /// Text that is not in the expected format; `line` is 0 when the problem
+    /// is with the text as a whole
+    #[error("{}", if *line == 0 { message.clone() } else { format!("line {}: {}", line, message) })]
+    ParseError { line: usize, message: String },
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+    /// A message from a bot or client that breaks the protocol
+    #[error("{0}")]
+    ProtocolError(String),
+}
+
+impl UrError {
+    /// A problem with the text as a whole rather than one of its lines
+    pub fn parse(message: impl Into<String>) -> Self {
+        UrError::ParseError { line: 0, message: message.into() }
+    }
+
+    /// The error placed at `line`, for a problem found in one line parsed on its own
+    pub fn at_line(self, line: usize) -> Self {
+        match self {
+            UrError::ParseError { line: 0, message } => UrError::ParseError { line, message },
+            other => other,
+        }
+    }
+}
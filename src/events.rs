@@ -0,0 +1,113 @@
+//! Events that drive an interactive game. Everything the game reacts to comes
+//! from one `Events` source: key presses and terminal resizes, whole lines when
+//! input is piped, clock ticks, and AI moves searched on a worker thread. The
+//! game is a state machine fed from it, so a prompt can count down and the
+//! board can be redrawn while an AI is thinking.
+
+use std::io::{self, IsTerminal};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+use crate::ai::SearchInfo;
+use crate::input::{read_line, InputResult};
+use crate::terminal::restore_terminal;
+
+/// Time between `Tick`s
+pub const TICK: Duration = Duration::from_millis(100);
+/// How long to wait for a key before checking on a running AI search again
+const SEARCH_POLL: Duration = Duration::from_millis(20);
+
+pub enum GameEvent {
+    Key(KeyEvent),
+    /// A line of piped input; without a terminal there are no single keys
+    Line(String),
+    /// The terminal changed size
+    Resize,
+    Tick,
+    /// The search handed to `Events::await_search` found this move
+    AiMove(u8, SearchInfo),
+}
+
+pub struct Events {
+    /// Keys can be read one at a time
+    terminal: bool,
+    search: Option<Receiver<(u8, SearchInfo)>>,
+    next_tick: Instant,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let terminal = io::stdin().is_terminal() && enable_raw_mode().and_then(|_| disable_raw_mode()).is_ok();
+        Events { terminal, search: None, next_tick: Instant::now() + TICK }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    /// Report the move sent on `search` as an `AiMove` once it arrives
+    pub fn await_search(&mut self, search: Receiver<(u8, SearchInfo)>) {
+        self.search = Some(search);
+    }
+
+    /// Wait for the next event. `reading` says a prompt is waiting for input;
+    /// piped input is only read then, since a line cannot be read partway.
+    pub fn next(&mut self, reading: bool) -> InputResult<GameEvent> {
+        loop {
+            if let Some(search) = &self.search {
+                match search.try_recv() {
+                    Ok((piece, info)) => {
+                        self.search = None;
+                        return Ok(GameEvent::AiMove(piece, info));
+                    }
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => panic!("the AI search stopped without choosing a move"),
+                }
+            }
+
+            let now = Instant::now();
+            if now >= self.next_tick {
+                self.next_tick = now + TICK;
+                return Ok(GameEvent::Tick);
+            }
+            let mut wait = self.next_tick - now;
+            if self.search.is_some() {
+                wait = wait.min(SEARCH_POLL);
+            }
+
+            if self.terminal {
+                if let Some(event) = read_terminal(wait)? {
+                    return Ok(event);
+                }
+            } else if reading && self.search.is_none() {
+                // Nothing else can happen before the line arrives
+                return read_line().map(GameEvent::Line);
+            } else {
+                thread::sleep(wait);
+            }
+        }
+    }
+}
+
+/// A key press or resize within `wait`. Raw mode is only on while waiting, so
+/// the game prints as usual between events.
+fn read_terminal(wait: Duration) -> io::Result<Option<GameEvent>> {
+    enable_raw_mode()?;
+    let event = event::poll(wait).and_then(|ready| if ready { event::read().map(Some) } else { Ok(None) });
+    disable_raw_mode()?;
+    Ok(match event? {
+        // Raw mode swallows the interrupt signal, so honor ctrl-C here
+        Some(Event::Key(key)) if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) => {
+            restore_terminal();
+            println!();
+            std::process::exit(130);
+        }
+        Some(Event::Key(key)) if key.kind == KeyEventKind::Press => Some(GameEvent::Key(key)),
+        Some(Event::Resize(..)) => Some(GameEvent::Resize),
+        _ => None,
+    })
+}
@@ -0,0 +1,281 @@
+//! The Expectimax AI, the tier between the Smart AI and MCTS: it scores moves
+//! with the Smart AI's evaluation, but looks past each one to the expected
+//! best reply over every roll the next player may get. A move's value is its
+//! own score less the reply's, so a move that leaves a piece to be captured
+//! pays for the capture. Where the search stops on a move that leaves a
+//! piece in reach, it goes on over just the exchange on that square (the
+//! rolls that take the piece, and those that take the taker back) for up to
+//! `QUIESCENCE_PLIES` more, so it does not walk into a recapture just past
+//! its depth. Deeper searches follow a reply further only for its first
+//! `REPLY_WIDTH` moves in the order `order_moves` puts them.
+
+use crate::ai_helpers::{classify_move, evaluate_move_weighted, order_moves, EvalWeights, MoveClass};
+use crate::dice;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::GameStateRepr;
+
+/// Plies the Expectimax AI searches: its move and the reply; with the
+/// exchanges played out past them this beats a plain search of three or four
+pub const DEFAULT_PLIES: u8 = 2;
+/// Deepest search a player spec may ask for; each ply multiplies the work
+/// by the dozen or so replies a roll allows
+pub const MAX_PLIES: u8 = 5;
+/// Moves of a reply searched further than their own ply, the first in the
+/// order `order_moves` gives; the rest are not searched at all
+const REPLY_WIDTH: usize = 2;
+/// Plies a search may add past its depth to see a capture that is in the air
+/// settled
+const QUIESCENCE_PLIES: u8 = 2;
+
+/// The move with the best expected value `plies` deep, and how many moves
+/// were evaluated to find it; one ply is the Smart AI's choice. `moves` must
+/// be non-empty.
+pub fn choose_expectimax_move<S: GameStateRepr>(game: &S, player: FastPlayer, moves: &[u8], roll: u8, plies: u8) -> (u8, usize) {
+    let quiescence = if plies > 1 { QUIESCENCE_PLIES } else { 0 };
+    let mut search = Search { evaluations: 0, width: REPLY_WIDTH, quiescence, extended: 0 };
+    (search.best_move(game, player, moves, roll, plies.max(1)), search.evaluations)
+}
+
+/// One search and the work it has done
+struct Search {
+    evaluations: usize,
+    /// Moves searched deeper at each reply; every move is at the root, and
+    /// at the last ply, where looking at a move costs no more than ordering it
+    width: usize,
+    /// Plies that may be added past the search's depth to settle captures
+    quiescence: u8,
+    /// Plies past the search's depth at the move being looked at
+    extended: u8,
+}
+
+impl Search {
+    /// The best of `moves`; ties go to the move listed first, as with the Smart AI
+    fn best_move<S: GameStateRepr>(&mut self, game: &S, player: FastPlayer, moves: &[u8], roll: u8, plies: u8) -> u8 {
+        let mut best_move = moves[0];
+        let mut best_value = f64::NEG_INFINITY;
+
+        for &piece_idx in moves {
+            let value = self.move_value(game, player, piece_idx, roll, plies);
+            if value > best_value {
+                best_value = value;
+                best_move = piece_idx;
+            }
+        }
+
+        best_move
+    }
+
+    /// Moving `piece_idx` by `roll`, for `player`: the move's score plus what
+    /// follows it, from the point of view of whoever moves next
+    fn move_value<S: GameStateRepr>(&mut self, game: &S, player: FastPlayer, piece_idx: u8, roll: u8, plies: u8) -> f64 {
+        self.evaluations += 1;
+        let score = evaluate_move_weighted(game, player, piece_idx, roll, &EvalWeights::DEFAULT);
+        let mut next = *game;
+        let Ok(info) = next.make_move(piece_idx, roll) else {
+            return score;
+        };
+        if next.is_winner(player) {
+            return score;
+        }
+        let follow = if plies > 1 {
+            self.turn_value(&next, plies - 1)
+        } else if self.quiescence > 0 && matches!(classify_move(game, player, piece_idx, roll), MoveClass::RiskyCapture | MoveClass::RiskyAdvance) {
+            // The piece can be hit where it landed: play out the exchange there
+            self.exchange_value(&next, FastGameState::path_to_global(player, info.to_pos - 1))
+        } else {
+            return score;
+        };
+        // A rosette gives the mover the next turn as well
+        if next.current_player() == player { score + follow } else { score - follow }
+    }
+
+    /// Expected value of the turn about to be rolled, for the player to move
+    fn turn_value<S: GameStateRepr>(&mut self, game: &S, plies: u8) -> f64 {
+        let player = game.current_player();
+        let dice = dice::model_for(player);
+        let mut value = 0.0;
+        for roll in 0..=dice.max_roll() {
+            let chance = dice.probability(roll);
+            if chance > 0.0 {
+                value += chance * self.roll_value(game, player, roll, plies);
+            }
+        }
+        value
+    }
+
+    /// Expected gain for the player to move from taking the piece on
+    /// `square`, past the search's depth: the capture's score, less what the
+    /// piece that took it then stands to lose there. Only the exchange is
+    /// counted, and nothing for a roll that cannot capture, as no other
+    /// move is at the positions that are not extended.
+    fn exchange_value<S: GameStateRepr>(&mut self, game: &S, square: u8) -> f64 {
+        let player = game.current_player();
+        let Some(victim_path_idx) = path_index_on(game, player.opposite(), square) else {
+            return 0.0;
+        };
+        let weights = &EvalWeights::DEFAULT;
+        let gain = weights.capture + victim_path_idx as f64 * weights.capture_progress;
+
+        let dice = dice::model_for(player);
+        let mut value = 0.0;
+        for roll in 1..=dice.max_roll() {
+            let moves = game.generate_move_list(roll);
+            let Some(&piece_idx) = moves.iter().find(|&&piece_idx| landing_square(game, player, piece_idx, roll) == Some(square)) else {
+                continue;
+            };
+            self.evaluations += 1;
+            let mut next = *game;
+            if next.make_move(piece_idx, roll).is_err() {
+                continue;
+            }
+            let recapture = if self.extended + 1 < self.quiescence && next.current_player() != player {
+                self.extended += 1;
+                let recapture = self.exchange_value(&next, square);
+                self.extended -= 1;
+                recapture
+            } else {
+                0.0
+            };
+            value += dice.probability(roll) * (gain - recapture).max(0.0);
+        }
+        value
+    }
+
+    /// Value of the best move with `roll`; a roll without one passes the turn
+    fn roll_value<S: GameStateRepr>(&mut self, game: &S, player: FastPlayer, roll: u8, plies: u8) -> f64 {
+        let moves = game.generate_move_list(roll);
+        if moves.is_empty() {
+            if plies == 1 {
+                return 0.0;
+            }
+            let mut passed = *game;
+            passed.pass_turn();
+            return -self.turn_value(&passed, plies - 1);
+        }
+
+        let mut ordered = [0; 7];
+        let ordered = &mut ordered[..moves.len()];
+        ordered.copy_from_slice(&moves);
+        let width = if plies > 1 {
+            order_moves(game, player, ordered, roll);
+            self.width
+        } else {
+            ordered.len()
+        };
+        ordered.iter().take(width)
+            .map(|&piece_idx| self.move_value(game, player, piece_idx, roll, plies))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+/// Path index of `player`'s piece on `square`, if one is there
+fn path_index_on<S: GameStateRepr>(game: &S, player: FastPlayer, square: u8) -> Option<u8> {
+    (0..7).map(|piece_idx| game.get_piece_pos(player, piece_idx))
+        .filter(|&pos| FastGameState::is_on_board(pos))
+        .map(|pos| pos - 1)
+        .find(|&path_idx| FastGameState::path_to_global(player, path_idx) == square)
+}
+
+/// The square moving `piece_idx` by `roll` lands on; None when bearing off
+fn landing_square<S: GameStateRepr>(game: &S, player: FastPlayer, piece_idx: u8, roll: u8) -> Option<u8> {
+    let path_idx = match game.get_piece_pos(player, piece_idx) {
+        0 => 0,
+        pos => pos - 1 + roll,
+    };
+    (path_idx < FastGameState::path_len()).then(|| FastGameState::path_to_global(player, path_idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_helpers::choose_smart_move_fast;
+    use crate::rng::GameRng;
+
+    /// Play one game with the dice from `seed`; whether the Expectimax AI, as `side`, won
+    fn expectimax_wins(side: FastPlayer, seed: u64) -> bool {
+        let mut dice = GameRng::from_seed(seed);
+        let mut game = FastGameState::new();
+        while !game.status().is_over() {
+            let player = game.current_player();
+            let roll = dice.roll_dice(player);
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            let piece = if player == side {
+                choose_expectimax_move(&game, player, &moves, roll, DEFAULT_PLIES).0
+            } else {
+                choose_smart_move_fast(&game, player, &moves, roll)
+            };
+            game.make_move(piece, roll).expect("generated moves are legal");
+        }
+        game.status().winner() == Some(side)
+    }
+
+    #[test]
+    fn looking_ahead_beats_the_smart_ai() {
+        // It won 149 of these games when this was written; 133 is about 2.5 standard deviations below
+        let wins = (0..100).flat_map(|seed| [FastPlayer::One, FastPlayer::Two].map(|side| expectimax_wins(side, seed)))
+            .filter(|&won| won)
+            .count();
+        assert!(wins > 133, "Expectimax won only {} of 200 games against the Smart AI", wins);
+    }
+
+    #[test]
+    fn narrow_replies_rarely_change_the_move() {
+        // Two plies search every reply anyway, as the last ply is not ordered
+        let (mut narrow, mut full, mut changed, mut decisions) = (0, 0, 0, 0);
+        for seed in 0..10 {
+            let mut dice = GameRng::from_seed(seed);
+            let mut game = FastGameState::new();
+            while !game.status().is_over() {
+                let player = game.current_player();
+                let roll = dice.roll_dice(player);
+                let moves = game.generate_moves(roll);
+                if moves.is_empty() {
+                    game.pass_turn();
+                    continue;
+                }
+                let (piece, evaluations) = choose_expectimax_move(&game, player, &moves, roll, 3);
+                let mut search = Search { evaluations: 0, width: 7, quiescence: QUIESCENCE_PLIES, extended: 0 };
+                changed += usize::from(search.best_move(&game, player, &moves, roll, 3) != piece);
+                decisions += 1;
+                narrow += evaluations;
+                full += search.evaluations;
+                game.make_move(piece, roll).expect("generated moves are legal");
+            }
+        }
+        // Searching every reply took twice the evaluations and changed 5 of 1085 moves over these games
+        assert!(narrow * 3 < full * 2, "{} evaluations searching narrowly, {} searching every reply", narrow, full);
+        assert!(changed * 100 < decisions, "{} of {} moves changed", changed, decisions);
+    }
+
+    #[test]
+    fn quiescence_plays_out_the_exchange_past_its_depth() {
+        // Two to move with a 2: running its piece on to the central lane leaves it in reach of One's piece behind
+        let game = crate::position_code::decode("UR:100000050000014").unwrap();
+        let moves = game.generate_moves(2);
+        let mut flat = Search { evaluations: 0, width: REPLY_WIDTH, quiescence: 0, extended: 0 };
+        assert_eq!(flat.best_move(&game, FastPlayer::Two, &moves, 2, DEFAULT_PLIES), 0);
+        assert_eq!(choose_expectimax_move(&game, FastPlayer::Two, &moves, 2, DEFAULT_PLIES).0, 1);
+    }
+
+    #[test]
+    fn one_ply_is_the_smart_ai() {
+        let mut dice = GameRng::from_seed(5);
+        let mut game = FastGameState::new();
+        while !game.status().is_over() {
+            let player = game.current_player();
+            let roll = dice.roll_dice(player);
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            let piece = choose_smart_move_fast(&game, player, &moves, roll);
+            assert_eq!(choose_expectimax_move(&game, player, &moves, roll, 1).0, piece);
+            game.make_move(piece, roll).expect("generated moves are legal");
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Built-in experiments that play many silent games and summarize AI strength,
+//! or how the rules shape the game. Matches alternate seats and mirror dice
+//! within each pair of games to cut variance.
+
+use std::fs;
+use std::io::{self, Write};
+
+use crate::ai::HybridAI;
+use crate::ai_helpers::EvalWeights;
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::leaderboard::{record_match, DEFAULT_LEADERBOARD_FILE};
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::rating::elo_from_score;
+use crate::rng::GameRng;
+use crate::stats::{play_silent_game, play_silent_game_from, StatsAgent};
+use crate::workers;
+
+/// Outcome of a candidate-vs-baseline match, from the candidate's point of view
+#[derive(Debug, Clone, Copy)]
+pub struct MatchResult {
+    pub games: usize,
+    pub wins: usize,
+}
+
+impl MatchResult {
+    pub fn score(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    /// Standard error of the score (binomial approximation)
+    pub fn std_error(&self) -> f64 {
+        let p = self.score();
+        (p * (1.0 - p) / self.games.max(1) as f64).sqrt()
+    }
+
+    pub fn elo(&self) -> f64 {
+        elo_from_score(self.score())
+    }
+
+    /// 95% confidence interval on the Elo difference
+    pub fn elo_interval(&self) -> (f64, f64) {
+        let margin = 1.96 * self.std_error();
+        (elo_from_score(self.score() - margin), elo_from_score(self.score() + margin))
+    }
+}
+
+/// Play `games` games, swapping seats every game and reusing dice within each pair,
+/// and add the result to the leaderboard; drawn games are left out of both
+pub fn play_match(candidate: &StatsAgent, baseline: &StatsAgent, games: usize) -> MatchResult {
+    let mut wins = 0;
+    let mut draws = 0;
+    let mut pair_seed = 0;
+
+    for game_idx in 0..games {
+        if game_idx % 2 == 0 {
+            pair_seed = GameRng::random().seed();
+        }
+        let mut dice = GameRng::from_seed(pair_seed);
+
+        let candidate_seat = if game_idx % 2 == 0 { FastPlayer::One } else { FastPlayer::Two };
+        let game = match candidate_seat {
+            FastPlayer::One => play_silent_game(candidate, baseline, &mut dice),
+            FastPlayer::Two => play_silent_game(baseline, candidate, &mut dice),
+        };
+        match game.status.winner() {
+            Some(winner) if winner == candidate_seat => wins += 1,
+            Some(_) => {}
+            None => draws += 1,
+        }
+    }
+
+    record_match(&candidate.label(), &baseline.label(), games - draws, wins);
+    MatchResult { games: games - draws, wins }
+}
+
+/// Render one horizontal bar per row, scaled between the smallest and largest value
+fn print_bar_chart(rows: &[(String, f64)], unit: &str) {
+    const WIDTH: f64 = 40.0;
+    let min = rows.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    let max = rows.iter().map(|(_, v)| *v).fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(1.0);
+    let label_width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+
+    for (label, value) in rows {
+        let len = 1 + ((value - min) / range * WIDTH).round() as usize;
+        println!("  {:>width$} │{} {:+.0} {}", label, "█".repeat(len), value, unit, width = label_width);
+    }
+}
+
+pub struct ScalingOptions {
+    pub games_per_step: usize,
+    pub max_simulations: usize,
+    pub threads: usize,
+    pub csv_path: String,
+}
+
+impl ScalingOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--max-sims", "--threads", "--csv"], &[])?;
+        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        Ok(ScalingOptions {
+            games_per_step: flags.get("--games", 100)?,
+            max_simulations: flags.get("--max-sims", 4000)?,
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            csv_path: flags.get_str("--csv").unwrap_or("scaling.csv").to_string(),
+        })
+    }
+}
+
+/// Play MCTS at doubling simulation budgets against the Smart heuristic and report Elo per budget
+pub fn run_scaling_study(options: &ScalingOptions) -> io::Result<()> {
+    let mut ladder = vec![];
+    let mut sims = 250;
+    while sims <= options.max_simulations {
+        ladder.push(sims);
+        sims *= 2;
+    }
+
+    println!("=== MCTS SCALING STUDY ===");
+    println!("Baseline: Smart AI | {} games per step | {} threads", options.games_per_step, options.threads);
+    println!();
+
+    let baseline = StatsAgent::Smart;
+    let mut csv = String::from("simulations,games,wins,score,elo,elo_low,elo_high\n");
+    let mut chart_rows = vec![];
+
+    for &simulations in &ladder {
+        print!("  {:>6} simulations... ", simulations);
+        io::stdout().flush()?;
+
+        let candidate = StatsAgent::MCTS(HybridAI::new_with_threads(simulations, options.threads));
+        let result = play_match(&candidate, &baseline, options.games_per_step);
+        let (low, high) = result.elo_interval();
+
+        println!("{}/{} wins ({:.1}%), Elo {:+.0} [{:+.0}, {:+.0}]",
+                 result.wins, result.games, result.score() * 100.0, result.elo(), low, high);
+
+        csv.push_str(&format!("{},{},{},{:.4},{:.1},{:.1},{:.1}\n",
+                              simulations, result.games, result.wins, result.score(), result.elo(), low, high));
+        chart_rows.push((format!("{} sims", simulations), result.elo()));
+    }
+
+    println!();
+    println!("Elo vs Smart AI:");
+    print_bar_chart(&chart_rows, "Elo");
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Results written to {} and added to {}", options.csv_path, DEFAULT_LEADERBOARD_FILE);
+    Ok(())
+}
+
+pub struct SweepOptions {
+    pub games_per_value: usize,
+    pub simulations: usize,
+    pub min_constant: f64,
+    pub max_constant: f64,
+    pub steps: usize,
+    pub threads: usize,
+    pub csv_path: String,
+}
+
+impl SweepOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--sims", "--min", "--max", "--steps", "--threads", "--csv"], &[])?;
+        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let options = SweepOptions {
+            games_per_value: flags.get("--games", 100)?,
+            simulations: flags.get("--sims", 1000)?,
+            min_constant: flags.get("--min", 0.25)?,
+            max_constant: flags.get("--max", 2.5)?,
+            steps: flags.get("--steps", 10)?.max(1),
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            csv_path: flags.get_str("--csv").unwrap_or("sweep.csv").to_string(),
+        };
+        if !(options.min_constant >= 0.0 && options.min_constant <= options.max_constant) {
+            return Err("--min must be non-negative and no larger than --max".to_string());
+        }
+        Ok(options)
+    }
+
+    /// Evenly spaced exploration constants from min to max inclusive
+    fn constants(&self) -> Vec<f64> {
+        if self.steps == 1 {
+            return vec![self.min_constant];
+        }
+        let step = (self.max_constant - self.min_constant) / (self.steps - 1) as f64;
+        (0..self.steps).map(|i| self.min_constant + step * i as f64).collect()
+    }
+}
+
+/// Play MCTS with each UCB1 exploration constant against the Smart heuristic and report the best one
+pub fn run_exploration_sweep(options: &SweepOptions) -> io::Result<()> {
+    println!("=== UCB1 EXPLORATION SWEEP ===");
+    println!("Baseline: Smart AI | {} simulations | {} games per value | {} threads",
+             options.simulations, options.games_per_value, options.threads);
+    println!();
+
+    let baseline = StatsAgent::Smart;
+    let mut csv = String::from("exploration,games,wins,score,elo,elo_low,elo_high\n");
+    let mut chart_rows = vec![];
+    let mut results = vec![];
+
+    for constant in options.constants() {
+        print!("  c = {:<5.3}... ", constant);
+        io::stdout().flush()?;
+
+        let mut ai = HybridAI::new_with_threads(options.simulations, options.threads);
+        ai.mcts.exploration_constant = constant;
+        let result = play_match(&StatsAgent::MCTS(ai), &baseline, options.games_per_value);
+        let (low, high) = result.elo_interval();
+
+        println!("{}/{} wins ({:.1}%), Elo {:+.0} [{:+.0}, {:+.0}]",
+                 result.wins, result.games, result.score() * 100.0, result.elo(), low, high);
+
+        csv.push_str(&format!("{:.4},{},{},{:.4},{:.1},{:.1},{:.1}\n",
+                              constant, result.games, result.wins, result.score(), result.elo(), low, high));
+        chart_rows.push((format!("c={:.3}", constant), result.elo()));
+        results.push((constant, result));
+    }
+
+    println!();
+    println!("Elo vs Smart AI:");
+    print_bar_chart(&chart_rows, "Elo");
+
+    if let Some(&(best_constant, best)) = results.iter().max_by(|a, b| a.1.score().total_cmp(&b.1.score())) {
+        let (low, high) = best.elo_interval();
+        println!();
+        println!("Best constant: {:.3} (Elo {:+.0} [{:+.0}, {:+.0}], default is √2 ≈ {:.3})",
+                 best_constant, best.elo(), low, high, std::f64::consts::SQRT_2);
+
+        // Any value whose interval reaches the best one's lower bound is not clearly worse
+        let contenders: Vec<String> = results.iter()
+            .filter(|(c, r)| *c != best_constant && r.elo_interval().1 >= low)
+            .map(|(c, _)| format!("{:.3}", c))
+            .collect();
+        if !contenders.is_empty() {
+            println!("Statistically indistinguishable at 95%: {}", contenders.join(", "));
+        }
+    }
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Results written to {} and added to {}", options.csv_path, DEFAULT_LEADERBOARD_FILE);
+    Ok(())
+}
+
+pub struct AblationOptions {
+    pub games_per_pair: usize,
+    pub vulnerability: f64,
+    pub csv_path: String,
+}
+
+impl AblationOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--vulnerability", "--csv"], &[])?;
+        Ok(AblationOptions {
+            games_per_pair: flags.get("--games", 2000)?,
+            vulnerability: flags.get("--vulnerability", 200.0)?,
+            csv_path: flags.get_str("--csv").unwrap_or("ablation.csv").to_string(),
+        })
+    }
+}
+
+/// The full weight set followed by one variant per feature with that feature zeroed
+fn ablation_variants(full: EvalWeights) -> Vec<(&'static str, EvalWeights)> {
+    vec![
+        ("full", full),
+        ("no-rosette", EvalWeights { rosette: 0.0, ..full }),
+        ("no-capture", EvalWeights { capture: 0.0, capture_progress: 0.0, ..full }),
+        ("no-vulnerability", EvalWeights { vulnerability: 0.0, ..full }),
+        ("no-advancement", EvalWeights { advancement: 0.0, ..full }),
+    ]
+}
+
+/// Round-robin between the full heuristic and each single-feature ablation
+pub fn run_ablation(options: &AblationOptions) -> io::Result<()> {
+    let full = EvalWeights { vulnerability: options.vulnerability, ..EvalWeights::DEFAULT };
+    let variants = ablation_variants(full);
+    let agents: Vec<StatsAgent> = variants.iter().map(|(_, w)| StatsAgent::Weighted(*w)).collect();
+
+    println!("=== HEURISTIC FEATURE ABLATION ===");
+    println!("{} variants | {} games per pairing | vulnerability weight {}",
+             variants.len(), options.games_per_pair, options.vulnerability);
+    println!();
+
+    // results[i][j] is variant i's match against variant j
+    let mut results = vec![vec![None; variants.len()]; variants.len()];
+    let mut csv = String::from("variant,opponent,games,wins,score,elo\n");
+
+    for i in 0..variants.len() {
+        for j in (i + 1)..variants.len() {
+            print!("  {} vs {}... ", variants[i].0, variants[j].0);
+            io::stdout().flush()?;
+
+            let result = play_match(&agents[i], &agents[j], options.games_per_pair);
+            println!("{:.1}%", result.score() * 100.0);
+
+            let mirrored = MatchResult { games: result.games, wins: result.games - result.wins };
+            for (a, b, r) in [(i, j, result), (j, i, mirrored)] {
+                csv.push_str(&format!("{},{},{},{},{:.4},{:.1}\n",
+                                      variants[a].0, variants[b].0, r.games, r.wins, r.score(), r.elo()));
+                results[a][b] = Some(r);
+            }
+        }
+    }
+
+    println!();
+    println!("ROUND-ROBIN STANDINGS:");
+    let mut standings: Vec<(usize, MatchResult)> = results.iter().enumerate().map(|(i, row)| {
+        let total = row.iter().flatten().fold(MatchResult { games: 0, wins: 0 }, |acc, r| MatchResult {
+            games: acc.games + r.games,
+            wins: acc.wins + r.wins,
+        });
+        (i, total)
+    }).collect();
+    standings.sort_by(|a, b| b.1.score().total_cmp(&a.1.score()));
+    for (i, total) in &standings {
+        println!("  {:<18} {:>5.1}%  Elo vs field {:+.0}", variants[*i].0, total.score() * 100.0, total.elo());
+    }
+
+    println!();
+    println!("FEATURE CONTRIBUTION (full vs ablated, 95% interval):");
+    let mut chart_rows = vec![];
+    for (j, (name, _)) in variants.iter().enumerate().skip(1) {
+        let Some(result) = results[0][j] else { continue };
+        let (low, high) = result.elo_interval();
+        let feature = name.trim_start_matches("no-");
+        println!("  {:<14} {:+6.0} Elo [{:+.0}, {:+.0}]", feature, result.elo(), low, high);
+        chart_rows.push((feature.to_string(), result.elo()));
+    }
+    println!();
+    print_bar_chart(&chart_rows, "Elo");
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Results written to {} and added to {}", options.csv_path, DEFAULT_LEADERBOARD_FILE);
+    Ok(())
+}
+
+pub struct VariantStudyOptions {
+    pub games_per_variant: usize,
+    pub threads: usize,
+    pub csv_path: String,
+}
+
+impl VariantStudyOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--threads", "--csv"], &[])?;
+        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        Ok(VariantStudyOptions {
+            games_per_variant: flags.get("--games", 20_000)?.max(1),
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            csv_path: flags.get_str("--csv").unwrap_or("variants.csv").to_string(),
+        })
+    }
+}
+
+/// One rule set of the variant study
+struct RuleVariant {
+    name: &'static str,
+    board: BoardVariant,
+    /// Pieces each side plays with; the rest start borne off
+    pieces: u8,
+}
+
+const RULE_VARIANTS: [RuleVariant; 5] = [
+    RuleVariant { name: "standard", board: BoardVariant::Standard, pieces: 7 },
+    RuleVariant { name: "open central rosette", board: BoardVariant::OpenRosette, pieces: 7 },
+    RuleVariant { name: "five rosettes", board: BoardVariant::FiveRosettes, pieces: 7 },
+    RuleVariant { name: "5 pieces", board: BoardVariant::Standard, pieces: 5 },
+    RuleVariant { name: "extended board", board: BoardVariant::Extended, pieces: 7 },
+];
+
+/// The starting position for a game with `pieces` pieces a side: pieces not in
+/// play are already home, which plays exactly like a game with fewer pieces
+pub fn start_with_pieces(pieces: u8) -> FastGameState {
+    let pieces: [u8; 7] = std::array::from_fn(|idx| if idx < pieces as usize { 0 } else { FastGameState::finished_pos() });
+    FastGameState::from_piece_positions(pieces, pieces, FastPlayer::One).expect("pieces off the board or home")
+}
+
+/// Totals over the games of one variant
+#[derive(Debug, Clone, Copy, Default)]
+struct VariantTally {
+    games: usize,
+    first_player_wins: usize,
+    turns: usize,
+    captures: usize,
+}
+
+impl VariantTally {
+    fn merge(self, other: VariantTally) -> VariantTally {
+        VariantTally {
+            games: self.games + other.games,
+            first_player_wins: self.first_player_wins + other.first_player_wins,
+            turns: self.turns + other.turns,
+            captures: self.captures + other.captures,
+        }
+    }
+
+    fn first_player_score(&self) -> MatchResult {
+        MatchResult { games: self.games, wins: self.first_player_wins }
+    }
+
+    fn per_game(&self, total: usize) -> f64 {
+        total as f64 / self.games.max(1) as f64
+    }
+}
+
+/// Play the Smart AI against itself under each rule variant and report how the
+/// rules shift the first player's edge, game length and captures. Every variant
+/// gets the same dice seeds, so differences come from the rules.
+pub fn run_variant_study(options: &VariantStudyOptions) -> io::Result<()> {
+    println!("=== RULE VARIANT STUDY ===");
+    println!("Smart AI vs itself | {} games per variant | {} threads", options.games_per_variant, options.threads);
+    println!();
+
+    let seeds: Vec<u64> = (0..options.games_per_variant).map(|_| GameRng::random().seed()).collect();
+    let mut csv = String::from("variant,board,pieces,games,first_player_wins,first_player_score,avg_turns,captures_per_game\n");
+    let mut tallies = vec![];
+
+    for variant in &RULE_VARIANTS {
+        print!("  {}... ", variant.name);
+        io::stdout().flush()?;
+        // Every thread plays on the board switched to here
+        board::switch(variant.board);
+        let start = start_with_pieces(variant.pieces);
+        let chunk = seeds.len().div_ceil(options.threads);
+        let jobs = seeds.chunks(chunk).map(|seeds| move || {
+            let agent = StatsAgent::Smart;
+            seeds.iter().fold(VariantTally::default(), |tally, &seed| {
+                let game = play_silent_game_from(start, &agent, &agent, &mut GameRng::from_seed(seed));
+                tally.merge(VariantTally {
+                    games: 1,
+                    first_player_wins: usize::from(game.status.winner() == Some(FastPlayer::One)),
+                    turns: game.turns,
+                    captures: game.captures_p1 + game.captures_p2,
+                })
+            })
+        });
+        let tally = workers::run_parallel("study", jobs).into_iter().fold(VariantTally::default(), VariantTally::merge);
+        println!("first player {:.1}%", tally.first_player_score().score() * 100.0);
+
+        csv.push_str(&format!("{},{},{},{},{},{:.4},{:.2},{:.3}\n",
+                              variant.name, variant.board.label(), variant.pieces, tally.games, tally.first_player_wins,
+                              tally.first_player_score().score(), tally.per_game(tally.turns), tally.per_game(tally.captures)));
+        tallies.push(tally);
+    }
+
+    println!();
+    println!("RESULTS (first player's score with 95% interval, change from standard):");
+    let baseline = tallies[0];
+    let mut chart_rows = vec![];
+    for (variant, tally) in RULE_VARIANTS.iter().zip(&tallies) {
+        let result = tally.first_player_score();
+        let margin = 1.96 * result.std_error() * 100.0;
+        println!("  {:<20} {:>5.1}% ±{:.1}  ({:+.1})  {:>6.1} turns ({:+.1})  {:>5.2} captures ({:+.2})",
+                 variant.name, result.score() * 100.0, margin,
+                 (result.score() - baseline.first_player_score().score()) * 100.0,
+                 tally.per_game(tally.turns), tally.per_game(tally.turns) - baseline.per_game(baseline.turns),
+                 tally.per_game(tally.captures), tally.per_game(tally.captures) - baseline.per_game(baseline.captures));
+        chart_rows.push((variant.name.to_string(), result.elo()));
+    }
+    println!();
+    println!("FIRST-PLAYER ADVANTAGE:");
+    print_bar_chart(&chart_rows, "Elo");
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Results written to {}", options.csv_path);
+    Ok(())
+}
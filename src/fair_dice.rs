@@ -0,0 +1,313 @@
+//! Fair dice by commit-reveal, for `ur match --fair-dice`: before each roll
+//! both seats pick a secret 32-byte nonce and send its SHA-256 hash (the
+//! commitment); once both commitments are in, each reveals its nonce, and the
+//! roll follows from the two nonces and the roll's number. Neither side can
+//! steer the dice, since it has to commit before seeing the other's nonce, and
+//! neither can back out of a nonce, since the referee checks it against the
+//! commitment. In-process players leave their share to the referee. Every
+//! roll's nonces go to a dice log, which `ur verify-dice` checks roll by roll.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rand::rngs::OsRng;
+use rand::{RngCore, TryRngCore};
+use sha2::{Digest, Sha256};
+
+use crate::args::Flags;
+use crate::dice::{self, DiceVariant};
+use crate::error::UrError;
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+pub const DEFAULT_DICE_LOG: &str = "ur_dice_log.txt";
+
+const LOG_HEADER: &str = "# Royal Game of Ur fair dice log";
+const LOG_VERSION: u32 = 1;
+/// Hashed ahead of each roll's nonces, so a roll cannot be passed off as any other hash
+const ROLL_DOMAIN: &[u8] = b"ur fair dice roll";
+
+/// A seat's secret share of one roll
+pub type Nonce = [u8; 32];
+/// SHA-256 of a nonce, sent before any nonce is revealed
+pub type Commitment = [u8; 32];
+
+pub fn fresh_nonce() -> Nonce {
+    let mut nonce = [0; 32];
+    OsRng.unwrap_err().fill_bytes(&mut nonce);
+    nonce
+}
+
+pub fn commit(nonce: &Nonce) -> Commitment {
+    Sha256::digest(nonce).into()
+}
+
+/// Roll `number` (counting from 1 in each game) of `player`'s dice, from both seats' nonces
+pub fn roll(number: u64, player: FastPlayer, nonces: &[Nonce; 2]) -> u8 {
+    let block = Sha256::new()
+        .chain_update(ROLL_DOMAIN)
+        .chain_update(number.to_le_bytes())
+        .chain_update(nonces[0])
+        .chain_update(nonces[1])
+        .finalize()
+        .into();
+    FastGameState::roll_dice_with(player, &mut HashStream { block, used: 0 })
+}
+
+/// Bytes of a hash, then of the hash of that, and so on, as random numbers
+/// for the dice model
+struct HashStream {
+    block: [u8; 32],
+    used: usize,
+}
+
+impl RngCore for HashStream {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for byte in dst {
+            if self.used == self.block.len() {
+                self.block = Sha256::digest(self.block).into();
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+pub fn to_hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 64 hex digits as 32 bytes
+pub fn from_hex(text: &str) -> Option<[u8; 32]> {
+    if text.len() != 64 || !text.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[idx * 2..idx * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// One roll as made, with the nonces it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairRoll {
+    pub number: u64,
+    pub player: FastPlayer,
+    /// Player 1's seat's nonce, then Player 2's
+    pub nonces: [Nonce; 2],
+    pub roll: u8,
+}
+
+impl FairRoll {
+    /// Whether the recorded roll is the one its nonces give
+    pub fn checks_out(&self) -> bool {
+        roll(self.number, self.player, &self.nonces) == self.roll
+    }
+}
+
+/// Every roll of a match's games, appended game by game
+pub struct DiceLog {
+    file: File,
+}
+
+impl DiceLog {
+    /// Start a log for rolls of the current dice, replacing any file at `path`
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", LOG_HEADER)?;
+        writeln!(file, "version {}", LOG_VERSION)?;
+        writeln!(file, "dice {}", dice::variant().label())?;
+        if dice::is_biased() {
+            let [p1, p2] = dice::pip_chances();
+            writeln!(file, "pips {} {}", p1, p2)?;
+        }
+        Ok(DiceLog { file })
+    }
+
+    pub fn write_game(&mut self, name: &str, rolls: &[FairRoll]) -> io::Result<()> {
+        let mut text = format!("game {}\n", name);
+        for roll in rolls {
+            text.push_str(&format!("roll {} {} {} {} {}\n", roll.number, roll.player as usize + 1,
+                                   to_hex(&roll.nonces[0]), to_hex(&roll.nonces[1]), roll.roll));
+        }
+        self.file.write_all(text.as_bytes())
+    }
+}
+
+/// A dice log read back: the dice, their pip chances and each game's rolls
+pub struct LoggedMatch {
+    dice: DiceVariant,
+    pips: Option<[f64; 2]>,
+    games: Vec<(String, Vec<FairRoll>)>,
+}
+
+/// Read back a log written by `DiceLog`
+pub fn parse_log(text: &str) -> Result<LoggedMatch, UrError> {
+    let mut version = None;
+    let mut logged = LoggedMatch { dice: DiceVariant::default(), pips: None, games: Vec::new() };
+
+    for (idx, raw) in text.lines().enumerate() {
+        let err = |message: String| UrError::ParseError { line: idx + 1, message };
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        match (key, fields.as_slice()) {
+            ("version", [v]) => {
+                let v: u32 = v.parse().map_err(|_| err("expected a version number".to_string()))?;
+                if v != LOG_VERSION {
+                    return Err(err(format!("unsupported dice log version {}", v)));
+                }
+                version = Some(v);
+            }
+            ("dice", [label]) => logged.dice = DiceVariant::from_label(label).ok_or_else(|| err(format!("unknown dice '{}'", label)))?,
+            ("pips", [p1, p2]) => {
+                let (Ok(p1), Ok(p2)) = (p1.parse(), p2.parse()) else {
+                    return Err(err("pip chances must be numbers".to_string()));
+                };
+                logged.pips = Some([p1, p2]);
+            }
+            ("game", _) => logged.games.push((rest.trim().to_string(), Vec::new())),
+            ("roll", [number, side, nonce1, nonce2, value]) => {
+                let game = logged.games.last_mut().ok_or_else(|| err("roll before any game".to_string()))?;
+                let player = match *side {
+                    "1" => FastPlayer::One,
+                    "2" => FastPlayer::Two,
+                    _ => return Err(err("side must be 1 or 2".to_string())),
+                };
+                let (Some(nonce1), Some(nonce2)) = (from_hex(nonce1), from_hex(nonce2)) else {
+                    return Err(err("nonces must be 64 hex digits".to_string()));
+                };
+                let (Ok(number), Ok(roll)) = (number.parse(), value.parse()) else {
+                    return Err(err("roll number and value must be numbers".to_string()));
+                };
+                game.1.push(FairRoll { number, player, nonces: [nonce1, nonce2], roll });
+            }
+            _ => return Err(err(format!("unrecognized line '{}'", line))),
+        }
+    }
+
+    version.ok_or_else(|| UrError::parse("missing 'version'"))?;
+    Ok(logged)
+}
+
+/// What is wrong with a game's rolls: one line per roll that is out of
+/// order or does not follow from its nonces
+fn check_game(game: &[FairRoll]) -> Vec<String> {
+    (1..).zip(game).filter_map(|(expected, roll)| {
+        if roll.number != expected {
+            Some(format!("roll {} is numbered {}", expected, roll.number))
+        } else if !roll.checks_out() {
+            Some(format!("roll {} was {}, but its nonces give {}", roll.number, roll.roll, self::roll(roll.number, roll.player, &roll.nonces)))
+        } else {
+            None
+        }
+    }).collect()
+}
+
+/// Options for `ur verify-dice [--file FILE]`
+pub struct VerifyDiceOptions {
+    pub file: PathBuf,
+}
+
+impl VerifyDiceOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--file"], &[])?;
+        Ok(VerifyDiceOptions { file: PathBuf::from(flags.get_str("--file").unwrap_or(DEFAULT_DICE_LOG)) })
+    }
+}
+
+/// Check every roll in a dice log against its nonces; returns whether all did
+pub fn run_verify_dice(options: &VerifyDiceOptions) -> Result<bool, UrError> {
+    let logged = parse_log(&fs::read_to_string(&options.file)?)?;
+    dice::select(logged.dice);
+    if let Some(pips) = logged.pips {
+        dice::select_pip_chances(pips);
+    }
+    println!("=== FAIR DICE CHECK ({}) ===", options.file.display());
+
+    let (mut rolls, mut bad) = (0, 0);
+    for (name, game) in &logged.games {
+        rolls += game.len();
+        for problem in check_game(game) {
+            bad += 1;
+            println!("❌ {}: {}", name, problem);
+        }
+    }
+    if bad == 0 {
+        println!("✅ All {} rolls in {} games follow from their nonces", rolls, logged.games.len());
+    } else {
+        println!("{} of {} rolls do not follow from their nonces", bad, rolls);
+    }
+    Ok(bad == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game's rolls made by commit-reveal, checking each nonce against its commitment
+    fn committed_rolls(count: u64) -> Vec<FairRoll> {
+        (1..=count).map(|number| {
+            let nonces = [fresh_nonce(), fresh_nonce()];
+            let commitments = nonces.map(|nonce| commit(&nonce));
+            assert!(nonces.iter().zip(&commitments).all(|(nonce, commitment)| commit(nonce) == *commitment));
+            let player = if number % 2 == 1 { FastPlayer::One } else { FastPlayer::Two };
+            FairRoll { number, player, nonces, roll: roll(number, player, &nonces) }
+        }).collect()
+    }
+
+    fn log_text(rolls: &[FairRoll]) -> String {
+        let mut text = format!("{}\nversion {}\ndice tetrahedra\ngame match 1\n", LOG_HEADER, LOG_VERSION);
+        for roll in rolls {
+            text.push_str(&format!("roll {} {} {} {} {}\n", roll.number, roll.player as usize + 1,
+                                   to_hex(&roll.nonces[0]), to_hex(&roll.nonces[1]), roll.roll));
+        }
+        text
+    }
+
+    #[test]
+    fn logged_rolls_verify_and_tampering_is_caught() {
+        let rolls = committed_rolls(12);
+        let logged = parse_log(&log_text(&rolls)).unwrap();
+        assert_eq!(logged.dice, DiceVariant::Tetrahedra);
+        assert_eq!(logged.games, vec![("match 1".to_string(), rolls.clone())]);
+        assert!(check_game(&logged.games[0].1).is_empty());
+
+        // Another nonce for roll 5 that gives another roll
+        let mut forged = rolls.clone();
+        let original = forged[4].roll;
+        while forged[4].checks_out() {
+            forged[4].nonces[1] = fresh_nonce();
+        }
+        assert_eq!(forged[4].roll, original);
+        let logged = parse_log(&log_text(&forged)).unwrap();
+        assert_eq!(check_game(&logged.games[0].1), vec![format!("roll 5 was {}, but its nonces give {}", original,
+                                                                  roll(5, FastPlayer::One, &forged[4].nonces))]);
+
+        let mut changed = rolls.clone();
+        changed[7].roll = (changed[7].roll + 1) % 5;
+        assert_eq!(check_game(&parse_log(&log_text(&changed)).unwrap().games[0].1).len(), 1);
+
+        let mut skipped = rolls;
+        skipped.remove(2);
+        assert!(!check_game(&parse_log(&log_text(&skipped)).unwrap().games[0].1).is_empty());
+        assert!(parse_log(&log_text(&[]).replace("version 1", "version 9")).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Allocation-free stats path for the Random and Smart agents.
+//! Move lists live on the stack, dice come from a per-run SmallRng, and nothing
+//! is formatted or spawned while games are being played.
+
+use std::time::{Duration, Instant};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::ai_helpers::choose_smart_move_fast;
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::state_repr::GameStateRepr;
+use crate::stats::{ExtraRolls, GameStatistics};
+
+/// Agents that can run on the fast path (MCTS needs threads and allocations)
+#[derive(Debug, Clone, Copy)]
+pub enum FastAgent {
+    Random,
+    Smart,
+}
+
+impl FastAgent {
+    /// Name on the leaderboard, shared with the equivalent stats agent
+    pub fn label(self) -> &'static str {
+        match self {
+            FastAgent::Random => "random",
+            FastAgent::Smart => "smart",
+        }
+    }
+}
+
+/// Outcome of a fast stats run
+pub struct FastStatsRun {
+    pub stats: GameStatistics,
+    pub elapsed: Duration,
+}
+
+impl FastStatsRun {
+    pub fn games_per_minute(&self) -> f64 {
+        self.stats.total_games() as f64 / self.elapsed.as_secs_f64().max(1e-9) * 60.0
+    }
+
+    pub fn display_throughput(&self) {
+        println!();
+        println!("THROUGHPUT:");
+        println!("  {} games in {:.2}s", self.stats.total_games(), self.elapsed.as_secs_f64());
+        println!("  {:.0} games/sec ({:.0} games/min)",
+                 self.games_per_minute() / 60.0, self.games_per_minute());
+    }
+}
+
+/// Play `num_games` games between two fast agents
+pub fn run_fast_stats(p1: FastAgent, p2: FastAgent, num_games: usize) -> FastStatsRun {
+    let mut rng = SmallRng::from_rng(&mut rand::rng());
+    let mut stats = GameStatistics::new();
+
+    let start = Instant::now();
+    for _ in 0..num_games {
+        let (status, turns, captures_p1, captures_p2, extra) = play_fast_game(p1, p2, &mut rng);
+        stats.add_game(status, turns, captures_p1, captures_p2);
+        stats.add_extra_rolls(&extra, status.winner());
+    }
+
+    FastStatsRun { stats, elapsed: start.elapsed() }
+}
+
+/// Same turn accounting and safety valve as stats::play_silent_game
+fn play_fast_game(p1: FastAgent, p2: FastAgent, rng: &mut SmallRng) -> (GameStatus, usize, usize, usize, ExtraRolls) {
+    let mut game = FastGameState::new();
+    let mut turn_count = 0;
+    let mut captures = [0usize; 2];
+    let mut extra = ExtraRolls::default();
+
+    loop {
+        turn_count += 1;
+        extra.turn(game.current_player());
+
+        let roll = FastGameState::roll_dice_with(game.current_player(), rng);
+        let moves = game.generate_move_list(roll);
+        if moves.is_empty() {
+            game.pass_turn();
+            continue;
+        }
+
+        let player = game.current_player();
+        let agent = match player {
+            FastPlayer::One => p1,
+            FastPlayer::Two => p2,
+        };
+        let piece = match agent {
+            FastAgent::Random => moves[rng.random_range(0..moves.len())],
+            FastAgent::Smart => choose_smart_move_fast(&game, player, &moves, roll),
+        };
+
+        if let Ok(move_info) = game.make_move(piece, roll) {
+            if move_info.captured_piece.is_some() {
+                captures[player as usize] += 1;
+            }
+            if game.is_winner(player) {
+                return (GameStatus::Won(player), turn_count, captures[0], captures[1], extra);
+            }
+        }
+
+        if turn_count > 1000 {
+            return (game.status_at_move_limit(), turn_count, captures[0], captures[1], extra);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! How an interactive game reaches the players. The game loop (`LiveGame` in
+//! main.rs) decides what happens and a `Frontend` decides how it looks. `Tui`
+//! draws on a terminal: each turn's board replaces the last one in place, rolls
+//! are colored and a spinner runs while an AI thinks. `Plain` writes everything
+//! once, line after line, for piped input and anything else that reads the
+//! game as text.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crossterm::{
+    cursor::MoveToColumn,
+    execute, queue,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::ai::SearchInfo;
+use crate::dice;
+use crate::display::{clear_screen, global_to_coord, render_board, render_piece_positions, render_score, Orientation};
+use crate::events::TICK;
+use crate::input::LineEditor;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::screen::{self, Border, Panel, Screen, Span};
+use crate::style;
+use crate::transcript::{console, out, outln};
+use crate::AIType;
+
+/// Widest chat line in the pane under the board
+const CHAT_COLUMNS: usize = 35;
+/// Frames of the spinner shown while an AI thinks, one per tick
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Something that happened in the game, for the players to be told
+pub enum Announcement {
+    /// The player to move rolled this and has no legal move, so the turn passes
+    NoMoves(u8),
+    /// A move was made. If it landed on a rosette, `chain` counts the extra
+    /// rolls in a row it makes, this one included; otherwise it is 0.
+    Moved { mover: FastPlayer, chain: usize },
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Announcement::NoMoves(0) => write!(f, "❌ No moves available. Turn passes."),
+            Announcement::NoMoves(roll) => write!(f, "❌ No legal moves with roll = {}. Turn passes.", roll),
+            Announcement::Moved { chain: 0, .. } => write!(f, "Turn passes."),
+            Announcement::Moved { mover, chain: 1 } => write!(f, "{} gets an extra roll (landed on rosette).", mover.name()),
+            Announcement::Moved { mover, chain } => write!(f, "{} gets an extra roll (landed on rosette). 🔥 {} extra roll in a row!",
+                                                           mover.name(), ordinal(chain)),
+        }
+    }
+}
+
+/// `n` as "1st", "2nd", "3rd", "4th" and so on
+pub fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// What the turn screen shows
+#[derive(Clone, Copy)]
+pub struct TurnView<'g> {
+    pub game: &'g FastGameState,
+    pub players: &'g [AIType; 2],
+    /// Captures each player has made this game
+    pub captures: [usize; 2],
+    /// The latest chat lines, for the pane under the board
+    pub chat: &'g [String],
+}
+
+/// What the game loop shows and how. Only the parts that differ between
+/// frontends need implementing; the rest print lines.
+pub trait Frontend {
+    /// Show a whole screen, such as a turn's board or the rules card
+    fn show_screen(&mut self, screen: Screen);
+
+    /// Bring a human's prompt up to date; called again while it waits
+    fn show_prompt(&mut self, editor: &mut LineEditor) -> io::Result<()>;
+
+    /// Show that `player` has been thinking since `since`; called again while it thinks
+    fn show_thinking(&mut self, player: &AIType, since: Instant) -> io::Result<()>;
+
+    /// Show that `player` has found its move
+    fn show_thought(&mut self, player: &AIType, info: &SearchInfo) -> io::Result<()>;
+
+    /// Show the board, piece summary, score and whose turn it is
+    fn show_turn(&mut self, view: TurnView) {
+        self.show_screen(turn_screen(view, &[]));
+    }
+
+    /// Show the board just after a capture, with the `squares` the capturing
+    /// piece moved between highlighted
+    fn show_capture(&mut self, view: TurnView, squares: &[u8]) {
+        self.show_screen(turn_screen(view, squares));
+    }
+
+    /// Show what the player to move rolled
+    fn show_roll(&mut self, roll: u8) {
+        outln!("🎲 Rolled: {} {}{}", roll, dice::model().render(roll), if roll == 0 { " (no moves)" } else { "" });
+    }
+
+    /// Show the numbered moves a human chooses from
+    fn show_moves(&mut self, game: &FastGameState, moves: &[u8], roll: u8) {
+        print_legal_moves(game, moves, roll);
+    }
+
+    /// Tell the players what happened, with a blank line after it
+    fn announce(&mut self, announcement: Announcement) {
+        outln!("{}\n", announcement);
+    }
+
+    /// Show a line of news, help or warning
+    fn message(&mut self, text: &str) {
+        outln!("{}", text);
+    }
+
+    /// Show a line of chat, or news of an offer, as it arrives, over
+    /// whatever prompt or spinner is showing
+    fn show_chat(&mut self, text: &str) {
+        outln!("{}", text);
+    }
+
+    /// Start the next screen on a clear terminal
+    fn clear(&mut self) {}
+}
+
+/// The terminal interface: screens drawn in place and colored, a live spinner
+/// and prompts edited a key at a time
+pub struct Tui;
+
+impl Frontend for Tui {
+    fn show_screen(&mut self, screen: Screen) {
+        screen.present();
+    }
+
+    fn show_prompt(&mut self, editor: &mut LineEditor) -> io::Result<()> {
+        editor.draw(true)
+    }
+
+    /// Redraw the line under the roll: a spinner, the time so far and, for a
+    /// search, how to cut it short
+    fn show_thinking(&mut self, player: &AIType, since: Instant) -> io::Result<()> {
+        let frame = (since.elapsed().as_millis() / TICK.as_millis()) as usize;
+        let hint = if matches!(player, AIType::MCTS | AIType::Adaptive) { "  (press any key to move now)" } else { "" };
+        let mut out = io::stdout();
+        queue!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(format!("{} {} is thinking… {:.1}s{}", SPINNER[frame % SPINNER.len()], player.display_name(), since.elapsed().as_secs_f64(), hint)),
+        )?;
+        out.flush()
+    }
+
+    /// Replace the spinner with how long the search took
+    fn show_thought(&mut self, player: &AIType, info: &SearchInfo) -> io::Result<()> {
+        let mut out = console();
+        queue!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(format!("🤔 {} thought for {:.1}s\n", player.display_name(), info.elapsed.as_secs_f64())),
+        )?;
+        out.flush()
+    }
+
+    /// The roll in its color along with the dice
+    fn show_roll(&mut self, roll: u8) {
+        out!("🎲 Rolled: ");
+        let dice_color = match roll {
+            0 => Color::DarkGrey,
+            1 => Color::White,
+            2 => Color::Yellow,
+            3 => Color::Cyan,
+            4 => Color::Green,
+            _ => Color::White,
+        };
+        let _ = execute!(
+            console(),
+            SetForegroundColor(dice_color),
+            Print(format!("{}", roll)),
+            ResetColor
+        );
+        outln!(" {}{}", dice::model().render(roll), if roll == 0 { " (no moves)" } else { "" });
+    }
+
+    /// Lost turns are greyed out
+    fn announce(&mut self, announcement: Announcement) {
+        if let Announcement::NoMoves(_) = announcement {
+            let _ = execute!(console(), SetForegroundColor(Color::DarkGrey), Print(&announcement), ResetColor);
+            outln!("\n");
+        } else {
+            outln!("{}\n", announcement);
+        }
+    }
+
+    /// The prompt or spinner is drawn again on the next tick
+    fn show_chat(&mut self, text: &str) {
+        let _ = execute!(io::stdout(), MoveToColumn(0), Clear(ClearType::CurrentLine));
+        outln!("{}", text);
+    }
+
+    fn clear(&mut self) {
+        clear_screen();
+    }
+}
+
+/// Line after line: each screen printed below the last, a prompt printed once
+/// and its answer read as a whole line
+#[derive(Default)]
+pub struct Plain {
+    /// The AI to move has been said to be thinking
+    thinking: bool,
+}
+
+impl Frontend for Plain {
+    fn show_screen(&mut self, screen: Screen) {
+        screen.print();
+    }
+
+    fn show_prompt(&mut self, editor: &mut LineEditor) -> io::Result<()> {
+        editor.draw(false)
+    }
+
+    fn show_thinking(&mut self, player: &AIType, _since: Instant) -> io::Result<()> {
+        if !self.thinking {
+            self.thinking = true;
+            out!("🤔 {} is thinking...", player.display_name());
+            io::stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    fn show_thought(&mut self, _player: &AIType, _info: &SearchInfo) -> io::Result<()> {
+        self.thinking = false;
+        outln!();
+        Ok(())
+    }
+
+    /// Ends the thinking line first; it is printed again afterwards
+    fn show_chat(&mut self, text: &str) {
+        if self.thinking {
+            self.thinking = false;
+            outln!();
+        }
+        outln!("{}", text);
+    }
+
+    /// Highlights need color, and the next turn's board follows anyway
+    fn show_capture(&mut self, _view: TurnView, _squares: &[u8]) {}
+}
+
+/// The board, with the `marked` squares highlighted, piece summary, score and
+/// turn banner
+fn turn_screen(view: TurnView, marked: &[u8]) -> Screen {
+    let TurnView { game, players, captures, chat } = view;
+    let current_player = game.current_player();
+    let hot_seat = players.iter().all(|p| *p == AIType::Human);
+    let mut screen = Screen::new();
+    render_board(&mut screen, game, "", Orientation::for_position(game, hot_seat), marked);
+    render_piece_positions(&mut screen, game, current_player);
+    render_score(&mut screen, game, Some(captures));
+
+    // Show whose turn it is with emphasis
+    let style = style::of(current_player);
+    let mut banner = Panel::new(Border::Single, 37);
+    banner.line(vec![Span::colored(format!("⭐ {}'s Turn {} ⭐", current_player.name(), style.marker), style.color)]);
+    screen.panel(&banner);
+    if !chat.is_empty() {
+        let mut pane = Panel::new(Border::Single, 37);
+        for line in chat {
+            pane.line(vec![Span::colored(clip(line, CHAT_COLUMNS), Color::DarkGrey)]);
+        }
+        screen.panel(&pane);
+    }
+    screen.blank();
+    screen
+}
+
+/// `text` cut to fit `columns`, with an ellipsis if anything was cut
+fn clip(text: &str, columns: usize) -> String {
+    if screen::width(text) <= columns {
+        return text.to_string();
+    }
+    let mut clipped = String::new();
+    for c in text.chars() {
+        if screen::width(&clipped) + c.width().unwrap_or(0) >= columns {
+            break;
+        }
+        clipped.push(c);
+    }
+    clipped.push('…');
+    clipped
+}
+
+/// Print the numbered list of legal moves for the current player
+pub fn print_legal_moves(game: &FastGameState, moves: &[u8], roll: u8) {
+    outln!("Legal moves:");
+    for (idx, &piece_idx) in moves.iter().enumerate() {
+        let pos = game.get_piece_pos(game.current_player(), piece_idx);
+        match pos {
+            0 => {
+                let target_square = FastGameState::path_to_global(game.current_player(), 0);
+                let (r, c) = global_to_coord(target_square);
+                let extra_info = if FastGameState::is_rosette(target_square) {
+                    ", lands on rosette (extra turn)"
+                } else if FastGameState::is_safe(target_square) {
+                    ", lands on safe square"
+                } else {
+                    ""
+                };
+                outln!("  [{}] Enter piece {} → path 0 (grid ({}, {})){}",
+                        idx, piece_idx, r, c, extra_info);
+            }
+            _ if FastGameState::is_on_board(pos) => {
+                let path_idx = pos - 1;
+                let new_path_idx = path_idx + roll;
+                if new_path_idx >= FastGameState::path_len() {
+                    outln!("  [{}] Move piece {} → EXIT", idx, piece_idx);
+                } else {
+                    let target_square = FastGameState::path_to_global(game.current_player(), new_path_idx);
+                    let (r, c) = global_to_coord(target_square);
+                    let extra_info = if FastGameState::is_rosette(target_square) {
+                        ", lands on rosette (extra turn)"
+                    } else if FastGameState::is_safe(target_square) {
+                        ", lands on safe square"
+                    } else {
+                        ""
+                    };
+                    outln!("  [{}] Move piece {} → path {} (grid ({}, {})){}",
+                            idx, piece_idx, new_path_idx, r, c, extra_info);
+                }
+            }
+            _ => {}
+        }
+    }
+}
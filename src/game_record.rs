@@ -0,0 +1,423 @@
+//! Plain-text record of a whole game: who played, the variant, the dice
+//! generator and seed, the starting position, every move with its roll and
+//! how the game ended. Written with `--record DIR` and read back by `ur analyze-db`.
+//! Each move carries a rolling hash of the position it led to, so a damaged
+//! record, or one replayed under other rules, is caught at the first move
+//! that no longer leads where it did.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+#[cfg(feature = "serde")]
+use crate::encoding;
+use crate::error::UrError;
+use crate::history::GameHistory;
+use crate::optimized_game::{EndReason, FastGameState, FastPlayer, GameStatus};
+use crate::rng::RngAlgorithm;
+use crate::state_repr::GameStateRepr;
+
+const RECORD_HEADER: &str = "# Royal Game of Ur game record";
+/// Version 2 added the state hash to each move
+const RECORD_VERSION: u32 = 2;
+/// Records in a directory are files with this extension
+const RECORD_EXTENSION: &str = "urg";
+
+/// One move: the side that made it, what it rolled and the piece it moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedMove {
+    pub player: FastPlayer,
+    pub roll: u8,
+    pub piece: u8,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRecord {
+    /// Label of who controlled each side (AI type labels, or "human")
+    pub players: [String; 2],
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    pub start: FastGameState,
+    /// Moves in order; zero rolls and blocked turns are not recorded
+    pub moves: Vec<RecordedMove>,
+    /// How the game ended, if it was over when recorded; a record without a
+    /// `result` line ends where its moves do
+    pub result: Option<GameStatus>,
+    /// Played under tournament conditions, without hints, coaching, undo or
+    /// the AI's thinking shown; written as `assistance off`
+    pub unassisted: bool,
+    /// Generator the dice were rolled with
+    pub rng: RngAlgorithm,
+    /// Seed of the dice, if the game was played with a replayable seed
+    pub seed: Option<u64>,
+    /// State hash after each move, as read from the record; empty for records
+    /// written without them. Written records always get fresh ones.
+    pub hashes: Vec<u32>,
+}
+
+/// Rolling hash of the positions a game passes through: each move folds in
+/// the position it led to, so two replays agree only while every move so far
+/// has gone the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateHash(u64);
+
+impl StateHash {
+    /// The hash before any move
+    pub const START: StateHash = StateHash(0x243f_6a88_85a3_08d3);
+
+    /// The hash once the game has moved on to `state`
+    pub fn after(self, state: &FastGameState) -> StateHash {
+        let words = [state.piece_positions as u64, (state.piece_positions >> 64) as u64, u64::from(state.scores_and_turn)];
+        StateHash(words.into_iter().fold(self.0, |hash, word| mix(hash.wrapping_add(0x9e37_79b9_7f4a_7c15) ^ word)))
+    }
+
+    /// The 32 bits kept in text records
+    pub fn short(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// The 8 bits kept per turn in archives
+    pub fn low_byte(self) -> u8 {
+        self.0 as u8
+    }
+}
+
+/// SplitMix64's finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl GameRecord {
+    /// Record the game as played (the main line of `history`) on the current board and dice
+    pub fn from_history(history: &GameHistory, players: [String; 2], result: Option<GameStatus>) -> Self {
+        let moves = history.moves()
+            .map(|record| RecordedMove { player: record.player, roll: record.roll, piece: record.info.piece_idx })
+            .collect();
+        GameRecord {
+            players,
+            board: board::variant(),
+            dice: dice::variant(),
+            start: history.node(0).position,
+            moves,
+            result: result.filter(|status| status.is_over()),
+            unassisted: false,
+            rng: RngAlgorithm::default(),
+            seed: None,
+            hashes: Vec::new(),
+        }
+    }
+
+    /// Position before each move, paired with the move; fails on the first
+    /// illegal move, or the first whose state hash differs from the record's
+    pub fn positions(&self) -> Result<Vec<(FastGameState, RecordedMove)>, UrError> {
+        let mut positions = Vec::with_capacity(self.moves.len());
+        self.replay(|idx, position, mv, hash| {
+            if let Some(&recorded) = self.hashes.get(idx) && hash.short() != recorded {
+                return Err(UrError::parse(format!(
+                    "move {}: state hash {:08x} does not match the recorded {:08x}; the record is damaged or was played under other rules",
+                    idx + 1, hash.short(), recorded)));
+            }
+            positions.push((position, mv));
+            Ok(())
+        })?;
+        Ok(positions)
+    }
+
+    /// Step through the moves from the start, handing `visit` each move's
+    /// index, the position before it, the move and the state hash after it
+    fn replay(&self, mut visit: impl FnMut(usize, FastGameState, RecordedMove, StateHash) -> Result<(), UrError>) -> Result<(), UrError> {
+        let mut game = self.start;
+        let mut hash = StateHash::START;
+        for (idx, &mv) in self.moves.iter().enumerate() {
+            // Turns lost to a zero roll or a blocked position leave no move behind
+            if game.current_player() != mv.player {
+                game.pass_turn();
+            }
+            if self.dice.model().probability(mv.roll) == 0.0 {
+                return Err(UrError::parse(format!("move {}: the {} dice cannot roll {}", idx + 1, self.dice.label(), mv.roll)));
+            }
+            let before = game;
+            game.play(mv.piece, mv.roll).map_err(|e| UrError::parse(format!("move {}: {}", idx + 1, e)))?;
+            hash = hash.after(&game);
+            visit(idx, before, mv, hash)?;
+        }
+        Ok(())
+    }
+
+    /// The record as text. Moves get their state hashes when they replay on
+    /// the current board; a record that does not is written without them.
+    pub fn to_text(&self) -> String {
+        let mut hashes = Vec::with_capacity(self.moves.len());
+        let replayed = self.replay(|_, _, _, hash| {
+            hashes.push(hash.short());
+            Ok(())
+        });
+        if replayed.is_err() {
+            hashes.clear();
+        }
+
+        let mut out = String::new();
+        out.push_str(RECORD_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", RECORD_VERSION));
+        out.push_str(&format!("players {} {}\n", self.players[0], self.players[1]));
+        out.push_str(&format!("board {}\n", self.board.label()));
+        out.push_str(&format!("dice {}\n", self.dice.label()));
+        if self.unassisted {
+            out.push_str("assistance off\n");
+        }
+        if self.rng != RngAlgorithm::default() {
+            out.push_str(&format!("rng {}\n", self.rng.label()));
+        }
+        if let Some(seed) = self.seed {
+            out.push_str(&format!("seed {}\n", seed));
+        }
+        if self.start != FastGameState::new() {
+            out.push_str(&format!("turn {}\n", side_number(self.start.current_player())));
+            for (key, player) in [("pieces1", FastPlayer::One), ("pieces2", FastPlayer::Two)] {
+                out.push_str(key);
+                for piece_idx in 0..7 {
+                    out.push_str(&format!(" {}", self.start.get_piece_pos(player, piece_idx)));
+                }
+                out.push('\n');
+            }
+        }
+        for (idx, mv) in self.moves.iter().enumerate() {
+            out.push_str(&format!("move {} {} {}", side_number(mv.player), mv.roll, mv.piece));
+            if let Some(hash) = hashes.get(idx) {
+                out.push_str(&format!(" {:08x}", hash));
+            }
+            out.push('\n');
+        }
+        match self.result {
+            Some(GameStatus::Won(winner)) => out.push_str(&format!("result won {}\n", side_number(winner))),
+            Some(GameStatus::Adjudicated(winner, reason)) => {
+                out.push_str(&format!("result adjudicated {} {}\n", side_number(winner), reason.label()));
+            }
+            Some(GameStatus::Drawn(reason)) => out.push_str(&format!("result drawn {}\n", reason.label())),
+            Some(GameStatus::InProgress) | None => {}
+        }
+        out
+    }
+
+    /// Parse a record. Positions are checked against the current board, so the
+    /// record's board must be selected before its moves are replayed.
+    pub fn from_text(text: &str) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut players = None;
+        let mut board_variant = BoardVariant::Standard;
+        let mut dice_variant = DiceVariant::Tetrahedra;
+        let mut turn = None;
+        let mut pieces: [Option<[u8; 7]>; 2] = [None, None];
+        let mut moves = Vec::new();
+        let mut result = None;
+        let mut unassisted = false;
+        let mut rng = RngAlgorithm::default();
+        let mut seed = None;
+        let mut hashes = Vec::new();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let err = |message: String| UrError::ParseError { line: idx + 1, message };
+
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+            let values: Vec<&str> = fields.collect();
+
+            match key {
+                "version" => {
+                    let v: u32 = values.first().and_then(|v| v.parse().ok())
+                        .ok_or_else(|| err("expected a version number".to_string()))?;
+                    if !(1..=RECORD_VERSION).contains(&v) {
+                        return Err(err(format!("unsupported record version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                "players" => match values.as_slice() {
+                    [p1, p2] => players = Some([p1.to_string(), p2.to_string()]),
+                    _ => return Err(err("expected two player labels".to_string())),
+                },
+                "board" => {
+                    board_variant = values.first().and_then(|v| BoardVariant::from_label(v))
+                        .ok_or_else(|| err("unknown board".to_string()))?;
+                }
+                "dice" => {
+                    dice_variant = values.first().and_then(|v| DiceVariant::from_label(v))
+                        .ok_or_else(|| err("unknown dice".to_string()))?;
+                }
+                "assistance" => unassisted = match values.as_slice() {
+                    ["on"] => false,
+                    ["off"] => true,
+                    _ => return Err(err("expected 'assistance on' or 'assistance off'".to_string())),
+                },
+                "rng" => {
+                    rng = values.first().and_then(|v| RngAlgorithm::from_label(v))
+                        .ok_or_else(|| err("unknown rng".to_string()))?;
+                }
+                "seed" => seed = Some(values.first().and_then(|v| v.parse().ok()).ok_or_else(|| err("expected a seed".to_string()))?),
+                "turn" => turn = Some(parse_side(values.first().copied()).ok_or_else(|| err("turn must be 1 or 2".to_string()))?),
+                "pieces1" | "pieces2" => {
+                    let positions: Vec<u8> = values.iter().filter_map(|v| v.parse().ok()).collect();
+                    let positions: [u8; 7] = positions.try_into()
+                        .map_err(|_| err(format!("{} needs 7 positions", key)))?;
+                    pieces[usize::from(key == "pieces2")] = Some(positions);
+                }
+                "move" => {
+                    let (side, roll, piece, hash) = match *values.as_slice() {
+                        [side, roll, piece] => (side, roll, piece, None),
+                        [side, roll, piece, hash] => (side, roll, piece, Some(hash)),
+                        _ => return Err(err("expected 'move <side> <roll> <piece> [<state hash>]'".to_string())),
+                    };
+                    let player = parse_side(Some(side)).ok_or_else(|| err("side must be 1 or 2".to_string()))?;
+                    let (Ok(roll), Ok(piece)) = (roll.parse(), piece.parse::<u8>()) else {
+                        return Err(err("roll and piece must be numbers".to_string()));
+                    };
+                    if piece >= 7 {
+                        return Err(err(format!("no piece {}", piece)));
+                    }
+                    // Hashes are kept for every move or for none
+                    match hash {
+                        Some(hash) if hashes.len() == moves.len() => hashes.push(u32::from_str_radix(hash, 16)
+                            .map_err(|_| err(format!("state hash '{}' is not 8 hex digits", hash)))?),
+                        None if hashes.is_empty() => {}
+                        _ => return Err(err("every move needs a state hash, or none does".to_string())),
+                    }
+                    moves.push(RecordedMove { player, roll, piece });
+                }
+                "result" => {
+                    let side = |value: &str| parse_side(Some(value)).ok_or_else(|| err("side must be 1 or 2".to_string()));
+                    let reason = |label: &str| EndReason::from_label(label).ok_or_else(|| err(format!("unknown end reason '{}'", label)));
+                    result = Some(match values.as_slice() {
+                        ["won", winner] => GameStatus::Won(side(winner)?),
+                        ["adjudicated", winner, why] => GameStatus::Adjudicated(side(winner)?, reason(why)?),
+                        ["drawn", why] => GameStatus::Drawn(reason(why)?),
+                        _ => return Err(err("expected 'result won <side>', 'result adjudicated <side> <reason>' or 'result drawn <reason>'".to_string())),
+                    });
+                }
+                _ => return Err(err(format!("unknown key '{}'", key))),
+            }
+        }
+
+        let missing = |what: &str| UrError::parse(format!("missing '{}'", what));
+        version.ok_or_else(|| missing("version"))?;
+        let players = players.ok_or_else(|| missing("players"))?;
+        let start = match (turn, pieces) {
+            (None, [None, None]) => FastGameState::new(),
+            (Some(turn), [Some(p1), Some(p2)]) => FastGameState::from_piece_positions(p1, p2, turn)?,
+            _ => return Err(missing("turn, pieces1 and pieces2")),
+        };
+
+        Ok(GameRecord { players, board: board_variant, dice: dice_variant, start, moves, result, unassisted, rng, seed, hashes })
+    }
+
+    /// Write to the next free `game-NNNN.urg` in `dir`, creating it if needed
+    pub fn write_to_dir(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = (1..)
+            .map(|n| dir.join(format!("game-{:04}.{}", n, RECORD_EXTENSION)))
+            .find(|path| !path.exists())
+            .expect("unbounded range");
+        fs::write(&path, self.to_text())?;
+        Ok(path)
+    }
+}
+
+fn side_number(player: FastPlayer) -> u8 {
+    match player {
+        FastPlayer::One => 1,
+        FastPlayer::Two => 2,
+    }
+}
+
+fn parse_side(value: Option<&str>) -> Option<FastPlayer> {
+    match value {
+        Some("1") => Some(FastPlayer::One),
+        Some("2") => Some(FastPlayer::Two),
+        _ => None,
+    }
+}
+
+/// Every record file in `dir`, sorted by name, with the parse result of
+/// each; with the `serde` feature, each game of an export made by `ur export`
+/// too, named by the export and its place there, as in `games.toml#3`
+pub fn load_dir(dir: &Path) -> io::Result<Vec<(PathBuf, Result<GameRecord, UrError>)>> {
+    let entries = fs::read_dir(dir).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", dir.display(), e)))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == RECORD_EXTENSION || is_export(ext)))
+        .collect();
+    paths.sort();
+
+    let mut records = Vec::new();
+    for path in paths {
+        let text = fs::read_to_string(&path).map_err(UrError::from);
+        #[cfg(feature = "serde")]
+        if path.extension().is_some_and(is_export) {
+            match text.and_then(|text| encoding::read_export(&text)) {
+                Ok(exported) => records.extend(exported.into_iter().enumerate()
+                    .map(|(idx, record)| (PathBuf::from(format!("{}#{}", path.display(), idx + 1)), Ok(record)))),
+                Err(e) => records.push((path, Err(e))),
+            }
+            continue;
+        }
+        records.push((path, text.and_then(|text| GameRecord::from_text(&text))));
+    }
+    Ok(records)
+}
+
+/// Whether a file with extension `ext` is an export `load_dir` reads
+#[cfg(feature = "serde")]
+fn is_export(ext: &OsStr) -> bool {
+    ext == encoding::EXPORT_EXTENSION
+}
+
+#[cfg(not(feature = "serde"))]
+fn is_export(_ext: &OsStr) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_hashes_catch_the_first_move_that_differs() {
+        let mut game = FastGameState::new();
+        let mut moves = Vec::new();
+        for roll in [4, 2, 3, 1, 2, 4] {
+            let player = game.current_player();
+            let piece = game.generate_move_list(roll)[0];
+            game.make_move(piece, roll).expect("generated moves are legal");
+            moves.push(RecordedMove { player, roll, piece });
+        }
+        let record = GameRecord { moves, ..GameRecord::from_history(&GameHistory::new(FastGameState::new()), ["a".into(), "b".into()], None) };
+        let text = record.to_text();
+        let read = GameRecord::from_text(&text).unwrap();
+        assert_eq!(read.hashes.len(), 6);
+        assert_eq!(read.positions().unwrap().len(), 6);
+
+        // The fourth move's roll damaged: its piece still moves, but somewhere else
+        let lines: Vec<&str> = text.lines().filter(|line| line.starts_with("move")).collect();
+        let fields: Vec<&str> = lines[3].split(' ').collect();
+        let damaged = text.replace(lines[3], &format!("move {} 2 {} {}", fields[1], fields[3], fields[4]));
+        let error = GameRecord::from_text(&damaged).unwrap().positions().unwrap_err().to_string();
+        assert!(error.starts_with("move 4: state hash"), "{}", error);
+
+        // A record from before hashes still reads
+        let unhashed: String = text.lines().map(|line| match line.strip_prefix("move ") {
+            Some(mv) => format!("move {}\n", mv.rsplit_once(' ').unwrap().0),
+            None => format!("{}\n", line.replace("version 2", "version 1")),
+        }).collect();
+        assert!(GameRecord::from_text(&unhashed).unwrap().positions().is_ok());
+    }
+}
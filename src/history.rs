@@ -0,0 +1,241 @@
+//! Move-by-move record of one game, and what it says about each piece:
+//! when it entered, where it landed, whether it was captured and when it got home.
+
+use crate::optimized_game::{FastGameState, FastPlayer, MoveInfo};
+use crate::transcript::outln;
+
+/// Timeline width in columns; longer games are squeezed to fit
+const TIMELINE_WIDTH: usize = 40;
+
+/// One move as played
+#[derive(Debug, Clone, Copy)]
+pub struct MoveRecord {
+    /// Game turn (roll) the move was made on, counting from 1
+    pub turn: usize,
+    pub player: FastPlayer,
+    pub roll: u8,
+    pub info: MoveInfo,
+}
+
+/// A position in the history tree and the move that led to it
+#[derive(Debug, Clone)]
+pub struct HistoryNode {
+    /// None for the starting position
+    pub record: Option<MoveRecord>,
+    /// Position after the move
+    pub position: FastGameState,
+    /// Moves played from here; the first is the line actually played,
+    /// any others are what-ifs explored afterwards
+    pub children: Vec<usize>,
+}
+
+/// Moves of one game as a tree: the first child of every node is the game as
+/// it was played (the main line), later children are alternatives branched
+/// off it, so exploring never changes the original record
+#[derive(Debug, Clone)]
+pub struct GameHistory {
+    nodes: Vec<HistoryNode>,
+}
+
+impl GameHistory {
+    pub fn new(start: FastGameState) -> Self {
+        GameHistory {
+            nodes: vec![HistoryNode { record: None, position: start, children: Vec::new() }],
+        }
+    }
+
+    pub fn node(&self, id: usize) -> &HistoryNode {
+        &self.nodes[id]
+    }
+
+    /// Add a move after node `parent`, returning the new node
+    pub fn add_move(&mut self, parent: usize, record: MoveRecord, position: FastGameState) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(HistoryNode { record: Some(record), position, children: Vec::new() });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Extend the main line
+    pub fn record(&mut self, turn: usize, player: FastPlayer, roll: u8, info: MoveInfo, position: FastGameState) {
+        let tip = *self.main_line().last().unwrap_or(&0);
+        self.add_move(tip, MoveRecord { turn, player, roll, info }, position);
+    }
+
+    /// Node ids of the game as played, starting position first
+    pub fn main_line(&self) -> Vec<usize> {
+        let mut line = vec![0];
+        while let Some(&next) = self.nodes[*line.last().unwrap_or(&0)].children.first() {
+            line.push(next);
+        }
+        line
+    }
+
+    /// Moves in the main line
+    pub fn len(&self) -> usize {
+        self.main_line().len() - 1
+    }
+
+    /// Forget every main-line move after the first `len`, for undo during play.
+    /// Only valid before any what-if has been branched off.
+    pub fn truncate(&mut self, len: usize) {
+        let keep = self.main_line()[len.min(self.len())];
+        debug_assert_eq!(self.main_line().len(), self.nodes.len(), "truncating a branched history");
+        self.nodes.truncate(keep + 1);
+        self.nodes[keep].children.clear();
+    }
+
+    /// Main-line moves in order
+    pub fn moves(&self) -> impl Iterator<Item = &MoveRecord> {
+        self.main_line().into_iter().filter_map(|id| self.nodes[id].record.as_ref())
+    }
+
+    /// Journeys of every piece of `player`, in piece order
+    pub fn journeys(&self, player: FastPlayer, game_turns: usize) -> Vec<PieceJourney> {
+        (0..7).map(|piece_idx| PieceJourney::trace(self, player, piece_idx, game_turns)).collect()
+    }
+
+    /// Print a timeline and summary line for every piece of both players
+    pub fn display_journeys(&self, game_turns: usize) {
+        if self.len() == 0 || game_turns == 0 {
+            return;
+        }
+
+        outln!();
+        outln!("PIECE JOURNEYS (turns 1-{}; ▸ entered, ━ on board, ✿ rosette, ✖ captured, ★ home)", game_turns);
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            outln!("  {}:", player.name());
+            for journey in self.journeys(player, game_turns) {
+                outln!("    {} {} {}", journey.piece_idx, journey.timeline(game_turns), journey.summary());
+            }
+        }
+    }
+}
+
+/// Something that happened to a piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JourneyEvent {
+    Entered,
+    /// Landed on a rosette
+    Rosette,
+    Captured,
+    Finished,
+}
+
+impl JourneyEvent {
+    /// Timeline mark; when several events share a column the strongest one shows
+    fn mark(self) -> char {
+        match self {
+            JourneyEvent::Entered => '▸',
+            JourneyEvent::Rosette => '✿',
+            JourneyEvent::Captured => '✖',
+            JourneyEvent::Finished => '★',
+        }
+    }
+
+    fn strength(self) -> u8 {
+        match self {
+            JourneyEvent::Entered => 0,
+            JourneyEvent::Rosette => 1,
+            JourneyEvent::Finished => 2,
+            JourneyEvent::Captured => 3,
+        }
+    }
+}
+
+/// One piece's path through a game
+#[derive(Debug, Clone)]
+pub struct PieceJourney {
+    pub piece_idx: u8,
+    /// Events in the order they happened, with their game turn
+    pub events: Vec<(usize, JourneyEvent)>,
+    /// Turns (entry to capture or exit) spent on the board, each stay in [start, end)
+    pub stays: Vec<(usize, usize)>,
+    /// Squares landed on, as a bitmask over the layout's squares
+    pub squares: u32,
+}
+
+impl PieceJourney {
+    fn trace(history: &GameHistory, player: FastPlayer, piece_idx: u8, game_turns: usize) -> Self {
+        let mut events = Vec::new();
+        let mut stays = Vec::new();
+        let mut squares = 0u32;
+        let mut entered_at = None;
+
+        for record in history.moves() {
+            let info = &record.info;
+            if record.player == player && info.piece_idx == piece_idx {
+                if info.from_pos == 0 {
+                    events.push((record.turn, JourneyEvent::Entered));
+                    entered_at = Some(record.turn);
+                }
+                if FastGameState::is_on_board(info.to_pos) {
+                    squares |= 1 << FastGameState::path_to_global(player, info.to_pos - 1);
+                    if info.extra_turn {
+                        events.push((record.turn, JourneyEvent::Rosette));
+                    }
+                } else {
+                    events.push((record.turn, JourneyEvent::Finished));
+                    stays.extend(entered_at.take().map(|start| (start, record.turn)));
+                }
+            } else if record.player == player.opposite() && info.captured_piece == Some(piece_idx) {
+                events.push((record.turn, JourneyEvent::Captured));
+                stays.extend(entered_at.take().map(|start| (start, record.turn)));
+            }
+        }
+        // Still on the board when the game ended
+        stays.extend(entered_at.map(|start| (start, game_turns + 1)));
+
+        PieceJourney { piece_idx, events, stays, squares }
+    }
+
+    pub fn turns_on_board(&self) -> usize {
+        self.stays.iter().map(|(start, end)| end - start).sum()
+    }
+
+    pub fn count(&self, event: JourneyEvent) -> usize {
+        self.events.iter().filter(|(_, e)| *e == event).count()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.count(JourneyEvent::Finished) > 0
+    }
+
+    /// The game squeezed into at most `TIMELINE_WIDTH` columns
+    fn timeline(&self, game_turns: usize) -> String {
+        let width = game_turns.min(TIMELINE_WIDTH);
+        (0..width).map(|col| {
+            // Turns [first, last) fall in this column
+            let first = 1 + col * game_turns / width;
+            let last = 1 + (col + 1) * game_turns / width;
+            let event = self.events.iter()
+                .filter(|(turn, _)| (first..last).contains(turn))
+                .map(|&(_, event)| event)
+                .max_by_key(|event| event.strength());
+            let on_board = self.stays.iter().any(|&(start, end)| start < last && first < end);
+            match event {
+                Some(event) => event.mark(),
+                None if on_board => '━',
+                None => '·',
+            }
+        }).collect()
+    }
+
+    fn summary(&self) -> String {
+        let mut parts = vec![format!("{} turns on board", self.turns_on_board())];
+        let captured = self.count(JourneyEvent::Captured);
+        if captured > 0 {
+            parts.push(format!("captured {}×", captured));
+        }
+        parts.push(format!("{} squares", self.squares.count_ones()));
+        let rosettes = self.count(JourneyEvent::Rosette);
+        if rosettes > 0 {
+            parts.push(format!("{} rosette{}", rosettes, if rosettes == 1 { "" } else { "s" }));
+        }
+        if !self.finished() {
+            parts.push("never got home".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
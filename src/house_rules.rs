@@ -0,0 +1,288 @@
+//! `ur house-rules`: search for house rules that make the game fairer and
+//! less a matter of luck, by simulated annealing over where the rosettes are,
+//! which shared squares are safe and how many pieces each side plays. Every
+//! candidate is scored by Smart AI self-play on the stats engine, with the
+//! same dice seeds throughout so differences come from the rules: its cost is
+//! the first player's edge plus a weighted share of the outcome that the luck
+//! ledger puts down to the dice.
+//!
+//! Rules stay symmetric: a step of the path is a rosette, or safe, for both
+//! players. Safe squares only matter where the paths are shared, so only
+//! those are searched. Seven pieces is the most a position can hold; fewer
+//! start borne off, as in the variant study.
+
+use std::fs;
+use std::io::{self, Write};
+
+use rand::rngs::SmallRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::display::{render_board, Orientation};
+use crate::experiments::start_with_pieces;
+use crate::luck;
+use crate::optimized_game::FastPlayer;
+use crate::rng::GameRng;
+use crate::screen::Screen;
+use crate::stats::{play_silent_game_from, StatsAgent};
+use crate::workers;
+
+/// Fewest pieces a side may play with
+const MIN_PIECES: u8 = 3;
+/// Temperature at the first and last step, in points of cost
+const START_TEMPERATURE: f64 = 2.0;
+const END_TEMPERATURE: f64 = 0.1;
+
+pub struct HouseRuleOptions {
+    pub games_per_candidate: usize,
+    pub steps: usize,
+    pub threads: usize,
+    /// Seed for the dice and the search, random if not given
+    pub seed: Option<u64>,
+    /// Points of cost per percent of luck share
+    pub luck_weight: f64,
+    /// Board whose shape the rules are tried on
+    pub board: BoardVariant,
+    pub csv_path: String,
+}
+
+impl HouseRuleOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--games", "--steps", "--threads", "--seed", "--luck-weight", "--board", "--csv"], &[])?;
+        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        Ok(HouseRuleOptions {
+            games_per_candidate: flags.get("--games", 2000)?.max(1),
+            steps: flags.get("--steps", 150)?,
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            seed: flags.get_opt("--seed")?,
+            luck_weight: flags.get("--luck-weight", 0.25)?,
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            csv_path: flags.get_str("--csv").unwrap_or("house_rules.csv").to_string(),
+        })
+    }
+}
+
+/// One rule set, as steps of the path (bit `i` for step `i`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HouseRules {
+    rosettes: u32,
+    /// Safe steps, among the shared ones
+    safe: u32,
+    pieces: u8,
+}
+
+impl HouseRules {
+    /// The chosen board's own rules
+    fn of_board() -> Self {
+        let layout = board::layout();
+        let steps_where = |mask: u32| {
+            layout.paths[0].iter().enumerate()
+                .filter(|&(_, &square)| (mask >> square) & 1 != 0)
+                .fold(0, |steps, (step, _)| steps | 1 << step)
+        };
+        HouseRules { rosettes: steps_where(layout.rosettes), safe: steps_where(layout.safe) & shared_steps(), pieces: 7 }
+    }
+
+    /// Rosette and safe squares to play with, for `board::switch_squares`;
+    /// squares private to a player stay as the layout has them
+    fn squares(self) -> (u32, u32) {
+        let layout = board::layout();
+        let squares_at = |steps: u32| {
+            layout.paths.iter()
+                .flat_map(|path| path.iter().enumerate())
+                .filter(|&(step, _)| (steps >> step) & 1 != 0)
+                .fold(0, |squares, (_, &square)| squares | 1 << square)
+        };
+        let private_safe = layout.safe & !squares_at(shared_steps());
+        (squares_at(self.rosettes), private_safe | squares_at(self.safe))
+    }
+
+    /// A random small change: a rosette added, removed or moved one step, a
+    /// shared square made safe or open, or a piece more or fewer
+    fn neighbour(self, rng: &mut SmallRng) -> Self {
+        let steps = board::layout().paths[0].len();
+        let shared: Vec<usize> = (0..steps).filter(|&step| (shared_steps() >> step) & 1 != 0).collect();
+        loop {
+            let mut next = self;
+            match rng.random_range(0..4) {
+                0 => next.rosettes ^= 1 << rng.random_range(0..steps),
+                1 => {
+                    let placed: Vec<usize> = (0..steps).filter(|&step| (self.rosettes >> step) & 1 != 0).collect();
+                    if let Some(&from) = placed.get(rng.random_range(0..placed.len().max(1))) {
+                        let to = if rng.random_bool(0.5) { from.wrapping_sub(1) } else { from + 1 };
+                        if to < steps && (self.rosettes >> to) & 1 == 0 {
+                            next.rosettes ^= (1 << from) | (1 << to);
+                        }
+                    }
+                }
+                2 if !shared.is_empty() => next.safe ^= 1 << shared[rng.random_range(0..shared.len())],
+                3 => next.pieces = if rng.random_bool(0.5) { self.pieces.saturating_sub(1).max(MIN_PIECES) } else { (self.pieces + 1).min(7) },
+                _ => {}
+            }
+            if next != self {
+                return next;
+            }
+        }
+    }
+
+    fn describe(self) -> String {
+        let list = |steps: u32| {
+            let listed: Vec<String> = (0..32).filter(|&step| (steps >> step) & 1 != 0).map(|step: u32| step.to_string()).collect();
+            if listed.is_empty() { "none".to_string() } else { listed.join(",") }
+        };
+        format!("rosettes {} | safe {} | {} pieces", list(self.rosettes), list(self.safe), self.pieces)
+    }
+}
+
+/// Steps of the path both players share
+fn shared_steps() -> u32 {
+    let [one, two] = board::layout().paths;
+    one.iter().zip(two).enumerate()
+        .filter(|&(_, (a, b))| a == b)
+        .fold(0, |steps, (step, _)| steps | 1 << step)
+}
+
+/// Totals over one candidate's games
+#[derive(Debug, Clone, Copy, Default)]
+struct Fitness {
+    games: usize,
+    first_player_wins: usize,
+    turns: usize,
+    /// Sums over games of how far apart the players' luck, and skill, ended
+    luck_gap: f64,
+    skill_gap: f64,
+}
+
+impl Fitness {
+    fn merge(self, other: Fitness) -> Fitness {
+        Fitness {
+            games: self.games + other.games,
+            first_player_wins: self.first_player_wins + other.first_player_wins,
+            turns: self.turns + other.turns,
+            luck_gap: self.luck_gap + other.luck_gap,
+            skill_gap: self.skill_gap + other.skill_gap,
+        }
+    }
+
+    /// How far the first player's score is from even, in points
+    fn edge(&self) -> f64 {
+        (self.first_player_wins as f64 / self.games.max(1) as f64 - 0.5) * 100.0
+    }
+
+    /// Share of the difference between the players that came from the dice, in percent
+    fn luck_share(&self) -> f64 {
+        self.luck_gap / (self.luck_gap + self.skill_gap).max(1e-9) * 100.0
+    }
+
+    fn average_turns(&self) -> f64 {
+        self.turns as f64 / self.games.max(1) as f64
+    }
+
+    fn cost(&self, luck_weight: f64) -> f64 {
+        self.edge().abs() + luck_weight * self.luck_share()
+    }
+}
+
+/// Play the Smart AI against itself on every seed under `rules`
+fn evaluate(rules: HouseRules, seeds: &[u64], threads: usize) -> Fitness {
+    // Every thread plays with the squares switched to here
+    board::switch_squares(Some(rules.squares()));
+    let start = start_with_pieces(rules.pieces);
+    let chunk = seeds.len().div_ceil(threads);
+    let jobs = seeds.chunks(chunk).map(|seeds| move || {
+        let agent = StatsAgent::Smart;
+        seeds.iter().fold(Fitness::default(), |fitness, &seed| {
+            let game = play_silent_game_from(start, &agent, &agent, &mut GameRng::from_seed(seed));
+            let ledger = luck::assess(start, &game.log);
+            fitness.merge(Fitness {
+                games: 1,
+                first_player_wins: usize::from(game.status.winner() == Some(FastPlayer::One)),
+                turns: game.turns,
+                luck_gap: (ledger.luck[0] - ledger.luck[1]).abs(),
+                skill_gap: (ledger.skill[0] - ledger.skill[1]).abs(),
+            })
+        })
+    });
+    let fitness = workers::run_parallel("house-rule", jobs).into_iter().fold(Fitness::default(), Fitness::merge);
+    board::switch_squares(None);
+    fitness
+}
+
+fn summary(fitness: &Fitness, luck_weight: f64) -> String {
+    format!("edge {:+.1}, luck {:.1}%, cost {:.2}", fitness.edge(), fitness.luck_share(), fitness.cost(luck_weight))
+}
+
+/// Anneal from the board's own rules and report the best rules found
+pub fn run_house_rules(options: &HouseRuleOptions) -> io::Result<()> {
+    board::select(options.board);
+    if !board::layout().is_mirror_symmetric() {
+        println!("❌ The {} board's paths differ between the players, so its rules cannot be searched", options.board.label());
+        return Ok(());
+    }
+    let seed = options.seed.unwrap_or_else(|| GameRng::random().seed());
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let seeds: Vec<u64> = (0..options.games_per_candidate).map(|_| rng.next_u64()).collect();
+
+    println!("=== HOUSE RULE EXPLORER ===");
+    println!("Smart AI vs itself on the {} board | {} games per candidate | {} steps | {} threads | seed {}",
+             options.board.label(), options.games_per_candidate, options.steps, options.threads, seed);
+    println!("Cost = |first player's edge| in points + {} × luck share in percent; path steps count from 0", options.luck_weight);
+    println!();
+
+    let original = HouseRules::of_board();
+    let original_fitness = evaluate(original, &seeds, options.threads);
+    println!("  start: {} → {}", original.describe(), summary(&original_fitness, options.luck_weight));
+    let mut csv = String::from("step,temperature,rosettes,safe,pieces,first_player_edge,luck_share,avg_turns,cost,accepted\n");
+    let mut csv_row = |step: usize, temperature: f64, rules: HouseRules, fitness: &Fitness, accepted: bool| {
+        csv.push_str(&format!("{},{:.3},{:#x},{:#x},{},{:.2},{:.2},{:.1},{:.3},{}\n",
+                              step, temperature, rules.rosettes, rules.safe, rules.pieces,
+                              fitness.edge(), fitness.luck_share(), fitness.average_turns(),
+                              fitness.cost(options.luck_weight), accepted));
+    };
+    csv_row(0, START_TEMPERATURE, original, &original_fitness, true);
+
+    let (mut current, mut current_cost) = (original, original_fitness.cost(options.luck_weight));
+    let (mut best, mut best_fitness, mut best_step) = (original, original_fitness, 0);
+    for step in 1..=options.steps {
+        let progress = step as f64 / options.steps.max(1) as f64;
+        let temperature = START_TEMPERATURE * (END_TEMPERATURE / START_TEMPERATURE).powf(progress);
+        let candidate = current.neighbour(&mut rng);
+        let fitness = evaluate(candidate, &seeds, options.threads);
+        let cost = fitness.cost(options.luck_weight);
+        let accepted = cost < current_cost || rng.random::<f64>() < ((current_cost - cost) / temperature).exp();
+        let improved = cost < best_fitness.cost(options.luck_weight);
+        print!("  step {:>3} (T {:.2}): {} → {}", step, temperature, candidate.describe(), summary(&fitness, options.luck_weight));
+        println!("{}", if improved { "  ★ best" } else if accepted { "  accepted" } else { "" });
+        io::stdout().flush()?;
+        csv_row(step, temperature, candidate, &fitness, accepted);
+        if accepted {
+            (current, current_cost) = (candidate, cost);
+        }
+        if improved {
+            (best, best_fitness, best_step) = (candidate, fitness, step);
+        }
+    }
+
+    println!();
+    if best == original {
+        println!("No candidate beat the {} board's own rules.", options.board.label());
+    } else {
+        println!("BEST HOUSE RULES (step {}):", best_step);
+        println!("  {}", best.describe());
+        board::switch_squares(Some(best.squares()));
+        let mut screen = Screen::new();
+        render_board(&mut screen, &start_with_pieces(best.pieces), "", Orientation::facing(FastPlayer::One), &[]);
+        screen.print();
+        board::switch_squares(None);
+        println!("  First player's edge {:+.1} → {:+.1} points | luck share {:.1}% → {:.1}% | {:.1} → {:.1} turns a game",
+                 original_fitness.edge(), best_fitness.edge(), original_fitness.luck_share(), best_fitness.luck_share(),
+                 original_fitness.average_turns(), best_fitness.average_turns());
+        println!("  Measured on the same {} dice seeds; check a find with more games before adopting it.", options.games_per_candidate);
+    }
+
+    fs::write(&options.csv_path, csv)?;
+    println!();
+    println!("Every candidate written to {}", options.csv_path);
+    Ok(())
+}
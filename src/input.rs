@@ -0,0 +1,298 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::time::Instant;
+
+use crossterm::{
+    cursor::MoveToColumn,
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    queue,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+use crate::transcript::{self, out, outln};
+
+/// Errors that can occur while reading interactive input
+#[derive(Debug)]
+pub enum InputError {
+    /// stdin was closed (EOF) before a line could be read
+    Closed,
+    /// Underlying terminal I/O failed
+    Io(io::Error),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Closed => write!(f, "input stream closed"),
+            InputError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+pub type InputResult<T> = Result<T, InputError>;
+
+/// Print a prompt (without newline) and read one trimmed line from stdin
+pub fn prompt_line(prompt: &str) -> InputResult<String> {
+    out!("{}", prompt);
+    io::stdout().flush()?;
+    let line = read_line()?;
+    transcript::record(&format!("{}\n", line));
+    Ok(line)
+}
+
+/// Read one trimmed line from stdin
+pub fn read_line() -> InputResult<String> {
+    let mut buf = String::new();
+    if io::stdin().read_line(&mut buf)? == 0 {
+        return Err(InputError::Closed);
+    }
+    Ok(buf.trim().to_string())
+}
+
+/// Prompt until the input parses as `T`; empty input selects `default`
+pub fn prompt_parse<T: FromStr>(prompt: &str, default: T) -> InputResult<T> {
+    loop {
+        let line = prompt_line(prompt)?;
+        if line.is_empty() {
+            return Ok(default);
+        }
+        match line.parse() {
+            Ok(value) => return Ok(value),
+            Err(_) => outln!("Invalid input '{}', try again.", line),
+        }
+    }
+}
+
+/// Prompt until the input is a number inside `range`; empty input selects `default`
+pub fn prompt_in_range(prompt: &str, range: RangeInclusive<usize>, default: usize) -> InputResult<usize> {
+    loop {
+        let value = prompt_parse(prompt, default)?;
+        if range.contains(&value) {
+            return Ok(value);
+        }
+        outln!("Please enter a number between {} and {}.", range.start(), range.end());
+    }
+}
+
+/// Ask a yes/no question; empty input selects `default`
+pub fn prompt_yes_no(prompt: &str, default: bool) -> InputResult<bool> {
+    loop {
+        let line = prompt_line(prompt)?.to_lowercase();
+        match line.as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => outln!("Please answer 'y' or 'n'."),
+        }
+    }
+}
+
+/// Single-key commands available at any human prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    /// Give the game up, losing it
+    Resign,
+    Save,
+    Hint,
+    Redraw,
+    Undo,
+    Share,
+    /// Switch the board between grid coordinates and path positions
+    Labels,
+    /// Draw a player's route over the board, or both, or neither
+    Route,
+    Help,
+}
+
+impl Command {
+    /// Parse a command key; anything else is treated as ordinary input
+    pub fn parse(line: &str) -> Option<Command> {
+        match line.to_lowercase().as_str() {
+            "q" => Some(Command::Quit),
+            "g" => Some(Command::Resign),
+            "s" => Some(Command::Save),
+            "h" => Some(Command::Hint),
+            "r" | "c" => Some(Command::Redraw),
+            "u" => Some(Command::Undo),
+            "p" => Some(Command::Share),
+            "l" => Some(Command::Labels),
+            "o" => Some(Command::Route),
+            "?" => Some(Command::Help),
+            _ => None,
+        }
+    }
+
+    /// Each command's key and what it does, for the help screen
+    pub const HELP: &[(&str, &str)] = &[
+        ("q", "quit the game"),
+        ("g", "give up: resign the game, which counts as a loss"),
+        ("s", "save the game"),
+        ("h", "show a hint"),
+        ("r", "redraw the board (also 'c')"),
+        ("u", "undo your last move"),
+        ("p", "show a position code to share"),
+        ("l", "switch square labels between path positions and grid coordinates"),
+        ("o", "show Player 1's route, Player 2's, both, then neither"),
+        ("?", "show this help"),
+    ];
+}
+
+/// Commands typed out after a slash, for talking to the other side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Slash {
+    /// Send a chat message
+    Say(String),
+    /// Offer a draw, or accept the one on offer
+    Draw,
+    /// Ask for another game with the same sides
+    Rematch,
+    /// Anything else after a slash
+    Unknown(String),
+}
+
+impl Slash {
+    /// Each slash command and what it does, for the help screen
+    pub const HELP: &[(&str, &str)] = &[
+        ("/say TEXT", "chat with the other side"),
+        ("/draw", "offer a draw, or accept one"),
+        ("/rematch", "ask to play again with the same sides"),
+        ("/resign", "the same as 'g'"),
+    ];
+}
+
+/// A line typed at a human prompt: a command, a slash command or plain text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    Command(Command),
+    Slash(Slash),
+    Text(String),
+}
+
+impl Response {
+    /// Split a shortcut or slash command out of a typed line
+    pub fn parse(line: String) -> Response {
+        if let Some(slash) = line.strip_prefix('/') {
+            let (name, rest) = slash.split_once(' ').unwrap_or((slash, ""));
+            return match name.to_lowercase().as_str() {
+                "say" | "chat" => Response::Slash(Slash::Say(rest.trim().to_string())),
+                "draw" => Response::Slash(Slash::Draw),
+                "rematch" => Response::Slash(Slash::Rematch),
+                "resign" => Response::Command(Command::Resign),
+                _ => Response::Slash(Slash::Unknown(name.to_string())),
+            };
+        }
+        match Command::parse(&line) {
+            Some(cmd) => Response::Command(cmd),
+            None => Response::Text(line),
+        }
+    }
+}
+
+/// What a key did at a `LineEditor` prompt
+pub enum Edit {
+    /// The line changed, or the key did nothing
+    Typing,
+    /// ENTER was pressed on this line
+    Submitted(String),
+}
+
+/// A prompt typed at one key at a time while the game goes on handling other
+/// events. On a terminal the prompt is redrawn in place with what has been typed
+/// and, when the turn is on a clock, the seconds left in front of it; with piped
+/// input it is printed once and the line arrives whole.
+pub struct LineEditor {
+    prompt: String,
+    line: String,
+    deadline: Option<Instant>,
+    /// Seconds left when the prompt was last drawn; None when it needs drawing
+    shown: Option<Option<u64>>,
+}
+
+impl LineEditor {
+    pub fn new(prompt: impl Into<String>, deadline: Option<Instant>) -> Self {
+        LineEditor { prompt: prompt.into(), line: String::new(), deadline, shown: None }
+    }
+
+    /// Put the prompt and the line entered at it in the transcript, which the
+    /// prompt's drawing is kept out of
+    pub fn record(&self, line: &str) {
+        transcript::record(&format!("{}{}\n", self.prompt, line));
+    }
+
+    /// Whether the prompt's deadline has passed
+    pub fn expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Start a fresh line below whatever was printed since the prompt was drawn
+    pub fn restart(&mut self) {
+        self.line.clear();
+        self.shown = None;
+    }
+
+    /// Draw the prompt again on the next `draw`, e.g. after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    /// Bring the prompt on screen up to date
+    pub fn draw(&mut self, terminal: bool) -> io::Result<()> {
+        let seconds = self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()).as_secs_f64().ceil() as u64);
+        if !terminal {
+            if self.shown.is_none() {
+                print!("{}", self.prompt);
+                io::stdout().flush()?;
+                self.shown = Some(seconds);
+            }
+            return Ok(());
+        }
+        if self.shown == Some(seconds) {
+            return Ok(());
+        }
+        let mut out = io::stdout();
+        queue!(out, MoveToColumn(0), terminal::Clear(ClearType::CurrentLine))?;
+        if let Some(seconds) = seconds {
+            queue!(
+                out,
+                SetForegroundColor(if seconds <= 5 { Color::Red } else { Color::Yellow }),
+                Print(format!("⏰ {:>2}s ", seconds)),
+                ResetColor,
+            )?;
+        }
+        queue!(out, Print(&self.prompt), Print(&self.line))?;
+        out.flush()?;
+        self.shown = Some(seconds);
+        Ok(())
+    }
+
+    /// Apply a key press; ctrl-D on an empty line closes input like EOF
+    pub fn key(&mut self, key: KeyEvent) -> InputResult<Edit> {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            // Some terminals send a line feed (ctrl-J) for ENTER
+            KeyCode::Enter => return Ok(Edit::Submitted(self.line.trim().to_string())),
+            KeyCode::Char('j') if control => return Ok(Edit::Submitted(self.line.trim().to_string())),
+            KeyCode::Char('d') if control && self.line.is_empty() => return Err(InputError::Closed),
+            KeyCode::Char(_) if control => {}
+            KeyCode::Char(c) => self.line.push(c),
+            KeyCode::Backspace => {
+                self.line.pop();
+            }
+            _ => {}
+        }
+        self.shown = None;
+        Ok(Edit::Typing)
+    }
+}
@@ -0,0 +1,177 @@
+//! Custom board layout files, loaded with `--board FILE.toml`: a name, each
+//! player's path as grid squares and the rosettes and safe squares among
+//! them. The grid is as many rows and columns as the squares reach, at most
+//! three rows so it draws like the built-in boards. The standard board
+//! written out as a file:
+//!
+//! ```toml
+//! name = "my-standard"
+//! description = "the traditional board, written out"
+//! # [row, col] from where a piece enters to the last square before bearing off
+//! path1 = [[0, 3], [0, 2], [0, 1], [0, 0], [1, 0], [1, 1], [1, 2], [1, 3],
+//!          [1, 4], [1, 5], [1, 6], [1, 7], [0, 7], [0, 6]]
+//! path2 = [[2, 3], [2, 2], [2, 1], [2, 0], [1, 0], [1, 1], [1, 2], [1, 3],
+//!          [1, 4], [1, 5], [1, 6], [1, 7], [2, 7], [2, 6]]
+//! rosettes = [[0, 6], [1, 3], [2, 6]]
+//! safe = [[0, 0], [0, 6], [1, 3], [2, 0], [2, 6]]
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use crate::board::{BoardLayout, BoardVariant, CustomBoard, MAX_SQUARES};
+use crate::error::UrError;
+use crate::toml_file::TomlFile;
+
+/// Most rows and columns a layout may span, so the board fits its panel
+const MAX_ROWS: usize = 3;
+const MAX_COLS: usize = 10;
+/// Longest path: positions along it, plus off the board and borne off, must
+/// fit `FastGameState`'s 5-bit fields
+const MAX_PATH: usize = 30;
+const MAX_NAME: usize = 20;
+const KEYS: [&str; 6] = ["name", "description", "path1", "path2", "rosettes", "safe"];
+
+/// Read and check the layout file at `path`
+pub fn load(path: &Path) -> Result<CustomBoard, UrError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Check a layout file's contents
+pub fn parse(text: &str) -> Result<CustomBoard, UrError> {
+    let file = TomlFile::parse(text)?;
+    let table = &file.table;
+    if let Some(key) = table.keys().find(|key| !KEYS.contains(&key.as_str())) {
+        return Err(file.error(key, format!("unknown key '{}'", key)));
+    }
+    let text_of = |key: &str| match table.get(key) {
+        None => Ok(None),
+        Some(toml::Value::String(text)) => Ok(Some(text.clone())),
+        Some(_) => Err(file.error(key, format!("'{}' must be a string", key))),
+    };
+
+    let name = text_of("name")?.ok_or_else(|| UrError::parse("missing 'name'"))?;
+    if name.is_empty() || name.len() > MAX_NAME || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(file.error("name", format!("the name must be 1 to {} lowercase letters, digits and dashes", MAX_NAME)));
+    }
+    if BoardVariant::ALL.iter().any(|variant| variant.label() == name) {
+        return Err(file.error("name", format!("'{}' is a built-in board's name", name)));
+    }
+    let description = text_of("description")?.unwrap_or_else(|| "a custom layout".to_string());
+
+    let paths = [squares(&file, "path1")?, squares(&file, "path2")?];
+    for (key, path) in ["path1", "path2"].iter().zip(&paths) {
+        if path.is_empty() || path.len() > MAX_PATH {
+            return Err(file.error(key, format!("'{}' must have 1 to {} squares", key, MAX_PATH)));
+        }
+        if let Some((idx, square)) = path.iter().enumerate().find(|&(idx, square)| path[..idx].contains(square)) {
+            return Err(file.error(&format!("{}.{}", key, idx), format!("'{}' visits {:?} twice", key, square)));
+        }
+    }
+    if paths[0].len() != paths[1].len() {
+        return Err(file.error("path2", "both paths must be the same length"));
+    }
+
+    // Squares are numbered in the order the paths first reach them
+    let mut coords: Vec<(usize, usize)> = Vec::new();
+    for &square in paths.iter().flatten() {
+        if !coords.contains(&square) {
+            coords.push(square);
+        }
+    }
+    if coords.len() > MAX_SQUARES as usize {
+        return Err(UrError::parse(format!("the paths cover {} squares; at most {} fit", coords.len(), MAX_SQUARES)));
+    }
+    let index = |square: &(usize, usize)| coords.iter().position(|c| c == square).map(|idx| idx as u8);
+    let mask = |key: &str| -> Result<u32, UrError> {
+        squares(&file, key)?.iter().enumerate().try_fold(0, |mask, (idx, square)| {
+            index(square).map(|sq| mask | 1 << sq)
+                .ok_or_else(|| file.error(&format!("{}.{}", key, idx), format!("'{}' lists {:?}, which is on neither path", key, square)))
+        })
+    };
+    let (rosettes, safe) = (mask("rosettes")?, mask("safe")?);
+
+    let path_indices = |path: &[(usize, usize)]| -> &'static [u8] {
+        path.iter().map(|square| index(square).expect("collected above")).collect::<Vec<_>>().leak()
+    };
+    let layout = BoardLayout {
+        rows: coords.iter().map(|&(row, _)| row + 1).max().unwrap_or(1),
+        cols: coords.iter().map(|&(_, col)| col + 1).max().unwrap_or(1),
+        paths: [path_indices(&paths[0]), path_indices(&paths[1])],
+        coords: coords.leak(),
+        rosettes,
+        safe,
+    };
+    Ok(CustomBoard { name, description, layout })
+}
+
+/// A list of `[row, col]` squares within the grid; missing is empty
+fn squares(file: &TomlFile, key: &str) -> Result<Vec<(usize, usize)>, UrError> {
+    let Some(value) = file.table.get(key) else {
+        return Ok(Vec::new());
+    };
+    let invalid = |at: &str| {
+        file.error(at, format!("'{}' must be a list of [row, col] squares, rows 0 to {} and columns 0 to {}", key, MAX_ROWS - 1, MAX_COLS - 1))
+    };
+    let list = value.as_array().ok_or_else(|| invalid(key))?;
+    list.iter().enumerate().map(|(idx, square)| {
+        let at = format!("{}.{}", key, idx);
+        let pair = square.as_array().filter(|pair| pair.len() == 2).ok_or_else(|| invalid(&at))?;
+        let coord = |value: &toml::Value, limit: usize| {
+            value.as_integer().and_then(|n| usize::try_from(n).ok()).filter(|&n| n < limit).ok_or_else(|| invalid(&at))
+        };
+        Ok((coord(&pair[0], MAX_ROWS)?, coord(&pair[1], MAX_COLS)?))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD_FILE: &str = "\
+name = \"my-standard\"
+path1 = [[0, 3], [0, 2], [0, 1], [0, 0], [1, 0], [1, 1], [1, 2], [1, 3],
+         [1, 4], [1, 5], [1, 6], [1, 7], [0, 7], [0, 6]]
+path2 = [[2, 3], [2, 2], [2, 1], [2, 0], [1, 0], [1, 1], [1, 2], [1, 3],
+         [1, 4], [1, 5], [1, 6], [1, 7], [2, 7], [2, 6]]
+rosettes = [[0, 6], [1, 3], [2, 6]]
+safe = [[0, 0], [0, 6], [1, 3], [2, 0], [2, 6]]
+";
+
+    type Square = (usize, usize);
+
+    fn path_squares(layout: &BoardLayout) -> Vec<Vec<Square>> {
+        layout.paths.iter().map(|path| path.iter().map(|&sq| layout.coords[sq as usize]).collect()).collect()
+    }
+
+    /// Grid squares of the squares in `mask`, sorted
+    fn marked_squares(layout: &BoardLayout, mask: u32) -> Vec<Square> {
+        let mut marked: Vec<_> = (0..layout.coords.len()).filter(|&sq| mask >> sq & 1 == 1).map(|sq| layout.coords[sq]).collect();
+        marked.sort();
+        marked
+    }
+
+    #[test]
+    fn standard_board_written_out_matches_the_built_in_one() {
+        let custom = parse(STANDARD_FILE).unwrap();
+        let standard = BoardVariant::Standard.layout();
+        assert_eq!((custom.layout.rows, custom.layout.cols), (standard.rows, standard.cols));
+        assert_eq!(path_squares(&custom.layout), path_squares(standard));
+        assert_eq!(marked_squares(&custom.layout, custom.layout.rosettes), marked_squares(standard, standard.rosettes));
+        assert_eq!(marked_squares(&custom.layout, custom.layout.safe), marked_squares(standard, standard.safe));
+
+        assert!(parse(&STANDARD_FILE.replace("my-standard", "standard")).is_err(), "built-in name");
+        assert!(parse(&STANDARD_FILE.replace("[0, 6]]\n", "[0, 3]]\n")).is_err(), "path1 visits [0, 3] twice");
+        assert!(parse(&STANDARD_FILE.replace("[[0, 6], [1, 3]", "[[0, 5], [1, 3]")).is_err(), "rosette off both paths");
+        assert!(parse(&format!("{}colour = \"red\"\n", STANDARD_FILE)).is_err(), "unknown key");
+
+        // Problems are reported at their line
+        let line = |text: &str| match parse(text) {
+            Err(UrError::ParseError { line, .. }) => line,
+            other => panic!("{:?} was not refused", other.map(|board| board.name)),
+        };
+        assert_eq!(line(&STANDARD_FILE.replace("[2, 6]]\nrosettes", "[3, 6]]\nrosettes")), 5, "row out of range on path2");
+        assert_eq!(line(&STANDARD_FILE.replace("safe = [[0, 0]", "safe = [[0, 9]")), 7, "safe square off both paths");
+        assert_eq!(line(&STANDARD_FILE.replace("rosettes = ", "rosettes = = ")), 6, "broken TOML");
+    }
+}
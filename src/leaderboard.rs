@@ -0,0 +1,240 @@
+//! Local leaderboard accumulated across stats-mode and experiment runs.
+//! Results are kept per pair of AI configurations, so re-running a matchup adds
+//! to the same entries; ratings are fitted to every stored result when the
+//! standings are shown, so the order runs were made in does not matter.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::error::UrError;
+use crate::screen::{Align, Screen, Span, Table};
+
+pub const DEFAULT_LEADERBOARD_FILE: &str = "ur_leaderboard.txt";
+
+const LEADERBOARD_HEADER: &str = "# Royal Game of Ur leaderboard";
+const LEADERBOARD_VERSION: u32 = 1;
+/// Rating of the virtual opponent every configuration is assumed to have drawn
+/// one game against, which keeps unbeaten or winless entries finite
+const ANCHOR_RATING: f64 = 1500.0;
+const FIT_ITERATIONS: usize = 500;
+
+/// Games and wins between two configurations, from the first one's side
+#[derive(Debug, Clone, Copy, Default)]
+struct PairResult {
+    games: usize,
+    wins: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Leaderboard {
+    /// Keyed by the two configuration names in sorted order
+    results: BTreeMap<(String, String), PairResult>,
+}
+
+/// One configuration's line in the standings
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    pub rating: f64,
+}
+
+impl Leaderboard {
+    /// Read a leaderboard file; a missing file is an empty leaderboard
+    pub fn load(path: &Path) -> Result<Self, UrError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Add `games` games between `a` and `b`, of which `a` won `a_wins`
+    pub fn add_result(&mut self, a: &str, b: &str, games: usize, a_wins: usize) {
+        let (key, wins) = if a <= b {
+            ((a.to_string(), b.to_string()), a_wins)
+        } else {
+            ((b.to_string(), a.to_string()), games - a_wins)
+        };
+        let entry = self.results.entry(key).or_default();
+        entry.games += games;
+        entry.wins += wins;
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(LEADERBOARD_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", LEADERBOARD_VERSION));
+        for ((a, b), result) in &self.results {
+            out.push_str(&format!("result {} {} {} {}\n", a, b, result.games, result.wins));
+        }
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut leaderboard = Leaderboard::default();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let err = |message: String| UrError::ParseError { line: idx + 1, message };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["version", v] => {
+                    let v: u32 = v.parse().map_err(|_| err("expected a version number".to_string()))?;
+                    if v != LEADERBOARD_VERSION {
+                        return Err(err(format!("unsupported leaderboard version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                ["result", a, b, games, wins] => {
+                    let (Ok(games), Ok(wins)) = (games.parse::<usize>(), wins.parse::<usize>()) else {
+                        return Err(err("games and wins must be numbers".to_string()));
+                    };
+                    if wins > games || a == b {
+                        return Err(err(format!("impossible result '{}'", line)));
+                    }
+                    leaderboard.add_result(a, b, games, wins);
+                }
+                _ => return Err(err(format!("unrecognized line '{}'", line))),
+            }
+        }
+
+        version.ok_or_else(|| UrError::parse("missing 'version'"))?;
+        Ok(leaderboard)
+    }
+
+    /// Every configuration, best rated first
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut names: Vec<&String> = self.results.keys().flat_map(|(a, b)| [a, b]).collect();
+        names.sort();
+        names.dedup();
+        let index = |name: &String| names.binary_search(&name).expect("name collected above");
+
+        let mut standings: Vec<Standing> = names.iter()
+            .map(|&name| Standing { name: name.clone(), games: 0, wins: 0, rating: ANCHOR_RATING })
+            .collect();
+        // (opponent, games) for each configuration
+        let mut opponents: Vec<Vec<(usize, usize)>> = vec![Vec::new(); names.len()];
+        for ((a, b), result) in &self.results {
+            let (i, j) = (index(a), index(b));
+            standings[i].games += result.games;
+            standings[i].wins += result.wins;
+            standings[j].games += result.games;
+            standings[j].wins += result.games - result.wins;
+            opponents[i].push((j, result.games));
+            opponents[j].push((i, result.games));
+        }
+
+        // Bradley-Terry strengths by minorization-maximization, with each
+        // configuration's virtual draw against an anchor of strength 1
+        let mut strength = vec![1.0; names.len()];
+        for _ in 0..FIT_ITERATIONS {
+            strength = standings.iter().enumerate().map(|(i, standing)| {
+                let expected: f64 = opponents[i].iter()
+                    .map(|&(j, games)| games as f64 / (strength[i] + strength[j]))
+                    .sum::<f64>() + 1.0 / (strength[i] + 1.0);
+                (standing.wins as f64 + 0.5) / expected
+            }).collect();
+        }
+        for (standing, s) in standings.iter_mut().zip(&strength) {
+            standing.rating = ANCHOR_RATING + 400.0 * s.log10();
+        }
+
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        standings
+    }
+}
+
+/// Leaderboard name for an AI configuration on the current board and dice;
+/// results on other variants, or with biased dice, are kept apart from the
+/// standard game's
+pub fn config_name(label: &str) -> String {
+    let mut name = label.to_string();
+    if board::variant() != BoardVariant::Standard {
+        name.push_str(&format!("@{}", board::variant().label()));
+    }
+    if dice::variant() != DiceVariant::Tetrahedra {
+        name.push_str(&format!("+{}", dice::variant().label()));
+    }
+    if dice::is_biased() {
+        let [p1, p2] = dice::pip_chances();
+        name.push_str(&format!("+pips{:.0}-{:.0}", p1 * 100.0, p2 * 100.0));
+    }
+    name
+}
+
+/// Add a finished match between two configurations to the leaderboard file.
+/// Mirror matches say nothing about relative strength and are not recorded;
+/// returns whether the match was recorded.
+pub fn record_match(a: &str, b: &str, games: usize, a_wins: usize) -> bool {
+    let (a, b) = (config_name(a), config_name(b));
+    if a == b || games == 0 {
+        return false;
+    }
+    let path = Path::new(DEFAULT_LEADERBOARD_FILE);
+    let updated = Leaderboard::load(path).and_then(|mut leaderboard| {
+        leaderboard.add_result(&a, &b, games, a_wins);
+        leaderboard.save(path).map_err(UrError::from)
+    });
+    if let Err(e) = &updated {
+        eprintln!("⚠️  Could not update {}: {}", path.display(), e);
+    }
+    updated.is_ok()
+}
+
+/// Options for `ur leaderboard [--file FILE]`
+pub struct LeaderboardOptions {
+    pub file: PathBuf,
+}
+
+impl LeaderboardOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--file"], &[])?;
+        Ok(LeaderboardOptions {
+            file: PathBuf::from(flags.get_str("--file").unwrap_or(DEFAULT_LEADERBOARD_FILE)),
+        })
+    }
+}
+
+pub fn run_leaderboard(options: &LeaderboardOptions) -> Result<(), UrError> {
+    let standings = Leaderboard::load(&options.file)?.standings();
+    println!("=== LEADERBOARD ({}) ===", options.file.display());
+    if standings.is_empty() {
+        println!("No results yet. Stats mode and the scaling, sweep and ablation experiments add to it.");
+        return Ok(());
+    }
+
+    let mut table = Table::new(&[Align::Right, Align::Left, Align::Right, Align::Right, Align::Right, Align::Right], 2);
+    table.row(["#", "Configuration", "Games", "Wins", "Win %", "Rating"].map(Span::plain).to_vec());
+    for (rank, standing) in standings.iter().enumerate() {
+        table.row(vec![
+            Span::plain((rank + 1).to_string()),
+            Span::plain(standing.name.as_str()),
+            Span::plain(standing.games.to_string()),
+            Span::plain(standing.wins.to_string()),
+            Span::plain(format!("{:.1}%", standing.wins as f64 / standing.games.max(1) as f64 * 100.0)),
+            Span::plain(format!("{:.0}", standing.rating)),
+        ]);
+    }
+    let mut screen = Screen::new();
+    screen.table(&table, 2);
+    screen.print();
+    println!();
+    println!("Ratings are fitted to every stored result, with {:.0} as the anchor.", ANCHOR_RATING);
+    Ok(())
+}
@@ -0,0 +1,1041 @@
+//! Matchmaking lobby: `ur lobby` listens for clients on a TCP port. Clients
+//! speak the JSON lines of `ur match` bots, plus a few lobby messages to name
+//! themselves, list the open seeks and the games in progress, and seek a game
+//! on the time control they want. Two seeks that want the same clock are
+//! paired at once, and each game is played on a thread of its own under the
+//! referee, so the lobby runs any number of games side by side. A client is
+//! in one game at a time and seeks again once it ends. The board and dice are
+//! the lobby's, chosen when it starts. A client that drops out of a game has
+//! `REJOIN_GRACE` to connect again and take its seat back with the session
+//! token it was welcomed with; the referee resumes it as it would a bot.
+//!
+//! `ur serve` runs the same lobby headless, as in a container: it takes its
+//! settings from `UR_*` environment variables as well as flags, logs one JSON
+//! object per line to stdout, and on SIGTERM or ctrl-C stops taking seeks,
+//! gives the games in progress `--drain` seconds to finish and decides the
+//! rest on their positions before it exits.
+//!
+//! Clients not in a game may also ask the lobby's AI about a position. Who
+//! may connect, and how fast they may send moves and ask for analyses, is set
+//! by the access file (see `access`).
+
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::access::{self, Access, Limits, RateLimit};
+use crate::ai::HybridAI;
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::cancel::CancelToken;
+use crate::chat::{self, Chat, Heard, Said};
+use crate::dice::{self, DiceVariant};
+use crate::fair_dice;
+use crate::metrics::{self, Gauges, Metrics};
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::position_code;
+use crate::referee::{Clock, Contestant, Referee, Resync, Violation, STARTUP_GRACE};
+use crate::rng::GameRng;
+use crate::json::json_string;
+use crate::subprocess_bot::{number_field, string_field, write_line, SharedWriter, SubprocessBot};
+use crate::terminal;
+
+pub const DEFAULT_LOBBY_PORT: u16 = 7878;
+pub const DEFAULT_LOBBY_LOG: &str = "ur_lobby.log";
+const MAX_NAME: usize = 20;
+/// Move times a seek may ask for, in milliseconds
+const MOVE_TIMES: RangeInclusive<u64> = 100..=60_000;
+const DEFAULT_MOVE_TIME: u64 = 1000;
+/// How often the lobby looks for new clients, and for the end of the games
+/// when it shuts down
+const POLL: Duration = Duration::from_millis(100);
+/// Playouts behind an analysis, run on the client's own thread
+const ANALYSIS_PLAYOUTS: usize = 2000;
+/// Longest line a client may send, in bytes; a longer one ends the connection
+const MAX_LINE: usize = 8192;
+pub const DEFAULT_MAX_CLIENTS: usize = 256;
+/// Lobby listings a client may ask for a minute
+const LISTS_PER_MINUTE: usize = 60;
+/// How long a client turned away at the door has to take its error
+const REFUSAL_TIMEOUT: Duration = Duration::from_secs(1);
+/// Lines waiting to go out to a client; a client further behind is cut off
+const OUTBOX_LINES: usize = 256;
+/// How long one write to a client may take before it is cut off
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Message types the lobby acts on; any other line is a reply in the client's game
+const LOBBY_MESSAGES: [&str; 9] = ["hello", "rejoin", "list", "seek", "cancel", "chat", "draw", "rematch", "analyze"];
+/// How long a client that drops out of its game has to rejoin it
+const REJOIN_GRACE: Duration = Duration::from_secs(30);
+/// Times a client may rejoin one game
+const REJOINS: usize = 3;
+/// Environment variables `ur serve` reads, each standing for a flag
+const ENVIRONMENT: [(&str, &str); 10] = [
+    ("UR_HOST", "--host"),
+    ("UR_PORT", "--port"),
+    ("UR_BOARD", "--board"),
+    ("UR_DICE", "--dice"),
+    ("UR_MAX_GAMES", "--max-games"),
+    ("UR_MAX_CLIENTS", "--max-clients"),
+    ("UR_LOG", "--log"),
+    ("UR_METRICS_PORT", "--metrics-port"),
+    ("UR_DRAIN_SECS", "--drain"),
+    ("UR_ACCESS", "--access"),
+];
+
+/// How the lobby reports what happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Logs {
+    /// Lines for someone watching the console
+    Console,
+    /// One JSON object per line on stdout, for a log collector
+    Json,
+}
+
+impl Logs {
+    /// Report `event` with its `fields`, given as JSON values: as a JSON line,
+    /// or on the console as `text` if the event is shown there
+    fn info(self, event: &str, fields: &[(&str, String)], text: Option<String>) {
+        match self {
+            Logs::Console => {
+                if let Some(text) = text {
+                    println!("{}", text);
+                }
+            }
+            Logs::Json => println!("{}", json_line("info", event, fields)),
+        }
+    }
+
+    fn warn(self, event: &str, fields: &[(&str, String)], text: String) {
+        match self {
+            Logs::Console => eprintln!("⚠️  {}", text),
+            Logs::Json => println!("{}", json_line("warn", event, fields)),
+        }
+    }
+}
+
+/// A log line: the time in seconds since the Unix epoch, the level, the event and its fields
+fn json_line(level: &str, event: &str, fields: &[(&str, String)]) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut line = format!(r#"{{"ts":{}.{:03},"level":"{}","event":"{}""#, now.as_secs(), now.subsec_millis(), level, event);
+    for (key, value) in fields {
+        line.push_str(&format!(r#","{}":{}"#, key, value));
+    }
+    line.push('}');
+    line
+}
+
+/// A client's outgoing lines, queued for a writer thread of its own, so
+/// nothing written to a client waits on its connection, least of all while
+/// the lobby is locked. A client that falls `OUTBOX_LINES` behind, or takes
+/// longer than `WRITE_TIMEOUT` over a write, is disconnected.
+struct Outbox {
+    queue: SyncSender<Vec<u8>>,
+    /// What has been written since the last flush
+    pending: Vec<u8>,
+    stream: TcpStream,
+}
+
+impl Outbox {
+    /// The outbox of client `id` on `stream`, with the thread that empties it
+    fn open(stream: &TcpStream, id: u64) -> io::Result<Outbox> {
+        let own = stream.try_clone()?;
+        let mut socket = stream.try_clone()?;
+        socket.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let (queue, lines) = mpsc::sync_channel::<Vec<u8>>(OUTBOX_LINES);
+        thread::Builder::new().name(format!("lobby client {} writer", id)).spawn(move || {
+            for line in lines {
+                if socket.write_all(&line).is_err() {
+                    let _ = socket.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        })?;
+        Ok(Outbox { queue, pending: Vec::new(), stream: own })
+    }
+}
+
+impl Write for Outbox {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Queue what has been written; a full queue cuts the client off
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        match self.queue.try_send(std::mem::take(&mut self.pending)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                let _ = self.stream.shutdown(Shutdown::Both);
+                Err(io::Error::new(io::ErrorKind::TimedOut, "client is not reading"))
+            }
+            Err(TrySendError::Disconnected(_)) => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
+
+/// A client waiting for an opponent
+struct Seek {
+    id: u64,
+    client: u64,
+    clock: Clock,
+}
+
+/// A game being played
+struct Game {
+    players: [u64; 2],
+    names: [String; 2],
+    clock: Clock,
+    since: Instant,
+    chat: Chat,
+}
+
+/// A connected client and, while it plays, its place in the game
+struct Client {
+    name: String,
+    /// The client's `Outbox`
+    writer: SharedWriter,
+    /// Token given in `welcome`, with which the client rejoins its game after dropping out
+    session: String,
+    game: Option<InGame>,
+    /// Whether it proved it is the listed client of its name
+    member: bool,
+    /// Spaces out its replies in games
+    replies: RateLimit,
+    analyses: RateLimit,
+    lists: RateLimit,
+    /// Set when the lobby turns the client away; its connection is closed
+    closing: bool,
+}
+
+impl Client {
+    fn new(name: String, writer: SharedWriter, limits: Limits) -> Self {
+        Client {
+            name,
+            writer,
+            session: fair_dice::to_hex(&fair_dice::fresh_nonce()),
+            game: None,
+            member: false,
+            replies: reply_spacing(limits),
+            analyses: RateLimit::new(limits.analyses_per_minute, Duration::from_secs(60)),
+            lists: RateLimit::new(LISTS_PER_MINUTE, Duration::from_secs(60)),
+            closing: false,
+        }
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        self.replies = reply_spacing(limits);
+        self.analyses.set_limit(limits.analyses_per_minute);
+    }
+}
+
+/// A client's seat in a game: where its replies go, and how a new
+/// connection reaches the seat if the client drops out
+struct InGame {
+    id: u64,
+    seat: usize,
+    replies: Sender<String>,
+    rejoin: Sender<Rejoin>,
+}
+
+/// A client that dropped out of a game, whose seat is kept for `REJOIN_GRACE`
+struct Away {
+    name: String,
+    member: bool,
+    game: u64,
+    seat: usize,
+    rejoin: Sender<Rejoin>,
+}
+
+/// The new connection of a client that rejoined, handed to its seat
+struct Rejoin {
+    writer: SharedWriter,
+    replies: Receiver<String>,
+}
+
+/// Replies are spread evenly over the second rather than let through in a
+/// burst and then held back, so a fast client is slowed a little on every
+/// move and never by a whole second, which would cost it the game
+fn reply_spacing(limits: Limits) -> RateLimit {
+    RateLimit::new(1, Duration::from_secs(1) / u32::try_from(limits.moves_per_second).unwrap_or(u32::MAX))
+}
+
+/// Everyone connected, the open seeks and the games in progress
+struct Lobby {
+    clients: HashMap<u64, Client>,
+    /// Clients that dropped out of their games, by session token
+    away: HashMap<String, Away>,
+    seeks: Vec<Seek>,
+    games: HashMap<u64, Game>,
+    next_id: u64,
+    max_games: usize,
+    log: PathBuf,
+    metrics: Arc<Metrics>,
+    logs: Logs,
+    access: Access,
+    /// Set once the lobby is shutting down, when it takes no more seeks
+    draining: bool,
+    /// Cancelled to stop the games still going at the end of the drain
+    stop: CancelToken,
+}
+
+/// Two clients paired for a game, ready to be played
+struct Pairing {
+    id: u64,
+    clock: Clock,
+    seats: [LobbySeat; 2],
+}
+
+impl Lobby {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Tell client `id` something, if it is still connected. The line is
+    /// queued in its outbox, so this never waits on the client.
+    fn tell(&self, id: u64, message: &str) {
+        if let Some(client) = self.clients.get(&id) {
+            let _ = write_line(&client.writer, message);
+        }
+    }
+
+    fn error(&self, id: u64, message: &str) {
+        self.metrics.client_error();
+        self.tell(id, &format!(r#"{{"type":"error","message":{}}}"#, json_string(message)));
+    }
+
+    fn welcome(&self, id: u64) {
+        let Some(client) = self.clients.get(&id) else {
+            return;
+        };
+        self.tell(id, &format!(r#"{{"type":"welcome","name":{},"session":"{}","board":"{}","dice":"{}","path_length":{}}}"#,
+                               json_string(&client.name), client.session, board::variant().label(), dice::variant().label(),
+                               FastGameState::path_len()));
+    }
+
+    /// The open seeks and the games in progress
+    fn listing(&self) -> String {
+        let name = |client: u64| self.clients.get(&client).map_or("", |client| client.name.as_str());
+        let seeks: Vec<String> = self.seeks.iter()
+            .map(|seek| format!(r#"{{"id":{},"name":{},{}}}"#, seek.id, json_string(name(seek.client)), clock_fields(&seek.clock)))
+            .collect();
+        let mut ids: Vec<&u64> = self.games.keys().collect();
+        ids.sort();
+        let games: Vec<String> = ids.into_iter().map(|id| {
+            let game = &self.games[id];
+            format!(r#"{{"id":{},"players":[{},{}],{},"seconds":{}}}"#, id, json_string(&game.names[0]), json_string(&game.names[1]),
+                    clock_fields(&game.clock), game.since.elapsed().as_secs())
+        }).collect();
+        format!(r#"{{"type":"lobby","seeks":[{}],"games":[{}]}}"#, seeks.join(","), games.join(","))
+    }
+
+    /// Pair seeks that want the same clock, oldest first, while there is room
+    /// for more games
+    fn pair(&mut self) -> Vec<Pairing> {
+        let mut pairings = Vec::new();
+        while self.games.len() < self.max_games {
+            let found = (0..self.seeks.len()).find_map(|i| {
+                (i + 1..self.seeks.len())
+                    .find(|&j| self.seeks[j].clock == self.seeks[i].clock && self.seeks[j].client != self.seeks[i].client)
+                    .map(|j| (i, j))
+            });
+            let Some((i, j)) = found else {
+                break;
+            };
+            let second = self.seeks.remove(j);
+            let first = self.seeks.remove(i);
+            // Who moves first is drawn by lot
+            let players = if rand::random() { [first.client, second.client] } else { [second.client, first.client] };
+            pairings.push(self.start_game(players, first.clock));
+        }
+        pairings
+    }
+
+    fn start_game(&mut self, players: [u64; 2], clock: Clock) -> Pairing {
+        let id = self.next_id();
+        let names = players.map(|client| self.clients[&client].name.clone());
+        let seats = [FastPlayer::One, FastPlayer::Two].map(|seat| {
+            let idx = seat as usize;
+            let (sender, replies) = mpsc::channel();
+            let (rejoin, rejoins) = mpsc::channel();
+            let client = self.clients.get_mut(&players[idx]).expect("seeks are removed with their clients");
+            client.game = Some(InGame { id, seat: idx, replies: sender, rejoin });
+            let bot = SubprocessBot::attach(Arc::clone(&client.writer), replies);
+            LobbySeat { name: names[idx].clone(), bot, rejoins, stop: self.stop.clone(), metrics: Arc::clone(&self.metrics) }
+        });
+        for (idx, &client) in players.iter().enumerate() {
+            self.tell(client, &format!(r#"{{"type":"paired","game":{},"seat":{},"opponent":{}}}"#, id, idx + 1, json_string(&names[1 - idx])));
+        }
+        self.games.insert(id, Game { players, names, clock, since: Instant::now(), chat: Chat::new() });
+        Pairing { id, clock, seats }
+    }
+
+    /// Whether client `id` may do more than say hello: in allowlist mode, only
+    /// once it has proved it is listed
+    fn admits(&self, id: u64) -> bool {
+        !self.access.allowlist || self.clients.get(&id).is_some_and(|client| client.member)
+    }
+
+    /// Tell client `id` why it is turned away, and close its connection
+    fn shut_out(&mut self, id: u64, message: &str) {
+        self.error(id, message);
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.closing = true;
+        }
+    }
+
+    /// Whether client `id` has been turned away
+    fn closing(&self, id: u64) -> bool {
+        self.clients.get(&id).is_some_and(|client| client.closing)
+    }
+
+    /// Act on a lobby message from client `id`; anything else goes to its game
+    fn handle(&mut self, id: u64, line: &str) -> Vec<Pairing> {
+        let kind = string_field(line, "type");
+        if !matches!(kind.as_deref(), Some("hello" | "rejoin")) && !self.admits(id) {
+            self.shut_out(id, "this lobby is for listed clients; say hello with your name and token");
+            return Vec::new();
+        }
+        match kind.as_deref() {
+            Some("hello") => self.rename(id, line),
+            Some("rejoin") => self.rejoin(id, line),
+            Some("list") => match self.clients.get_mut(&id).map(|client| client.lists.admit()) {
+                Some(true) => self.tell(id, &self.listing()),
+                Some(false) => self.error(id, &format!("at most {} listings a minute", LISTS_PER_MINUTE)),
+                None => {}
+            },
+            Some("seek") => return self.seek(id, line),
+            Some("cancel") => self.seeks.retain(|seek| seek.client != id),
+            Some("chat") => self.relay(id, line),
+            Some("draw" | "rematch") => self.error(id, "draws and rematches are not offered in the lobby; seek again for another game"),
+            _ => {
+                let Some(client) = self.clients.get_mut(&id) else {
+                    return Vec::new();
+                };
+                // `serve` has waited for the limit, so this is counted
+                client.replies.admit();
+                match &client.game {
+                    Some(in_game) => {
+                        let _ = in_game.replies.send(line.to_string());
+                    }
+                    None => self.error(id, "not in a game"),
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    /// Take the name in a hello; a listed name needs its token
+    fn rename(&mut self, id: u64, line: &str) {
+        let name = string_field(line, "name").unwrap_or_default();
+        if name.is_empty() || name.len() > MAX_NAME || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return self.error(id, &format!("a name is 1 to {} letters, digits, dashes and underscores", MAX_NAME));
+        }
+        if self.clients.iter().any(|(&other, client)| other != id && client.name == name) || self.away.values().any(|away| away.name == name) {
+            return self.error(id, &format!("'{}' is taken", name));
+        }
+        let limits = match (self.access.member(&name), string_field(line, "token")) {
+            (Some(_), Some(token)) => match self.access.authenticate(&name, &token) {
+                Some(member) => Some(member.limits),
+                None => return self.refuse(id, &name, "wrong token"),
+            },
+            (Some(_), None) => return self.refuse(id, &name, "a listed name needs its token"),
+            (None, Some(_)) => return self.refuse(id, &name, "not a listed name"),
+            (None, None) if self.access.allowlist => return self.refuse(id, &name, "only listed clients may play here"),
+            (None, None) => None,
+        };
+        let defaults = self.access.limits;
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.name = name;
+            client.member = limits.is_some();
+            client.set_limits(limits.unwrap_or(defaults));
+        }
+        self.welcome(id);
+    }
+
+    /// Seat client `id` in the game it dropped out of, given the session
+    /// token it was welcomed with then. It takes back its name and keeps the
+    /// token, and the referee brings it up to date with a `resume`.
+    fn rejoin(&mut self, id: u64, line: &str) {
+        if self.clients.get(&id).is_some_and(|client| client.game.is_some()) {
+            return self.error(id, "already in a game");
+        }
+        let session = string_field(line, "session").unwrap_or_default();
+        let Some(away) = self.away.remove(&session) else {
+            return self.shut_out(id, "no game is waiting for that session");
+        };
+        let Some(game) = self.games.get_mut(&away.game) else {
+            return self.error(id, "that game is over");
+        };
+        game.players[away.seat] = id;
+        self.seeks.retain(|seek| seek.client != id);
+        let opponent = game.names[1 - away.seat].clone();
+        let limits = self.access.member(&away.name).filter(|_| away.member).map_or(self.access.limits, |member| member.limits);
+        let (sender, replies) = mpsc::channel();
+        let Some(client) = self.clients.get_mut(&id) else {
+            return;
+        };
+        client.name = away.name.clone();
+        client.member = away.member;
+        client.session = session;
+        client.set_limits(limits);
+        client.game = Some(InGame { id: away.game, seat: away.seat, replies: sender, rejoin: away.rejoin.clone() });
+        let writer = Arc::clone(&client.writer);
+        self.tell(id, &format!(r#"{{"type":"rejoined","name":{},"game":{},"seat":{},"opponent":{}}}"#,
+                               json_string(&away.name), away.game, away.seat + 1, json_string(&opponent)));
+        let _ = away.rejoin.send(Rejoin { writer, replies });
+        self.logs.info("client_rejoined", &[("client", id.to_string()), ("name", json_string(&away.name)), ("game", away.game.to_string())],
+                       Some(format!("↩️  {} rejoined game {}", away.name, away.game)));
+    }
+
+    /// Turn down client `id`'s claim to `name`, and the client with it
+    fn refuse(&mut self, id: u64, name: &str, why: &str) {
+        self.logs.warn("auth_failed", &[("client", id.to_string()), ("name", json_string(name)), ("reason", json_string(why))],
+                       format!("Client {} could not take the name '{}': {}", id, name, why));
+        self.shut_out(id, &format!("'{}': {}", name, why));
+    }
+
+    fn seek(&mut self, id: u64, line: &str) -> Vec<Pairing> {
+        if self.draining {
+            self.error(id, "the lobby is shutting down");
+            return Vec::new();
+        }
+        if self.clients.get(&id).is_some_and(|client| client.game.is_some()) {
+            self.error(id, "already in a game");
+            return Vec::new();
+        }
+        if let Some(wanted) = string_field(line, "board").filter(|label| label != board::variant().label())
+            .or_else(|| string_field(line, "dice").filter(|label| label != dice::variant().label())) {
+            self.error(id, &format!("this lobby plays the {} board with {} dice, not {}", board::variant().label(), dice::variant().label(), wanted));
+            return Vec::new();
+        }
+        let per_move = number_field(line, "time_ms").map_or(DEFAULT_MOVE_TIME, |ms| ms as u64);
+        if !MOVE_TIMES.contains(&per_move) {
+            self.error(id, &format!("time_ms must be {} to {}", MOVE_TIMES.start(), MOVE_TIMES.end()));
+            return Vec::new();
+        }
+        let bank = number_field(line, "bank_ms").filter(|&ms| ms > 0.0).map(|ms| Duration::from_millis(ms as u64));
+        let clock = Clock { per_move: Duration::from_millis(per_move), bank };
+
+        // A new seek replaces the client's last one
+        self.seeks.retain(|seek| seek.client != id);
+        let seek_id = self.next_id();
+        self.seeks.push(Seek { id: seek_id, client: id, clock });
+        self.tell(id, &format!(r#"{{"type":"seeking","id":{},{}}}"#, seek_id, clock_fields(&clock)));
+        self.pair()
+    }
+
+    /// The position and roll client `id` wants analysed, if it may have them
+    fn analysis_request(&mut self, id: u64, line: &str) -> Option<(FastGameState, u8)> {
+        if !self.admits(id) {
+            self.shut_out(id, "this lobby is for listed clients; say hello with your name and token");
+            return None;
+        }
+        let client = self.clients.get_mut(&id)?;
+        if client.game.is_some() {
+            self.error(id, "no analyses while in a game");
+            return None;
+        }
+        if !client.analyses.admit() {
+            let limit = client.analyses.limit();
+            self.error(id, &format!("at most {} analyses a minute", limit));
+            return None;
+        }
+        let game = match position_code::decode(&string_field(line, "position").unwrap_or_default()) {
+            Ok(game) => game,
+            Err(e) => {
+                self.error(id, &e.to_string());
+                return None;
+            }
+        };
+        let roll = number_field(line, "roll").unwrap_or(-1.0);
+        if roll < 0.0 || roll.fract() != 0.0 || dice::model().probability(roll as u8) <= 0.0 {
+            self.error(id, &format!("roll must be a possible roll of the {} dice", dice::variant().label()));
+            return None;
+        }
+        if game.status() != GameStatus::InProgress {
+            self.error(id, "that game is over");
+            return None;
+        }
+        Some((game, roll as u8))
+    }
+
+    /// Pass a chat line on to the client's opponent, within the chat limits
+    fn relay(&mut self, id: u64, line: &str) {
+        let Some(game_id) = self.clients.get(&id).and_then(|client| client.game.as_ref()).map(|in_game| in_game.id) else {
+            return self.error(id, "not in a game");
+        };
+        let Some(game) = self.games.get_mut(&game_id) else {
+            return;
+        };
+        let seat = if game.players[0] == id { FastPlayer::One } else { FastPlayer::Two };
+        let said = Said::Text(chat::clean(&string_field(line, "text").unwrap_or_default()));
+        let opponent = game.players[seat.opposite() as usize];
+        match game.chat.hear(seat, &game.names[seat as usize], &said) {
+            Heard::Line(_) => self.tell(opponent, &said.to_json(seat)),
+            _ => self.error(id, &format!("at most {} chat messages every {} seconds", chat::RATE_LIMIT, chat::RATE_WINDOW.as_secs())),
+        }
+    }
+
+    /// Client `id` disconnected: its seek goes. Its seat in a game is kept
+    /// for it to rejoin with its session token; the game waits for it at its
+    /// next request, and it forfeits if it is not back within `REJOIN_GRACE`.
+    fn leave(&mut self, id: u64) {
+        self.seeks.retain(|seek| seek.client != id);
+        let Some(client) = self.clients.remove(&id) else {
+            return;
+        };
+        let fields = [("client", id.to_string()), ("name", json_string(&client.name))];
+        match client.game {
+            Some(in_game) => {
+                self.logs.info("client_away", &fields, None);
+                // The seat's replies end here, so the game finds out at once
+                let away = Away { name: client.name, member: client.member, game: in_game.id, seat: in_game.seat, rejoin: in_game.rejoin };
+                self.away.insert(client.session, away);
+            }
+            None => self.logs.info("client_left", &fields, None),
+        }
+    }
+
+    /// Game `id` is over: its players may seek again
+    fn release(&mut self, id: u64) {
+        for client in self.games.get(&id).map_or([0; 2], |game| game.players) {
+            if let Some(client) = self.clients.get_mut(&client) {
+                client.game = None;
+            }
+        }
+    }
+
+    /// Game `id` has been reported: it goes, and waiting seeks may now fit
+    fn finish(&mut self, id: u64) -> Vec<Pairing> {
+        self.release(id);
+        self.games.remove(&id);
+        self.away.retain(|_, away| away.game != id);
+        self.pair()
+    }
+}
+
+/// A clock's fields in lobby messages
+fn clock_fields(clock: &Clock) -> String {
+    let bank = clock.bank.map_or(String::new(), |bank| format!(r#","bank_ms":{}"#, bank.as_millis()));
+    format!(r#""time_ms":{}{}"#, clock.per_move.as_millis(), bank)
+}
+
+/// A lobby client seated in a game
+struct LobbySeat {
+    name: String,
+    bot: SubprocessBot,
+    /// The client's new connection, if it drops out and rejoins
+    rejoins: Receiver<Rejoin>,
+    /// The lobby's stop, which also ends the wait for a client to rejoin
+    stop: CancelToken,
+    metrics: Arc<Metrics>,
+}
+
+impl Contestant for LobbySeat {
+    fn label(&self) -> String {
+        self.name.clone()
+    }
+
+    fn start_game(&mut self, seat: FastPlayer, clock: &Clock, session: &str) -> Result<(), Violation> {
+        self.bot.start_game(seat, clock, session)
+    }
+
+    fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation> {
+        let asked = Instant::now();
+        let reply = self.bot.request_move(game, moves, roll, allowed);
+        if reply.is_ok() {
+            self.metrics.move_made(asked.elapsed());
+        }
+        reply
+    }
+
+    /// The result is sent by `play` once the lobby has let the client go, so
+    /// it can seek again as soon as it hears
+    fn end_game(&mut self, _status: GameStatus, _reason: &str) {}
+
+    /// Wait for the client to rejoin, then bring its new connection up to date
+    fn resume(&mut self, resync: &Resync) -> Result<(), Violation> {
+        let deadline = Instant::now() + REJOIN_GRACE;
+        let rejoin = loop {
+            match self.rejoins.recv_timeout(POLL) {
+                Ok(rejoin) => break rejoin,
+                Err(RecvTimeoutError::Timeout) if Instant::now() < deadline && !self.stop.is_cancelled() => {}
+                Err(_) => return Err(Violation::Failed(format!("left and did not rejoin within {} s", REJOIN_GRACE.as_secs()))),
+            }
+        };
+        self.bot = SubprocessBot::attach(rejoin.writer, rejoin.replies);
+        self.bot.resume(resync, resync.clock.per_move + STARTUP_GRACE)
+    }
+}
+
+type Shared = Arc<Mutex<Lobby>>;
+
+/// Play each pairing on a thread of its own
+fn start_games(lobby: &Shared, pairings: Vec<Pairing>) {
+    let logs = lobby.lock().unwrap().logs;
+    for pairing in pairings {
+        let (id, names) = (pairing.id, [pairing.seats[0].name.clone(), pairing.seats[1].name.clone()]);
+        let mut fields = vec![("game", id.to_string()), ("players", format!("[{},{}]", json_string(&names[0]), json_string(&names[1])))];
+        fields.push(("time_ms", pairing.clock.per_move.as_millis().to_string()));
+        if let Some(bank) = pairing.clock.bank {
+            fields.push(("bank_ms", bank.as_millis().to_string()));
+        }
+        logs.info("game_started", &fields,
+                  Some(format!("🎲 game {}: {} vs {} ({} ms per move)", id, names[0], names[1], pairing.clock.per_move.as_millis())));
+        let shared = Arc::clone(lobby);
+        let started = thread::Builder::new().name(format!("lobby game {}", id)).spawn(move || play(&shared, pairing));
+        if let Err(e) = started {
+            logs.warn("game_failed", &[("game", id.to_string()), ("error", json_string(&e.to_string()))],
+                      format!("Could not start a thread for game {}: {}", id, e));
+            let more = {
+                let mut state = lobby.lock().unwrap();
+                for client in state.games.get(&id).map_or([0; 2], |game| game.players) {
+                    state.error(client, "the lobby is too busy to start the game; seek again");
+                }
+                state.finish(id)
+            };
+            start_games(lobby, more);
+        }
+    }
+}
+
+fn play(lobby: &Shared, pairing: Pairing) {
+    let Pairing { id, clock, seats: [mut first, mut second] } = pairing;
+    let (log, metrics, logs, stop) = {
+        let lobby = lobby.lock().unwrap();
+        (lobby.log.clone(), Arc::clone(&lobby.metrics), lobby.logs, lobby.stop.clone())
+    };
+    let game_name = format!("lobby game {}: {} vs {}", id, first.name, second.name);
+    let mut referee = Referee::new(clock, &log).with_stop(stop).with_reconnects(REJOINS);
+    let result = referee.play_game([&mut first, &mut second], &mut GameRng::random(), &game_name);
+    metrics.game_finished(result.violation.as_ref());
+    let reason = result.reason();
+    let winner = result.status.winner().map(|seat| [&first.name, &second.name][seat as usize]);
+    logs.info("game_over", &[
+        ("game", id.to_string()),
+        ("winner", winner.map_or("null".to_string(), |name| json_string(name))),
+        ("reason", json_string(&reason)),
+        ("moves", result.moves.to_string()),
+        ("illegal_move", match result.violation {
+            Some(Violation::IllegalMove(_, reason)) => json_string(reason.label()),
+            _ => "null".to_string(),
+        }),
+    ], Some(match winner {
+        Some(name) => format!("🏁 game {}: {} wins, {}", id, name, reason),
+        None => format!("🏁 game {}: {}", id, reason),
+    }));
+
+    // The players are let go before they hear the result, so they can seek
+    // again as soon as they do
+    lobby.lock().unwrap().release(id);
+    for seat in [&mut first, &mut second] {
+        seat.bot.end_game(result.status, &reason);
+    }
+    let more = lobby.lock().unwrap().finish(id);
+    start_games(lobby, more);
+}
+
+/// The next line from a client, at most `MAX_LINE` bytes; None at the end
+/// of the connection
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    if Read::take(reader, MAX_LINE as u64 + 1).read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.len() > MAX_LINE && !line.ends_with(b"\n") {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("lines are at most {} bytes", MAX_LINE)));
+    }
+    String::from_utf8(line).map(Some).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "lines must be UTF-8"))
+}
+
+/// Read client `id`'s lines until it disconnects or is turned away. Replies
+/// in its game wait for its rate limit, and analyses run here, outside the
+/// lobby's lock.
+fn serve(lobby: &Shared, id: u64, stream: TcpStream) {
+    let mut reader = BufReader::new(&stream);
+    loop {
+        let line = match read_line(&mut reader) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                if e.kind() == io::ErrorKind::InvalidData {
+                    lobby.lock().unwrap().error(id, &e.to_string());
+                }
+                break;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match string_field(line, "type").as_deref() {
+            Some("analyze") => {
+                let request = lobby.lock().unwrap().analysis_request(id, line);
+                if let Some((game, roll)) = request {
+                    let stop = lobby.lock().unwrap().stop.clone();
+                    let reply = analyze(&game, roll, &stop);
+                    lobby.lock().unwrap().tell(id, &reply);
+                }
+                if lobby.lock().unwrap().closing(id) {
+                    break;
+                }
+                continue;
+            }
+            Some(kind) if LOBBY_MESSAGES.contains(&kind) => {}
+            _ => {
+                let wait = lobby.lock().unwrap().clients.get(&id).map_or(Duration::ZERO, |client| client.replies.wait());
+                thread::sleep(wait);
+            }
+        }
+        let pairings = lobby.lock().unwrap().handle(id, line);
+        start_games(lobby, pairings);
+        if lobby.lock().unwrap().closing(id) {
+            break;
+        }
+    }
+    lobby.lock().unwrap().leave(id);
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// The AI's view of `roll` in `game`: its move and the candidates it weighed
+fn analyze(game: &FastGameState, roll: u8, stop: &CancelToken) -> String {
+    let ai = HybridAI::new_with_threads(ANALYSIS_PLAYOUTS, 1);
+    let (best, info) = match ai.choose_move_cancellable(game, game.current_player(), roll, stop) {
+        Some((piece, info)) => (piece.to_string(), Some(info)),
+        None => ("null".to_string(), None),
+    };
+    let candidates: Vec<String> = info.iter().flat_map(|info| &info.candidates)
+        .map(|candidate| format!(r#"{{"piece":{},"visits":{},"win_rate":{:.4}}}"#, candidate.piece, candidate.visits, candidate.win_rate))
+        .collect();
+    format!(r#"{{"type":"analysis","position":"{}","roll":{},"best":{},"candidates":[{}],"playouts":{}}}"#,
+            position_code::encode(game), roll, best, candidates.join(","), info.map_or(0, |info| info.nodes))
+}
+
+/// Wind down: take no more seeks, give the games in progress `drain` to
+/// finish, then stop the rest, decided on their positions
+fn shut_down(lobby: &Shared, drain: Duration) {
+    let logs = {
+        let mut state = lobby.lock().unwrap();
+        state.draining = true;
+        state.seeks.clear();
+        let notice = format!(r#"{{"type":"shutdown","drain_ms":{}}}"#, drain.as_millis());
+        for &id in state.clients.keys() {
+            state.tell(id, &notice);
+        }
+        state.logs.info("stopping", &[("games", state.games.len().to_string()), ("drain_ms", drain.as_millis().to_string())],
+                        Some(format!("Stopping: {} games in progress, {} s to finish", state.games.len(), drain.as_secs())));
+        state.logs
+    };
+    let deadline = Instant::now() + drain;
+    let mut stopped = 0;
+    loop {
+        let state = lobby.lock().unwrap();
+        if state.games.is_empty() {
+            break;
+        }
+        if Instant::now() >= deadline && !state.stop.is_cancelled() {
+            stopped = state.games.len();
+            state.stop.cancel();
+        }
+        drop(state);
+        thread::sleep(POLL);
+    }
+    logs.info("stopped", &[("games_stopped", stopped.to_string())], Some(format!("Stopped, {} games decided on their positions", stopped)));
+}
+
+/// Options for `ur lobby [--host ADDR] [--port N] [--board NAME] [--dice NAME] [--max-games N] [--max-clients N]
+/// [--log FILE] [--metrics-port N] [--access FILE]`, and for `ur serve`, which also takes `[--drain SECS]`
+pub struct LobbyOptions {
+    pub host: String,
+    pub port: u16,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    pub max_games: usize,
+    /// Clients connected at once; more are turned away
+    pub max_clients: usize,
+    pub log: PathBuf,
+    /// Where `/metrics` is served, if it is
+    pub metrics_port: Option<u16>,
+    /// Run as `ur serve`: JSON logs, and a drain on interrupt
+    pub headless: bool,
+    /// Time the games in progress have to finish when a headless lobby stops
+    pub drain: Duration,
+    /// Who may connect and how fast they may go; open to all, at the default limits, without `--access`
+    pub access: Access,
+}
+
+impl LobbyOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        Self::parse(args, false)
+    }
+
+    /// Options for `ur serve`: the `UR_*` environment variables, overridden by any flags
+    pub fn headless_from_args(args: &[String]) -> Result<Self, String> {
+        let mut all = Vec::new();
+        for (variable, flag) in ENVIRONMENT {
+            if let Ok(value) = env::var(variable) {
+                all.push(flag.to_string());
+                all.push(value);
+            }
+        }
+        all.extend_from_slice(args);
+        Self::parse(&all, true)
+    }
+
+    fn parse(args: &[String], headless: bool) -> Result<Self, String> {
+        let mut value_flags = vec!["--host", "--port", "--board", "--dice", "--max-games", "--max-clients", "--log", "--metrics-port", "--access"];
+        if headless {
+            value_flags.push("--drain");
+        }
+        let flags = Flags::parse(args, &value_flags, &[])?;
+        let max_games = flags.get("--max-games", 64)?;
+        if max_games == 0 {
+            return Err("--max-games must be at least 1".to_string());
+        }
+        let max_clients = flags.get("--max-clients", DEFAULT_MAX_CLIENTS)?;
+        if max_clients < 2 {
+            return Err("--max-clients must be at least 2".to_string());
+        }
+        // Headless, the lobby is usually behind a container's port mapping,
+        // so it listens on every interface
+        let host = if headless { "0.0.0.0" } else { "127.0.0.1" };
+        Ok(LobbyOptions {
+            host: flags.get_str("--host").unwrap_or(host).to_string(),
+            port: flags.get("--port", DEFAULT_LOBBY_PORT)?,
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            max_games,
+            max_clients,
+            log: PathBuf::from(flags.get_str("--log").unwrap_or(DEFAULT_LOBBY_LOG)),
+            metrics_port: flags.get_opt("--metrics-port")?,
+            headless,
+            drain: Duration::from_secs_f64(flags.get("--drain", 5.0f64)?.max(0.0)),
+            access: match flags.get_str("--access") {
+                Some(path) => access::load(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?,
+                None => Access::default(),
+            },
+        })
+    }
+}
+
+/// Accept clients until the process is stopped; headless, an interrupt stops
+/// it gracefully
+pub fn run_lobby(options: &LobbyOptions) -> io::Result<()> {
+    board::select(options.board);
+    dice::select(options.dice);
+    let logs = if options.headless { Logs::Json } else { Logs::Console };
+    let lobby: Shared = Arc::new(Mutex::new(Lobby {
+        clients: HashMap::new(),
+        away: HashMap::new(),
+        seeks: Vec::new(),
+        games: HashMap::new(),
+        next_id: 0,
+        max_games: options.max_games,
+        log: options.log.clone(),
+        metrics: Arc::new(Metrics::new()),
+        logs,
+        access: options.access.clone(),
+        draining: false,
+        stop: CancelToken::new(),
+    }));
+    let metrics_address = match options.metrics_port {
+        Some(port) => {
+            let listener = TcpListener::bind((options.host.as_str(), port))?;
+            let address = listener.local_addr()?;
+            let shared = Arc::clone(&lobby);
+            let gauges = move || {
+                let lobby = shared.lock().unwrap();
+                Gauges { games: lobby.games.len(), clients: lobby.clients.len(), seeks: lobby.seeks.len() }
+            };
+            metrics::serve(listener, gauges, Arc::clone(&lobby.lock().unwrap().metrics))?;
+            Some(address)
+        }
+        None => None,
+    };
+    let listener = TcpListener::bind((options.host.as_str(), options.port))?;
+    listener.set_nonblocking(true)?;
+    let address = listener.local_addr()?;
+    let mut text = format!("=== UR LOBBY on {} ===\n{} board, {} dice | up to {} games and {} clients at once | violations logged to {}",
+                           address, board::variant().label(), dice::variant().label(), options.max_games, options.max_clients,
+                           Path::new(&options.log).display());
+    if let Some(address) = metrics_address {
+        text.push_str(&format!("\nMetrics at http://{}/metrics", address));
+    }
+    if options.access.members() > 0 || options.access.allowlist {
+        text.push_str(&format!("\n{} listed clients{}", options.access.members(), if options.access.allowlist { ", and only they may play" } else { "" }));
+    }
+    text.push('\n');
+    logs.info("listening", &[
+        ("address", json_string(&address.to_string())),
+        ("board", json_string(board::variant().label())),
+        ("dice", json_string(dice::variant().label())),
+        ("max_games", options.max_games.to_string()),
+        ("max_clients", options.max_clients.to_string()),
+        ("metrics", metrics_address.map_or("null".to_string(), |address| json_string(&address.to_string()))),
+        ("listed_clients", options.access.members().to_string()),
+        ("allowlist", options.access.allowlist.to_string()),
+    ], Some(text));
+
+    let interrupted = CancelToken::new();
+    if options.headless {
+        let interrupted = interrupted.clone();
+        terminal::on_interrupt(move || interrupted.cancel());
+    }
+    while !interrupted.is_cancelled() {
+        let (stream, peer) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(POLL);
+                continue;
+            }
+            Err(e) => {
+                logs.warn("accept_failed", &[("error", json_string(&e.to_string()))], format!("Could not accept a client: {}", e));
+                continue;
+            }
+        };
+        if lobby.lock().unwrap().clients.len() >= options.max_clients {
+            logs.warn("client_refused", &[("peer", json_string(&peer.to_string()))],
+                      format!("Turned away {}: {} clients connected", peer, options.max_clients));
+            let _ = stream.set_nonblocking(false);
+            let _ = stream.set_write_timeout(Some(REFUSAL_TIMEOUT));
+            let _ = writeln!(&stream, r#"{{"type":"error","message":"the lobby is full; try again later"}}"#);
+            let _ = stream.shutdown(Shutdown::Both);
+            continue;
+        }
+        // Replies are small and each waits on the last, so send them at once
+        let _ = stream.set_nodelay(true);
+        let id = lobby.lock().unwrap().next_id();
+        let writer: Box<dyn Write + Send> = match stream.set_nonblocking(false).and_then(|_| Outbox::open(&stream, id)) {
+            Ok(outbox) => Box::new(outbox),
+            Err(e) => {
+                logs.warn("client_failed", &[("error", json_string(&e.to_string()))], format!("Could not set up a client: {}", e));
+                continue;
+            }
+        };
+        {
+            let mut lobby = lobby.lock().unwrap();
+            let client = Client::new(format!("guest-{}", id), Arc::new(Mutex::new(writer)), lobby.access.limits);
+            lobby.clients.insert(id, client);
+            lobby.welcome(id);
+        }
+        logs.info("client_connected", &[("client", id.to_string()), ("peer", json_string(&peer.to_string()))], None);
+        let shared = Arc::clone(&lobby);
+        if let Err(e) = thread::Builder::new().name(format!("lobby client {}", id)).spawn(move || serve(&shared, id, stream)) {
+            logs.warn("client_failed", &[("error", json_string(&e.to_string()))], format!("Could not start a thread for a client: {}", e));
+            lobby.lock().unwrap().leave(id);
+        }
+    }
+    shut_down(&lobby, options.drain);
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Luck and skill: how much each roll and each move changed a player's
+//! chances. A roll's luck is how much better the best move it allows leaves
+//! the roller than an average roll would have; a move's skill is what it gave
+//! up against that best move, so it is zero for the best move and negative
+//! otherwise. Over a game a player's luck and skill add up to roughly the win
+//! probability their own turns gained them.
+//!
+//! Positions are rated by a race model rather than a search, so a whole stats
+//! run can be measured: each side's pips still to travel are turned into the
+//! turns it needs at its dice's average roll, and the gap between the sides,
+//! against the spread of that many rolls, gives a win probability. It knows
+//! nothing of rosettes or threats beyond what a capture does to the race, so
+//! moves a deeper search prefers for safety can show as small skill losses.
+
+use crate::archive::Turn;
+use crate::dice;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::GameStateRepr;
+
+/// Spread of the race per square root of the turns left in it, in turns;
+/// this and the tempo fit the outcomes of Smart AI self-play best
+const RACE_SPREAD: f64 = 0.7;
+/// Turns the side to move is ahead by having the next roll
+const TEMPO: f64 = 0.5;
+
+/// Average roll of `player`'s dice
+fn average_roll(player: FastPlayer) -> f64 {
+    let model = dice::model_for(player);
+    (0..=model.max_roll()).map(|roll| roll as f64 * model.probability(roll)).sum()
+}
+
+/// Squares `player`'s pieces still have to travel to get home
+fn pips_left(game: &FastGameState, player: FastPlayer) -> u8 {
+    (0..7).map(|idx| FastGameState::finished_pos() - game.get_piece_pos(player, idx)).sum()
+}
+
+/// Estimated chance that `player` wins from `game`
+pub fn win_probability(game: &FastGameState, player: FastPlayer) -> f64 {
+    if let Some(winner) = game.status().winner() {
+        return if winner == player { 1.0 } else { 0.0 };
+    }
+    let turns = |side: FastPlayer| pips_left(game, side) as f64 / average_roll(side);
+    let (ours, theirs) = (turns(player), turns(player.opposite()));
+    let tempo = if game.current_player() == player { TEMPO } else { -TEMPO };
+    let lead = (theirs - ours + tempo) / (RACE_SPREAD * (ours + theirs).sqrt());
+    1.0 / (1.0 + (-lead).exp())
+}
+
+/// The mover's chances after their best move with `roll`, or after losing
+/// the turn if they have none
+fn best_after(game: &FastGameState, roll: u8) -> f64 {
+    let player = game.current_player();
+    let moves = if roll == 0 { Default::default() } else { game.generate_move_list(roll) };
+    if moves.is_empty() {
+        let mut passed = *game;
+        passed.pass_turn();
+        return win_probability(&passed, player);
+    }
+    moves.iter().map(|&piece| {
+        let mut next = *game;
+        next.make_move(piece, roll).expect("generated moves are legal");
+        win_probability(&next, player)
+    }).fold(0.0, f64::max)
+}
+
+/// The mover's chances before rolling, if they then play the best move
+fn before_roll(game: &FastGameState) -> f64 {
+    let model = dice::model_for(game.current_player());
+    (0..=model.max_roll())
+        .map(|roll| (roll, model.probability(roll)))
+        .filter(|&(_, chance)| chance > 0.0)
+        .map(|(roll, chance)| chance * best_after(game, roll))
+        .sum()
+}
+
+/// Luck and skill so far in one game, indexed by seat, as win probability
+/// (1.0 is a whole game's worth)
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ledger {
+    pub luck: [f64; 2],
+    pub skill: [f64; 2],
+}
+
+impl Ledger {
+    /// Credit `roll` to the player to move in `game`
+    pub fn roll(&mut self, game: &FastGameState, roll: u8) {
+        self.luck[game.current_player() as usize] += best_after(game, roll) - before_roll(game);
+    }
+
+    /// Credit moving `piece` with `roll` from `game` to the player to move
+    pub fn moved(&mut self, game: &FastGameState, piece: u8, roll: u8) {
+        let player = game.current_player();
+        let mut next = *game;
+        next.make_move(piece, roll).expect("only legal moves are credited");
+        self.skill[player as usize] += win_probability(&next, player) - best_after(game, roll);
+    }
+}
+
+/// Luck and skill over a game's turns from `start`; the turns must be legal
+pub fn assess(start: FastGameState, turns: &[Turn]) -> Ledger {
+    let mut game = start;
+    let mut ledger = Ledger::default();
+    for turn in turns {
+        ledger.roll(&game, turn.roll);
+        match turn.piece {
+            Some(piece) => {
+                ledger.moved(&game, piece, turn.roll);
+                game.make_move(piece, turn.roll).expect("the game's moves were legal");
+            }
+            None => game.pass_turn(),
+        }
+    }
+    ledger
+}
+
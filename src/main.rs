@@ -1,3 +1,5 @@
+#![allow(clippy::upper_case_acronyms)]
+
 // General Rules of the Royal Game of Ur:
 //
 // The Game of Ur is a race game and is probably an ancestor of the tables family of
@@ -46,321 +48,1384 @@
 // the board. Once a player removes all their pieces off the board in this manner,
 // that player wins the game.
 
-use std::io::{self, Write};
-use std::{thread, time::Duration};
-use crossterm::{
-    execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
-};
+use std::path::PathBuf;
+use std::iter;
+use std::sync::mpsc;
+use std::{thread, time::{Duration, Instant}};
+use crossterm::style::Color;
 
+mod access;
+mod adaptive;
+mod analysis;
+mod analysis_cache;
+mod archive;
+mod archive_stats;
 mod ai;
+mod args;
+mod autotune;
+mod bench;
+mod board;
+mod campaign;
+mod cancel;
+mod chat;
+mod daily;
+mod dice;
+#[cfg(feature = "serde")]
+mod encoding;
+mod error;
+mod events;
+mod fair_dice;
 mod optimized_game;
+mod openings;
+mod opponents;
+mod position_code;
+mod presets;
+mod profile;
+mod rating;
+mod reachability;
+mod resources;
+mod replay;
+mod rng;
+#[cfg(test)]
+mod rules_corpus;
 mod ai_helpers;
+mod coach;
+mod compare;
+mod demo;
+mod difficulty;
 mod display;
+mod expectimax;
+mod experiments;
+mod fast_stats;
+mod frontend;
+mod game_record;
+mod history;
+mod house_rules;
+mod input;
+mod json;
+mod layout_file;
+mod leaderboard;
+mod lobby;
+mod luck;
+mod metrics;
+mod miner;
+mod referee;
+mod save;
+mod scripting;
+mod screen;
+mod session;
+mod setup;
+mod square_stats;
+mod state_repr;
 mod stats;
+mod style;
+mod subprocess_bot;
+mod symmetry;
+mod terminal;
+mod tile_notation;
+mod toml_file;
+mod transcript;
+mod turn_clock;
+mod workers;
 
-use optimized_game::{FastGameState, FastPlayer};
-use ai::HybridAI;
+use optimized_game::{EndReason, FastGameState, FastPlayer, GameStatus};
+use opponents::Opponent;
+use adaptive::AdaptiveOpponent;
+use analysis::{run_analyze_db, AnalyzeOptions};
+use archive::{run_selfplay, SelfPlayOptions};
+use archive_stats::{run_archive_stats, ArchiveStatsOptions};
+use ai::{HybridAI, SearchInfo};
+use analysis::{rate_game, Accuracy};
 use ai_helpers::{choose_random_move_fast, choose_smart_move_fast};
-use display::{clear_screen, display_board, print_piece_positions, print_score, global_to_coord, show_winner};
-use stats::run_statistics_menu;
+use args::Flags;
+use bench::{run_bench, BenchOptions};
+use board::BoardVariant;
+use campaign::run_campaign;
+use cancel::CancelToken;
+use chat::{Chat, Heard, Said};
+use daily::run_daily;
+use dice::DiceVariant;
+use fair_dice::{run_verify_dice, VerifyDiceOptions};
+use coach::{explain_candidates, explain_weak_move};
+use demo::{run_demo, DemoOptions};
+use difficulty::Difficulty;
+use expectimax::choose_expectimax_move;
+use game_record::GameRecord;
+use history::GameHistory;
+use house_rules::{run_house_rules, HouseRuleOptions};
+use replay::replay_game;
+use rng::{GameRng, RngAlgorithm};
+use presets::{Budget, Preset, PLAY_PRESET};
+use profile::{calibrated_rating, record_rated_game, run_profile, ProfileOptions, DEFAULT_PROFILE_FILE};
+use rating::Glicko;
+use reachability::{run_reachability, ReachabilityOptions};
+use referee::{run_match, Clock, MatchOptions};
+use experiments::{run_ablation, run_exploration_sweep, run_scaling_study, run_variant_study, AblationOptions, ScalingOptions, SweepOptions, VariantStudyOptions};
+use leaderboard::{config_name, record_match, run_leaderboard, LeaderboardOptions};
+use lobby::{run_lobby, LobbyOptions};
+use luck::Ledger;
+use miner::{run_miner, MinerOptions};
+use input::{prompt_in_range, prompt_line, prompt_yes_no, Command, Edit, InputError, InputResult, LineEditor, Response, Slash};
+use events::{Events, GameEvent};
+use frontend::{Announcement, Frontend, Plain, Tui, TurnView};
+use save::{load_game, save_game, SavedGame, DEFAULT_SAVE_FILE};
+use session::{GameSummary, GameTally, Session};
+use transcript::outln;
+use display::{cycle_route, move_squares, render_rules, rules_summary, toggle_path_labels, global_to_coord, show_winner};
+use screen::{Align, Border, Panel, Screen, Span, Table};
+use state_repr::GameStateRepr;
+use stats::{run_statistics_menu, StatsAgent, TunedAI};
+use style::Styles;
+use subprocess_bot::BotChat;
+use terminal::install_restore_handlers;
+use tile_notation::{run_import, ImportOptions};
+use turn_clock::{TimeoutAction, TurnClock};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 enum AIType {
     Human,
     Random,
     Smart,
+    /// The Smart AI's evaluation looked at a few plies deep over the dice
+    Expectimax,
     MCTS,
+    /// MCTS that tunes its strength to the human over a session
+    Adaptive,
+    /// The Rhai script loaded with --script
+    Script,
+    /// A bot program seated with `subprocess_bot::seat`, by its index there
+    External(usize),
+    /// One of the named opponents
+    Opponent(&'static Opponent),
+    /// An AI with its own settings, from a player spec
+    Tuned(TunedAI),
+}
+
+impl AIType {
+    /// Short label used in save files
+    fn label(&self) -> &str {
+        match self {
+            AIType::Human => "human",
+            AIType::Random => "random",
+            AIType::Smart => "smart",
+            AIType::Expectimax => "expectimax",
+            AIType::MCTS => "mcts",
+            AIType::Adaptive => "adaptive",
+            AIType::Script => "script",
+            AIType::External(_) => "external",
+            AIType::Opponent(opponent) => opponent.key,
+            AIType::Tuned(tuned) => tuned.label(),
+        }
+    }
+
+    fn from_label(label: &str) -> Option<AIType> {
+        match label {
+            "human" => Some(AIType::Human),
+            "random" => Some(AIType::Random),
+            "smart" => Some(AIType::Smart),
+            "expectimax" => Some(AIType::Expectimax),
+            "mcts" => Some(AIType::MCTS),
+            "adaptive" => Some(AIType::Adaptive),
+            // A saved script player can only resume with a script loaded
+            "script" if scripting::loaded().is_some() => Some(AIType::Script),
+            _ => match opponents::find(label) {
+                Some(opponent) => Some(AIType::Opponent(opponent)),
+                None => StatsAgent::from_spec(label, autotune::threads()).ok().map(|agent| AIType::Tuned(TunedAI::new(agent))),
+            },
+        }
+    }
+
+    fn display_name(&self) -> &str {
+        match self {
+            AIType::Human => "Human",
+            AIType::Random => "Random AI",
+            AIType::Smart => "Smart AI",
+            AIType::Expectimax => "Expectimax AI",
+            AIType::MCTS => "MCTS AI",
+            AIType::Adaptive => "Adaptive AI",
+            AIType::Script => "Script AI",
+            AIType::External(_) => "External bot",
+            AIType::Opponent(opponent) => opponent.name,
+            AIType::Tuned(tuned) => tuned.label(),
+        }
+    }
+
+    /// Pick a move for a bot player; `moves` must be non-empty
+    fn choose_move(&self, game: &FastGameState, moves: &[u8], roll: u8, mcts_ai: &HybridAI) -> u8 {
+        self.choose_move_with_info(game, moves, roll, mcts_ai).0
+    }
+
+    /// Like `choose_move`, also reporting the search effort behind the move
+    fn choose_move_with_info(&self, game: &FastGameState, moves: &[u8], roll: u8, mcts_ai: &HybridAI) -> (u8, SearchInfo) {
+        self.choose_move_cancellable(game, moves, roll, mcts_ai, &CancelToken::new())
+    }
+
+    /// Like `choose_move_with_info`; cancelling `cancel` ends an MCTS search early with its best move so far
+    fn choose_move_cancellable(&self, game: &FastGameState, moves: &[u8], roll: u8, mcts_ai: &HybridAI, cancel: &CancelToken) -> (u8, SearchInfo) {
+        let start = Instant::now();
+        match self {
+            AIType::Random => (choose_random_move_fast(moves), SearchInfo::new(0, start.elapsed())),
+            AIType::Smart => {
+                let piece_idx = choose_smart_move_fast(game, game.current_player(), moves, roll);
+                (piece_idx, SearchInfo::new(moves.len(), start.elapsed()))
+            }
+            AIType::Expectimax => {
+                let (piece_idx, evaluations) = choose_expectimax_move(game, game.current_player(), moves, roll, expectimax::DEFAULT_PLIES);
+                (piece_idx, SearchInfo::new(evaluations, start.elapsed()))
+            }
+            AIType::MCTS | AIType::Adaptive => {
+                if let Some(result) = mcts_ai.choose_move_cancellable(game, game.current_player(), roll, cancel) {
+                    result
+                } else {
+                    (choose_random_move_fast(moves), SearchInfo::new(0, start.elapsed()))
+                }
+            }
+            AIType::Script => {
+                let script = scripting::loaded().expect("script players need a loaded script");
+                (script.choose_move(game, moves, roll), SearchInfo::new(0, start.elapsed()))
+            }
+            AIType::Opponent(opponent) => opponent.choose_move_cancellable(game, moves, roll, cancel),
+            AIType::Tuned(tuned) => tuned.agent().choose_move_cancellable(game, moves, roll, cancel),
+            AIType::External(idx) => match subprocess_bot::seated_move(*idx, game, moves, roll, EXTERNAL_MOVE_TIME) {
+                Ok(piece) => (piece, SearchInfo::new(0, start.elapsed())),
+                Err(violation) => {
+                    outln!("\n⚠️  {}: {}; the smart AI moves instead", subprocess_bot::seated_label(*idx), violation);
+                    (choose_smart_move_fast(game, game.current_player(), moves, roll), SearchInfo::new(moves.len(), start.elapsed()))
+                }
+            },
+            AIType::Human => unreachable!(),
+        }
+    }
+}
+
+/// Options for the interactive game
+#[derive(Debug, Clone, Default)]
+struct PlayOptions {
+    /// Print time and nodes for every AI move
+    verbose: bool,
+    /// Explain clearly inferior human moves
+    coach: bool,
+    /// List the top MCTS candidates behind every AI move
+    explain: bool,
+    /// Print the MCTS work counters behind every AI move
+    ai_profile: bool,
+    /// Board layout to play on, if chosen on the command line instead of in setup
+    board: Option<BoardVariant>,
+    /// Dice to roll, if chosen on the command line instead of in setup
+    dice: Option<DiceVariant>,
+    /// Chance each player's binary dice land pip side up, if either is biased
+    pip_chances: Option<[f64; 2]>,
+    /// Directory to write a record of every finished game to
+    record: Option<PathBuf>,
+    /// File to write a plain-text transcript of the session to
+    transcript: Option<PathBuf>,
+    /// Position code to start games from instead of the opening position
+    position: Option<String>,
+    /// MCTS preset for the computer opponent instead of asking
+    preset: Option<String>,
+    /// Rhai script to offer as an AI
+    script: Option<PathBuf>,
+    /// Time limit for each human turn
+    clock: Option<TurnClock>,
+    /// Tournament conditions: no hints, undo or AI thinking readout, and
+    /// records marked as played without assistance
+    tournament: bool,
+    /// Rate human-vs-AI games in the player's profile; implies tournament conditions
+    rated: bool,
+    /// Profile file for rated games, if not the default
+    profile: Option<PathBuf>,
+    /// Player colors, piece glyphs and board orientation
+    styles: Styles,
+    /// Generator behind the dice, if not the default
+    rng: Option<RngAlgorithm>,
+}
+
+impl PlayOptions {
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(
+            args,
+            &["--board", "--dice", "--record", "--position", "--preset", "--script", "--move-time", "--on-timeout",
+              "--p1-color", "--p2-color", "--pieces", "--view", "--p1-pip-chance", "--p2-pip-chance",
+              "--transcript", "--profile", "--rng"],
+            &["--verbose", "--coach", "--explain", "--ai-profile", "--tournament", "--rated"],
+        )?;
+        let on_timeout = flags.get("--on-timeout", TimeoutAction::default())?;
+        let dice: Option<DiceVariant> = flags.get_opt("--dice")?;
+        let pip_chances = dice::pip_chances_from_flags(&flags, dice)?;
+        if flags.get_str("--on-timeout").is_some() && flags.get_str("--move-time").is_none() {
+            return Err("--on-timeout needs --move-time".to_string());
+        }
+        if flags.get_str("--profile").is_some() && !flags.has("--rated") {
+            return Err("--profile needs --rated".to_string());
+        }
+        if let Some(conditions) = ["--tournament", "--rated"].into_iter().find(|flag| flags.has(flag))
+            && let Some(aid) = ["--verbose", "--coach", "--explain", "--ai-profile"].into_iter().find(|aid| flags.has(aid)) {
+            return Err(format!("{} cannot be used with {}, which turns assistance off", aid, conditions));
+        }
+        Ok(PlayOptions {
+            verbose: flags.has("--verbose"),
+            coach: flags.has("--coach"),
+            explain: flags.has("--explain"),
+            ai_profile: flags.has("--ai-profile"),
+            board: board::from_flags(&flags)?,
+            dice,
+            pip_chances,
+            record: flags.get_str("--record").map(PathBuf::from),
+            transcript: flags.get_str("--transcript").map(PathBuf::from),
+            position: flags.get_str("--position").map(str::to_string),
+            preset: flags.get_str("--preset").map(str::to_string),
+            script: flags.get_str("--script").map(PathBuf::from),
+            clock: flags.get_opt("--move-time")?.map(|secs| TurnClock::from_seconds(secs, on_timeout)).transpose()?,
+            styles: Styles::from_flags(&flags)?,
+            tournament: flags.has("--tournament") || flags.has("--rated"),
+            rated: flags.has("--rated"),
+            profile: flags.get_str("--profile").map(PathBuf::from),
+            rng: rng::from_flags(&flags)?,
+        })
+    }
+}
+
+/// What a human decided to do at a prompt
+enum HumanAction {
+    Undo,
+    Quit,
+    Resign,
 }
 
 
 fn main() {
-    println!("=== Royal Game of Ur (Optimized Edition) ===\n");
-    println!("Rules Summary:");
-    println!("- Two players (Player 1 = top row, Player 2 = bottom row).");
-    println!("- Each has 7 pieces off‐board initially.");
-    println!("- Roll 4 binary dice => move 0..4 steps; '0' = pass turn.");
-    println!("- Each piece travels a 14‐square path; exact roll to exit.");
-    println!("- Capture by landing on opponent on a non‐rosette shared square.");
-    println!("- Safe squares (5 total) protect from capture; rosettes (3 of them) give extra rolls.");
-    println!();
-
-    println!("Choose game mode:");
-    println!("  0: Watch two smart AI bots play against each other");
-    println!("  1: Play against smart AI (you are Player 1)");
-    println!("  2: Two human players");
-    println!("  3: Watch random AI vs smart AI");
-    println!("  4: Statistics - Run multiple games and show results");
-    println!("  5: Play against MCTS AI (you are Player 1)");
-    println!("  6: Watch MCTS AI vs Smart AI");
-    println!("  7: Watch two MCTS AI bots play against each other");
-    print!("Enter choice [0-7]: ");
-    io::stdout().flush().unwrap();
-
-    let mut buf = String::new();
-    io::stdin().read_line(&mut buf).unwrap();
-    let choice: usize = buf.trim().parse().unwrap_or(1);
-
-    println!();
-
-    // Handle statistics mode separately
-    if choice == 4 {
-        run_statistics_menu();
-        return;
+    install_restore_handlers();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run_interactive(PlayOptions::default()),
+        Some(flag) if flag.starts_with("--") => match PlayOptions::from_args(&args) {
+            Ok(options) => run_interactive(options),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("demo") => match DemoOptions::from_args(&args[1..]) {
+            Ok(options) => run_demo(&options),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("scaling") => match ScalingOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_scaling_study(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("sweep") => match SweepOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_exploration_sweep(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("ablation") => match AblationOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_ablation(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("variants") => match VariantStudyOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_variant_study(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("house-rules") => match HouseRuleOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_house_rules(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("selfplay") => match SelfPlayOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_selfplay(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("disagree") => match MinerOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_miner(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("archive-stats") => match ArchiveStatsOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_archive_stats(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("reachability") => match ReachabilityOptions::from_args(&args[1..]) {
+            Ok(options) => run_reachability(&options),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("analyze-db") => match AnalyzeOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_analyze_db(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        #[cfg(feature = "serde")]
+        Some("export") => match encoding::ExportOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(encoding::run_export(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        #[cfg(not(feature = "serde"))]
+        Some("export") => exit_with_usage("export needs the serde feature"),
+        Some("import") => match ImportOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_import(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("leaderboard") => match LeaderboardOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_leaderboard(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("profile") => match ProfileOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_profile(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("match") => match MatchOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_match(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("lobby") => match LobbyOptions::from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_lobby(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("serve") => match LobbyOptions::headless_from_args(&args[1..]) {
+            Ok(options) => exit_on_error(run_lobby(&options)),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("verify-dice") => match VerifyDiceOptions::from_args(&args[1..]) {
+            Ok(options) => {
+                if !exit_on_error(run_verify_dice(&options)) {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => exit_with_usage(&e),
+        },
+        Some("bench") => match BenchOptions::from_args(&args[1..]) {
+            Ok(options) => run_bench(&options),
+            Err(e) => exit_with_usage(&e),
+        },
+        Some(other) => exit_with_usage(&format!("unknown command '{}'", other)),
     }
+}
 
-    // Configure threading for MCTS
-    let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
-    println!("System has {} logical cores available", num_cpus);
+/// The command's result, or its error printed and the process ended with status 1
+fn exit_on_error<T, E: std::fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1)
+    })
+}
 
-    let use_threads = if choice == 0 || choice == 5 || choice == 6 || choice == 7 {
-        // For AI vs AI or human vs MCTS, ask about threading
-        print!("Use multithreaded MCTS? [Y/n]: ");
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        !input.trim().to_lowercase().starts_with('n')
-    } else {
-        true // Default to using threads
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("Error: {}", error);
+    eprintln!();
+    eprintln!("Usage:");
+    eprintln!("  ur [--verbose] [--coach] [--explain] [--ai-profile] [--board NAME]");
+    eprintln!("     [--dice tetrahedra|binary3|d4|knucklebones] [--record DIR] [--position UR:CODE]");
+    eprintln!("     [--preset NAME] [--script FILE] [--move-time SECS [--on-timeout move|forfeit]]");
+    eprintln!("     [--p1-color COLOR] [--p2-color COLOR] [--pieces dots|shapes|numbers] [--view p1|p2|auto]");
+    eprintln!("     [--p1-pip-chance P] [--p2-pip-chance P] [--transcript FILE] [--tournament]");
+    eprintln!("     [--rated [--profile FILE]] [--rng xoshiro|pcg|chacha|os]");
+    eprintln!("                                      menu and game setup; --verbose shows AI search stats,");
+    eprintln!("                                      --coach explains weak moves, --explain lists the top");
+    eprintln!("                                      MCTS candidates, --ai-profile prints MCTS work counters,");
+    eprintln!("                                      --board and --dice fix the variant instead of asking");
+    eprintln!("                                      (--board also takes a layout file, FILE.toml),");
+    eprintln!("                                      --record saves every finished game to DIR,");
+    eprintln!("                                      --position starts games from a shared position code,");
+    eprintln!("                                      --preset picks the MCTS preset,");
+    eprintln!("                                      --script adds a Rhai script AI (needs --features scripting),");
+    eprintln!("                                      --move-time limits each human turn,");
+    eprintln!("                                      --p1/--p2-pip-chance bias each side's binary dice,");
+    eprintln!("                                      --transcript writes what the game shows to FILE,");
+    eprintln!("                                      --tournament turns hints, undo and AI readouts off,");
+    eprintln!("                                      --rated also rates games against calibrated AIs,");
+    eprintln!("                                      --rng picks the dice generator,");
+    eprintln!("                                      --p1-color, --p2-color, --pieces and --view set");
+    eprintln!("                                      how the players look (colors: blue, red, green, yellow,");
+    eprintln!("                                      purple, white)");
+    eprintln!("  ur demo [--games N] [--delay MS] [--board NAME] [--dice NAME] [--record DIR]");
+    eprintln!("          [--preset NAME] [--script FILE] [--p1 SPEC] [--p2 SPEC] [--p1-color COLOR]");
+    eprintln!("          [--p2-color COLOR] [--pieces dots|shapes|numbers] [--view p1|p2|auto]");
+    eprintln!("          [--compare [--threshold PTS]]");
+    eprintln!("                                      AI-vs-AI attract mode; --p1 and --p2 play one");
+    eprintln!("                                      matchup of player specs as in `ur match`;");
+    eprintln!("                                      --compare shows both engines' evaluations each");
+    eprintln!("                                      move, flagging gaps of PTS points (default 5)");
+    eprintln!("  ur scaling [--games N] [--max-sims N] [--threads N] [--csv FILE]");
+    eprintln!("                                      MCTS strength vs simulation budget");
+    eprintln!("  ur sweep [--games N] [--sims N] [--min C] [--max C] [--steps N] [--threads N] [--csv FILE]");
+    eprintln!("                                      UCB1 exploration constant sweep");
+    eprintln!("  ur ablation [--games N] [--vulnerability W] [--csv FILE]");
+    eprintln!("                                      Smart AI evaluation feature ablation");
+    eprintln!("  ur variants [--games N] [--threads N] [--csv FILE]");
+    eprintln!("                                      how rule variants change first-player edge, length and captures");
+    eprintln!("  ur house-rules [--games N] [--steps N] [--threads N] [--seed N] [--luck-weight W]");
+    eprintln!("                 [--board NAME] [--csv FILE]");
+    eprintln!("                                      search rosettes, safe squares and piece count for fairer,");
+    eprintln!("                                      less luck-driven rules by simulated annealing");
+    eprintln!("  ur reachability [--max-states N] [--max-memory MB] [--board NAME] [--dice NAME] [--mirror]");
+    eprintln!("                                      count the positions reachable from the start;");
+    eprintln!("                                      --mirror counts a position and its mirror image once,");
+    eprintln!("                                      --max-memory stops the search at that much memory");
+    eprintln!("  ur selfplay [--games N] [--p1 SPEC] [--p2 SPEC] [--out FILE] [--threads N] [--seed N]");
+    eprintln!("             [--board NAME] [--dice NAME] [--rng NAME]");
+    eprintln!("                                      play silent games (random, smart, an opponent or a preset)");
+    eprintln!("                                      into a compressed binary game archive");
+    eprintln!("  ur disagree --a SPEC --b SPEC [--games N] [--threshold PTS] [--out FILE] [--threads N]");
+    eprintln!("             [--seed N] [--board NAME] [--dice NAME]");
+    eprintln!("                                      play two player specs against each other and write a");
+    eprintln!("                                      position pack of the rolls they split on by PTS points");
+    eprintln!("                                      or more (default 10)");
+    eprintln!("  ur archive-stats --file FILE        stats-mode statistics for the games in an archive");
+    eprintln!("  ur analyze-db [--dir DIR] [--sims N] [--threads N] [--blunder PCT] [--csv FILE]");
+    eprintln!("                [--board NAME] [--dice NAME] [--preset NAME] [--openings]");
+    eprintln!("                [--cache FILE] [--cache-size N] [--no-cache]");
+    eprintln!("                                      MCTS accuracy report for a directory of recorded games;");
+    eprintln!("                                      --openings explores how the games began instead;");
+    eprintln!("                                      verdicts are cached for the next run");
+    eprintln!("  ur export [--dir DIR] [--out FILE]  a directory of game records as one versioned TOML");
+    eprintln!("                                      document, which analyze-db reads back (needs the serde");
+    eprintln!("                                      feature)");
+    eprintln!("  ur import --file FILE [--dir DIR]   convert a tile-notation game (RoyalUr.net coordinates)");
+    eprintln!("                                      into a game record");
+    eprintln!("  ur leaderboard [--file FILE]        standings of every AI configuration from stats mode and");
+    eprintln!("                                      the experiments");
+    eprintln!("  ur profile [--file FILE]            rating and rating history from rated games");
+    eprintln!("  ur match --p1 SPEC --p2 SPEC [--games N] [--time MS] [--bank SECS] [--seed N] [--log FILE]");
+    eprintln!("           [--board NAME] [--dice NAME] [--p1-pip-chance P] [--p2-pip-chance P]");
+    eprintln!("           [--fair-dice [--dice-log FILE]] [--reconnects N]");
+    eprintln!("                                      refereed match between built-in AIs (random, smart,");
+    eprintln!("                                      scribe, raider, oracle, a preset, or tuned as in");
+    eprintln!("                                      smart-vulnerability200, expectimax-3, mcts-5000-risk+0.50),");
+    eprintln!("                                      .rhai scripts and");
+    eprintln!("                                      external bot commands speaking JSON on stdin/stdout;");
+    eprintln!("                                      violations forfeit and are logged; --fair-dice rolls");
+    eprintln!("                                      from both seats' committed nonces, --reconnects");
+    eprintln!("                                      restarts a bot that drops out and resyncs it");
+    eprintln!("  ur lobby [--host ADDR] [--port N] [--board NAME] [--dice NAME] [--max-games N] [--log FILE]");
+    eprintln!("           [--max-clients N] [--metrics-port N] [--access FILE]");
+    eprintln!("                                      matchmaking server: clients connect over TCP, seek a");
+    eprintln!("                                      time control and are paired and refereed as bots;");
+    eprintln!("                                      --metrics-port serves Prometheus metrics (needs the");
+    eprintln!("                                      server feature); --access sets client tokens, rate");
+    eprintln!("                                      limits and allowlist mode");
+    eprintln!("  ur serve [lobby options] [--drain SECS]");
+    eprintln!("                                      the lobby headless, for containers: settings also from");
+    eprintln!("                                      UR_HOST, UR_PORT, UR_BOARD, UR_DICE, UR_MAX_GAMES,");
+    eprintln!("                                      UR_MAX_CLIENTS, UR_LOG, UR_METRICS_PORT, UR_DRAIN_SECS");
+    eprintln!("                                      and UR_ACCESS, JSON logs;");
+    eprintln!("                                      SIGTERM lets games finish for --drain seconds (default 5)");
+    eprintln!("  ur verify-dice [--file FILE]        check every roll in a --fair-dice log");
+    eprintln!("  ur bench [--sims N] [--threads N] [--streams N] [--ai-profile]");
+    eprintln!("                                      fixed MCTS workload; prints a search signature and");
+    eprintln!("                                      nodes per second; --streams makes the signature the");
+    eprintln!("                                      same for any --threads");
+    std::process::exit(2);
+}
+
+fn run_interactive(options: PlayOptions) {
+    style::select(options.styles);
+    rng::select(options.rng.unwrap_or_default());
+    if let Some(chances) = options.pip_chances {
+        dice::select_pip_chances(chances);
+    }
+    // Codes are board-specific, so they can only be read once the board is chosen
+    let start = match options.position.as_deref() {
+        Some(code) => {
+            board::select(options.board.unwrap_or_default());
+            match position_code::decode(code) {
+                Ok(game) => Some(game),
+                Err(e) => exit_with_usage(&e.to_string()),
+            }
+        }
+        None => None,
     };
+    let preset = match options.preset.as_deref().map(presets::lookup) {
+        Some(Ok(preset)) => Some(preset),
+        Some(Err(e)) => exit_with_usage(&e.to_string()),
+        None => None,
+    };
+    if let Some(Err(e)) = options.script.as_deref().map(scripting::load) {
+        exit_with_usage(&e);
+    }
+    if let Some(Err(e)) = options.transcript.as_deref().map(transcript::start) {
+        exit_with_usage(&format!("could not create transcript: {}", e));
+    }
+    match run(&options, start, preset) {
+        Ok(()) => {}
+        Err(InputError::Closed) => outln!("\nInput closed, exiting."),
+        Err(e) => eprintln!("\nError: {}", e),
+    }
+}
+
+/// Interactive menu, game setup and game loop. `start` is the position from
+/// `--position`, with its board already selected.
+fn run(options: &PlayOptions, start: Option<FastGameState>, preset: Option<&'static Preset>) -> InputResult<()> {
+    outln!("=== Royal Game of Ur (Optimized Edition) ===\n");
+    outln!("Choose what to do:");
+    outln!("  1: New game - choose the players, rules, time control and seed");
+    outln!("  2: Resume a saved game or position code");
+    outln!("  3: Statistics - Run multiple games and show results");
+    outln!("  4: Campaign - climb a ladder of ever stronger computer opponents");
+    outln!("  5: Daily challenge - today's dice, the same for every player");
+    let choice = prompt_in_range("Enter choice [1-5] (default 1): ", 1..=5, 1)?;
+    outln!();
 
-    let num_threads = if use_threads {
-        print!("Number of threads to use [1-{}] (default {}): ", num_cpus * 2, num_cpus);
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        input.trim().parse().unwrap_or(num_cpus).min(num_cpus * 2).max(1)
+    if choice == 5 {
+        return run_daily(options);
+    }
+
+    if choice == 3 || choice == 4 {
+        board::select(options.board.unwrap_or_default());
+        dice::select(options.dice.unwrap_or_default());
+        return if choice == 3 { run_statistics_menu() } else { run_campaign(options) };
+    }
+
+    let resumed = choice == 2;
+    let (players, mut game, seed, options) = if resumed {
+        board::select(options.board.unwrap_or_default());
+        dice::select(options.dice.unwrap_or_default());
+        let (player1_type, player2_type, game) = load_saved_game()?;
+        ([player1_type, player2_type], game, GameRng::random().seed(), options.clone())
     } else {
-        1
+        let setup = setup::choose(options)?;
+        board::select(setup.board);
+        dice::select(setup.dice);
+        let options = PlayOptions { clock: setup.clock, ..options.clone() };
+        (setup.players, start.unwrap_or_else(FastGameState::new), setup.seed, options)
     };
+    let options = &options;
 
-    let (player1_type, player2_type) = match choice {
-        0 => (AIType::Smart, AIType::Smart),      // Two smart AIs
-        1 => (AIType::Human, AIType::Smart),      // Human vs Smart AI
-        2 => (AIType::Human, AIType::Human),      // Two humans
-        3 => (AIType::Random, AIType::Smart),     // Random AI vs Smart AI
-        5 => (AIType::Human, AIType::MCTS),       // Human vs MCTS AI
-        6 => (AIType::MCTS, AIType::Smart),       // MCTS AI vs Smart AI
-        7 => (AIType::MCTS, AIType::MCTS),        // Two MCTS AIs
-        _ => (AIType::Human, AIType::Smart),      // Default: Human vs Smart AI
+    outln!("Rules Summary:");
+    for rule in rules_summary() {
+        outln!("- {}", rule);
+    }
+    if let Some(clock) = options.clock {
+        outln!("- Move clock: {}.", clock);
+    }
+    if options.tournament {
+        outln!("- Tournament conditions: no hints or undo, and the AI's thinking is not shown.");
+    }
+    outln!("- Press ? during a game for a quick reference of the rules and squares.");
+    outln!();
+
+    let has_mcts = players.iter().any(|p| matches!(p, AIType::MCTS | AIType::Adaptive));
+    let has_adaptive = players.iter().any(|p| matches!(p, AIType::Adaptive));
+    // The adaptive AI picks its own budget; other MCTS players come from a preset
+    let preset = match preset {
+        Some(preset) => preset,
+        None if has_mcts && !has_adaptive => presets::choose("MCTS presets:", PLAY_PRESET)?,
+        None => presets::lookup(PLAY_PRESET).expect("built-in preset"),
     };
 
-    // Create MCTS AI instances with explicit threading configuration
-    let mcts_simulations = if use_threads {
-        // More simulations when using multiple threads
-        num_threads * 1000
-    } else {
-        // Fewer simulations for single-threaded
-        2000
+    // MCTS searches on every core but one, unless its preset says how many
+    let num_threads = match preset.threads {
+        Some(threads) if !has_adaptive => threads,
+        _ if !has_mcts => 1,
+        _ => autotune::threads(),
     };
 
-    let mcts_ai = HybridAI::new_with_threads(mcts_simulations, num_threads);
+    let mut adaptive = AdaptiveOpponent::new();
+    let mut mcts_ai = if has_adaptive {
+        adaptive.build_ai(num_threads)
+    } else {
+        preset.build(num_threads)
+    };
 
     // Show AI configuration for MCTS players
-    if matches!(player1_type, AIType::MCTS) || matches!(player2_type, AIType::MCTS) {
-        println!("MCTS AI Configuration: {}", mcts_ai.get_info());
-        println!();
+    if has_mcts {
+        if let Budget::Auto(think) = preset.budget && !has_adaptive {
+            outln!("Automatic setup: {}", autotune::summary(num_threads, think));
+        }
+        outln!("MCTS AI Configuration: {}", mcts_ai.get_info());
+        outln!();
     }
 
-    let mut game = FastGameState::new();
+    let mut players = players;
+    let has_human = players.iter().any(|p| matches!(p, AIType::Human));
+    let mut session = Session::new(participant_names(&players));
+    // Only a human-vs-AI session has a difficulty to adjust
+    let mut difficulty = match &players {
+        [AIType::Human, ai] | [ai, AIType::Human] => Difficulty::for_ai(ai),
+        _ => None,
+    };
+    let mut swapped = false;
+    // Each game's dice follow from the seed, so the whole session can be replayed
+    let mut seeds = GameRng::from_seed(seed);
+    let mut game_seed = seed;
 
     loop {
-        // Check for a winner at the start of the turn
-        let winner = if game.is_winner(FastPlayer::One) {
-            Some(FastPlayer::One)
-        } else if game.is_winner(FastPlayer::Two) {
-            Some(FastPlayer::Two)
+        let rated = if options.rated { rated_opponent(&game, &players, &mcts_ai) } else { None };
+        let Some(summary) = play_game(game, players.clone(), &mcts_ai, options, GameRng::from_seed(game_seed))? else {
+            return Ok(());
+        };
+        record_opponent_game(&players, summary.winner, &mcts_ai);
+        if !has_human {
+            return Ok(());
+        }
+
+        session.record(&summary, swapped, difficulty);
+        outln!();
+        session.display();
+        match (rated, summary.winner) {
+            (Some((opponent, opponent_rating)), Some(winner)) => {
+                let path = options.profile.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_PROFILE_FILE));
+                record_rated_game(&path, &opponent, opponent_rating, players[winner as usize] == AIType::Human);
+            }
+            (Some(_), None) => outln!("  Drawn games are not rated."),
+            (None, _) => {}
+        }
+        if has_adaptive && let Some(winner) = summary.winner {
+            let human_won = players[winner as usize] == AIType::Human;
+            adaptive.record_game(human_won);
+            mcts_ai = adaptive.build_ai(num_threads);
+            outln!("  {}", adaptive.describe());
+        }
+        outln!();
+
+        let rematch = if summary.rematch {
+            outln!("🔁 Rematch agreed: same sides.");
+            Rematch::SameSides
         } else {
-            None
+            choose_rematch(&session, &players, swapped, difficulty)?
         };
+        match rematch {
+            Rematch::Quit => return Ok(()),
+            Rematch::SameSides => {}
+            Rematch::SwapSides => {
+                players.swap(0, 1);
+                swapped = !swapped;
+            }
+            Rematch::Difficulty(level) => {
+                for player in players.iter_mut().filter(|p| !matches!(p, AIType::Human)) {
+                    *player = level.ai_type();
+                }
+                if let Some(scale) = level.mcts_scale() {
+                    mcts_ai = preset.build_scaled(num_threads, scale);
+                }
+                difficulty = Some(level);
+                outln!("Computer difficulty is now {}.", level.name());
+            }
+        }
+        // Rematches of a resumed game start over; those from --position replay it
+        game = match start {
+            Some(start) if !resumed => start,
+            _ => FastGameState::new(),
+        };
+        game_seed = seeds.next_seed();
+    }
+}
+
+/// The configuration name `player` has on the leaderboard ("human" for a
+/// person), or None if it cannot have a standing
+fn standing_label(player: &AIType, mcts_ai: &HybridAI) -> Option<String> {
+    match player {
+        AIType::Human => Some("human".to_string()),
+        AIType::Random => Some("random".to_string()),
+        AIType::Smart => Some("smart".to_string()),
+        AIType::Expectimax => Some("expectimax".to_string()),
+        AIType::MCTS => Some(mcts_ai.config_label()),
+        // Its strength changes from game to game, so it has no standing
+        AIType::Adaptive => None,
+        AIType::Script => scripting::loaded().map(|script| script.label()),
+        AIType::External(idx) => Some(subprocess_bot::seated_label(*idx)),
+        AIType::Tuned(tuned) => Some(tuned.label().to_string()),
+        AIType::Opponent(opponent) => Some(opponent.key.to_string()),
+    }
+}
+
+/// Put a finished game against a named opponent on the leaderboard, with the
+/// other side under its own configuration name; draws are left off
+fn record_opponent_game(players: &[AIType; 2], winner: Option<FastPlayer>, mcts_ai: &HybridAI) {
+    let Some(winner) = winner.filter(|_| players.iter().any(|p| matches!(p, AIType::Opponent(_)))) else {
+        return;
+    };
+    if let [Some(a), Some(b)] = players.each_ref().map(|player| standing_label(player, mcts_ai)) {
+        record_match(&a, &b, 1, usize::from(winner == FastPlayer::One));
+    }
+}
 
-        if let Some(winner_player) = winner {
-            show_winner(winner_player, &game);
-            break;
+/// The AI a rated game from `game` is against, by leaderboard name, and its
+/// calibrated rating; None, saying why, if the game cannot be rated
+fn rated_opponent(game: &FastGameState, players: &[AIType; 2], mcts_ai: &HybridAI) -> Option<(String, Glicko)> {
+    let ai = match players {
+        [AIType::Human, ai] | [ai, AIType::Human] if *ai != AIType::Human => ai,
+        _ => {
+            outln!("⚠️  Only games between a human and an AI are rated; this one is not.");
+            return None;
+        }
+    };
+    if *game != FastGameState::new() {
+        outln!("⚠️  Only games from the opening position are rated; this one is not.");
+        return None;
+    }
+    let Some(label) = standing_label(ai, mcts_ai) else {
+        outln!("⚠️  {} has no fixed strength, so games against it are not rated.", ai.display_name());
+        return None;
+    };
+    match calibrated_rating(&label) {
+        Ok(rating) => {
+            outln!("🏅 Rated game against {} ({:.0} ± {:.0}).", ai.display_name(), rating.rating, rating.deviation);
+            Some((config_name(&label), rating))
+        }
+        Err(e) => {
+            outln!("⚠️  {}; this game is not rated.", e);
+            None
         }
+    }
+}
 
-        clear_screen();
-        display_board(&game);
-        print_piece_positions(&game, game.current_player());
-        print_score(&game);
+/// Session scoreboard names, numbered when both players are the same kind
+fn participant_names(players: &[AIType; 2]) -> [String; 2] {
+    match players {
+        [a, b] if a == b => [1, 2].map(|n| format!("{} {}", a.display_name(), n)),
+        // The AI side may change difficulty between games
+        [AIType::Human, _] => ["Human".to_string(), "Computer".to_string()],
+        [_, AIType::Human] => ["Computer".to_string(), "Human".to_string()],
+        _ => players.each_ref().map(|p| p.display_name().to_string()),
+    }
+}
 
-        // Show whose turn it is with emphasis
-        let current_player = game.current_player();
-        let (player_color, player_symbol) = match current_player {
-            FastPlayer::One => (Color::Blue, "🔵"),
-            FastPlayer::Two => (Color::Red, "🔴"),
-        };
+/// What to do after a game in a session with a human player
+enum Rematch {
+    SameSides,
+    SwapSides,
+    Difficulty(Difficulty),
+    Quit,
+}
 
-        println!("┌─────────────────────────────────────┐");
-        print!("│ ");
-        let _ = execute!(
-            io::stdout(),
-            SetForegroundColor(player_color),
-            Print(format!("⭐ {}'s Turn {} ⭐", current_player.name(), player_symbol)),
-            ResetColor
-        );
-        println!("                │");
-        println!("└─────────────────────────────────────┘");
-        println!();
-
-        // Check if current player is human or bot
-        let current_player_type = match game.current_player() {
-            FastPlayer::One => player1_type,
-            FastPlayer::Two => player2_type,
-        };
-        let current_player_is_human = matches!(current_player_type, AIType::Human);
+/// Offer rematch options, defaulting to a difficulty change after two straight results either way
+fn choose_rematch(
+    session: &Session,
+    players: &[AIType; 2],
+    swapped: bool,
+    difficulty: Option<Difficulty>,
+) -> InputResult<Rematch> {
+    let mut options = vec![
+        ("Rematch with sides swapped".to_string(), Rematch::SwapSides),
+        ("Rematch, same sides".to_string(), Rematch::SameSides),
+    ];
+    let mut suggested = 1;
 
-        // Roll dice
-        if current_player_is_human {
-            print!("⚡ Press ENTER to roll dice... ");
-            io::stdout().flush().unwrap();
-            let _ = io::stdin().read_line(&mut String::new());
-        } else {
-            // Bot turn - pause to show thinking
-            let ai_type_name = match current_player_type {
-                AIType::Random => "🎲 Random AI",
-                AIType::Smart => "🧠 Smart AI",
-                AIType::MCTS => "🤖 MCTS AI",
-                AIType::Human => unreachable!(),
+    if let Some(level) = difficulty {
+        let human_seat = players.iter().position(|p| matches!(p, AIType::Human)).unwrap_or(0);
+        let human = if swapped { 1 - human_seat } else { human_seat };
+        let streak = session.streak(human);
+
+        if let Some(stronger) = level.stronger() {
+            options.push((format!("Rematch with a stronger AI ({} → {})", level.name(), stronger.name()), Rematch::Difficulty(stronger)));
+            if streak >= 2 {
+                suggested = options.len();
+            }
+        }
+        if let Some(weaker) = level.weaker() {
+            options.push((format!("Rematch with a weaker AI ({} → {})", level.name(), weaker.name()), Rematch::Difficulty(weaker)));
+            if streak <= -2 {
+                suggested = options.len();
+            }
+        }
+    }
+
+    outln!("What next?");
+    for (i, (label, _)) in options.iter().enumerate() {
+        outln!("  {}: {}{}", i + 1, label, if i + 1 == suggested { " (suggested)" } else { "" });
+    }
+    outln!("  0: Quit");
+    let choice = prompt_in_range(&format!("Enter choice [0-{}] (default {}): ", options.len(), suggested), 0..=options.len(), suggested)?;
+
+    Ok(match choice {
+        0 => Rematch::Quit,
+        n => options.swap_remove(n - 1).1,
+    })
+}
+
+/// Play one game to the end, rolling `dice`; `None` if a human quit partway through
+fn play_game(
+    game: FastGameState,
+    players: [AIType; 2],
+    mcts_ai: &HybridAI,
+    options: &PlayOptions,
+    dice: GameRng,
+) -> InputResult<Option<GameSummary>> {
+    let bots: Vec<(usize, FastPlayer)> = [FastPlayer::One, FastPlayer::Two].into_iter()
+        .filter_map(|seat| match players[seat as usize] {
+            AIType::External(idx) => Some((idx, seat)),
+            _ => None,
+        })
+        .collect();
+    let mut chats = Vec::new();
+    for &(idx, seat) in &bots {
+        let clock = Clock { per_move: EXTERNAL_MOVE_TIME, bank: None };
+        match subprocess_bot::start_seated(idx, seat, &clock) {
+            Ok(chat) => chats.push((seat, subprocess_bot::seated_label(idx), chat)),
+            Err(violation) => outln!("⚠️  {}: {}; the smart AI will move for it", subprocess_bot::seated_label(idx), violation),
+        }
+    }
+
+    // AI moves are searched on scoped threads so the game keeps handling events meanwhile
+    thread::scope(|scope| {
+        let mut events = Events::new();
+        // Keys read one at a time need the terminal interface to show what is typed
+        let frontend: Box<dyn Frontend> = if events.is_terminal() { Box::new(Tui) } else { Box::new(Plain::default()) };
+        let mut live = LiveGame::new(game, players, mcts_ai, options, dice, frontend, chats);
+        live.start_turn(scope, &mut events)?;
+        loop {
+            if let Phase::Over { status } = live.phase {
+                for &(idx, _) in &bots {
+                    subprocess_bot::end_seated(idx, status);
+                }
+                return live.finish(status);
+            }
+            if let Phase::Prompt { editor, .. } = &mut live.phase {
+                live.frontend.show_prompt(editor)?;
+            }
+            let reading = matches!(live.phase, Phase::Prompt { .. });
+            let event = events.next(reading)?;
+            live.handle(event, scope, &mut events)?;
+        }
+    })
+}
+
+/// Time an external bot has for each move in an interactive game
+const EXTERNAL_MOVE_TIME: Duration = Duration::from_secs(10);
+const ROLL_PROMPT: &str = "⚡ Press ENTER to roll dice (? for help)... ";
+/// Shortest time an AI appears to think, so its moves can be followed
+const MIN_THINKING: Duration = Duration::from_millis(900);
+/// How long an AI's move stays on screen before the next turn
+const AI_MOVE_PAUSE: Duration = Duration::from_millis(1000);
+/// How long a passed turn stays on screen
+const PASS_PAUSE: Duration = Duration::from_millis(1500);
+/// How long a capture stays on screen, whoever made it
+const CAPTURE_PAUSE: Duration = Duration::from_millis(2000);
+
+/// Where the current turn stands
+enum Phase {
+    /// A human is at the roll prompt (`rolled` is None) or choosing a move
+    Prompt { editor: LineEditor, rolled: Option<(u8, Vec<u8>)> },
+    /// An AI's move is being searched on a worker thread; cancelling `cancel` makes it move now
+    Thinking { roll: u8, since: Instant, cancel: CancelToken, found: Option<(u8, SearchInfo)> },
+    /// Leaving the last move on screen until the next turn
+    Pause { until: Instant },
+    /// The game ended, or `status` is None if a human quit
+    Over { status: Option<GameStatus> },
+}
+
+/// An interactive game in progress, advanced one event at a time
+struct LiveGame<'a> {
+    game: FastGameState,
+    players: [AIType; 2],
+    mcts_ai: &'a HybridAI,
+    options: &'a PlayOptions,
+    dice: GameRng,
+    frontend: Box<dyn Frontend>,
+    /// Positions, tallies and history lengths at the start of each human turn that ended in a move, for undo
+    undo_stack: Vec<(FastGameState, GameTally, usize)>,
+    tally: GameTally,
+    history: GameHistory,
+    /// Where the current turn started, pushed on the undo stack if a human moves
+    turn_start: (FastGameState, GameTally, usize),
+    /// When the current human turn runs out of time
+    deadline: Option<Instant>,
+    /// The result when the game ended before the position decided it: a
+    /// human resigned or ran out of time with --on-timeout forfeit
+    ended: Option<GameStatus>,
+    phase: Phase,
+    /// Seated bots' side of the chat, with each bot's seat and name
+    bots: Vec<(FastPlayer, String, BotChat)>,
+    chat: Chat,
+}
+
+impl<'a> LiveGame<'a> {
+    fn new(
+        game: FastGameState,
+        players: [AIType; 2],
+        mcts_ai: &'a HybridAI,
+        options: &'a PlayOptions,
+        dice: GameRng,
+        frontend: Box<dyn Frontend>,
+        bots: Vec<(FastPlayer, String, BotChat)>,
+    ) -> Self {
+        LiveGame {
+            game,
+            players,
+            mcts_ai,
+            options,
+            dice,
+            frontend,
+            undo_stack: Vec::new(),
+            tally: GameTally::default(),
+            history: GameHistory::new(game),
+            turn_start: (game, GameTally::default(), 0),
+            deadline: None,
+            ended: None,
+            phase: Phase::Pause { until: Instant::now() },
+            bots,
+            chat: Chat::new(),
+        }
+    }
+
+    fn current_type(&self) -> &AIType {
+        &self.players[self.game.current_player() as usize]
+    }
+
+    /// Begin the next turn, or end the game if someone has won
+    fn start_turn<'s>(&mut self, scope: &'s thread::Scope<'s, 'a>, events: &mut Events) -> InputResult<()> {
+        let status = self.ended.unwrap_or_else(|| self.game.status());
+        if status.is_over() {
+            self.phase = Phase::Over { status: Some(status) };
+            return Ok(());
+        }
+
+        let view = TurnView { game: &self.game, players: &self.players, captures: self.tally.captures, chat: self.chat.lines() };
+        self.frontend.show_turn(view);
+        self.turn_start = (self.game, self.tally, self.history.len());
+        let player_type = self.current_type().clone();
+        self.deadline = None;
+        if matches!(player_type, AIType::Human) {
+            self.deadline = self.options.clock.map(|clock| clock.deadline());
+            self.phase = Phase::Prompt { editor: LineEditor::new(ROLL_PROMPT, self.deadline), rolled: None };
+        } else if let Some((roll, moves)) = self.roll() {
+            let (game, mcts_ai) = (self.game, self.mcts_ai);
+            let cancel = CancelToken::new();
+            let (sender, search) = mpsc::channel();
+            let worker_cancel = cancel.clone();
+            let search_move = move || {
+                let _ = sender.send(player_type.choose_move_cancellable(&game, &moves, roll, mcts_ai, &worker_cancel));
             };
-            print!("🤔 {} is thinking", ai_type_name);
-            for _ in 0..3 {
-                thread::sleep(Duration::from_millis(300));
-                print!(".");
-                io::stdout().flush().unwrap();
-            }
-            println!();
-        }
-
-        let roll = FastGameState::roll_dice();
-        print!("🎲 Rolled: ");
-        let dice_color = match roll {
-            0 => Color::DarkGrey,
-            1 => Color::White,
-            2 => Color::Yellow,
-            3 => Color::Cyan,
-            4 => Color::Green,
-            _ => Color::White,
-        };
-        let _ = execute!(
-            io::stdout(),
-            SetForegroundColor(dice_color),
-            Print(format!("{}", roll)),
-            ResetColor
-        );
-
-        let dice_visual = match roll {
-            0 => " (no moves)",
-            1 => " 🎯",
-            2 => " 🎯🎯",
-            3 => " 🎯🎯🎯",
-            4 => " 🎯🎯🎯🎯",
-            _ => "",
-        };
-        println!("{}", dice_visual);
-
-        if roll == 0 {
-            let _ = execute!(
-                io::stdout(),
-                SetForegroundColor(Color::DarkGrey),
-                Print("❌ No moves available. Turn passes."),
-                ResetColor
-            );
-            println!("\n");
-            thread::sleep(Duration::from_millis(1500));
-            game.scores_and_turn ^= 1 << 6; // Switch turn manually
-            continue;
-        }
-
-        // Compute valid moves
-        let moves = game.generate_moves(roll);
-        if moves.is_empty() {
-            let _ = execute!(
-                io::stdout(),
-                SetForegroundColor(Color::DarkGrey),
-                Print(format!("❌ No legal moves with roll = {}. Turn passes.", roll)),
-                ResetColor
-            );
-            println!("\n");
-            thread::sleep(Duration::from_millis(1500));
-            game.scores_and_turn ^= 1 << 6; // Switch turn manually
-            continue;
-        }
-
-        let chosen_piece = if current_player_is_human {
-            // Human player chooses
-            println!("Legal moves:");
-            for (idx, &piece_idx) in moves.iter().enumerate() {
-                let pos = game.get_piece_pos(game.current_player(), piece_idx);
-                match pos {
-                    0 => {
-                        let target_square = FastGameState::path_to_global(game.current_player(), 0);
-                        let (r, c) = global_to_coord(target_square);
-                        let extra_info = if FastGameState::is_rosette(target_square) {
-                            ", lands on rosette (extra turn)"
-                        } else if FastGameState::is_safe(target_square) {
-                            ", lands on safe square"
-                        } else {
-                            ""
-                        };
-                        println!("  [{}] Enter piece {} → path 0 (grid ({}, {})){}",
-                                idx, piece_idx, r, c, extra_info);
+            // Without a thread to spare the search runs here, and the game waits for it
+            if let Err(search_move) = workers::spawn_scoped(scope, "the AI's search", search_move) {
+                search_move();
+            }
+            events.await_search(search);
+            let since = Instant::now();
+            if !self.options.tournament {
+                self.frontend.show_thinking(&self.players[self.game.current_player() as usize], since)?;
+            }
+            self.phase = Phase::Thinking { roll, since, cancel, found: None };
+        }
+        Ok(())
+    }
+
+    /// Roll for the player to move. If they cannot move the turn passes, the
+    /// game pauses on the message and None is returned.
+    fn roll(&mut self) -> Option<(u8, Vec<u8>)> {
+        let roll = self.dice.roll_dice(self.game.current_player());
+        self.tally.turns += 1;
+        self.tally.extra_rolls.turn(self.game.current_player());
+        self.tally.luck.roll(&self.game, roll);
+        self.frontend.show_roll(roll);
+
+        let moves = if roll == 0 { Vec::new() } else { self.game.generate_moves(roll) };
+        if !moves.is_empty() {
+            return Some((roll, moves));
+        }
+        self.frontend.announce(Announcement::NoMoves(roll));
+        self.game.pass_turn();
+        self.phase = Phase::Pause { until: Instant::now() + PASS_PAUSE };
+        None
+    }
+
+    fn handle<'s>(&mut self, event: GameEvent, scope: &'s thread::Scope<'s, 'a>, events: &mut Events) -> InputResult<()> {
+        match event {
+            GameEvent::Key(key) => match &mut self.phase {
+                Phase::Prompt { editor, .. } => {
+                    if let Edit::Submitted(line) = editor.key(key)? {
+                        // Ends the prompt's line on screen; the transcript has it from submit
+                        println!();
+                        self.submit(line, scope, events)?;
                     }
-                    1..=14 => {
-                        let path_idx = pos - 1;
-                        let new_path_idx = path_idx + roll;
-                        if new_path_idx >= 14 {
-                            println!("  [{}] Move piece {} → EXIT", idx, piece_idx);
-                        } else {
-                            let target_square = FastGameState::path_to_global(game.current_player(), new_path_idx);
-                            let (r, c) = global_to_coord(target_square);
-                            let extra_info = if FastGameState::is_rosette(target_square) {
-                                ", lands on rosette (extra turn)"
-                            } else if FastGameState::is_safe(target_square) {
-                                ", lands on safe square"
-                            } else {
-                                ""
-                            };
-                            println!("  [{}] Move piece {} → path {} (grid ({}, {})){}",
-                                    idx, piece_idx, new_path_idx, r, c, extra_info);
+                }
+                // Any key asks the AI to move now
+                Phase::Thinking { cancel, .. } => cancel.cancel(),
+                Phase::Pause { .. } | Phase::Over { .. } => {}
+            },
+            GameEvent::Line(line) => self.submit(line, scope, events)?,
+            GameEvent::Resize => self.redraw(),
+            GameEvent::AiMove(piece, info) => {
+                if let Phase::Thinking { found, .. } = &mut self.phase {
+                    *found = Some((piece, info));
+                }
+            }
+            GameEvent::Tick => {
+                self.listen();
+                match &mut self.phase {
+                    Phase::Prompt { editor, .. } if editor.expired() => {
+                        println!();
+                        self.time_out(scope, events)?;
+                    }
+                    Phase::Thinking { roll, since, cancel, found } => {
+                        // A move asked for early is played as soon as it is found
+                        let ready = since.elapsed() >= MIN_THINKING || cancel.is_cancelled();
+                        if let Some((piece, info)) = found.take_if(|_| ready) {
+                            let roll = *roll;
+                            if !self.options.tournament {
+                                self.frontend.show_thought(&self.players[self.game.current_player() as usize], &info)?;
+                            }
+                            self.announce_ai_move(piece, roll, &info);
+                            self.play(piece, roll, Some(AI_MOVE_PAUSE), scope, events)?;
+                        } else if !self.options.tournament {
+                            let since = *since;
+                            self.frontend.show_thinking(&self.players[self.game.current_player() as usize], since)?;
                         }
                     }
+                    Phase::Pause { until } if Instant::now() >= *until => self.start_turn(scope, events)?,
                     _ => {}
                 }
             }
-            print!("Choose move index [0..{}]: ", moves.len() - 1);
-            io::stdout().flush().unwrap();
-            let mut inp = String::new();
-            io::stdin().read_line(&mut inp).unwrap();
-            let choice: usize = inp.trim().parse().unwrap_or(0).min(moves.len() - 1);
-            moves[choice]
-        } else {
-            // Bot player chooses
-            let mv = match current_player_type {
-                AIType::Random => choose_random_move_fast(&moves),
-                AIType::Smart => choose_smart_move_fast(&game, game.current_player(), &moves, roll),
-                AIType::MCTS => {
-                    if let Some(piece_idx) = mcts_ai.choose_move(&game, game.current_player(), roll) {
-                        piece_idx
-                    } else {
-                        choose_random_move_fast(&moves)
+        }
+        Ok(())
+    }
+
+    /// Act on a line entered at the human's prompt
+    fn submit<'s>(&mut self, line: String, scope: &'s thread::Scope<'s, 'a>, events: &mut Events) -> InputResult<()> {
+        let Phase::Prompt { editor, rolled } = &self.phase else {
+            return Ok(());
+        };
+        editor.record(&line);
+        // Without a terminal a line can only be checked against the clock once it arrives
+        if editor.expired() {
+            return self.time_out(scope, events);
+        }
+
+        match (Response::parse(line), rolled.clone()) {
+            (Response::Command(cmd), rolled) => {
+                let rolled = rolled.as_ref().map(|(roll, moves)| (*roll, moves.as_slice()));
+                let can_undo = !self.undo_stack.is_empty();
+                let view = TurnView { game: &self.game, players: &self.players, captures: self.tally.captures, chat: self.chat.lines() };
+                match handle_command(self.frontend.as_mut(), cmd, view, rolled, can_undo, self.options)? {
+                    Some(HumanAction::Undo) => {
+                        if let Some((previous_game, previous_tally, moves_played)) = self.undo_stack.pop() {
+                            (self.game, self.tally) = (previous_game, previous_tally);
+                            self.history.truncate(moves_played);
+                        }
+                        self.start_turn(scope, events)?;
                     }
-                },
-                AIType::Human => unreachable!(),
-            };
+                    Some(HumanAction::Quit) => self.phase = Phase::Over { status: None },
+                    Some(HumanAction::Resign) => {
+                        self.ended = Some(GameStatus::Adjudicated(self.game.current_player().opposite(), EndReason::Resignation));
+                        self.start_turn(scope, events)?;
+                    }
+                    None => self.restart_prompt(),
+                }
+            }
+            (Response::Slash(slash), _) => {
+                self.slash(slash);
+                self.restart_prompt();
+            }
+            (Response::Text(_), None) => {
+                if let Some((roll, moves)) = self.roll() {
+                    self.frontend.show_moves(&self.game, &moves, roll);
+                    let prompt = format!("Choose move index [0..{}] (? for help): ", moves.len() - 1);
+                    self.phase = Phase::Prompt { editor: LineEditor::new(prompt, self.deadline), rolled: Some((roll, moves)) };
+                }
+            }
+            (Response::Text(text), Some((roll, moves))) => {
+                let last = moves.len() - 1;
+                match text.parse::<usize>() {
+                    Ok(idx) if idx <= last => {
+                        let piece = moves[idx];
+                        self.undo_stack.push(self.turn_start);
+                        if self.options.coach
+                            && let Some(explanation) = explain_weak_move(&self.game, self.game.current_player(), piece, &moves, roll) {
+                            self.frontend.message(&format!("💡 {}", explanation));
+                            prompt_line("   Press ENTER to continue... ")?;
+                        }
+                        self.play(piece, roll, None, scope, events)?;
+                    }
+                    Ok(idx) => {
+                        self.frontend.message(&format!("❌ Move index {} is out of range, choose 0..{}.", idx, last));
+                        self.restart_prompt();
+                    }
+                    Err(_) => {
+                        self.frontend.message(&format!("❌ '{}' is not a move index, try again.", text));
+                        self.restart_prompt();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-            // Print which piece it moved and to where
-            let ai_type = match current_player_type {
-                AIType::Random => "random AI",
-                AIType::Smart => "smart AI",
-                AIType::MCTS => "MCTS AI",
-                AIType::Human => unreachable!(),
-            };
+    /// Act on a slash command from the player to move
+    fn slash(&mut self, slash: Slash) {
+        let said = match slash {
+            Slash::Say(text) if text.is_empty() => {
+                return self.frontend.message("💬 Type your message after /say, e.g. '/say good luck'.");
+            }
+            Slash::Say(text) if text.chars().count() > chat::MAX_LEN => {
+                return self.frontend.message(&format!("❌ Chat messages are at most {} characters.", chat::MAX_LEN));
+            }
+            Slash::Say(text) => Said::Text(chat::clean(&text)),
+            Slash::Draw => Said::Draw,
+            Slash::Rematch => Said::Rematch,
+            Slash::Unknown(name) => return self.frontend.message(&format!("❌ Unknown command '/{}'; press ? for the list.", name)),
+        };
+        self.speak(self.game.current_player(), &said);
+    }
+
+    /// Take in whatever the seated bots have said since the last tick
+    fn listen(&mut self) {
+        let heard: Vec<(FastPlayer, Said)> = self.bots.iter()
+            .flat_map(|(seat, _, chat)| iter::from_fn(|| chat.hear()).map(|said| (*seat, said)))
+            .collect();
+        for (seat, said) in heard {
+            self.speak(seat, &said);
+        }
+    }
+
+    /// Show what the player at `seat` said, pass it on to the other side's
+    /// bot and act on any agreement it makes
+    fn speak(&mut self, seat: FastPlayer, said: &Said) {
+        let name = match self.bots.iter().find(|(bot_seat, ..)| *bot_seat == seat) {
+            Some((_, label, _)) => label.clone(),
+            None => seat.name().to_string(),
+        };
+        let other = self.players[seat.opposite() as usize].clone();
+        // Built-in AIs answer at once: never a draw, always a rematch
+        let built_in = !matches!(other, AIType::Human | AIType::External(_));
+        let heard = match self.chat.hear(seat, &name, said) {
+            Heard::Asked if *said == Said::Rematch && built_in => self.chat.hear(seat.opposite(), other.display_name(), said),
+            heard => heard,
+        };
+        if heard != Heard::TooFast {
+            for (_, _, chat) in self.bots.iter().filter(|(bot_seat, ..)| *bot_seat != seat) {
+                chat.tell(seat, said);
+            }
+        }
+        let human = self.players[seat as usize] == AIType::Human;
+        let answer = |how: &str| if other == AIType::Human { format!(" ({})", how) } else { String::new() };
+        let news = match (heard, said) {
+            (Heard::Line(line), _) => format!("💬 {}", line),
+            (Heard::TooFast, _) if human => {
+                format!("✋ Slow down: at most {} chat messages every {} seconds.", chat::RATE_LIMIT, chat::RATE_WINDOW.as_secs())
+            }
+            (Heard::Repeated, _) if human => "⏳ Still waiting for an answer.".to_string(),
+            (Heard::TooFast | Heard::Repeated, _) => return,
+            (Heard::Asked, Said::Draw) if built_in => format!("🤝 {} offers a draw; the {} plays on.", name, other.display_name()),
+            (Heard::Asked, Said::Draw) => format!("🤝 {} offers a draw{}.", name, answer("/draw to accept")),
+            (Heard::Asked, _) => format!("🔁 {} asks for a rematch{}.", name, answer("/rematch to agree")),
+            (Heard::Agreed, Said::Draw) => {
+                self.frontend.show_chat("🤝 Draw agreed.");
+                self.ended = Some(GameStatus::Drawn(EndReason::Agreement));
+                if let Phase::Thinking { cancel, .. } = &self.phase {
+                    cancel.cancel();
+                }
+                self.phase = Phase::Over { status: self.ended };
+                return;
+            }
+            (Heard::Agreed, _) => "🔁 Rematch agreed: the next game starts straight after this one.".to_string(),
+        };
+        self.frontend.show_chat(&news);
+        if let Phase::Prompt { editor, .. } = &mut self.phase {
+            editor.invalidate();
+        }
+    }
+
+    /// Show the prompt again on a new line after a message
+    fn restart_prompt(&mut self) {
+        if let Phase::Prompt { editor, .. } = &mut self.phase {
+            editor.restart();
+        }
+    }
+
+    /// The human's clock ran out: forfeit, or let the Smart AI roll and move for them
+    fn time_out<'s>(&mut self, scope: &'s thread::Scope<'s, 'a>, events: &mut Events) -> InputResult<()> {
+        let Phase::Prompt { rolled, .. } = &mut self.phase else {
+            return Ok(());
+        };
+        let rolled = rolled.take();
+        let player = self.game.current_player();
+        let forfeit = self.options.clock.is_some_and(|clock| clock.on_timeout == TimeoutAction::Forfeit);
+        self.frontend.message(&format!("⏰ {} is out of time{}", player.name(), if forfeit { " and forfeits the game." } else { "." }));
+        if forfeit {
+            self.ended = Some(GameStatus::Adjudicated(player.opposite(), EndReason::Timeout));
+            return self.start_turn(scope, events);
+        }
+
+        let Some((roll, moves)) = rolled.or_else(|| self.roll()) else {
+            return Ok(());
+        };
+        self.undo_stack.push(self.turn_start);
+        let piece = choose_smart_move_fast(&self.game, player, &moves, roll);
+        self.frontend.message(&format!("🧠 The smart AI moves piece {} for {}.", piece, player.name()));
+        self.play(piece, roll, Some(AI_MOVE_PAUSE), scope, events)
+    }
+
+    /// Make the chosen move, then start the next turn after `pause`
+    fn play<'s>(
+        &mut self,
+        piece: u8,
+        roll: u8,
+        pause: Option<Duration>,
+        scope: &'s thread::Scope<'s, 'a>,
+        events: &mut Events,
+    ) -> InputResult<()> {
+        let mover = self.game.current_player();
+        let before = self.game;
+        let mut pause = pause;
+        match self.game.play(piece, roll) {
+            Ok(move_info) => {
+                self.tally.luck.moved(&before, piece, roll);
+                self.history.record(self.tally.turns, mover, roll, move_info, self.game);
+                if let Some(captured) = move_info.captured_piece {
+                    self.tally.captures[mover as usize] += 1;
+                    self.show_capture(mover, captured, move_info.from_pos, move_info.to_pos);
+                    // Slow the game down so the capture is not missed
+                    pause = Some(pause.map_or(CAPTURE_PAUSE, |pause| pause.max(CAPTURE_PAUSE)));
+                }
+                // make_move leaves the turn with the mover on an extra turn, one
+                // more in a row than this turn was
+                let chain = if move_info.extra_turn { self.tally.extra_rolls.chain() + 1 } else { 0 };
+                self.frontend.announce(Announcement::Moved { mover, chain });
+            }
+            Err(e) => self.frontend.message(&format!("❌ {}.", e)),
+        }
+        match pause {
+            Some(pause) => self.phase = Phase::Pause { until: Instant::now() + pause },
+            None => self.start_turn(scope, events)?,
+        }
+        Ok(())
+    }
+
+    /// Show the board just after `mover` captured the opponent's `captured`
+    /// piece by moving from path position `from_pos` to `to_pos`, both squares
+    /// highlighted, and the capture counts
+    fn show_capture(&mut self, mover: FastPlayer, captured: u8, from_pos: u8, to_pos: u8) {
+        let view = TurnView { game: &self.game, players: &self.players, captures: self.tally.captures, chat: self.chat.lines() };
+        self.frontend.show_capture(view, &move_squares(mover, from_pos, to_pos));
+        let (row, col) = global_to_coord(FastGameState::path_to_global(mover, to_pos - 1));
+        let [p1, p2] = self.tally.captures;
+        self.frontend.message(&format!("💥 Piece sent home! {} captures {}'s piece {} at grid ({}, {}). Captures: {} {} | {} {}",
+                                       mover.name(), mover.opposite().name(), captured, row, col,
+                                       style::of(FastPlayer::One).marker, p1, style::of(FastPlayer::Two).marker, p2));
+    }
 
-            let pos = game.get_piece_pos(game.current_player(), mv);
-            match pos {
-                0 => {
-                    let target_square = FastGameState::path_to_global(game.current_player(), 0);
+    /// Describe the AI's move and, if asked for, the search behind it
+    fn announce_ai_move(&mut self, mv: u8, roll: u8, search_info: &SearchInfo) {
+        let game = &self.game;
+        let ai_type = match self.current_type() {
+            AIType::Random => "random AI",
+            AIType::Smart => "smart AI",
+            AIType::Expectimax => "expectimax AI",
+            AIType::MCTS => "MCTS AI",
+            AIType::Adaptive => "adaptive AI",
+            AIType::Script => "script AI",
+            AIType::External(_) => "external bot",
+            AIType::Opponent(opponent) => opponent.name,
+            AIType::Tuned(tuned) => tuned.label(),
+            AIType::Human => unreachable!(),
+        };
+
+        let pos = game.get_piece_pos(game.current_player(), mv);
+        match pos {
+            0 => {
+                let target_square = FastGameState::path_to_global(game.current_player(), 0);
+                let (r, c) = global_to_coord(target_square);
+                let extra_info = if FastGameState::is_rosette(target_square) {
+                    " (rosette - extra turn!)"
+                } else if FastGameState::is_safe(target_square) {
+                    " (safe square)"
+                } else {
+                    ""
+                };
+                self.frontend.message(&format!("{} ({}) enters piece {} → path 0, grid ({}, {}){}",
+                        game.current_player().name(), ai_type, mv, r, c, extra_info));
+            }
+            _ if FastGameState::is_on_board(pos) => {
+                let path_idx = pos - 1;
+                let new_path_idx = path_idx + roll;
+                if new_path_idx >= FastGameState::path_len() {
+                    self.frontend.message(&format!("{} ({}) moves piece {} → EXIT",
+                            game.current_player().name(), ai_type, mv));
+                } else {
+                    let target_square = FastGameState::path_to_global(game.current_player(), new_path_idx);
                     let (r, c) = global_to_coord(target_square);
                     let extra_info = if FastGameState::is_rosette(target_square) {
                         " (rosette - extra turn!)"
@@ -369,52 +1434,259 @@ fn main() {
                     } else {
                         ""
                     };
-                    println!("{} ({}) enters piece {} → path 0, grid ({}, {}){}",
-                            game.current_player().name(), ai_type, mv, r, c, extra_info);
+                    self.frontend.message(&format!("{} ({}) moves piece {} → path {}, grid ({}, {}){}",
+                            game.current_player().name(), ai_type, mv, new_path_idx, r, c, extra_info));
                 }
-                1..=14 => {
-                    let path_idx = pos - 1;
-                    let new_path_idx = path_idx + roll;
-                    if new_path_idx >= 14 {
-                        println!("{} ({}) moves piece {} → EXIT",
-                                game.current_player().name(), ai_type, mv);
-                    } else {
-                        let target_square = FastGameState::path_to_global(game.current_player(), new_path_idx);
-                        let (r, c) = global_to_coord(target_square);
-                        let extra_info = if FastGameState::is_rosette(target_square) {
-                            " (rosette - extra turn!)"
-                        } else if FastGameState::is_safe(target_square) {
-                            " (safe square)"
-                        } else {
-                            ""
-                        };
-                        println!("{} ({}) moves piece {} → path {}, grid ({}, {}){}",
-                                game.current_player().name(), ai_type, mv, new_path_idx, r, c, extra_info);
-                    }
+            }
+            _ => {}
+        }
+        if self.options.verbose {
+            self.frontend.message(&format!("   ⏱  {}", search_info.summary()));
+        }
+        if self.options.ai_profile && search_info.profile.playouts > 0 {
+            self.frontend.message(&format!("   📈 {}", search_info.profile.summary()));
+        }
+        if self.options.explain {
+            for line in explain_candidates(game, game.current_player(), roll, search_info) {
+                self.frontend.message(&format!("   🔎 {}", line));
+            }
+        }
+    }
+
+    /// Draw the screen again after the terminal was resized
+    fn redraw(&mut self) {
+        self.frontend.clear();
+        let view = TurnView { game: &self.game, players: &self.players, captures: self.tally.captures, chat: self.chat.lines() };
+        self.frontend.show_turn(view);
+        match &mut self.phase {
+            Phase::Prompt { editor, rolled } => {
+                if let Some((roll, moves)) = rolled {
+                    self.frontend.show_roll(*roll);
+                    self.frontend.show_moves(&self.game, moves, *roll);
+                }
+                editor.invalidate();
+            }
+            Phase::Thinking { roll, since, .. } => {
+                self.frontend.show_roll(*roll);
+                if !self.options.tournament {
+                    let _ = self.frontend.show_thinking(&self.players[self.game.current_player() as usize], *since);
                 }
-                _ => {}
             }
+            Phase::Pause { .. } | Phase::Over { .. } => {}
+        }
+    }
 
-            // Pause so we can observe
-            thread::sleep(Duration::from_millis(1000));
-            mv
+    /// Show the result and offer the replay; the game's summary, or None if a human quit
+    fn finish(&mut self, status: Option<GameStatus>) -> InputResult<Option<GameSummary>> {
+        let Some(status) = status else {
+            return Ok(None);
         };
-
-        // Apply the chosen move
-        if let Some(move_info) = game.make_move(chosen_piece, roll) {
-            // Check for extra turn
-            if move_info.extra_turn {
-                println!("{} gets an extra roll (landed on rosette).", game.current_player().name());
-                println!();
-                continue;
+        show_winner(status, &self.game);
+        self.history.display_journeys(self.tally.turns);
+        show_luck(&self.players, &self.tally.luck);
+        let record = GameRecord {
+            unassisted: self.options.tournament,
+            rng: self.dice.algorithm(),
+            seed: self.dice.algorithm().is_seeded().then(|| self.dice.seed()),
+            ..GameRecord::from_history(&self.history, self.players.each_ref().map(|p| p.label().to_string()), Some(status))
+        };
+        // Rating takes a few seconds, so it is left out of games between computers
+        let efficiency = if self.players.contains(&AIType::Human) {
+            self.frontend.message("\n📐 Rating every move...");
+            let accuracy = rate_game(&record);
+            show_efficiency(&self.players, &accuracy);
+            accuracy.map(|accuracy| accuracy.efficiency())
+        } else {
+            [None; 2]
+        };
+        if let Some(dir) = &self.options.record {
+            match record.write_to_dir(dir) {
+                Ok(path) => self.frontend.message(&format!("\n📝 Game recorded to {}", path.display())),
+                Err(e) => self.frontend.message(&format!("\n❌ Could not record the game: {}", e)),
             }
+        }
+        self.frontend.message("");
+        if prompt_yes_no("Replay the game and explore what-ifs? [y/N]: ", false)? {
+            replay_game(&mut self.history, &self.players, self.mcts_ai)?;
+        }
+        Ok(Some(GameSummary {
+            // Interactive games have no move limit, so only an agreed draw has no winner
+            winner: status.winner(),
+            tally: self.tally,
+            scores: [FastPlayer::One, FastPlayer::Two].map(|player| self.game.get_score(player)),
+            efficiency,
+            rematch: self.chat.rematch_agreed(),
+        }))
+    }
+}
 
-            // Turn switching is handled automatically by make_move()
-        } else {
-            println!("Invalid move attempt!");
-            continue;
+/// How much each side's rolls and moves gained or cost them in a finished game
+fn show_luck(players: &[AIType; 2], ledger: &Ledger) {
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right], 2);
+    table.row(["", "Luck", "Skill"].map(Span::plain).to_vec());
+    for player in [FastPlayer::One, FastPlayer::Two] {
+        let style = style::of(player);
+        table.row(vec![
+            Span::colored(format!("{} {}", style.marker, players[player as usize].display_name()), style.color),
+            Span::plain(format!("{:+.1}", ledger.luck[player as usize] * 100.0)),
+            Span::plain(format!("{:+.1}", ledger.skill[player as usize] * 100.0)),
+        ]);
+    }
+
+    let mut panel = Panel::new(Border::Single, 37);
+    panel.centered(vec![Span::plain("🎲 Luck and skill")]);
+    panel.rule();
+    panel.table(&table);
+    panel.line(vec![Span::colored("Win chance from rolls and moves, in", Color::DarkGrey)]);
+    panel.line(vec![Span::colored("points; best moves score 0 skill", Color::DarkGrey)]);
+    let mut screen = Screen::new();
+    screen.panel(&panel);
+    screen.print();
+}
+
+/// Each side's efficiency in a finished game, with the counts behind it
+fn show_efficiency(players: &[AIType; 2], accuracy: &[Accuracy; 2]) {
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right, Align::Right], 2);
+    table.row(["", "Efficiency", "Best moves", "Blunders"].map(Span::plain).to_vec());
+    for (player, accuracy) in [FastPlayer::One, FastPlayer::Two].into_iter().zip(accuracy) {
+        let style = style::of(player);
+        let name = Span::colored(format!("{} {}", style.marker, players[player as usize].display_name()), style.color);
+        table.row(match accuracy.efficiency() {
+            Some(efficiency) => vec![
+                name,
+                Span::plain(format!("{:.1}", efficiency)),
+                Span::plain(format!("{:.0}%", accuracy.best_move_rate())),
+                Span::plain(accuracy.blunders.to_string()),
+            ],
+            None => vec![name, Span::plain("-"), Span::plain("no choices"), Span::plain("")],
+        });
+    }
+
+    let mut panel = Panel::new(Border::Single, 37);
+    panel.centered(vec![Span::plain("📐 Efficiency")]);
+    panel.rule();
+    panel.table(&table);
+    panel.line(vec![Span::colored("Win chance kept per move, out of 100", Color::DarkGrey)]);
+    let mut screen = Screen::new();
+    screen.panel(&panel);
+    screen.print();
+}
+
+/// Run a shortcut command; returns an action if it ends the prompt
+fn handle_command(
+    frontend: &mut dyn Frontend,
+    cmd: Command,
+    view: TurnView,
+    rolled: Option<(u8, &[u8])>,
+    can_undo: bool,
+    options: &PlayOptions,
+) -> InputResult<Option<HumanAction>> {
+    let TurnView { game, players, .. } = view;
+    match cmd {
+        Command::Quit => {
+            if prompt_yes_no("Really quit? [y/N]: ", false)? {
+                return Ok(Some(HumanAction::Quit));
+            }
+        }
+        Command::Resign => {
+            if prompt_yes_no("Resign this game? It counts as a loss. [y/N]: ", false)? {
+                return Ok(Some(HumanAction::Resign));
+            }
+        }
+        Command::Save => {
+            let path = prompt_line(&format!("Save file (default {}): ", DEFAULT_SAVE_FILE))?;
+            let path = if path.is_empty() { DEFAULT_SAVE_FILE } else { path.as_str() };
+            let saved = SavedGame {
+                game: *game,
+                players: [players[0].label().to_string(), players[1].label().to_string()],
+            };
+            match save_game(path, &saved) {
+                Ok(()) => frontend.message(&format!("💾 Game saved to {} (position code {})", path, position_code::encode(game))),
+                Err(e) => frontend.message(&format!("❌ Could not save game: {}", e)),
+            }
+        }
+        Command::Hint | Command::Undo if options.tournament => {
+            frontend.message("🏆 Hints and undo are off under tournament conditions.");
+        }
+        Command::Hint => match rolled {
+            Some((roll, moves)) => {
+                let piece = choose_smart_move_fast(game, game.current_player(), moves, roll);
+                let idx = moves.iter().position(|&m| m == piece).unwrap_or(0);
+                frontend.message(&format!("💡 Hint: move [{}] (piece {})", idx, piece));
+            }
+            None => frontend.message("💡 Roll the dice first, then ask for a hint."),
+        },
+        Command::Redraw | Command::Labels | Command::Route => {
+            match cmd {
+                Command::Labels => {
+                    toggle_path_labels();
+                }
+                Command::Route => cycle_route(),
+                _ => frontend.clear(),
+            }
+            redraw_turn(frontend, view, rolled);
+        }
+        Command::Share => frontend.message(&format!("📋 Position code: {}", position_code::encode(game))),
+        Command::Undo => {
+            if can_undo {
+                return Ok(Some(HumanAction::Undo));
+            }
+            frontend.message("Nothing to undo yet.");
+        }
+        Command::Help => {
+            let mut screen = Screen::new();
+            render_rules(&mut screen, options.clock);
+            frontend.show_screen(screen);
+            prompt_line("Press ENTER to return to the game... ")?;
+            redraw_turn(frontend, view, rolled);
+            if rolled.is_some() {
+                frontend.message("Enter the number shown in brackets next to a move, e.g. '0'.");
+            } else {
+                frontend.message("Press ENTER to roll the dice.");
+            }
         }
+    }
+    Ok(None)
+}
 
-        println!("Turn passes.\n");
+/// Draw the turn screen again, with the roll and legal moves if the dice
+/// have been rolled
+fn redraw_turn(frontend: &mut dyn Frontend, view: TurnView, rolled: Option<(u8, &[u8])>) {
+    frontend.show_turn(view);
+    if let Some((roll, moves)) = rolled {
+        frontend.show_roll(roll);
+        frontend.show_moves(view.game, moves, roll);
     }
-}
\ No newline at end of file
+}
+
+/// Ask for a save file until one loads, returning its players and position
+fn load_saved_game() -> InputResult<(AIType, AIType, FastGameState)> {
+    loop {
+        let path = prompt_line(&format!("Save file or position code to load (default {}): ", DEFAULT_SAVE_FILE))?;
+        if position_code::is_code(&path) {
+            match position_code::decode(&path) {
+                Ok(game) => {
+                    outln!("Loaded position {}; you play Player 1 against the smart AI", path);
+                    return Ok((AIType::Human, AIType::Smart, game));
+                }
+                Err(e) => {
+                    outln!("❌ Could not read {}: {}", path, e);
+                    continue;
+                }
+            }
+        }
+        let path = if path.is_empty() { DEFAULT_SAVE_FILE } else { path.as_str() };
+        match load_game(path) {
+            Ok(saved) => {
+                let p1 = AIType::from_label(&saved.players[0]);
+                let p2 = AIType::from_label(&saved.players[1]);
+                if let (Some(p1), Some(p2)) = (p1, p2) {
+                    outln!("Loaded game from {}", path);
+                    return Ok((p1, p2, saved.game));
+                }
+                outln!("❌ {} has unknown player types '{}' / '{}'", path, saved.players[0], saved.players[1]);
+            }
+            Err(e) => outln!("❌ Could not load {}: {}", path, e),
+        }
+    }
+}
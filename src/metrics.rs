@@ -0,0 +1,207 @@
+//! Lobby metrics for monitoring a hosted server: `ur lobby --metrics-port N`
+//! answers `GET /metrics` with the games in progress, connected clients and
+//! open seeks, games played, moves made (a counter, so moves per second is
+//! its rate), a histogram of how long players take over their moves, and
+//! rule violations and refused client messages, in the Prometheus text
+//! format. The endpoint needs the `server` feature; without it the counters
+//! cost nothing and `--metrics-port` reports how to enable it.
+
+use std::time::Duration;
+
+#[cfg(not(feature = "server"))]
+use std::io;
+#[cfg(not(feature = "server"))]
+use std::net::TcpListener;
+#[cfg(not(feature = "server"))]
+use std::sync::Arc;
+#[cfg(feature = "server")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "server")]
+use std::time::Instant;
+
+use crate::referee::Violation;
+
+/// Upper bounds of the think-time histogram's buckets, in seconds
+#[cfg(feature = "server")]
+const THINK_BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Kinds of violation, as labelled in `ur_lobby_violations_total`
+#[cfg(feature = "server")]
+const VIOLATION_KINDS: [&str; 5] = ["timeout", "illegal_move", "bad_reply", "failed", "broken_commitment"];
+
+/// Counters kept while the lobby runs
+pub struct Metrics {
+    #[cfg(feature = "server")]
+    started: Instant,
+    #[cfg(feature = "server")]
+    games: AtomicU64,
+    #[cfg(feature = "server")]
+    moves: AtomicU64,
+    /// Moves per think-time bucket, the last for moves slower than every bound
+    #[cfg(feature = "server")]
+    think: [AtomicU64; THINK_BUCKETS.len() + 1],
+    #[cfg(feature = "server")]
+    think_micros: AtomicU64,
+    #[cfg(feature = "server")]
+    violations: [AtomicU64; VIOLATION_KINDS.len()],
+    #[cfg(feature = "server")]
+    client_errors: AtomicU64,
+}
+
+/// What the lobby holds at the moment of a scrape
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub struct Gauges {
+    pub games: usize,
+    pub clients: usize,
+    pub seeks: usize,
+}
+
+#[cfg(not(feature = "server"))]
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {}
+    }
+
+    pub fn move_made(&self, _took: Duration) {}
+
+    pub fn game_finished(&self, _violation: Option<&Violation>) {}
+
+    pub fn client_error(&self) {}
+}
+
+/// Stands in for the endpoint in builds without it
+#[cfg(not(feature = "server"))]
+pub fn serve(_listener: TcpListener, _gauges: impl Fn() -> Gauges + Send + 'static, _metrics: Arc<Metrics>) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "this build has no metrics endpoint (rebuild with `--features server`)"))
+}
+
+#[cfg(feature = "server")]
+pub use endpoint::serve;
+
+#[cfg(feature = "server")]
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            started: Instant::now(),
+            games: AtomicU64::new(0),
+            moves: AtomicU64::new(0),
+            think: Default::default(),
+            think_micros: AtomicU64::new(0),
+            violations: Default::default(),
+            client_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// A player moved, taking `took` over it
+    pub fn move_made(&self, took: Duration) {
+        let seconds = took.as_secs_f64();
+        let bucket = THINK_BUCKETS.iter().position(|&bound| seconds <= bound).unwrap_or(THINK_BUCKETS.len());
+        self.moves.fetch_add(1, Ordering::Relaxed);
+        self.think[bucket].fetch_add(1, Ordering::Relaxed);
+        self.think_micros.fetch_add(took.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// A game ended, forfeited for `violation` if there was one
+    pub fn game_finished(&self, violation: Option<&Violation>) {
+        self.games.fetch_add(1, Ordering::Relaxed);
+        if let Some(violation) = violation {
+            let kind = match violation {
+                Violation::Timeout(_) => 0,
+                Violation::IllegalMove(..) => 1,
+                Violation::BadReply(_) => 2,
+                Violation::Failed(_) => 3,
+                Violation::BrokenCommitment => 4,
+            };
+            self.violations[kind].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// A client was sent an error
+    pub fn client_error(&self) {
+        self.client_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Everything in the Prometheus text format
+    pub fn render(&self, gauges: &Gauges) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+            for (suffix, value) in samples {
+                text.push_str(&format!("{}{} {}\n", name, suffix, value));
+            }
+        };
+        let one = |value: u64| [(String::new(), value.to_string())];
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        metric("ur_lobby_uptime_seconds", "gauge", "Seconds since the lobby started",
+               &[(String::new(), format!("{:.3}", self.started.elapsed().as_secs_f64()))]);
+        metric("ur_lobby_games_in_progress", "gauge", "Games being played", &one(gauges.games as u64));
+        metric("ur_lobby_clients", "gauge", "Clients connected", &one(gauges.clients as u64));
+        metric("ur_lobby_seeks", "gauge", "Seeks waiting for an opponent", &one(gauges.seeks as u64));
+        metric("ur_lobby_games_total", "counter", "Games played to the end", &one(load(&self.games)));
+        metric("ur_lobby_moves_total", "counter", "Moves made in all games", &one(load(&self.moves)));
+
+        let mut buckets = Vec::new();
+        let mut count = 0;
+        for (idx, bucket) in self.think.iter().enumerate() {
+            count += load(bucket);
+            let bound = THINK_BUCKETS.get(idx).map_or("+Inf".to_string(), f64::to_string);
+            buckets.push((format!("_bucket{{le=\"{}\"}}", bound), count.to_string()));
+        }
+        buckets.push(("_sum".to_string(), format!("{:.6}", load(&self.think_micros) as f64 / 1e6)));
+        buckets.push(("_count".to_string(), count.to_string()));
+        metric("ur_lobby_think_seconds", "histogram", "Time players took over their moves", &buckets);
+
+        let violations: Vec<(String, String)> = VIOLATION_KINDS.iter().zip(&self.violations)
+            .map(|(kind, counter)| (format!("{{kind=\"{}\"}}", kind), load(counter).to_string()))
+            .collect();
+        metric("ur_lobby_violations_total", "counter", "Games forfeited, by the violation", &violations);
+        metric("ur_lobby_client_errors_total", "counter", "Client messages the lobby refused", &one(load(&self.client_errors)));
+        text
+    }
+}
+
+#[cfg(feature = "server")]
+mod endpoint {
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{Gauges, Metrics};
+
+    /// How long a scraper may take to send its request
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Answer scrapes on `listener` from a thread of its own
+    pub fn serve(listener: TcpListener, gauges: impl Fn() -> Gauges + Send + 'static, metrics: Arc<Metrics>) -> io::Result<()> {
+        thread::Builder::new().name("lobby metrics".to_string()).spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let _ = answer(stream, &gauges, &metrics);
+            }
+        })?;
+        Ok(())
+    }
+
+    fn answer(mut stream: TcpStream, gauges: &impl Fn() -> Gauges, metrics: &Metrics) -> io::Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // The headers are read and ignored
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+        let mut parts = request.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render(&gauges())),
+            (Some("GET"), _) => ("404 Not Found", "only /metrics is served\n".to_string()),
+            _ => ("405 Method Not Allowed", "only GET is served\n".to_string()),
+        };
+        write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+               status, body.len(), body)?;
+        stream.flush()
+    }
+}
@@ -0,0 +1,187 @@
+//! `ur disagree`: mine games for positions where two AI configurations pick
+//! different moves and one of them thinks the difference is large. The two
+//! configurations play each other, taking each seat in turn. At every roll
+//! with a real choice, both are asked what they would play, the waiting one
+//! included, and `compare` measures the gap. Positions at or above the
+//! threshold go into a position pack, which is a text file with one position
+//! code, roll and pair of picks per line. A pack serves as tuning and test
+//! material, and its widest gaps make puzzle candidates.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Instant;
+
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::compare;
+use crate::dice::{self, DiceVariant};
+use crate::optimized_game::FastGameState;
+use crate::position_code;
+use crate::rng::GameRng;
+use crate::state_repr::GameStateRepr;
+use crate::stats::StatsAgent;
+use crate::workers;
+
+/// Points of win probability a disagreement must reach to be kept
+const DEFAULT_THRESHOLD: f64 = 10.0;
+/// Turns after which a game is abandoned, as in stats mode
+const MAX_TURNS: usize = 1000;
+
+/// Options for `ur disagree`
+pub struct MinerOptions {
+    /// Player specs of the two configurations, as in `ur match`
+    pub players: [String; 2],
+    pub games: usize,
+    pub threshold: f64,
+    pub out: PathBuf,
+    pub threads: usize,
+    pub seed: Option<u64>,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+}
+
+impl MinerOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--a", "--b", "--games", "--threshold", "--out", "--threads", "--seed", "--board", "--dice"], &[])?;
+        let player = |flag: &str| flags.get_str(flag).map(str::to_string).ok_or_else(|| format!("{} is required", flag));
+        let num_cpus = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let options = MinerOptions {
+            players: [player("--a")?, player("--b")?],
+            games: flags.get("--games", 200)?,
+            threshold: flags.get("--threshold", DEFAULT_THRESHOLD)?,
+            out: PathBuf::from(flags.get_str("--out").unwrap_or("disagreements.txt")),
+            threads: flags.get("--threads", num_cpus)?.max(1),
+            seed: flags.get_opt("--seed")?,
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+        };
+        for spec in &options.players {
+            StatsAgent::from_spec(spec, 1)?;
+        }
+        Ok(options)
+    }
+}
+
+/// A position the configurations split on
+#[derive(Debug, Clone)]
+struct Find {
+    position: FastGameState,
+    roll: u8,
+    /// The moves configurations A and B picked
+    picks: [u8; 2],
+    /// Their win probabilities for their own picks, for the player to move
+    values: [Option<f64>; 2],
+    gap: f64,
+}
+
+/// What one worker's games turned up
+#[derive(Default)]
+struct Haul {
+    finds: Vec<Find>,
+    decisions: usize,
+    splits: usize,
+}
+
+impl Haul {
+    fn merge(mut self, other: Haul) -> Haul {
+        self.finds.extend(other.finds);
+        self.decisions += other.decisions;
+        self.splits += other.splits;
+        self
+    }
+}
+
+/// Play the games with these dice seeds, the first of each pair with A as Player 1
+fn mine_chunk(options: &MinerOptions, seeds: &[(usize, u64)]) -> Haul {
+    let agents = options.players.each_ref().map(|spec| StatsAgent::from_spec(spec, 1).expect("checked when parsing"));
+    let mut haul = Haul::default();
+    for &(index, seed) in seeds {
+        let mut dice = GameRng::from_seed(seed);
+        let mut game = FastGameState::new();
+        // Which configuration sits in each seat
+        let seats = if index % 2 == 0 { [0, 1] } else { [1, 0] };
+        for _ in 0..MAX_TURNS {
+            if game.status().winner().is_some() {
+                break;
+            }
+            let roll = dice.roll_dice(game.current_player());
+            let moves = game.generate_moves(roll);
+            if roll == 0 || moves.is_empty() {
+                game.pass_turn();
+                continue;
+            }
+            let mover = seats[game.current_player() as usize];
+            if moves.len() == 1 {
+                let _ = game.make_move(moves[0], roll);
+                continue;
+            }
+
+            let [a, b] = agents.each_ref().map(|agent| compare::evaluate_agent(agent, &game, &moves, roll));
+            haul.decisions += 1;
+            if compare::differ(&game, roll, &a, &b) {
+                haul.splits += 1;
+                let gap = compare::disagreement(&game, roll, &a, &b);
+                if gap >= options.threshold {
+                    let values = [a.value(a.choice), b.value(b.choice)];
+                    haul.finds.push(Find { position: game, roll, picks: [a.choice, b.choice], values, gap });
+                }
+            }
+            let piece = if mover == 0 { a.choice } else { b.choice };
+            let _ = game.make_move(piece, roll);
+        }
+    }
+    haul
+}
+
+/// Play `options.games` games across threads and write the positions the
+/// configurations disagree on to a position pack
+pub fn run_miner(options: &MinerOptions) -> io::Result<()> {
+    board::select(options.board);
+    dice::select(options.dice);
+    let mut seeds = options.seed.map_or_else(GameRng::random, GameRng::from_seed);
+    let seeds: Vec<(usize, u64)> = (0..options.games).map(|index| (index, seeds.next_seed())).collect();
+
+    println!("=== DISAGREEMENT MINER ===");
+    println!("{} vs {} | {} games | {} threads | {:.1} points or more | board {} | dice {}",
+             options.players[0], options.players[1], options.games, options.threads, options.threshold,
+             options.board.label(), options.dice.label());
+
+    let start_time = Instant::now();
+    let chunk = seeds.len().div_ceil(options.threads).max(1);
+    let jobs = seeds.chunks(chunk).map(|seeds| move || mine_chunk(options, seeds));
+    let haul = workers::run_parallel("miner", jobs).into_iter().fold(Haul::default(), Haul::merge);
+
+    // A position and roll met more than once is kept once, with its widest gap
+    let mut widest: HashMap<(FastGameState, u8), Find> = HashMap::new();
+    for find in haul.finds {
+        let entry = widest.entry((find.position, find.roll)).or_insert_with(|| find.clone());
+        if find.gap > entry.gap {
+            *entry = find;
+        }
+    }
+    let mut finds: Vec<Find> = widest.into_values().collect();
+    finds.sort_by(|a, b| b.gap.total_cmp(&a.gap).then_with(|| position_code::encode(&a.position).cmp(&position_code::encode(&b.position))));
+
+    let labels = options.players.each_ref().map(|spec| StatsAgent::from_spec(spec, 1).map_or_else(|_| spec.clone(), |agent| agent.label()));
+    let mut out = io::BufWriter::new(fs::File::create(&options.out)?);
+    writeln!(out, "# ur position pack: positions where {} and {} disagree by {:.1} points or more", labels[0], labels[1], options.threshold)?;
+    writeln!(out, "# board {} | dice {} | {} games", options.board.label(), options.dice.label(), options.games)?;
+    writeln!(out, "# position\troll\t{}\t{}\tgap", labels[0], labels[1])?;
+    let value = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |value| format!("{:.1}%", value * 100.0));
+    for find in &finds {
+        writeln!(out, "{}\t{}\t{} ({})\t{} ({})\t{:.1}", position_code::encode(&find.position), find.roll,
+                 find.picks[0], value(find.values[0]), find.picks[1], value(find.values[1]), find.gap)?;
+    }
+    out.flush()?;
+
+    println!("{} decisions with a choice, {} split ({:.1}%), {} positions kept",
+             haul.decisions, haul.splits, 100.0 * haul.splits as f64 / haul.decisions.max(1) as f64, finds.len());
+    if let Some(top) = finds.first() {
+        println!("Widest gap: {:.1} points at {} with a roll of {}", top.gap, position_code::encode(&top.position), top.roll);
+    }
+    println!("Wrote {} in {:.1}s", options.out.display(), start_time.elapsed().as_secs_f64());
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Opening explorer for `ur analyze-db --openings`: how the recorded games
+//! began and how each start turned out. Each side's first moves are counted by
+//! roll and by kind (entering a piece or advancing one), and the positions
+//! reached after them are grouped with piece identities dropped and listed by
+//! position code, so a common opening can be loaded with `--position`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+
+use crate::analysis::AnalyzeOptions;
+use crate::game_record::{load_dir, RecordedMove};
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::position_code;
+use crate::screen::{Align, Screen, Span, Table};
+
+/// Moves per side that make up an opening
+const OPENING_MOVES: usize = 2;
+/// Opening positions listed, most common first
+const TOP_POSITIONS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Choice {
+    Enter,
+    Advance,
+    BearOff,
+}
+
+impl Choice {
+    fn of(position: &FastGameState, mv: RecordedMove) -> Self {
+        let from = position.get_piece_pos(mv.player, mv.piece);
+        if from == 0 {
+            Choice::Enter
+        } else if from + mv.roll == FastGameState::finished_pos() {
+            Choice::BearOff
+        } else {
+            Choice::Advance
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Choice::Enter => "enter",
+            Choice::Advance => "advance",
+            Choice::BearOff => "bear off",
+        }
+    }
+}
+
+/// Games that reached something, and how many of them a given side won
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    games: usize,
+    wins: usize,
+}
+
+impl Tally {
+    fn add(&mut self, won: bool) {
+        self.games += 1;
+        self.wins += usize::from(won);
+    }
+
+    fn win_rate(self) -> String {
+        format!("{:.1}%", self.wins as f64 / self.games.max(1) as f64 * 100.0)
+    }
+}
+
+/// Print the opening explorer for the games in `options.dir`; the board and
+/// dice must already be selected
+pub fn run_openings(options: &AnalyzeOptions) -> io::Result<()> {
+    // (side, nth move of that side, roll, choice) → results for that side
+    let mut choices: BTreeMap<(usize, usize, u8, Choice), Tally> = BTreeMap::new();
+    // Position code after the opening → results for Player 1
+    let mut positions: HashMap<String, Tally> = HashMap::new();
+    let (mut games, mut skipped, mut unfinished) = (0, 0, 0);
+
+    for (_, record) in load_dir(&options.dir)? {
+        // Only games from the usual start on the chosen rules share openings
+        let record = match record {
+            Ok(record) if record.board == options.board && record.dice == options.dice && record.start == FastGameState::new() => record,
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let Ok(moves) = record.positions() else {
+            skipped += 1;
+            continue;
+        };
+        // A recorded result also covers games decided on time or by resignation
+        let Some(winner) = record.result.and_then(GameStatus::winner).or_else(|| moves.last().and_then(|(position, mv)| {
+            let mut end = *position;
+            end.make_move(mv.piece, mv.roll).expect("recorded moves are checked");
+            end.status().winner()
+        })) else {
+            unfinished += 1;
+            continue;
+        };
+        games += 1;
+
+        let mut made = [0; 2];
+        for (position, mv) in &moves {
+            let side = mv.player as usize;
+            if made[side] == OPENING_MOVES {
+                continue;
+            }
+            made[side] += 1;
+            choices.entry((side, made[side], mv.roll, Choice::of(position, *mv))).or_default().add(mv.player == winner);
+            if made == [OPENING_MOVES; 2] {
+                let mut after = *position;
+                after.make_move(mv.piece, mv.roll).expect("recorded moves are checked");
+                // Positions that differ only in which piece sits where are one opening
+                let canonical = FastGameState::from_canonical_key(after.canonical_key()).expect("key of a real position");
+                let code = position_code::encode(&canonical);
+                positions.entry(code).or_default().add(winner == FastPlayer::One);
+                break;
+            }
+        }
+    }
+
+    println!("=== OPENING EXPLORER ===");
+    println!("Directory: {} | {} finished games from the start position ({} skipped, {} unfinished)",
+             options.dir.display(), games, skipped, unfinished);
+    if games == 0 {
+        println!("No games to explore. Record some with --record {}.", options.dir.display());
+        return Ok(());
+    }
+    println!();
+
+    println!("FIRST {} MOVES OF EACH SIDE (win rate of the side moving):", OPENING_MOVES);
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right, Align::Left, Align::Right, Align::Right], 2);
+    table.row(["Side", "Move", "Roll", "Choice", "Games", "Won"].map(Span::plain).to_vec());
+    for (&(side, nth, roll, choice), tally) in &choices {
+        table.row(vec![
+            Span::plain(format!("Player {}", side + 1)),
+            Span::plain(nth.to_string()),
+            Span::plain(roll.to_string()),
+            Span::plain(choice.label()),
+            Span::plain(tally.games.to_string()),
+            Span::plain(tally.win_rate()),
+        ]);
+    }
+    let mut screen = Screen::new();
+    screen.table(&table, 2);
+    screen.print();
+
+    let mut common: Vec<(&String, &Tally)> = positions.iter().collect();
+    common.sort_by(|a, b| b.1.games.cmp(&a.1.games).then(a.0.cmp(b.0)));
+    println!();
+    println!("MOST COMMON POSITIONS AFTER THEM ({} different):", positions.len());
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right], 2);
+    table.row(["Position", "Games", "P1 won"].map(Span::plain).to_vec());
+    for (code, tally) in common.into_iter().take(TOP_POSITIONS) {
+        table.row(vec![Span::plain(code.as_str()), Span::plain(tally.games.to_string()), Span::plain(tally.win_rate())]);
+    }
+    let mut screen = Screen::new();
+    screen.table(&table, 2);
+    screen.print();
+    println!();
+    println!("Play one out with --position CODE.");
+    Ok(())
+}
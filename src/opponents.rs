@@ -0,0 +1,111 @@
+//! Named computer opponents. Each is a fixed configuration of one of the
+//! engine's AIs with a playing style of its own, so a player can get to know
+//! them, and their results are kept under their own names on the leaderboard.
+
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::ai::{HybridAI, SearchInfo};
+use crate::ai_helpers::{choose_weighted_move, EvalWeights};
+use crate::cancel::CancelToken;
+use crate::optimized_game::FastGameState;
+use crate::presets::{self, PLAY_PRESET};
+
+/// The AI behind an opponent
+enum Engine {
+    /// The Smart AI's one-move lookahead with these weights
+    Weighted(EvalWeights),
+    /// MCTS with the play preset's budget multiplied by this much
+    Mcts(f64),
+}
+
+pub struct Opponent {
+    /// Name shown in games, e.g. "Scribe"
+    pub name: &'static str,
+    /// Name in save files, `ur match` and on the leaderboard, e.g. "scribe"
+    pub key: &'static str,
+    /// How it plays, in a few words
+    pub style: &'static str,
+    engine: Engine,
+    /// The MCTS search of an `Engine::Mcts` opponent, built when first needed
+    search: OnceLock<HybridAI>,
+}
+
+/// Every named opponent
+pub static ROSTER: [Opponent; 3] = [
+    Opponent {
+        name: "Scribe",
+        key: "scribe",
+        style: "careful, keeps pieces out of reach and sits on rosettes",
+        engine: Engine::Weighted(EvalWeights {
+            enter: 30.0,
+            rosette: 300.0,
+            capture: 100.0,
+            vulnerability: 400.0,
+            ..EvalWeights::DEFAULT
+        }),
+        search: OnceLock::new(),
+    },
+    Opponent {
+        name: "Raider",
+        key: "raider",
+        style: "aggressive, takes every capture it can, the deeper the better",
+        engine: Engine::Weighted(EvalWeights {
+            advancement: 6.0,
+            rosette: 120.0,
+            capture: 450.0,
+            capture_progress: 20.0,
+            ..EvalWeights::DEFAULT
+        }),
+        search: OnceLock::new(),
+    },
+    Opponent {
+        name: "Oracle",
+        key: "oracle",
+        style: "MCTS with four times the usual search on every core",
+        engine: Engine::Mcts(4.0),
+        search: OnceLock::new(),
+    },
+];
+
+/// The opponent called `name`, ignoring case
+pub fn find(name: &str) -> Option<&'static Opponent> {
+    ROSTER.iter().find(|opponent| opponent.key.eq_ignore_ascii_case(name))
+}
+
+impl Opponent {
+    /// Pick a move; `moves` must be non-empty. Cancelling `cancel` ends an
+    /// MCTS search early with its best move so far.
+    pub fn choose_move_cancellable(&self, game: &FastGameState, moves: &[u8], roll: u8, cancel: &CancelToken) -> (u8, SearchInfo) {
+        match &self.engine {
+            Engine::Weighted(weights) => {
+                let start = Instant::now();
+                let piece_idx = choose_weighted_move(game, game.current_player(), moves, roll, weights);
+                (piece_idx, SearchInfo::new(moves.len(), start.elapsed()))
+            }
+            Engine::Mcts(scale) => {
+                let search = self.search.get_or_init(|| {
+                    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+                    let mut ai = presets::lookup(PLAY_PRESET).expect("built-in preset").build_scaled(threads, *scale);
+                    ai.name = Some(self.key.to_string());
+                    ai
+                });
+                search.choose_move_cancellable(game, game.current_player(), roll, cancel)
+                    .unwrap_or_else(|| (moves[0], SearchInfo::new(0, Duration::ZERO)))
+            }
+        }
+    }
+}
+
+impl PartialEq for Opponent {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl fmt::Debug for Opponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Opponent({})", self.name)
+    }
+}
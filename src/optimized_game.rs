@@ -1,27 +1,37 @@
-/// Ultra-fast GameState implementation inspired by chess engines
-/// Key optimizations:
-/// 1. Bitboards for O(1) occupancy checks
-/// 2. Packed representation (fits in 128 bits total)
-/// 3. Make/unmake moves instead of cloning
-/// 4. Zero-allocation design for performance
-/// 5. SIMD-friendly operations where possible
-
+//! Ultra-fast GameState implementation inspired by chess engines
+//! Key optimizations:
+//! 1. Bitboards for O(1) occupancy checks
+//! 2. Packed representation (fits in 192 bits total)
+//! 3. Make/unmake moves instead of cloning
+//! 4. Zero-allocation design for performance
+//! 5. SIMD-friendly operations where possible
+
+use std::cmp::Ordering;
 use std::fmt;
+use std::ops::Deref;
+use rand::RngCore;
+use thiserror::Error;
+
+use crate::board::{self, MAX_SQUARES};
+use crate::dice;
+use crate::error::UrError;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FastGameState {
-    /// Bitboard for both players: bits 0-19 = Player 1, bits 20-39 = Player 2
+    /// Bitboard for both players: bits 0-31 = Player 1, bits 32-63 = Player 2
+    /// (one bit per square of the selected board layout)
     pub occupied_squares: u64,
-    /// Packed piece positions: 4 bits per piece, 7 pieces per player = 56 bits total
-    /// Lower 28 bits = Player 1, upper 28 bits = Player 2
-    /// Each 4-bit value: 0=OffBoard, 1-14=OnBoard(0-13), 15=Finished
-    pub piece_positions: u64,
+    /// Packed piece positions: 5 bits per piece, 7 pieces per player = 70 bits total
+    /// Bits 0-34 = Player 1, bits 35-69 = Player 2
+    /// Each 5-bit value: 0=OffBoard, 1-N=OnBoard(0-(N-1)), N+1=Finished, N = path length
+    pub piece_positions: u128,
     /// Packed scores and turn: bits 0-2=P1 score, bits 3-5=P2 score, bit 6=turn
     pub scores_and_turn: u8,
 }
 
 /// Move representation that can be undone
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveInfo {
     pub piece_idx: u8,
     pub from_pos: u8,
@@ -30,8 +40,70 @@ pub struct MoveInfo {
     pub extra_turn: bool,
 }
 
+/// Why a piece cannot move with a roll
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum MoveError {
+    #[error("a roll of 0 moves nothing")]
+    NoRoll,
+    #[error("there is no such piece")]
+    NoSuchPiece,
+    #[error("the piece is already home")]
+    Finished,
+    #[error("its own piece is on the square")]
+    OwnPiece,
+    #[error("the opponent's piece there is on a safe square")]
+    Protected,
+    /// The roll carries the piece past the end of its path; bearing off takes the exact roll
+    #[error("the roll overshoots the end; bearing off takes the exact roll")]
+    Overshoot,
+}
+
+impl MoveError {
+    /// Name in machine-readable reports
+    pub fn label(self) -> &'static str {
+        match self {
+            MoveError::NoRoll => "no-roll",
+            MoveError::NoSuchPiece => "no-such-piece",
+            MoveError::Finished => "finished",
+            MoveError::OwnPiece => "own-piece",
+            MoveError::Protected => "protected",
+            MoveError::Overshoot => "overshoot",
+        }
+    }
+}
+
+/// Fixed-capacity list of movable piece indices that lives on the stack
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MoveList {
+    moves: [u8; 7],
+    len: u8,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList { moves: [0; 7], len: 0 }
+    }
+
+    #[inline]
+    pub fn push(&mut self, piece_idx: u8) {
+        self.moves[self.len as usize] = piece_idx;
+        self.len += 1;
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.moves[..self.len as usize]
+    }
+}
+
 /// Player enumeration that packs into single bits
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum FastPlayer {
     One = 0,
     Two = 1,
@@ -53,19 +125,102 @@ impl FastPlayer {
     }
 }
 
+/// Why a game was decided before either side brought every piece home
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum EndReason {
+    /// The loser gave the game up
+    Resignation,
+    /// The loser ran out of time
+    Timeout,
+    /// The loser broke the rules: an illegal move, an unreadable reply, a crash
+    Forfeit,
+    /// The game reached its move limit and was decided on the position
+    MoveLimit,
+    /// Both sides agreed to a draw
+    Agreement,
+    /// Play was stopped, as when a server shuts down, and the game decided on the position
+    Stopped,
+}
 
-
-impl FastGameState {
-    const PATHS: [[u8; 14]; 2] = [
-        [3, 2, 1, 0, 6, 7, 8, 9, 10, 11, 12, 13, 5, 4],      // Player 1
-        [17, 16, 15, 14, 6, 7, 8, 9, 10, 11, 12, 13, 19, 18], // Player 2
+impl EndReason {
+    pub const ALL: [EndReason; 6] = [
+        EndReason::Resignation,
+        EndReason::Timeout,
+        EndReason::Forfeit,
+        EndReason::MoveLimit,
+        EndReason::Agreement,
+        EndReason::Stopped,
     ];
 
-    /// Rosette squares (give extra turns)
-    const ROSETTES: u32 = (1 << 4) | (1 << 9) | (1 << 18);
+    /// Name in game records
+    pub fn label(self) -> &'static str {
+        match self {
+            EndReason::Resignation => "resignation",
+            EndReason::Timeout => "timeout",
+            EndReason::Forfeit => "forfeit",
+            EndReason::MoveLimit => "move-limit",
+            EndReason::Agreement => "agreement",
+            EndReason::Stopped => "stopped",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| reason.label() == label)
+    }
+}
+
+/// Where a game stands. The position alone only tells `InProgress` from
+/// `Won`; whoever runs the game decides the other endings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameStatus {
+    InProgress,
+    /// All of the winner's pieces came home
+    Won(FastPlayer),
+    /// Decided for this player before the end
+    Adjudicated(FastPlayer, EndReason),
+    /// Ended with no winner
+    Drawn(EndReason),
+}
+
+impl GameStatus {
+    pub fn winner(self) -> Option<FastPlayer> {
+        match self {
+            GameStatus::Won(winner) | GameStatus::Adjudicated(winner, _) => Some(winner),
+            GameStatus::InProgress | GameStatus::Drawn(_) => None,
+        }
+    }
+
+    pub fn is_over(self) -> bool {
+        self != GameStatus::InProgress
+    }
+
+    /// How the game ended, e.g. "Player 2 wins on time"
+    pub fn describe(self) -> String {
+        match self {
+            GameStatus::InProgress => "game in progress".to_string(),
+            GameStatus::Won(winner) => format!("{} wins with all pieces home", winner.name()),
+            GameStatus::Adjudicated(winner, reason) => format!("{} wins {}", winner.name(), match reason {
+                EndReason::Resignation => "by resignation",
+                EndReason::Timeout => "on time",
+                EndReason::Forfeit => "by forfeit",
+                EndReason::MoveLimit => "on the position at the move limit",
+                EndReason::Agreement => "by agreement",
+                EndReason::Stopped => "on the position when play stopped",
+            }),
+            GameStatus::Drawn(EndReason::Agreement) => "drawn by agreement".to_string(),
+            GameStatus::Drawn(EndReason::Stopped) => "drawn when play stopped".to_string(),
+            GameStatus::Drawn(_) => "drawn at the move limit".to_string(),
+        }
+    }
+}
 
-    /// Safe squares (cannot be captured)
-    const SAFE_SQUARES: u32 = (1 << 0) | (1 << 4) | (1 << 9) | (1 << 14) | (1 << 18);
+impl FastGameState {
+    /// Bits per packed piece position
+    const POS_BITS: u8 = 5;
+    const POS_MASK: u128 = (1 << Self::POS_BITS) - 1;
 
     pub fn new() -> Self {
         FastGameState {
@@ -75,6 +230,90 @@ impl FastGameState {
         }
     }
 
+    /// Build a state from explicit piece positions (0=OffBoard, 1-N=OnBoard(0-(N-1)), N+1=Finished)
+    /// Occupancy and scores are derived; an error if the position is impossible.
+    pub fn from_piece_positions(p1: [u8; 7], p2: [u8; 7], to_move: FastPlayer) -> Result<Self, UrError> {
+        let mut game = FastGameState::new();
+        if to_move == FastPlayer::Two {
+            game.scores_and_turn |= 1 << 6;
+        }
+
+        for (player, positions) in [(FastPlayer::One, p1), (FastPlayer::Two, p2)] {
+            let player_offset = Self::square_offset(player);
+            let mut finished = 0;
+
+            for (piece_idx, &pos) in positions.iter().enumerate() {
+                match pos {
+                    0 => {}
+                    _ if Self::is_on_board(pos) => {
+                        let square = Self::path_to_global(player, pos - 1);
+                        // Squares can hold only one piece of either colour
+                        if game.get_occupant(square).is_some() {
+                            return Err(UrError::InvalidState(format!("two pieces on path position {} of {}", pos - 1, player.name())));
+                        }
+                        game.occupied_squares |= 1u64 << (square + player_offset);
+                    }
+                    _ if pos == Self::finished_pos() => finished += 1,
+                    _ => return Err(UrError::InvalidState(format!("{}'s piece {} is past the end of its path", player.name(), piece_idx))),
+                }
+                game.set_piece_pos(player, piece_idx as u8, pos);
+            }
+            game.set_score(player, finished);
+        }
+
+        Ok(game)
+    }
+
+    /// Key of the position up to which piece is which: for each player a
+    /// bitmask of the path squares they occupy (bits 0-26) and how many pieces
+    /// they have borne off (bits 27-29), Player 2 30 bits above Player 1, and
+    /// the side to move at bit 60. Positions that differ only in which of a
+    /// player's pieces stands where share a key. Paths of up to 27 squares fit,
+    /// which covers every board.
+    pub fn canonical_key(self) -> u64 {
+        let mut key = 0;
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            let mut half = (self.get_score(player) as u64) << Self::KEY_PATH_BITS;
+            for piece_idx in 0..7 {
+                let pos = self.get_piece_pos(player, piece_idx);
+                if Self::is_on_board(pos) {
+                    half |= 1 << (pos - 1);
+                }
+            }
+            key |= half << (player as u32 * Self::KEY_PLAYER_BITS);
+        }
+        key | (((self.scores_and_turn >> 6) & 1) as u64) << (2 * Self::KEY_PLAYER_BITS)
+    }
+
+    /// The position with `key`, each player's pieces numbered in path order
+    /// (waiting pieces first, finished ones last); an error if no position has it
+    pub fn from_canonical_key(key: u64) -> Result<Self, UrError> {
+        let mut pieces = [[0; 7]; 2];
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            let half = key >> (player as u32 * Self::KEY_PLAYER_BITS);
+            let path = half & ((1 << Self::KEY_PATH_BITS) - 1);
+            let finished = ((half >> Self::KEY_PATH_BITS) & 0x7) as usize;
+            let on_board = path.count_ones() as usize;
+            if on_board + finished > 7 || path >> Self::path_len() != 0 {
+                return Err(UrError::InvalidState(format!("key {:#x} has no position", key)));
+            }
+            let slots = &mut pieces[player as usize];
+            let mut next = 7 - on_board - finished;
+            for pos in 1..=Self::path_len() {
+                if (path >> (pos - 1)) & 1 != 0 {
+                    slots[next] = pos;
+                    next += 1;
+                }
+            }
+            slots[next..].fill(Self::finished_pos());
+        }
+        let to_move = if (key >> (2 * Self::KEY_PLAYER_BITS)) & 1 == 0 { FastPlayer::One } else { FastPlayer::Two };
+        Self::from_piece_positions(pieces[0], pieces[1], to_move)
+    }
+
+    const KEY_PATH_BITS: u32 = 27;
+    const KEY_PLAYER_BITS: u32 = Self::KEY_PATH_BITS + 3;
+
     /// Get current player
     #[inline]
     pub fn current_player(self) -> FastPlayer {
@@ -107,43 +346,66 @@ impl FastGameState {
         }
     }
 
-    /// Get piece position (0=OffBoard, 1-14=OnBoard(0-13), 15=Finished)
+    /// Get piece position (0=OffBoard, 1-N=OnBoard(0-(N-1)), N+1=Finished)
     #[inline]
     pub fn get_piece_pos(self, player: FastPlayer, piece_idx: u8) -> u8 {
-        let shift = match player {
-            FastPlayer::One => piece_idx * 4,
-            FastPlayer::Two => 28 + piece_idx * 4,
-        };
-        ((self.piece_positions >> shift) & 0xF) as u8
+        let shift = Self::pos_shift(player, piece_idx);
+        ((self.piece_positions >> shift) & Self::POS_MASK) as u8
     }
 
     /// Set piece position
     #[inline]
     pub fn set_piece_pos(&mut self, player: FastPlayer, piece_idx: u8, pos: u8) {
-        let shift = match player {
-            FastPlayer::One => piece_idx * 4,
-            FastPlayer::Two => 28 + piece_idx * 4,
-        };
-        let mask = !(0xF << shift);
-        self.piece_positions = (self.piece_positions & mask) | ((pos as u64 & 0xF) << shift);
+        let shift = Self::pos_shift(player, piece_idx);
+        let mask = !(Self::POS_MASK << shift);
+        self.piece_positions = (self.piece_positions & mask) | ((pos as u128 & Self::POS_MASK) << shift);
+    }
+
+    #[inline]
+    fn pos_shift(player: FastPlayer, piece_idx: u8) -> u8 {
+        (player as u8 * 7 + piece_idx) * Self::POS_BITS
+    }
+
+    /// First occupancy bit of a player's half of the bitboard
+    #[inline]
+    fn square_offset(player: FastPlayer) -> u8 {
+        player as u8 * MAX_SQUARES
+    }
+
+    /// Squares on each player's path for the selected board
+    #[inline]
+    pub fn path_len() -> u8 {
+        board::layout().paths[0].len() as u8
+    }
+
+    /// Position value of a piece that has borne off
+    #[inline]
+    pub fn finished_pos() -> u8 {
+        Self::path_len() + 1
+    }
+
+    /// Whether a position value is a square on the path
+    #[inline]
+    pub fn is_on_board(pos: u8) -> bool {
+        pos >= 1 && pos <= Self::path_len()
     }
 
     /// Path to global square conversion
     #[inline]
     pub fn path_to_global(player: FastPlayer, path_idx: u8) -> u8 {
-        Self::PATHS[player as usize][path_idx as usize]
+        board::layout().paths[player as usize][path_idx as usize]
     }
 
     /// Check if square is a rosette
     #[inline]
     pub fn is_rosette(square: u8) -> bool {
-        (Self::ROSETTES >> square) & 1 != 0
+        (board::rosettes() >> square) & 1 != 0
     }
 
     /// Check if square is safe
     #[inline]
     pub fn is_safe(square: u8) -> bool {
-        (Self::SAFE_SQUARES >> square) & 1 != 0
+        (board::safe() >> square) & 1 != 0
     }
 
     /// Check if square is occupied and by whom
@@ -151,45 +413,50 @@ impl FastGameState {
     pub fn get_occupant(self, square: u8) -> Option<FastPlayer> {
         if (self.occupied_squares >> square) & 1 != 0 {
             Some(FastPlayer::One)
-        } else if (self.occupied_squares >> (square + 20)) & 1 != 0 {
+        } else if (self.occupied_squares >> (square + MAX_SQUARES)) & 1 != 0 {
             Some(FastPlayer::Two)
         } else {
             None
         }
     }
 
-    /// Make a move and return undo information
-    pub fn make_move(&mut self, piece_idx: u8, roll: u8) -> Option<MoveInfo> {
+    /// Make a move and return undo information, or why the move is illegal
+    pub fn make_move(&mut self, piece_idx: u8, roll: u8) -> Result<MoveInfo, MoveError> {
+        if roll == 0 {
+            return Err(MoveError::NoRoll);
+        }
+        if piece_idx >= 7 {
+            return Err(MoveError::NoSuchPiece);
+        }
         let player = self.current_player();
         let from_pos = self.get_piece_pos(player, piece_idx);
 
         let to_pos = match from_pos {
             0 => 1,  // Off board to path position 0 (encoded as 1)
-            1..=14 => {
+            _ if Self::is_on_board(from_pos) => {
                 let path_idx = from_pos - 1;
                 let new_path_idx = path_idx + roll;
-                if new_path_idx >= 14 {
-                    15  // Finished
-                } else {
-                    new_path_idx + 1  // On board (encoded as path_idx + 1)
+                match new_path_idx.cmp(&Self::path_len()) {
+                    Ordering::Equal => Self::finished_pos(),
+                    Ordering::Greater => return Err(MoveError::Overshoot),
+                    Ordering::Less => new_path_idx + 1,  // On board (encoded as path_idx + 1)
                 }
             }
-            15 => return None,  // Already finished
-            _ => return None,
+            _ => return Err(MoveError::Finished),
         };
 
         // Validate move
         let mut captured_piece = None;
-        if to_pos >= 1 && to_pos <= 14 {
+        if Self::is_on_board(to_pos) {
             let target_square = Self::path_to_global(player, to_pos - 1);
             match self.get_occupant(target_square) {
-                Some(occupant) if occupant == player => return None,
-                Some(_) if Self::is_safe(target_square) => return None,
+                Some(occupant) if occupant == player => return Err(MoveError::OwnPiece),
+                Some(_) if Self::is_safe(target_square) => return Err(MoveError::Protected),
                 Some(_) => {
                     // Capture
                     for i in 0..7 {
                         let opp_pos = self.get_piece_pos(player.opposite(), i);
-                        if opp_pos >= 1 && opp_pos <= 14 {
+                        if Self::is_on_board(opp_pos) {
                             let opp_square = Self::path_to_global(player.opposite(), opp_pos - 1);
                             if opp_square == target_square {
                                 captured_piece = Some(i);
@@ -202,7 +469,7 @@ impl FastGameState {
             }
         }
 
-        let extra_turn = to_pos >= 1 && to_pos <= 14 &&
+        let extra_turn = Self::is_on_board(to_pos) &&
                         Self::is_rosette(Self::path_to_global(player, to_pos - 1));
 
         let move_info = MoveInfo {
@@ -216,18 +483,26 @@ impl FastGameState {
         // Apply the move
         self.apply_move_internal(player, &move_info);
 
-        Some(move_info)
+        Ok(move_info)
+    }
+
+    /// `make_move`, with the error naming the move
+    pub fn play(&mut self, piece_idx: u8, roll: u8) -> Result<MoveInfo, UrError> {
+        self.make_move(piece_idx, roll).map_err(|reason| UrError::IllegalMove { piece: piece_idx, roll, reason })
+    }
+
+    /// Why `piece_idx` cannot move `roll`, if it cannot
+    pub fn check_move(self, piece_idx: u8, roll: u8) -> Result<(), MoveError> {
+        let mut after = self;
+        after.make_move(piece_idx, roll).map(|_| ())
     }
 
     /// Apply move to the board
     fn apply_move_internal(&mut self, player: FastPlayer, move_info: &MoveInfo) {
-        let player_offset = match player {
-            FastPlayer::One => 0,
-            FastPlayer::Two => 20,
-        };
+        let player_offset = Self::square_offset(player);
 
         // Remove from old position
-        if move_info.from_pos >= 1 && move_info.from_pos <= 14 {
+        if Self::is_on_board(move_info.from_pos) {
             let old_square = Self::path_to_global(player, move_info.from_pos - 1);
             self.occupied_squares &= !(1u64 << (old_square + player_offset));
         }
@@ -235,10 +510,7 @@ impl FastGameState {
         // Handle capture
         if let Some(cap_piece) = move_info.captured_piece {
             let opp_player = player.opposite();
-            let opp_offset = match opp_player {
-                FastPlayer::One => 0,
-                FastPlayer::Two => 20,
-            };
+            let opp_offset = Self::square_offset(opp_player);
             let cap_pos = self.get_piece_pos(opp_player, cap_piece);
             let cap_square = Self::path_to_global(opp_player, cap_pos - 1);
 
@@ -249,10 +521,10 @@ impl FastGameState {
         // Set new position
         self.set_piece_pos(player, move_info.piece_idx, move_info.to_pos);
 
-        if move_info.to_pos >= 1 && move_info.to_pos <= 14 {
+        if Self::is_on_board(move_info.to_pos) {
             let new_square = Self::path_to_global(player, move_info.to_pos - 1);
             self.occupied_squares |= 1u64 << (new_square + player_offset);
-        } else if move_info.to_pos == 15 {
+        } else if move_info.to_pos == Self::finished_pos() {
             // Update score
             let current_score = self.get_score(player);
             self.set_score(player, current_score + 1);
@@ -266,16 +538,13 @@ impl FastGameState {
 
     /// Unmake a move (restore previous state)
     pub fn unmake_move(&mut self, player: FastPlayer, move_info: &MoveInfo) {
-        let player_offset = match player {
-            FastPlayer::One => 0,
-            FastPlayer::Two => 20,
-        };
+        let player_offset = Self::square_offset(player);
 
         // Remove from current position
-        if move_info.to_pos >= 1 && move_info.to_pos <= 14 {
+        if Self::is_on_board(move_info.to_pos) {
             let square = Self::path_to_global(player, move_info.to_pos - 1);
             self.occupied_squares &= !(1u64 << (square + player_offset));
-        } else if move_info.to_pos == 15 {
+        } else if move_info.to_pos == Self::finished_pos() {
             // Undo score
             let current_score = self.get_score(player);
             self.set_score(player, current_score - 1);
@@ -283,7 +552,7 @@ impl FastGameState {
 
         // Restore to old position
         self.set_piece_pos(player, move_info.piece_idx, move_info.from_pos);
-        if move_info.from_pos >= 1 && move_info.from_pos <= 14 {
+        if Self::is_on_board(move_info.from_pos) {
             let old_square = Self::path_to_global(player, move_info.from_pos - 1);
             self.occupied_squares |= 1u64 << (old_square + player_offset);
         }
@@ -291,10 +560,7 @@ impl FastGameState {
         // Restore captured piece
         if let Some(cap_piece) = move_info.captured_piece {
             let opp_player = player.opposite();
-            let opp_offset = match opp_player {
-                FastPlayer::One => 0,
-                FastPlayer::Two => 20,
-            };
+            let opp_offset = Self::square_offset(opp_player);
 
             // Find where it was captured
             let cap_square = Self::path_to_global(player, move_info.to_pos - 1);
@@ -316,14 +582,51 @@ impl FastGameState {
         self.get_score(player) >= 7
     }
 
+    /// Won by whoever has brought every piece home, else in progress
+    #[inline]
+    pub fn status(self) -> GameStatus {
+        if self.is_winner(FastPlayer::One) {
+            GameStatus::Won(FastPlayer::One)
+        } else if self.is_winner(FastPlayer::Two) {
+            GameStatus::Won(FastPlayer::Two)
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    /// The result when a game is stopped at its move limit
+    pub fn status_at_move_limit(self) -> GameStatus {
+        self.status_on_position(EndReason::MoveLimit)
+    }
+
+    /// The result when a game is decided before its end for `reason`: the side
+    /// ahead on score, then on how far its pieces have come, wins; an exact tie
+    /// is drawn
+    pub fn status_on_position(self, reason: EndReason) -> GameStatus {
+        let standing = |player| {
+            let progress: u32 = (0..7).map(|idx| self.get_piece_pos(player, idx) as u32).sum();
+            (self.get_score(player), progress)
+        };
+        match standing(FastPlayer::One).cmp(&standing(FastPlayer::Two)) {
+            Ordering::Greater => GameStatus::Adjudicated(FastPlayer::One, reason),
+            Ordering::Less => GameStatus::Adjudicated(FastPlayer::Two, reason),
+            Ordering::Equal => GameStatus::Drawn(reason),
+        }
+    }
+
     /// Generate all valid moves for current player with given roll
     pub fn generate_moves(self, roll: u8) -> Vec<u8> {
+        self.generate_move_list(roll).to_vec()
+    }
+
+    /// Allocation-free variant of generate_moves for hot loops
+    pub fn generate_move_list(self, roll: u8) -> MoveList {
+        let mut moves = MoveList::new();
         if roll == 0 {
-            return vec![];
+            return moves;
         }
 
         let player = self.current_player();
-        let mut moves = Vec::with_capacity(7);
 
         for piece_idx in 0..7 {
             let pos = self.get_piece_pos(player, piece_idx);
@@ -336,24 +639,23 @@ impl FastGameState {
                         moves.push(piece_idx);
                     }
                 }
-                1..=14 => {
+                _ if Self::is_on_board(pos) => {
                     let path_idx = pos - 1;
                     let new_path_idx = path_idx + roll;
 
-                    if new_path_idx == 14 {
+                    if new_path_idx == Self::path_len() {
                         // Exact move to finish
                         moves.push(piece_idx);
-                    } else if new_path_idx < 14 {
+                    } else if new_path_idx < Self::path_len() {
                         let target_square = Self::path_to_global(player, new_path_idx);
                         if self.can_move_to(player, target_square) {
                             moves.push(piece_idx);
                         }
                     }
                 }
-                15 => {
+                _ => {
                     // Already finished
                 }
-                _ => {}
             }
         }
 
@@ -369,21 +671,19 @@ impl FastGameState {
         }
     }
 
-    /// Roll dice (same as original)
-    pub fn roll_dice() -> u8 {
-        use rand::Rng;
-        let mut rng = rand::rng();
-        let mut total = 0;
-        for _ in 0..4 {
-            if rng.random_bool(0.5) {
-                total += 1;
-            }
-        }
-        total
+    /// Roll `player`'s dice with the thread RNG
+    pub fn roll_dice(player: FastPlayer) -> u8 {
+        Self::roll_dice_with(player, &mut rand::rng())
+    }
+
+    /// Roll `player`'s dice using the given RNG
+    #[inline]
+    pub fn roll_dice_with<R: RngCore>(player: FastPlayer, rng: &mut R) -> u8 {
+        dice::model_for(player).roll(rng)
     }
 
     fn global_to_path(player: FastPlayer, global: u8) -> u8 {
-        for (i, &square) in Self::PATHS[player as usize].iter().enumerate() {
+        for (i, &square) in board::layout().paths[player as usize].iter().enumerate() {
             if square == global {
                 return i as u8;
             }
@@ -405,8 +705,8 @@ impl fmt::Display for FastGameState {
                 let pos = self.get_piece_pos(player, piece_idx);
                 let desc = match pos {
                     0 => "OffBoard".to_string(),
-                    1..=14 => format!("OnBoard({})", pos - 1),
-                    15 => "Finished".to_string(),
+                    _ if Self::is_on_board(pos) => format!("OnBoard({})", pos - 1),
+                    _ if pos == Self::finished_pos() => "Finished".to_string(),
                     _ => "Invalid".to_string(),
                 };
                 writeln!(f, "    Piece {}: {}", piece_idx, desc)?;
@@ -416,3 +716,177 @@ impl fmt::Display for FastGameState {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ai::HybridAI;
+    use crate::ai_helpers::{choose_random_move_fast, choose_smart_move_fast};
+    use crate::cancel::CancelToken;
+    use crate::referee::{Clock, Contestant, Referee, Violation};
+    use crate::rng::GameRng;
+    use crate::state_repr::GameStateRepr;
+    use crate::stats::StatsAgent;
+
+    /// Turns after which a game is taken to be stuck
+    const MAX_TURNS: usize = 5000;
+
+    /// Check what has to hold after each turn `play` makes: scores count the
+    /// pieces home and never go down, every piece on the board is recorded on
+    /// its square, and no two pieces share a square
+    fn check_turn(before: &FastGameState, after: &FastGameState) {
+        let mut occupied = Vec::new();
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            let home = (0..7).filter(|&idx| after.get_piece_pos(player, idx) == FastGameState::finished_pos()).count();
+            assert_eq!(after.get_score(player) as usize, home, "{}'s score is not its pieces home", player.name());
+            assert!(after.get_score(player) >= before.get_score(player), "{}'s score went down", player.name());
+            for idx in 0..7 {
+                let pos = after.get_piece_pos(player, idx);
+                assert!(pos <= FastGameState::finished_pos(), "{}'s piece {} is past home", player.name(), idx);
+                if FastGameState::is_on_board(pos) {
+                    let square = FastGameState::path_to_global(player, pos - 1);
+                    assert_eq!(after.get_occupant(square), Some(player));
+                    occupied.push(square);
+                }
+            }
+        }
+        let on_board = occupied.len();
+        occupied.sort_unstable();
+        occupied.dedup();
+        assert_eq!(occupied.len(), on_board, "two pieces share a square");
+    }
+
+    /// Play a game from the start with the dice from `seed` through the move
+    /// API alone (`generate_moves`, `check_move`, `make_move`, `pass_turn`),
+    /// `choose` picking every move, checking each turn; the final position.
+    /// `refereed_games_keep_the_invariants` plays whole games the way matches do
+    fn play(seed: u64, mut choose: impl FnMut(&FastGameState, &[u8], u8) -> u8) -> FastGameState {
+        let mut dice = GameRng::from_seed(seed);
+        let mut game = FastGameState::new();
+        for _ in 0..MAX_TURNS {
+            if game.status().is_over() {
+                return game;
+            }
+            let before = game;
+            let roll = dice.roll_dice(game.current_player());
+            let moves = game.generate_moves(roll);
+            for piece in 0..7 {
+                assert_eq!(game.check_move(piece, roll).is_ok(), moves.contains(&piece), "piece {} with {} in {}", piece, roll, game);
+            }
+            if moves.is_empty() {
+                game.pass_turn();
+            } else {
+                let piece = choose(&game, &moves, roll);
+                assert!(moves.contains(&piece), "chose piece {} from {:?}", piece, moves);
+                let info = game.make_move(piece, roll).expect("generated moves are legal");
+                assert_eq!(game.current_player() == before.current_player(), info.extra_turn);
+            }
+            check_turn(&before, &game);
+        }
+        panic!("the game with seed {} did not end within {} turns", seed, MAX_TURNS);
+    }
+
+    /// The game was won outright: the winner has every piece home and the loser does not
+    fn check_finished(game: &FastGameState) {
+        let GameStatus::Won(winner) = game.status() else {
+            panic!("game ended as {:?}", game.status());
+        };
+        assert!((0..7).all(|idx| game.get_piece_pos(winner, idx) == FastGameState::finished_pos()));
+        assert!(game.get_score(winner.opposite()) < 7);
+    }
+
+    #[test]
+    fn illegal_moves_say_why() {
+        let mut game = FastGameState::new();
+        assert_eq!(game.check_move(0, 0), Err(MoveError::NoRoll));
+        assert_eq!(game.check_move(7, 2), Err(MoveError::NoSuchPiece));
+        game.make_move(0, 2).unwrap();
+        game.pass_turn();
+        assert_eq!(game.check_move(1, 2), Err(MoveError::OwnPiece));
+
+        let last = FastGameState::path_len();
+        game.set_piece_pos(FastPlayer::One, 2, last);
+        game.set_piece_pos(FastPlayer::One, 3, FastGameState::finished_pos());
+        assert_eq!(game.check_move(2, 1), Ok(()));
+        assert_eq!(game.check_move(2, 2), Err(MoveError::Overshoot));
+        assert_eq!(game.check_move(3, 1), Err(MoveError::Finished));
+    }
+
+    #[test]
+    fn random_games_end_with_pieces_conserved() {
+        for seed in 0..50 {
+            check_finished(&play(seed, |_, moves, _| choose_random_move_fast(moves)));
+        }
+    }
+
+    #[test]
+    fn smart_against_random_games_end_with_pieces_conserved() {
+        for seed in 0..20 {
+            check_finished(&play(seed, |game, moves, roll| match game.current_player() {
+                FastPlayer::One => choose_smart_move_fast(game, FastPlayer::One, moves, roll),
+                FastPlayer::Two => choose_random_move_fast(moves),
+            }));
+        }
+    }
+
+    #[test]
+    fn mcts_against_smart_games_end_with_pieces_conserved() {
+        let mcts = HybridAI::new_with_threads(50, 1);
+        let cancel = CancelToken::new();
+        for seed in 0..3 {
+            check_finished(&play(seed, |game, moves, roll| match game.current_player() {
+                FastPlayer::One => mcts.choose_move_cancellable(game, FastPlayer::One, roll, &cancel).expect("there are moves").0,
+                FastPlayer::Two => choose_smart_move_fast(game, FastPlayer::Two, moves, roll),
+            }));
+        }
+    }
+
+    /// A player that keeps every position its moves lead to, in a log both seats share
+    struct Watched {
+        agent: StatsAgent,
+        positions: Rc<RefCell<Vec<FastGameState>>>,
+    }
+
+    impl Contestant for Watched {
+        fn label(&self) -> String {
+            self.agent.label()
+        }
+
+        fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation> {
+            let piece = Contestant::request_move(&mut self.agent, game, moves, roll, allowed)?;
+            let mut after = *game;
+            after.make_move(piece as u8, roll).expect("the agent picks from the legal moves");
+            self.positions.borrow_mut().push(after);
+            Ok(piece)
+        }
+    }
+
+    #[test]
+    fn refereed_games_keep_the_invariants() {
+        let log = std::env::temp_dir().join(format!("ur-refereed-games-{}.log", std::process::id()));
+        let mut referee = Referee::new(Clock { per_move: Duration::from_secs(10), bank: None }, &log);
+        for (seed, specs) in (0..20).zip([["smart", "random"], ["random", "smart"]].into_iter().cycle()) {
+            let positions = Rc::new(RefCell::new(Vec::new()));
+            let [mut one, mut two] = specs.map(|spec| Watched { agent: StatsAgent::from_spec(spec, 1).unwrap(), positions: Rc::clone(&positions) });
+            let adjudication = referee.play_game([&mut one, &mut two], &mut GameRng::from_seed(seed), "test");
+            assert!(adjudication.violation.is_none(), "seed {}: {}", seed, adjudication.reason());
+
+            // Positions between two moves differ only by the other side's moves and lost turns
+            let positions = positions.borrow();
+            assert_eq!(positions.len(), adjudication.moves);
+            let mut before = FastGameState::new();
+            for after in positions.iter() {
+                check_turn(&before, after);
+                before = *after;
+            }
+            check_finished(&before);
+            assert_eq!(adjudication.status, before.status());
+        }
+        assert_eq!(referee.violations, 0);
+        let _ = std::fs::remove_file(&log);
+    }
+}
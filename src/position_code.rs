@@ -0,0 +1,125 @@
+//! Short shareable position codes such as `UR:05dq0000000k8v2`: the packed piece
+//! positions, side to move and board of a `FastGameState` in 15 base32 characters.
+//! Scores and occupancy are derived from the pieces, so they are not stored.
+
+use crate::board::{self, BoardVariant};
+use crate::error::UrError;
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+pub const CODE_PREFIX: &str = "UR:";
+
+/// Crockford's base32 alphabet: no i, l, o or u to misread
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+/// 70 bits of piece positions, 1 for the side to move, 4 for the board
+const CODE_CHARS: usize = 15;
+const PIECE_BITS: u32 = 70;
+const TURN_BIT: u32 = 70;
+const BOARD_SHIFT: u32 = 71;
+/// Board field of a position on the custom board, whichever one it was
+const CUSTOM_BOARD: usize = 15;
+
+/// Code for `game` on the current board
+pub fn encode(game: &FastGameState) -> String {
+    let board_idx = BoardVariant::ALL.iter().position(|&v| v == board::variant()).unwrap_or(CUSTOM_BOARD) as u128;
+    let turn = match game.current_player() {
+        FastPlayer::One => 0,
+        FastPlayer::Two => 1,
+    };
+    let value = game.piece_positions | (turn << TURN_BIT) | (board_idx << BOARD_SHIFT);
+
+    let mut code = String::from(CODE_PREFIX);
+    for idx in (0..CODE_CHARS).rev() {
+        code.push(ALPHABET[((value >> (idx * 5)) & 31) as usize] as char);
+    }
+    code
+}
+
+/// Whether `text` looks like a position code rather than, say, a file name
+pub fn is_code(text: &str) -> bool {
+    text.get(..CODE_PREFIX.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(CODE_PREFIX))
+}
+
+/// Position for a code; it must have been made on the current board
+pub fn decode(code: &str) -> Result<FastGameState, UrError> {
+    let body = if is_code(code) { &code[CODE_PREFIX.len()..] } else { code };
+    if body.len() != CODE_CHARS {
+        return Err(UrError::parse(format!("a position code has {} characters after '{}'", CODE_CHARS, CODE_PREFIX)));
+    }
+
+    let mut value: u128 = 0;
+    for c in body.chars() {
+        // Accept the letters Crockford's alphabet leaves out as the digits they resemble
+        let c = match c.to_ascii_lowercase() {
+            'o' => '0',
+            'i' | 'l' => '1',
+            c => c,
+        };
+        let digit = ALPHABET.iter().position(|&a| a as char == c).ok_or_else(|| UrError::parse(format!("'{}' is not a code character", c)))?;
+        value = (value << 5) | digit as u128;
+    }
+
+    let board = match (value >> BOARD_SHIFT) as usize {
+        CUSTOM_BOARD => BoardVariant::Custom,
+        idx => BoardVariant::ALL.get(idx).copied().ok_or_else(|| UrError::parse("unknown board in position code"))?,
+    };
+    if board == BoardVariant::Custom && board::variant() != board {
+        return Err(UrError::InvalidState("this position is on a custom board; restart with --board and its layout file".to_string()));
+    }
+    if board != board::variant() {
+        return Err(UrError::InvalidState(format!("this position is on the {} board; restart with --board {}", board.label(), board.label())));
+    }
+
+    // Read the piece fields back through the packed state's own accessors
+    let packed = FastGameState { piece_positions: value & ((1 << PIECE_BITS) - 1), ..FastGameState::new() };
+    let pieces = [FastPlayer::One, FastPlayer::Two].map(|player| {
+        let mut positions = [0u8; 7];
+        for (piece_idx, pos) in positions.iter_mut().enumerate() {
+            *pos = packed.get_piece_pos(player, piece_idx as u8);
+        }
+        positions
+    });
+    let turn = if (value >> TURN_BIT) & 1 == 0 { FastPlayer::One } else { FastPlayer::Two };
+
+    FastGameState::from_piece_positions(pieces[0], pieces[1], turn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_round_trip() {
+        let start = FastGameState::new();
+        assert_eq!(encode(&start), "UR:000000000000000");
+        assert_eq!(decode("ur:OOOOOOOOOOOOOOO").unwrap(), start);
+
+        let home = FastGameState::finished_pos();
+        let finished = FastGameState::from_piece_positions([home; 7], [home, home, 3, 0, 0, 9, 0], FastPlayer::Two).unwrap();
+        let two_to_move = FastGameState::from_piece_positions([1, 0, 0, 5, 0, 0, home], [0, 2, 0, 0, 13, 0, 0], FastPlayer::Two).unwrap();
+        for game in [finished, two_to_move] {
+            let code = encode(&game);
+            assert_eq!(decode(&code).unwrap(), game, "{}", code);
+            assert_eq!(decode(&code[CODE_PREFIX.len()..].to_uppercase()).unwrap(), game);
+        }
+        assert_eq!(decode(&encode(&finished)).unwrap().status().winner(), Some(FastPlayer::One));
+        assert_eq!(decode(&encode(&two_to_move)).unwrap().current_player(), FastPlayer::Two);
+    }
+
+    #[test]
+    fn bad_codes_are_rejected() {
+        let code_for = |pieces: &[(FastPlayer, u8, u8)]| {
+            let mut game = FastGameState::new();
+            for &(player, idx, pos) in pieces {
+                game.set_piece_pos(player, idx, pos);
+            }
+            encode(&game)
+        };
+        let shared_square = code_for(&[(FastPlayer::One, 0, 4), (FastPlayer::One, 1, 4)]);
+        let past_home = code_for(&[(FastPlayer::Two, 6, FastGameState::finished_pos() + 1)]);
+        for code in ["UR:00000000000000u", "UR:00000000000000!", "UR:0000", "UR:0000000000000000", "", &shared_square, &past_home] {
+            assert!(decode(code).is_err(), "{:?} was accepted", code);
+        }
+        // Board fields between the built-in boards and the custom board name no board
+        assert!(decode("UR:a00000000000000").unwrap_err().to_string().contains("unknown board"));
+    }
+}
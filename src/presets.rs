@@ -0,0 +1,243 @@
+//! Named MCTS presets. A few are built in; more can be defined in `ur_presets.txt`
+//! in the working directory, one per line in the form
+//!   `mcts-fast: 2000 sims, 4 threads`
+//!   `mcts-deep: 60s/move`
+//!   `mcts-quick: auto 300ms/move`
+//! A preset's name is used for the AI in output and on the leaderboard.
+
+use std::f64::consts::SQRT_2;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::ai::{HybridAI, PlayoutPolicy};
+use crate::autotune;
+use crate::error::UrError;
+use crate::input::{prompt_line, InputResult};
+use crate::transcript::outln;
+
+pub const PRESETS_FILE: &str = "ur_presets.txt";
+/// Preset for MCTS opponents in interactive play
+pub const PLAY_PRESET: &str = "mcts";
+/// Preset for MCTS players in stats mode
+pub const STATS_PRESET: &str = "mcts-stats";
+/// Preset for MCTS players in demo mode
+pub const DEMO_PRESET: &str = "mcts-demo";
+/// Think time per move of the interactive preset
+const PLAY_THINK_TIME: Duration = Duration::from_millis(250);
+/// Built-in presets and their budgets
+const BUILTIN: [(&str, Budget); 3] = [
+    (PLAY_PRESET, Budget::Auto(PLAY_THINK_TIME)),
+    (STATS_PRESET, Budget::SimulationsPerThread(400)),
+    (DEMO_PRESET, Budget::SimulationsPerThread(500)),
+];
+
+/// How long a preset searches for each move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Simulations(usize),
+    /// Simulations for each search thread, so more threads search more
+    SimulationsPerThread(usize),
+    Time(Duration),
+    /// As many simulations as take about this long here, found by
+    /// `autotune` when the AI is built; unlike `Time`, every move of a game
+    /// then gets the same number
+    Auto(Duration),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub budget: Budget,
+    /// Search threads; None leaves it to the mode, which uses every core but
+    /// one in interactive play and all of them elsewhere
+    pub threads: Option<usize>,
+    pub exploration: f64,
+    pub risk: f64,
+    pub opponent_model: PlayoutPolicy,
+    pub progressive_widening: bool,
+}
+
+impl Preset {
+    fn builtin(name: &str, budget: Budget) -> Self {
+        Preset {
+            name: name.to_string(),
+            budget,
+            threads: None,
+            exploration: SQRT_2,
+            risk: 0.0,
+            opponent_model: PlayoutPolicy::Mixed,
+            progressive_widening: false,
+        }
+    }
+
+    /// Parse a `name: setting, setting` line. Settings are `N sims`,
+    /// `N sims per thread`, `Ns/move` or `Nms/move`, `auto Ns/move` or
+    /// `auto Nms/move`, `N threads`, `exploration C`,
+    /// `risk R`, `opponent random|smart|mixed` and `widening`.
+    pub fn parse(line: &str) -> Result<Self, UrError> {
+        let (name, settings) = line.split_once(':').ok_or_else(|| UrError::parse("expected 'name: settings'"))?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(UrError::parse(format!("'{}' is not a preset name (one word, no spaces)", name)));
+        }
+        let mut preset = Preset::builtin(name, Budget::SimulationsPerThread(1000));
+        let mut budget = None;
+
+        for setting in settings.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let words: Vec<&str> = setting.split_whitespace().collect();
+            let number = |text: &str| text.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0)
+                .ok_or_else(|| UrError::parse(format!("'{}' is not a number in '{}'", text, setting)));
+            let think_time = |time: &str| {
+                let time = time.trim_end_matches("/move");
+                if let Some(ms) = time.strip_suffix("ms") {
+                    Ok(Duration::from_secs_f64(number(ms)? / 1000.0))
+                } else if let Some(secs) = time.strip_suffix('s') {
+                    Ok(Duration::from_secs_f64(number(secs)?))
+                } else {
+                    Err(UrError::parse(format!("'{}' needs a unit, e.g. 500ms/move or 60s/move", setting)))
+                }
+            };
+            match words.as_slice() {
+                [n, "sims"] => budget = Some(Budget::Simulations(number(n)? as usize)),
+                [n, "sims", "per", "thread"] => budget = Some(Budget::SimulationsPerThread(number(n)? as usize)),
+                [n, "threads" | "thread"] => preset.threads = Some((number(n)? as usize).max(1)),
+                ["exploration", c] => preset.exploration = number(c)?,
+                ["risk", r] => {
+                    preset.risk = r.parse::<f64>().map_err(|_| UrError::parse(format!("'{}' is not a number", r)))?.clamp(-1.0, 1.0)
+                }
+                ["opponent", model] => {
+                    preset.opponent_model = match *model {
+                        "mixed" => PlayoutPolicy::Mixed,
+                        "random" => PlayoutPolicy::Random,
+                        "smart" => PlayoutPolicy::Smart,
+                        _ => return Err(UrError::parse(format!("unknown opponent model '{}'", model))),
+                    }
+                }
+                ["widening"] => preset.progressive_widening = true,
+                [time] if time.ends_with("/move") => budget = Some(Budget::Time(think_time(time)?)),
+                ["auto", time] if time.ends_with("/move") => budget = Some(Budget::Auto(think_time(time)?)),
+                _ => return Err(UrError::parse(format!("unknown setting '{}'", setting))),
+            }
+        }
+
+        preset.budget = budget.ok_or_else(|| UrError::parse(format!("preset '{}' needs a budget such as '2000 sims' or '60s/move'", name)))?;
+        Ok(preset)
+    }
+
+    /// The preset's settings in the file syntax
+    pub fn describe(&self) -> String {
+        let mut parts = vec![match self.budget {
+            Budget::Simulations(n) => format!("{} sims", n),
+            Budget::SimulationsPerThread(n) => format!("{} sims per thread", n),
+            Budget::Time(budget) => format!("{}ms/move", budget.as_millis()),
+            Budget::Auto(think) => format!("auto {}ms/move", think.as_millis()),
+        }];
+        if let Some(threads) = self.threads {
+            parts.push(format!("{} threads", threads));
+        }
+        if self.exploration != SQRT_2 {
+            parts.push(format!("exploration {}", self.exploration));
+        }
+        if self.risk != 0.0 {
+            parts.push(format!("risk {}", self.risk));
+        }
+        if self.opponent_model != PlayoutPolicy::Mixed {
+            parts.push(format!("opponent {}", self.opponent_model.label()));
+        }
+        if self.progressive_widening {
+            parts.push("widening".to_string());
+        }
+        parts.join(", ")
+    }
+
+    /// Build the AI, using `default_threads` unless the preset fixes the thread count
+    pub fn build(&self, default_threads: usize) -> HybridAI {
+        self.build_scaled(default_threads, 1.0)
+    }
+
+    /// Like `build`, with the search budget multiplied by `scale` (for difficulty levels)
+    pub fn build_scaled(&self, default_threads: usize, scale: f64) -> HybridAI {
+        let threads = self.threads.unwrap_or(default_threads).max(1);
+        let scaled = |n: usize| ((n as f64 * scale).round() as usize).max(1);
+        let mut ai = match self.budget {
+            Budget::Simulations(n) => HybridAI::new_with_threads(scaled(n), threads),
+            Budget::SimulationsPerThread(n) => HybridAI::new_with_threads(scaled(n * threads), threads),
+            Budget::Time(budget) => HybridAI::new_with_time_budget(budget.mul_f64(scale), threads),
+            Budget::Auto(think) => HybridAI::new_with_threads(scaled(autotune::simulations_for(think, threads)), threads),
+        };
+        ai.mcts.exploration_constant = self.exploration;
+        ai.mcts.progressive_widening = self.progressive_widening;
+        ai.name = Some(if scale == 1.0 { self.name.clone() } else { format!("{}-x{}", self.name, scale) });
+        ai.with_risk(self.risk).with_opponent_model(self.opponent_model)
+    }
+}
+
+/// Built-in presets followed by those in the presets file; a file preset with
+/// a built-in name replaces it
+fn load() -> Result<Vec<Preset>, UrError> {
+    let mut presets = builtins();
+    let text = match fs::read_to_string(PRESETS_FILE) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(presets),
+        Err(e) => return Err(e.into()),
+    };
+    for (idx, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let preset = Preset::parse(line).map_err(|e| e.at_line(idx + 1))?;
+        presets.retain(|p| p.name != preset.name);
+        presets.push(preset);
+    }
+    Ok(presets)
+}
+
+/// Every available preset; a broken presets file is reported once and ignored
+pub fn all() -> &'static [Preset] {
+    static PRESETS: OnceLock<Vec<Preset>> = OnceLock::new();
+    PRESETS.get_or_init(|| load().unwrap_or_else(|e| {
+        eprintln!("⚠️  Ignoring {}: {}", PRESETS_FILE, e);
+        builtins()
+    }))
+}
+
+fn builtins() -> Vec<Preset> {
+    BUILTIN.iter().map(|&(name, budget)| Preset::builtin(name, budget)).collect()
+}
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+    all().iter().find(|preset| preset.name == name)
+}
+
+/// Preset named `name`, or an error listing the available ones
+pub fn lookup(name: &str) -> Result<&'static Preset, UrError> {
+    find(name).ok_or_else(|| {
+        let names: Vec<&str> = all().iter().map(|p| p.name.as_str()).collect();
+        UrError::parse(format!("unknown preset '{}' (available: {})", name, names.join(", ")))
+    })
+}
+
+/// Let the user pick a preset when the presets file adds any; otherwise `default`
+pub fn choose(prompt: &str, default: &str) -> InputResult<&'static Preset> {
+    let default_preset = find(default).expect("built-in preset");
+    if all() == builtins().as_slice() {
+        return Ok(default_preset);
+    }
+    outln!("{}", prompt);
+    for preset in all() {
+        outln!("  {:<14} {}", preset.name, preset.describe());
+    }
+    loop {
+        let name = prompt_line(&format!("Preset (default {}): ", default))?;
+        if name.is_empty() {
+            return Ok(default_preset);
+        }
+        match lookup(&name) {
+            Ok(preset) => return Ok(preset),
+            Err(e) => outln!("❌ {}", e),
+        }
+    }
+}
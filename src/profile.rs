@@ -0,0 +1,238 @@
+//! Player profile for rated games (`--rated`): a Glicko-2 rating that moves
+//! after every finished game against a calibrated AI, one whose strength is
+//! known from its rating on the leaderboard, and the games behind it.
+//! `ur profile` shows the rating and charts its course.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::args::Flags;
+use crate::error::UrError;
+use crate::leaderboard::{config_name, Leaderboard, DEFAULT_LEADERBOARD_FILE};
+use crate::rating::Glicko;
+use crate::screen::{Align, Screen, Span, Table};
+use crate::transcript::outln;
+
+pub const DEFAULT_PROFILE_FILE: &str = "ur_profile.txt";
+
+const PROFILE_HEADER: &str = "# Royal Game of Ur player profile";
+const PROFILE_VERSION: u32 = 1;
+/// Standard error of a leaderboard rating times the square root of the games
+/// it rests on, for evenly matched games
+const CALIBRATION_SPREAD: f64 = 347.0;
+/// Least deviation given an AI's rating, however many games it rests on
+const MIN_AI_DEVIATION: f64 = 30.0;
+/// Rows and most recent games in the rating chart
+const CHART_HEIGHT: usize = 10;
+const CHART_GAMES: usize = 60;
+/// Rated games listed under the chart
+const RECENT_GAMES: usize = 10;
+
+/// One rated game and the rating it left the player with
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatedGame {
+    /// The AI's leaderboard name
+    pub opponent: String,
+    /// The AI's rating and deviation when the game was played
+    pub opponent_rating: Glicko,
+    pub won: bool,
+    pub after: Glicko,
+}
+
+/// Every rated game, oldest first
+#[derive(Debug, Default, PartialEq)]
+pub struct Profile {
+    games: Vec<RatedGame>,
+}
+
+impl Profile {
+    /// Read a profile; a missing file is a player with no rated games yet
+    pub fn load(path: &Path) -> Result<Self, UrError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Profile::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// The rating after the last rated game
+    pub fn rating(&self) -> Glicko {
+        self.games.last().map(|game| game.after).unwrap_or_default()
+    }
+
+    /// Rate a game against `opponent`, rated `opponent_rating`
+    pub fn add_game(&mut self, opponent: &str, opponent_rating: Glicko, won: bool) -> &RatedGame {
+        let after = self.rating().update(&[(opponent_rating, if won { 1.0 } else { 0.0 })]);
+        self.games.push(RatedGame { opponent: opponent.to_string(), opponent_rating, won, after });
+        self.games.last().expect("just added")
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(PROFILE_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", PROFILE_VERSION));
+        for game in &self.games {
+            out.push_str(&format!("game {} {:.2} {:.2} {} {:.2} {:.2} {:.6}\n",
+                                  game.opponent, game.opponent_rating.rating, game.opponent_rating.deviation,
+                                  if game.won { "won" } else { "lost" },
+                                  game.after.rating, game.after.deviation, game.after.volatility));
+        }
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut profile = Profile::default();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let err = |message: String| UrError::ParseError { line: idx + 1, message };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["version", v] => {
+                    let v: u32 = v.parse().map_err(|_| err("expected a version number".to_string()))?;
+                    if v != PROFILE_VERSION {
+                        return Err(err(format!("unsupported profile version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                ["game", opponent, opponent_rating, opponent_deviation, result, rating, deviation, volatility] => {
+                    let won = match *result {
+                        "won" => true,
+                        "lost" => false,
+                        _ => return Err(err(format!("result must be 'won' or 'lost', not '{}'", result))),
+                    };
+                    let numbers: Vec<f64> = [opponent_rating, opponent_deviation, rating, deviation, volatility]
+                        .iter().filter_map(|v| v.parse().ok()).collect();
+                    let [opponent_rating, opponent_deviation, rating, deviation, volatility] = numbers[..] else {
+                        return Err(err("ratings, deviations and volatility must be numbers".to_string()));
+                    };
+                    profile.games.push(RatedGame {
+                        opponent: opponent.to_string(),
+                        opponent_rating: Glicko { rating: opponent_rating, deviation: opponent_deviation, ..Glicko::default() },
+                        won,
+                        after: Glicko { rating, deviation, volatility },
+                    });
+                }
+                _ => return Err(err(format!("unrecognized line '{}'", line))),
+            }
+        }
+
+        version.ok_or_else(|| UrError::parse("missing 'version'"))?;
+        Ok(profile)
+    }
+}
+
+/// Rating of the AI configuration `label` on the leaderboard for the current
+/// board and dice, with a deviation that shrinks with the games it rests on
+pub fn calibrated_rating(label: &str) -> Result<Glicko, String> {
+    let name = config_name(label);
+    let leaderboard = Leaderboard::load(Path::new(DEFAULT_LEADERBOARD_FILE))
+        .map_err(|e| format!("could not read {}: {}", DEFAULT_LEADERBOARD_FILE, e))?;
+    let standing = leaderboard.standings().into_iter().find(|standing| standing.name == name)
+        .ok_or_else(|| format!("{} has no rating on the leaderboard yet (stats mode, matches and the experiments give it one)", name))?;
+    let deviation = (CALIBRATION_SPREAD / (standing.games as f64).sqrt()).clamp(MIN_AI_DEVIATION, Glicko::default().deviation);
+    Ok(Glicko { rating: standing.rating, deviation, ..Glicko::default() })
+}
+
+/// Add a finished rated game to the profile at `path` and show the new rating
+pub fn record_rated_game(path: &Path, opponent: &str, opponent_rating: Glicko, won: bool) {
+    let updated = Profile::load(path).and_then(|mut profile| {
+        let before = profile.rating();
+        let after = profile.add_game(opponent, opponent_rating, won).after;
+        profile.save(path)?;
+        Ok((before, after, profile.games.len()))
+    });
+    match updated {
+        Ok((before, after, games)) => outln!("📈 Rating {:.0} ± {:.0} ({:+.0}) after {} rated game{}",
+                                             after.rating, after.deviation, after.rating - before.rating,
+                                             games, if games == 1 { "" } else { "s" }),
+        Err(e) => eprintln!("⚠️  Could not update {}: {}", path.display(), e),
+    }
+}
+
+/// Options for `ur profile [--file FILE]`
+pub struct ProfileOptions {
+    pub file: PathBuf,
+}
+
+impl ProfileOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--file"], &[])?;
+        Ok(ProfileOptions {
+            file: PathBuf::from(flags.get_str("--file").unwrap_or(DEFAULT_PROFILE_FILE)),
+        })
+    }
+}
+
+pub fn run_profile(options: &ProfileOptions) -> Result<(), UrError> {
+    let profile = Profile::load(&options.file)?;
+    println!("=== PLAYER PROFILE ({}) ===", options.file.display());
+    if profile.games.is_empty() {
+        println!("No rated games yet. Play with --rated against an AI on the leaderboard to get a rating.");
+        return Ok(());
+    }
+
+    let rating = profile.rating();
+    let wins = profile.games.iter().filter(|game| game.won).count();
+    let best = profile.games.iter().map(|game| game.after.rating).fold(f64::NEG_INFINITY, f64::max);
+    println!("Rating {:.0} ± {:.0} (volatility {:.3}) after {} rated games: {} won, {} lost; best {:.0}",
+             rating.rating, rating.deviation, rating.volatility, profile.games.len(), wins, profile.games.len() - wins, best);
+    println!();
+
+    // The chart starts from the rating before its first game
+    let first_charted = profile.games.len().saturating_sub(CHART_GAMES);
+    let start = if first_charted == 0 { Glicko::default() } else { profile.games[first_charted - 1].after };
+    println!("RATING HISTORY (last {} games):", profile.games.len() - first_charted);
+    let ratings: Vec<f64> = std::iter::once(start.rating)
+        .chain(profile.games[first_charted..].iter().map(|game| game.after.rating))
+        .collect();
+    print_chart(&ratings);
+    println!();
+
+    println!("RECENT GAMES:");
+    let mut table = Table::new(&[Align::Right, Align::Left, Align::Right, Align::Left, Align::Right, Align::Right], 2);
+    table.row(["#", "Opponent", "Their rating", "Result", "Rating", "Change"].map(Span::plain).to_vec());
+    let first_recent = profile.games.len().saturating_sub(RECENT_GAMES);
+    for (idx, game) in profile.games.iter().enumerate().skip(first_recent) {
+        let before = if idx == 0 { Glicko::default() } else { profile.games[idx - 1].after };
+        table.row(vec![
+            Span::plain((idx + 1).to_string()),
+            Span::plain(game.opponent.as_str()),
+            Span::plain(format!("{:.0}", game.opponent_rating.rating)),
+            Span::plain(if game.won { "won" } else { "lost" }),
+            Span::plain(format!("{:.0} ± {:.0}", game.after.rating, game.after.deviation)),
+            Span::plain(format!("{:+.0}", game.after.rating - before.rating)),
+        ]);
+    }
+    let mut screen = Screen::new();
+    screen.table(&table, 2);
+    screen.print();
+    println!();
+    println!("Ratings are Glicko-2, on the leaderboard's scale; ± is the rating deviation.");
+    Ok(())
+}
+
+/// Plot one column per rating, scaled between the lowest and highest
+fn print_chart(ratings: &[f64]) {
+    let min = ratings.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = ratings.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(1.0);
+    let row_of = |rating: f64| ((rating - min) / range * (CHART_HEIGHT - 1) as f64).round() as usize;
+
+    for row in (0..CHART_HEIGHT).rev() {
+        let level = min + range * row as f64 / (CHART_HEIGHT - 1) as f64;
+        let points: String = ratings.iter().map(|&rating| if row_of(rating) == row { '●' } else { ' ' }).collect();
+        println!("  {:>5.0} ┤{}", level, points);
+    }
+    println!("        └{}", "─".repeat(ratings.len()));
+}
@@ -0,0 +1,121 @@
+//! Rating helpers: Elo, shared by the experiments and the adaptive AI, and
+//! Glicko-2 for the rated games of player profiles.
+
+use std::f64::consts::PI;
+
+/// Rating change per game for the adaptive AI's estimate of a human
+pub const K_FACTOR: f64 = 32.0;
+
+/// Expected score of a player rated `rating` against one rated `opponent`
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Elo difference implied by an expected score in (0, 1)
+pub fn elo_from_score(score: f64) -> f64 {
+    let score = score.clamp(0.001, 0.999);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// New rating after scoring `score` (1 win, 0 loss) against `opponent`
+pub fn update_rating(rating: f64, opponent: f64, score: f64) -> f64 {
+    rating + K_FACTOR * (score - expected_score(rating, opponent))
+}
+
+/// Glicko-2 works on its own scale; this converts between it and ratings
+const GLICKO_SCALE: f64 = 173.7178;
+/// How much volatility may change in one rating period
+const GLICKO_TAU: f64 = 0.5;
+/// Precision the new volatility is found to
+const GLICKO_EPSILON: f64 = 0.000001;
+
+/// A Glicko-2 rating, on the same scale as Elo ratings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko {
+    pub rating: f64,
+    /// How far off the rating may be: about a 95% chance the true strength
+    /// is within twice this of it
+    pub deviation: f64,
+    /// How erratic the player's results are
+    pub volatility: f64,
+}
+
+impl Default for Glicko {
+    /// An unrated player
+    fn default() -> Self {
+        Glicko { rating: 1500.0, deviation: 350.0, volatility: 0.06 }
+    }
+}
+
+impl Glicko {
+    /// The rating after one rating period with `results`, each an opponent
+    /// and the score against them (1 win, 0 loss); there must be at least one
+    pub fn update(self, results: &[(Glicko, f64)]) -> Glicko {
+        let mu = (self.rating - 1500.0) / GLICKO_SCALE;
+        let phi = self.deviation / GLICKO_SCALE;
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+        // The sums behind the estimated variance and improvement
+        let (mut information, mut gain) = (0.0, 0.0);
+        for (opponent, score) in results {
+            let g_j = g(opponent.deviation / GLICKO_SCALE);
+            let expected = 1.0 / (1.0 + (-g_j * (mu - (opponent.rating - 1500.0) / GLICKO_SCALE)).exp());
+            information += g_j * g_j * expected * (1.0 - expected);
+            gain += g_j * (score - expected);
+        }
+        let variance = 1.0 / information;
+        let delta = variance * gain;
+
+        // The new volatility is the root of f, found by the Illinois method
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi * phi + variance + ex;
+            ex * (delta * delta - phi * phi - variance - ex) / (2.0 * d * d) - (x - a) / (GLICKO_TAU * GLICKO_TAU)
+        };
+        let mut low = a;
+        let mut high = if delta * delta > phi * phi + variance {
+            (delta * delta - phi * phi - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * GLICKO_TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * GLICKO_TAU
+        };
+        let (mut f_low, mut f_high) = (f(low), f(high));
+        while (high - low).abs() > GLICKO_EPSILON {
+            let next = low + (low - high) * f_low / (f_high - f_low);
+            let f_next = f(next);
+            if f_next * f_high <= 0.0 {
+                (low, f_low) = (high, f_high);
+            } else {
+                f_low /= 2.0;
+            }
+            (high, f_high) = (next, f_next);
+        }
+        let volatility = (low / 2.0).exp();
+
+        let new_phi = 1.0 / (1.0 / (phi * phi + volatility * volatility) + 1.0 / variance).sqrt();
+        Glicko {
+            rating: (mu + new_phi * new_phi * gain) * GLICKO_SCALE + 1500.0,
+            deviation: new_phi * GLICKO_SCALE,
+            volatility,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glicko_matches_the_worked_example() {
+        // The example in Glickman's description of Glicko-2
+        let player = Glicko { rating: 1500.0, deviation: 200.0, volatility: 0.06 };
+        let opponent = |rating, deviation| Glicko { rating, deviation, ..Glicko::default() };
+        let after = player.update(&[(opponent(1400.0, 30.0), 1.0), (opponent(1550.0, 100.0), 0.0), (opponent(1700.0, 300.0), 0.0)]);
+        assert!((after.rating - 1464.06).abs() < 0.01, "rating {}", after.rating);
+        assert!((after.deviation - 151.52).abs() < 0.01, "deviation {}", after.deviation);
+        assert!((after.volatility - 0.05999).abs() < 0.00001, "volatility {}", after.volatility);
+    }
+}
@@ -0,0 +1,187 @@
+//! `ur reachability`: enumerate the positions reachable from the start by a
+//! breadth-first search over every roll and move, with positions that differ
+//! only in which piece is which counted once (`FastGameState::canonical_key`).
+//! The counts by score and by pieces on the board describe the state space a
+//! solver or endgame table would have to cover. With `--mirror` a position and
+//! its mirror image (see `symmetry`) count once.
+
+use std::collections::HashSet;
+use std::mem;
+use std::time::Instant;
+
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::dice::{self, DiceVariant};
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::resources::{format_bytes, resident_bytes, ResourceMeter};
+use crate::screen::{Align, Screen, Span, Table};
+use crate::state_repr::GameStateRepr;
+use crate::symmetry::{canonical, symmetric_key};
+
+pub struct ReachabilityOptions {
+    /// Stop once this many positions have been found
+    pub max_states: usize,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    /// Count positions up to swapping the players
+    pub mirror: bool,
+    /// Stop once the process holds this many bytes of memory
+    pub max_memory: Option<u64>,
+}
+
+impl ReachabilityOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--max-states", "--max-memory", "--board", "--dice"], &["--mirror"])?;
+        Ok(ReachabilityOptions {
+            max_states: flags.get("--max-states", 20_000_000)?.max(1),
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            mirror: flags.has("--mirror"),
+            max_memory: flags.get_opt::<u64>("--max-memory")?.map(|mb| mb * 1024 * 1024),
+        })
+    }
+}
+
+/// Positions found between memory checks
+const MEMORY_CHECK_INTERVAL: usize = 1 << 16;
+
+/// Why a search stopped before finding every position
+enum Limit {
+    States,
+    Memory(u64),
+}
+
+/// Positions found, broken down
+struct Census {
+    /// By Player 1's and Player 2's score
+    by_score: [[usize; 8]; 8],
+    /// By Player 1's and Player 2's pieces on the board
+    by_on_board: [[usize; 8]; 8],
+    /// Positions where someone has won, which lead nowhere
+    terminal: usize,
+}
+
+impl Census {
+    fn add(&mut self, game: &FastGameState) {
+        let on_board = |player| (0..7).filter(|&idx| FastGameState::is_on_board(game.get_piece_pos(player, idx))).count();
+        self.by_score[game.get_score(FastPlayer::One) as usize][game.get_score(FastPlayer::Two) as usize] += 1;
+        self.by_on_board[on_board(FastPlayer::One)][on_board(FastPlayer::Two)] += 1;
+        if game.status().is_over() {
+            self.terminal += 1;
+        }
+    }
+}
+
+/// Every position one roll and move (or a lost turn) after `game`
+fn successors(game: FastGameState, rolls: &[u8], out: &mut Vec<FastGameState>) {
+    for &roll in rolls {
+        let moves = if roll == 0 { Default::default() } else { game.generate_move_list(roll) };
+        if moves.is_empty() {
+            let mut passed = game;
+            passed.pass_turn();
+            out.push(passed);
+        }
+        for &piece in moves.iter() {
+            let mut next = game;
+            next.make_move(piece, roll).expect("generated moves are legal");
+            out.push(next);
+        }
+    }
+}
+
+pub fn run_reachability(options: &ReachabilityOptions) {
+    board::select(options.board);
+    dice::select(options.dice);
+    if options.mirror && !board::layout().is_mirror_symmetric() {
+        println!("❌ The {} board's paths differ between the players, so --mirror cannot be used", options.board.label());
+        return;
+    }
+    let rolls: Vec<u8> = (0..=dice::model().max_roll()).filter(|&roll| dice::model().probability(roll) > 0.0).collect();
+
+    println!("=== REACHABLE STATE SPACE ===");
+    println!("Board {} | dice {} | up to {} positions, pieces not told apart{}",
+             options.board.label(), options.dice.label(), options.max_states,
+             if options.mirror { ", nor the players" } else { "" });
+    println!();
+
+    // With --mirror every position is stored, and counted, with Player 1 to move
+    let key_of = |game: &FastGameState| if options.mirror { symmetric_key(game) } else { game.canonical_key() };
+    let start_time = Instant::now();
+    let start = FastGameState::new();
+    let mut seen: HashSet<u64> = HashSet::from([key_of(&start)]);
+    let mut census = Census { by_score: [[0; 8]; 8], by_on_board: [[0; 8]; 8], terminal: 0 };
+    census.add(&start);
+    let mut frontier = vec![key_of(&start)];
+    let mut next_frontier = Vec::new();
+    let mut children = Vec::new();
+    let (mut depth, mut widest) = (0, 1);
+    let mut meter = ResourceMeter::new();
+    let mut stopped = None;
+
+    'search: while !frontier.is_empty() {
+        for &key in &frontier {
+            let game = FastGameState::from_canonical_key(key).expect("key of a real position");
+            if game.status().is_over() {
+                continue;
+            }
+            children.clear();
+            successors(game, &rolls, &mut children);
+            for child in &children {
+                let child_key = key_of(child);
+                if seen.insert(child_key) {
+                    census.add(&if options.mirror { canonical(child).0 } else { *child });
+                    next_frontier.push(child_key);
+                    if seen.len() >= options.max_states {
+                        stopped = Some(Limit::States);
+                        break 'search;
+                    }
+                    if let Some(max_memory) = options.max_memory
+                        && seen.len().is_multiple_of(MEMORY_CHECK_INTERVAL)
+                        && let Some(resident) = resident_bytes()
+                        && resident >= max_memory
+                    {
+                        stopped = Some(Limit::Memory(resident));
+                        break 'search;
+                    }
+                }
+            }
+        }
+        if next_frontier.is_empty() {
+            break;
+        }
+        depth += 1;
+        widest = widest.max(next_frontier.len());
+        println!("  depth {:>3}: {:>11} new, {:>12} total | {}", depth, next_frontier.len(), seen.len(), meter.summary());
+        frontier = mem::take(&mut next_frontier);
+    }
+
+    println!();
+    match stopped {
+        None => println!("Complete: {} reachable positions ({} terminal), every one within {} moves of the start; widest layer {}",
+                         seen.len(), census.terminal, depth, widest),
+        Some(Limit::States) => println!("Stopped at --max-states {} after {} full layers; the counts below are a lower bound",
+                                        options.max_states, depth),
+        Some(Limit::Memory(resident)) => println!("Stopped at --max-memory with {} in use and {} positions found after {} full layers; the counts below are a lower bound",
+                                                  format_bytes(resident), seen.len(), depth),
+    }
+    println!("Search took {:.1}s", start_time.elapsed().as_secs_f64());
+
+    for (title, counts) in [("BY SCORE", &census.by_score), ("BY PIECES ON THE BOARD", &census.by_on_board)] {
+        println!();
+        if options.mirror {
+            println!("{} (rows the side to move, columns the other side):", title);
+        } else {
+            println!("{} (rows Player 1, columns Player 2):", title);
+        }
+        let mut table = Table::new(&[Align::Right; 9], 2);
+        table.row(std::iter::once(Span::plain("")).chain((0..8).map(|n| Span::plain(n.to_string()))).collect());
+        for (p1, row) in counts.iter().enumerate() {
+            let mut cells = vec![Span::plain(p1.to_string())];
+            cells.extend(row.iter().map(|&count| Span::plain(if count == 0 { "-".to_string() } else { count.to_string() })));
+            table.row(cells);
+        }
+        let mut screen = Screen::new();
+        screen.table(&table, 2);
+        screen.print();
+    }
+}
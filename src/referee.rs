@@ -0,0 +1,644 @@
+//! Referee for games between players that are not trusted to follow the rules:
+//! external bots, scripts, and anything else that plays through `Contestant`.
+//! Unlike the interactive loop in main.rs, which takes every AI's move as
+//! given, the referee rolls the dice, checks each submitted move against
+//! `generate_moves`, times every move against the clock, writes each violation
+//! to a log and decides the result. A violation forfeits the game. With fair
+//! dice (see `fair_dice`) the referee rolls from both seats' nonces instead.
+
+use std::cell::Cell;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use crate::archive::Turn;
+use crate::args::Flags;
+use crate::board::{self, BoardVariant};
+use crate::cancel::CancelToken;
+use crate::dice::{self, DiceVariant};
+use crate::fair_dice::{self, Commitment, DiceLog, FairRoll, Nonce, DEFAULT_DICE_LOG};
+use crate::leaderboard::{record_match, DEFAULT_LEADERBOARD_FILE};
+use crate::optimized_game::{EndReason, FastGameState, FastPlayer, GameStatus, MoveError};
+use crate::position_code;
+use crate::rng::GameRng;
+use crate::scripting::ScriptAI;
+use crate::state_repr::GameStateRepr;
+use crate::stats::StatsAgent;
+use crate::subprocess_bot::ExternalBot;
+
+pub const DEFAULT_REFEREE_LOG: &str = "ur_referee.log";
+/// Extra time for each player's first move of a game, so process or
+/// interpreter start-up is not held against it
+pub const STARTUP_GRACE: Duration = Duration::from_secs(2);
+/// Moves after which a game is adjudicated instead of played out
+const MAX_MOVES: usize = 2000;
+
+/// A breach of the rules that forfeits the game
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// No move within the time allowed
+    Timeout(Duration),
+    /// A piece that cannot move with this roll, and why
+    IllegalMove(i64, MoveError),
+    /// A reply that breaks the protocol, and how
+    BadReply(String),
+    /// The player crashed, exited or could not be started
+    Failed(String),
+    /// A fair dice nonce that does not hash to the commitment made for it
+    BrokenCommitment,
+}
+
+impl Violation {
+    /// `piece`, a reply to `roll` in `game`, is not one of the legal moves
+    pub fn illegal_move(game: &FastGameState, piece: i64, roll: u8) -> Self {
+        let reason = u8::try_from(piece).map_err(|_| MoveError::NoSuchPiece)
+            .and_then(|piece| game.check_move(piece, roll))
+            .err()
+            .unwrap_or(MoveError::NoSuchPiece);
+        Violation::IllegalMove(piece, reason)
+    }
+
+    /// The ending it forces: on time for a timeout, by forfeit otherwise
+    pub fn end_reason(&self) -> EndReason {
+        match self {
+            Violation::Timeout(_) => EndReason::Timeout,
+            _ => EndReason::Forfeit,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Timeout(allowed) => write!(f, "no move within {} ms", allowed.as_millis()),
+            Violation::IllegalMove(piece, reason) => write!(f, "piece {} cannot move: {}", piece, reason),
+            Violation::BadReply(problem) => write!(f, "{}", problem),
+            Violation::Failed(reason) => write!(f, "{}", reason),
+            Violation::BrokenCommitment => write!(f, "revealed a nonce that does not match its commitment"),
+        }
+    }
+}
+
+/// Time control: a limit per move and, optionally, a bank for the whole game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    pub per_move: Duration,
+    pub bank: Option<Duration>,
+}
+
+/// A player the referee can run a game with
+pub trait Contestant {
+    /// Name in reports, logs and on the leaderboard
+    fn label(&self) -> String;
+
+    /// Called before each game; `session` is the game's token, the same
+    /// when the player is reconnected
+    fn start_game(&mut self, _seat: FastPlayer, _clock: &Clock, _session: &str) -> Result<(), Violation> {
+        Ok(())
+    }
+
+    /// The piece this player moves, which the referee checks; `allowed` is how
+    /// long it has
+    fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation>;
+
+    /// Called after each game
+    fn end_game(&mut self, _status: GameStatus, _reason: &str) {}
+
+    /// Fair dice: this player's commitment for roll `number`, or None to
+    /// leave its share to the referee, as in-process players do
+    fn commit_dice(&mut self, _number: u64, _allowed: Duration) -> Result<Option<Commitment>, Violation> {
+        Ok(None)
+    }
+
+    /// Fair dice: the nonce behind this player's commitment, asked for once
+    /// both seats' commitments are in
+    fn reveal_dice(&mut self, _number: u64, _commitments: &[Commitment; 2], _allowed: Duration) -> Result<Nonce, Violation> {
+        Err(Violation::Failed("made no commitment to reveal".to_string()))
+    }
+
+    /// Fair dice: a roll and the nonces it came from, so the player can check it
+    fn show_roll(&mut self, _roll: &FairRoll) {}
+
+    /// Start a player that dropped out again and bring it up to date with the
+    /// game so far; in-process players never drop out
+    fn resume(&mut self, _resync: &Resync) -> Result<(), Violation> {
+        Err(Violation::Failed("cannot be reconnected".to_string()))
+    }
+}
+
+/// What a player that dropped out needs to pick its game up again
+pub struct Resync<'a> {
+    pub session: &'a str,
+    pub seat: FastPlayer,
+    pub clock: &'a Clock,
+    /// What is left of the player's bank, if the clock has one
+    pub bank: Option<Duration>,
+    /// Every turn so far, lost ones included
+    pub turns: &'a [Turn],
+    pub game: &'a FastGameState,
+}
+
+impl Resync<'_> {
+    /// The turns as a JSON array of `[side, roll, piece]`, the piece null for a lost turn
+    pub fn history(&self) -> String {
+        let turns: Vec<String> = self.turns.iter()
+            .map(|turn| format!("[{},{},{}]", turn.player as usize + 1, turn.roll, turn.piece.map_or("null".to_string(), |piece| piece.to_string())))
+            .collect();
+        format!("[{}]", turns.join(","))
+    }
+
+    /// SHA-256 of `history()`, which the player echoes to show it has every turn
+    pub fn history_hash(&self) -> String {
+        fair_dice::to_hex(&Sha256::digest(self.history()).into())
+    }
+}
+
+impl Contestant for StatsAgent {
+    fn label(&self) -> String {
+        StatsAgent::label(self)
+    }
+
+    /// A search still running near the end of the time allowed is cut short,
+    /// so in-process AIs move in time whatever their preset's budget; the last
+    /// tenth of the time is left for the search threads to wind down
+    fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation> {
+        let cancel = CancelToken::expiring_at(Instant::now() + allowed.mul_f64(0.9));
+        Ok(self.choose_move_cancellable(game, moves, roll, &cancel).0 as i64)
+    }
+}
+
+impl Contestant for ScriptAI {
+    fn label(&self) -> String {
+        ScriptAI::label(self)
+    }
+
+    fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, _allowed: Duration) -> Result<i64, Violation> {
+        self.try_choose_move(game, moves, roll).map_err(|e| Violation::Failed(format!("script error: {}", e)))
+    }
+}
+
+/// How a refereed game ended
+#[derive(Debug, Clone)]
+pub struct Adjudication {
+    pub status: GameStatus,
+    /// What the loser did, when it broke the rules
+    pub violation: Option<Violation>,
+    pub moves: usize,
+}
+
+impl Adjudication {
+    pub fn reason(&self) -> String {
+        match (self.status, &self.violation) {
+            (GameStatus::Adjudicated(winner, _), Some(violation)) => format!("{} forfeits: {}", winner.opposite().name(), violation),
+            (GameStatus::Adjudicated(_, EndReason::Stopped) | GameStatus::Drawn(EndReason::Stopped), _) => {
+                format!("stopped after {} moves and decided on the position", self.moves)
+            }
+            (GameStatus::Adjudicated(..), None) => format!("adjudicated after {} moves", self.moves),
+            (GameStatus::Drawn(_), _) => format!("drawn after {} moves", self.moves),
+            _ => "all pieces home".to_string(),
+        }
+    }
+}
+
+/// Runs games under the rules and keeps the violation log
+pub struct Referee {
+    pub clock: Clock,
+    log: PathBuf,
+    /// Violations logged so far
+    pub violations: usize,
+    /// Times a player may be reconnected in each game after dropping out
+    reconnects: usize,
+    /// Reconnections made so far
+    pub reconnections: usize,
+    /// Where fair dice rolls are logged, when the dice are fair
+    dice_log: Option<DiceLog>,
+    /// Cancelled to stop games early, each decided on its position
+    stop: Option<CancelToken>,
+    /// The current game's fair dice rolls
+    fair_rolls: Vec<FairRoll>,
+    /// The current game's session token, turns, moves played and what is left of each seat's bank and reconnects
+    session: String,
+    turns: Vec<Turn>,
+    moves: usize,
+    bank: [Option<Duration>; 2],
+    reconnects_left: [usize; 2],
+}
+
+impl Referee {
+    pub fn new(clock: Clock, log: &Path) -> Self {
+        Referee {
+            clock,
+            log: log.to_path_buf(),
+            violations: 0,
+            reconnects: 0,
+            reconnections: 0,
+            dice_log: None,
+            stop: None,
+            fair_rolls: Vec::new(),
+            session: String::new(),
+            turns: Vec::new(),
+            moves: 0,
+            bank: [None; 2],
+            reconnects_left: [0; 2],
+        }
+    }
+
+    /// Reconnect a player that drops out (a bot that exits or cannot be
+    /// reached) up to `reconnects` times a game instead of forfeiting it
+    pub fn with_reconnects(self, reconnects: usize) -> Self {
+        Referee { reconnects, ..self }
+    }
+
+    /// Roll fair dice by commit-reveal instead of from the seeded dice, and log every roll
+    pub fn with_fair_dice(self, dice_log: DiceLog) -> Self {
+        Referee { dice_log: Some(dice_log), ..self }
+    }
+
+    /// Stop the game once `stop` is cancelled, deciding it on the position
+    /// before the next roll
+    pub fn with_stop(self, stop: CancelToken) -> Self {
+        Referee { stop: Some(stop), ..self }
+    }
+
+    /// Play one game from the opening position; `game_name` identifies it in the log
+    pub fn play_game(&mut self, mut seats: [&mut dyn Contestant; 2], dice: &mut GameRng, game_name: &str) -> Adjudication {
+        let labels = [seats[0].label(), seats[1].label()];
+        let mut game = FastGameState::new();
+        self.fair_rolls.clear();
+        self.session = fair_dice::to_hex(&fair_dice::fresh_nonce());
+        self.turns.clear();
+        self.moves = 0;
+        self.bank = [self.clock.bank; 2];
+        self.reconnects_left = [self.reconnects; 2];
+
+        let adjudication = self.run(&mut seats, &mut game, dice, game_name).unwrap_or_else(|(seat, violation, context)| {
+            self.log_violation(game_name, seat, &labels[seat as usize], &violation, &context);
+            Adjudication { status: GameStatus::Adjudicated(seat.opposite(), violation.end_reason()), violation: Some(violation), moves: self.moves }
+        });
+        let reason = adjudication.reason();
+        for seat in seats.iter_mut() {
+            seat.end_game(adjudication.status, &reason);
+        }
+        if let Some(Err(e)) = self.dice_log.as_mut().map(|log| log.write_game(game_name, &self.fair_rolls)) {
+            eprintln!("⚠️  Could not write to the dice log: {}", e);
+        }
+        adjudication
+    }
+
+    /// The game's result, or the seat that broke the rules with what it did and where
+    fn run(
+        &mut self,
+        seats: &mut [&mut dyn Contestant; 2],
+        game: &mut FastGameState,
+        dice: &mut GameRng,
+        game_name: &str,
+    ) -> Result<Adjudication, (FastPlayer, Violation, String)> {
+        for seat in [FastPlayer::One, FastPlayer::Two] {
+            let (clock, session) = (self.clock, self.session.clone());
+            self.attempt(&mut *seats[seat as usize], seat, game, game_name, |player| player.start_game(seat, &clock, &session))
+                .map_err(|v| (seat, v, "at the start of the game".to_string()))?;
+        }
+        let mut first_move = [true; 2];
+
+        loop {
+            let status = game.status();
+            if status.is_over() {
+                return Ok(Adjudication { status, violation: None, moves: self.moves });
+            }
+            if self.moves >= MAX_MOVES {
+                return Ok(Adjudication { status: game.status_at_move_limit(), violation: None, moves: self.moves });
+            }
+            if self.stop.as_ref().is_some_and(CancelToken::is_cancelled) {
+                return Ok(Adjudication { status: game.status_on_position(EndReason::Stopped), violation: None, moves: self.moves });
+            }
+            let mover = game.current_player();
+            let roll = if self.dice_log.is_some() {
+                self.fair_roll(seats, mover, game, game_name)?
+            } else {
+                dice.roll_dice(mover)
+            };
+            let moves = game.generate_moves(roll);
+            if moves.is_empty() {
+                game.pass_turn();
+                self.turns.push(Turn { player: mover, roll, piece: None });
+                continue;
+            }
+
+            let idx = mover as usize;
+            let grace = if first_move[idx] { STARTUP_GRACE } else { Duration::ZERO };
+            first_move[idx] = false;
+            let limit = self.bank[idx].map_or(self.clock.per_move, |left| left.min(self.clock.per_move));
+            let context = format!("roll {}, moves {:?}, {}", roll, moves, position_code::encode(game));
+
+            // A reconnected player's time runs from when it was asked again
+            let start = Cell::new(Instant::now());
+            let submitted = self.attempt(&mut *seats[idx], mover, game, game_name, |player| {
+                start.set(Instant::now());
+                player.request_move(game, &moves, roll, limit + grace)
+            });
+            let used = start.get().elapsed().saturating_sub(grace);
+            let piece = submitted.map_err(|v| match v {
+                // The contestant's own timer included the grace period
+                Violation::Timeout(_) => (mover, Violation::Timeout(limit), context.clone()),
+                v => (mover, v, context.clone()),
+            })?;
+            if used > limit {
+                return Err((mover, Violation::Timeout(limit), context));
+            }
+            if let Some(left) = self.bank[idx].as_mut() {
+                *left = left.saturating_sub(used);
+            }
+            let Some(piece) = moves.iter().copied().find(|&m| m as i64 == piece) else {
+                return Err((mover, Violation::illegal_move(game, piece, roll), context));
+            };
+            game.make_move(piece, roll).expect("found among the legal moves");
+            self.turns.push(Turn { player: mover, roll, piece: Some(piece) });
+            self.moves += 1;
+        }
+    }
+
+    /// Roll `mover`'s dice from both seats' nonces: each seat commits, then
+    /// reveals once both commitments are in, and the referee checks the nonces
+    /// against the commitments. The seats are shown the roll and its nonces.
+    fn fair_roll(
+        &mut self,
+        seats: &mut [&mut dyn Contestant; 2],
+        mover: FastPlayer,
+        game: &FastGameState,
+        game_name: &str,
+    ) -> Result<u8, (FastPlayer, Violation, String)> {
+        let number = self.fair_rolls.len() as u64 + 1;
+        let context = format!("fair dice roll {}", number);
+        let allowed = self.clock.per_move;
+        let mut own: [Option<Nonce>; 2] = [None; 2];
+        let mut commitments = [Commitment::default(); 2];
+        for seat in [FastPlayer::One, FastPlayer::Two] {
+            let idx = seat as usize;
+            let committed = self.attempt(&mut *seats[idx], seat, game, game_name, |player| player.commit_dice(number, allowed));
+            match committed.map_err(|v| (seat, v, context.clone()))? {
+                Some(commitment) => commitments[idx] = commitment,
+                None => {
+                    let nonce = fair_dice::fresh_nonce();
+                    commitments[idx] = fair_dice::commit(&nonce);
+                    own[idx] = Some(nonce);
+                }
+            }
+        }
+        let mut nonces = [Nonce::default(); 2];
+        for seat in [FastPlayer::One, FastPlayer::Two] {
+            let idx = seat as usize;
+            nonces[idx] = match own[idx] {
+                Some(nonce) => nonce,
+                None => self.attempt(&mut *seats[idx], seat, game, game_name, |player| player.reveal_dice(number, &commitments, allowed))
+                    .map_err(|v| (seat, v, context.clone()))?,
+            };
+            if fair_dice::commit(&nonces[idx]) != commitments[idx] {
+                return Err((seat, Violation::BrokenCommitment, context));
+            }
+        }
+        let roll = FairRoll { number, player: mover, nonces, roll: fair_dice::roll(number, mover, &nonces) };
+        for seat in seats.iter_mut() {
+            seat.show_roll(&roll);
+        }
+        let value = roll.roll;
+        self.fair_rolls.push(roll);
+        Ok(value)
+    }
+
+    /// Ask `player`, in `seat`, for something; if it has dropped out and has
+    /// reconnects left, reconnect it, bring it up to date and ask again
+    fn attempt<T>(
+        &mut self,
+        player: &mut dyn Contestant,
+        seat: FastPlayer,
+        game: &FastGameState,
+        game_name: &str,
+        mut request: impl FnMut(&mut dyn Contestant) -> Result<T, Violation>,
+    ) -> Result<T, Violation> {
+        loop {
+            match request(player) {
+                Err(Violation::Failed(reason)) if self.reconnects_left[seat as usize] > 0 => {
+                    self.reconnects_left[seat as usize] -= 1;
+                    self.reconnections += 1;
+                    self.append_log(&format!("{}: {} ({}) dropped out ({}) after {} turns; reconnecting to session {}",
+                                             game_name, seat.name(), player.label(), reason, self.turns.len(), self.session));
+                    let resync = Resync {
+                        session: &self.session,
+                        seat,
+                        clock: &self.clock,
+                        bank: self.bank[seat as usize],
+                        turns: &self.turns,
+                        game,
+                    };
+                    player.resume(&resync)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Append a violation to the log
+    fn log_violation(&mut self, game_name: &str, seat: FastPlayer, label: &str, violation: &Violation, context: &str) {
+        self.violations += 1;
+        self.append_log(&format!("{}: {} ({}) {} [{}]", game_name, seat.name(), label, violation, context));
+    }
+
+    fn append_log(&self, line: &str) {
+        let written = OpenOptions::new().create(true).append(true).open(&self.log)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = written {
+            eprintln!("⚠️  Could not write to {}: {}", self.log.display(), e);
+        }
+    }
+}
+
+/// A player for `ur match`: any player spec `StatsAgent::from_spec` knows, a
+/// `.rhai` script, or else a command that starts an external bot
+fn contestant(spec: &str) -> Result<Box<dyn Contestant>, String> {
+    if spec.ends_with(".rhai") {
+        return Ok(Box::new(ScriptAI::from_file(Path::new(spec))?));
+    }
+    Ok(match StatsAgent::from_spec(spec, 1) {
+        Ok(agent) => Box::new(agent),
+        // A misspelt setting of a built-in AI, rather than a program to run
+        Err(e) if matches!(spec.split('-').next(), Some("random" | "smart" | "expectimax" | "mcts")) => return Err(e),
+        Err(_) => Box::new(ExternalBot::new(spec)),
+    })
+}
+
+/// Options for `ur match --p1 SPEC --p2 SPEC [--games N] [--time MS] [--bank SECS] [--seed N] [--log FILE]
+/// [--fair-dice [--dice-log FILE]] [--reconnects N]`
+pub struct MatchOptions {
+    pub players: [String; 2],
+    pub games: usize,
+    pub clock: Clock,
+    pub seed: Option<u64>,
+    pub log: PathBuf,
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    /// Pip chances of the Player 1 and Player 2 seats' binary dice, if biased
+    pub pip_chances: Option<[f64; 2]>,
+    /// Where to log fair dice rolls, if the dice are rolled by commit-reveal
+    pub dice_log: Option<PathBuf>,
+    /// Times a bot that drops out may be reconnected in each game
+    pub reconnects: usize,
+}
+
+impl MatchOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--p1", "--p2", "--games", "--time", "--bank", "--seed", "--log", "--board", "--dice",
+                                           "--p1-pip-chance", "--p2-pip-chance", "--dice-log", "--reconnects"], &["--fair-dice"])?;
+        if flags.get_str("--dice-log").is_some() && !flags.has("--fair-dice") {
+            return Err("--dice-log needs --fair-dice".to_string());
+        }
+        let player = |flag: &str| flags.get_str(flag).map(str::to_string).ok_or_else(|| format!("{} is required", flag));
+        Ok(MatchOptions {
+            players: [player("--p1")?, player("--p2")?],
+            games: flags.get("--games", 10)?,
+            clock: Clock {
+                per_move: Duration::from_millis(flags.get("--time", 1000)?),
+                bank: flags.get_opt::<f64>("--bank")?.map(Duration::from_secs_f64),
+            },
+            seed: flags.get_opt("--seed")?,
+            log: PathBuf::from(flags.get_str("--log").unwrap_or(DEFAULT_REFEREE_LOG)),
+            board: board::from_flags(&flags)?.unwrap_or_default(),
+            dice: flags.get("--dice", DiceVariant::Tetrahedra)?,
+            pip_chances: dice::pip_chances_from_flags(&flags, flags.get_opt("--dice")?)?,
+            dice_log: flags.has("--fair-dice").then(|| PathBuf::from(flags.get_str("--dice-log").unwrap_or(DEFAULT_DICE_LOG))),
+            reconnects: flags.get("--reconnects", 0)?,
+        })
+    }
+}
+
+/// Play a refereed match between two players, swapping seats every game and
+/// reusing dice within each pair of games, and report wins and forfeits
+pub fn run_match(options: &MatchOptions) -> io::Result<()> {
+    board::select(options.board);
+    dice::select(options.dice);
+    if let Some(chances) = options.pip_chances {
+        dice::select_pip_chances(chances);
+    }
+    let mut players = Vec::new();
+    for spec in &options.players {
+        players.push(contestant(spec).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?);
+    }
+    let labels = [players[0].label(), players[1].label()];
+    let mut referee = Referee::new(options.clock, &options.log).with_reconnects(options.reconnects);
+    if let Some(path) = &options.dice_log {
+        referee = referee.with_fair_dice(DiceLog::create(path)?);
+    }
+    let mut seeds = options.seed.map_or_else(GameRng::random, GameRng::from_seed);
+
+    println!("=== MATCH: {} vs {} ===", labels[0], labels[1]);
+    print!("{} games | {} ms per move", options.games, options.clock.per_move.as_millis());
+    if let Some(bank) = options.clock.bank {
+        print!(" | {:.1} s per game", bank.as_secs_f64());
+    }
+    if let Some(path) = &options.dice_log {
+        print!(" | fair dice, logged to {}", path.display());
+    }
+    println!();
+    println!();
+
+    let mut wins = [0, 0];
+    let mut forfeits = [0, 0];
+    let mut draws = 0;
+    let mut pair_seed = 0;
+    let start = Instant::now();
+    for game_idx in 0..options.games {
+        if game_idx % 2 == 0 {
+            pair_seed = seeds.next_seed();
+        }
+        let mut dice = GameRng::from_seed(pair_seed);
+        // Player index sitting in each seat
+        let order = if game_idx % 2 == 0 { [0, 1] } else { [1, 0] };
+        let [first, second] = &mut players[..] else { unreachable!("two players") };
+        let seats: [&mut dyn Contestant; 2] = if order[0] == 0 { [first.as_mut(), second.as_mut()] } else { [second.as_mut(), first.as_mut()] };
+        let game_name = format!("{} vs {} game {}", labels[order[0]], labels[order[1]], game_idx + 1);
+        let result = referee.play_game(seats, &mut dice, &game_name);
+
+        let Some(seat) = result.status.winner() else {
+            draws += 1;
+            println!("  game {:>3}: {}", game_idx + 1, result.reason());
+            continue;
+        };
+        let winner = order[seat as usize];
+        wins[winner] += 1;
+        match (&result.violation, result.status) {
+            (Some(violation), _) => {
+                forfeits[1 - winner] += 1;
+                println!("  game {:>3}: {} wins, {} forfeits: {}", game_idx + 1, labels[winner], labels[1 - winner], violation);
+            }
+            (None, GameStatus::Adjudicated(..)) => println!("  game {:>3}: {} wins, {}", game_idx + 1, labels[winner], result.reason()),
+            (None, _) => println!("  game {:>3}: {} wins", game_idx + 1, labels[winner]),
+        }
+    }
+
+    println!();
+    println!("Finished in {:.1} s", start.elapsed().as_secs_f64());
+    for idx in 0..2 {
+        println!("  {}: {} wins ({:.1}%), {} forfeits", labels[idx], wins[idx],
+                 wins[idx] as f64 / options.games.max(1) as f64 * 100.0, forfeits[idx]);
+    }
+    if draws > 0 {
+        println!("  {} drawn", draws);
+    }
+    if let Some(path) = &options.dice_log {
+        println!("Every roll can be checked with `ur verify-dice --file {}`", path.display());
+    }
+    if referee.reconnections > 0 {
+        println!("{} reconnection{} logged to {}", referee.reconnections, if referee.reconnections == 1 { "" } else { "s" }, options.log.display());
+    }
+    if referee.violations > 0 {
+        println!("{} violation{} logged to {}", referee.violations, if referee.violations == 1 { "" } else { "s" }, options.log.display());
+    }
+    if record_match(&labels[0], &labels[1], options.games - draws, wins[0]) {
+        println!();
+        println!("Results added to {} (see `ur leaderboard`)", DEFAULT_LEADERBOARD_FILE);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays its first legal move `left` times, then answers with a piece that does not exist
+    struct Scripted {
+        left: usize,
+        made: usize,
+    }
+
+    impl Contestant for Scripted {
+        fn label(&self) -> String {
+            "scripted".to_string()
+        }
+
+        fn request_move(&mut self, _game: &FastGameState, moves: &[u8], _roll: u8, _allowed: Duration) -> Result<i64, Violation> {
+            if self.left == 0 {
+                return Ok(9);
+            }
+            self.left -= 1;
+            self.made += 1;
+            Ok(moves[0] as i64)
+        }
+    }
+
+    #[test]
+    fn a_forfeit_reports_the_moves_played() {
+        let log = std::env::temp_dir().join(format!("ur-referee-test-{}.log", std::process::id()));
+        let mut referee = Referee::new(Clock { per_move: Duration::from_secs(1), bank: None }, &log);
+        let (mut one, mut two) = (Scripted { left: 4, made: 0 }, Scripted { left: usize::MAX, made: 0 });
+        let adjudication = referee.play_game([&mut one, &mut two], &mut GameRng::from_seed(7), "test");
+        let _ = std::fs::remove_file(&log);
+        assert_eq!(adjudication.status, GameStatus::Adjudicated(FastPlayer::Two, EndReason::Forfeit));
+        assert_eq!(adjudication.violation, Some(Violation::IllegalMove(9, MoveError::NoSuchPiece)));
+        assert_eq!(one.made, 4);
+        assert_eq!(adjudication.moves, one.made + two.made);
+        assert_eq!(referee.violations, 1);
+    }
+}
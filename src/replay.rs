@@ -0,0 +1,314 @@
+//! Post-game replay: step through a finished game and branch off at any position
+//! to play out "what if I had moved differently" against the AI. Branches are
+//! added to the history tree beside the moves actually played, so the original
+//! game stays intact and can still be stepped through.
+//!
+//! The replay is a cursor into the game's main line, so every command (stepping,
+//! seeking to a turn, jumping to the next capture, playing forwards or backwards)
+//! is just a move of the cursor followed by a redraw of the position there.
+
+use std::io;
+use std::{thread, time::Duration};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    terminal::{disable_raw_mode, enable_raw_mode},
+};
+
+use crate::ai::HybridAI;
+use crate::display::{global_to_coord, move_squares, print_score, redraw_board};
+use crate::frontend::print_legal_moves;
+use crate::history::{GameHistory, MoveRecord};
+use crate::input::{prompt_line, InputResult};
+use crate::optimized_game::FastGameState;
+use crate::position_code;
+use crate::state_repr::GameStateRepr;
+use crate::transcript::outln;
+use crate::AIType;
+
+/// Delay between moves at each playback speed, slowest first
+const PLAYBACK_DELAYS_MS: [u64; 6] = [2000, 1200, 800, 500, 250, 100];
+const DEFAULT_SPEED: usize = 2;
+
+/// Where the replay is in the main line of a game
+struct Replay {
+    /// Node ids of the main line; position `i` is the one after move `i`
+    line: Vec<usize>,
+    cursor: usize,
+    /// Index into `PLAYBACK_DELAYS_MS`
+    speed: usize,
+}
+
+impl Replay {
+    fn last(&self) -> usize {
+        self.line.len() - 1
+    }
+
+    fn record(&self, history: &GameHistory, idx: usize) -> Option<MoveRecord> {
+        self.line.get(idx).and_then(|&id| history.node(id).record)
+    }
+
+    /// First move played on `turn` or later
+    fn seek_turn(&self, history: &GameHistory, turn: usize) -> Option<usize> {
+        (1..=self.last()).find(|&idx| self.record(history, idx).is_some_and(|r| r.turn >= turn))
+    }
+
+    /// Nearest move after (or, backwards, before) the one just played that matches `wanted`
+    fn find(&self, history: &GameHistory, forward: bool, wanted: fn(&MoveRecord) -> bool) -> Option<usize> {
+        let matches = |&idx: &usize| self.record(history, idx).is_some_and(|r| wanted(&r));
+        if forward {
+            (self.cursor + 1..=self.last()).find(matches)
+        } else {
+            (1..self.cursor).rev().find(matches)
+        }
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_millis(PLAYBACK_DELAYS_MS[self.speed])
+    }
+
+    fn show(&self, history: &GameHistory) {
+        let node = history.node(self.line[self.cursor]);
+        let mut captures = [0; 2];
+        for record in (1..=self.cursor).filter_map(|idx| self.record(history, idx)).filter(is_capture) {
+            captures[record.player as usize] += 1;
+        }
+        // A capture is shown with the squares the capturing piece moved between
+        let marked = node.record.filter(is_capture)
+            .map(|record| move_squares(record.player, record.info.from_pos, record.info.to_pos))
+            .unwrap_or_default();
+        redraw_board(&node.position, "", &marked);
+        print_score(&node.position, Some(captures));
+        outln!("📼 Replay: move {} of {} ({})", self.cursor, self.last(), position_code::encode(&node.position));
+        if let Some(record) = node.record {
+            outln!("   Last move: {}", describe_move(&record));
+        }
+        match self.record(history, self.cursor + 1) {
+            Some(record) => outln!("   Next: turn {}, {} rolled {}", record.turn, record.player.name(), record.roll),
+            None => outln!("   End of game"),
+        }
+        let what_ifs = node.children.len().saturating_sub(1);
+        if what_ifs > 0 {
+            outln!("   {} what-if{} explored from here", what_ifs, if what_ifs == 1 { "" } else { "s" });
+        }
+    }
+}
+
+fn is_capture(record: &MoveRecord) -> bool {
+    record.info.captured_piece.is_some()
+}
+
+/// Landing on a rosette is what earns the extra roll
+fn is_rosette(record: &MoveRecord) -> bool {
+    record.info.extra_turn
+}
+
+/// Step through the main line of `history`, exploring what-ifs on request
+pub fn replay_game(history: &mut GameHistory, players: &[AIType; 2], mcts_ai: &HybridAI) -> InputResult<()> {
+    let mut replay = Replay { line: history.main_line(), cursor: 0, speed: DEFAULT_SPEED };
+    let mut notice = None;
+
+    loop {
+        replay.show(history);
+        if let Some(message) = notice.take() {
+            outln!("{}", message);
+        }
+
+        let last = replay.last();
+        outln!("   ENTER/p step, N jump to move N, t N seek to turn N, c/r next capture/rosette (pc/pr previous)");
+        outln!("   f play forward, b play backward, +/- speed ({} ms/move), w what-if from here, q done",
+                 PLAYBACK_DELAYS_MS[replay.speed]);
+        let input = prompt_line("> ")?;
+        let lowered = input.to_lowercase();
+        let words: Vec<&str> = lowered.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["n"] => replay.cursor = (replay.cursor + 1).min(last),
+            ["p"] => replay.cursor = replay.cursor.saturating_sub(1),
+            ["q"] => return Ok(()),
+            ["w"] => match replay.record(history, replay.cursor + 1) {
+                Some(original) => explore_what_if(history, replay.line[replay.cursor], original, players, mcts_ai)?,
+                None => notice = Some("❌ The game is over here; step back to try something else.".to_string()),
+            },
+            // Stop just before the turn's first move, so it is the next one
+            ["t", turn] => match turn.parse::<usize>().ok().and_then(|turn| replay.seek_turn(history, turn)) {
+                Some(idx) => replay.cursor = idx - 1,
+                None => notice = Some(format!("❌ '{}' is not a turn of this game.", turn)),
+            },
+            [command @ ("c" | "r" | "pc" | "pr")] => {
+                let forward = !command.starts_with('p');
+                let capture = command.ends_with('c');
+                let kind = if capture { "capture" } else { "rosette landing" };
+                match replay.find(history, forward, if capture { is_capture } else { is_rosette }) {
+                    Some(idx) => replay.cursor = idx,
+                    None => notice = Some(format!("❌ No {} {}.", if forward { "later" } else { "earlier" }, kind)),
+                }
+            }
+            ["+"] => replay.speed = (replay.speed + 1).min(PLAYBACK_DELAYS_MS.len() - 1),
+            ["-"] => replay.speed = replay.speed.saturating_sub(1),
+            ["f"] => play(history, &mut replay, true)?,
+            ["b"] => play(history, &mut replay, false)?,
+            [number] => match number.parse::<usize>() {
+                Ok(target) if target <= last => replay.cursor = target,
+                _ => notice = Some(format!("❌ '{}' is not a command or a move between 0 and {}.", input, last)),
+            },
+            _ => notice = Some(format!("❌ '{}' is not a command.", input)),
+        }
+    }
+}
+
+/// Step the cursor one move at a time until either end of the game or a key
+/// press; + and - change the speed while playing
+fn play(history: &GameHistory, replay: &mut Replay, forward: bool) -> io::Result<()> {
+    loop {
+        let at_end = if forward { replay.cursor == replay.last() } else { replay.cursor == 0 };
+        if at_end {
+            return Ok(());
+        }
+        replay.cursor = if forward { replay.cursor + 1 } else { replay.cursor - 1 };
+        replay.show(history);
+        outln!("   {} at {} ms/move: + faster, - slower, any other key stops",
+                 if forward { "▶ Playing" } else { "◀ Reversing" }, PLAYBACK_DELAYS_MS[replay.speed]);
+
+        match wait_for_key(replay.delay())? {
+            Some('+') => replay.speed = (replay.speed + 1).min(PLAYBACK_DELAYS_MS.len() - 1),
+            Some('-') => replay.speed = replay.speed.saturating_sub(1),
+            Some(_) => return Ok(()),
+            None => {}
+        }
+    }
+}
+
+/// Wait up to `delay` for a key press. Without a terminal to read keys from
+/// (input piped in) this just sleeps, so playback runs to the end.
+fn wait_for_key(delay: Duration) -> io::Result<Option<char>> {
+    if enable_raw_mode().is_err() {
+        thread::sleep(delay);
+        return Ok(None);
+    }
+    let key = event::poll(delay).and_then(|ready| if ready { event::read().map(Some) } else { Ok(None) });
+    disable_raw_mode()?;
+    Ok(match key? {
+        Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+            KeyCode::Char(c) => Some(c),
+            _ => Some(' '),
+        },
+        _ => None,
+    })
+}
+
+/// Take over the side that moved next in the game from node `from`, replay the
+/// roll it had, then keep playing against the AI with fresh rolls
+fn explore_what_if(
+    history: &mut GameHistory,
+    from: usize,
+    original: MoveRecord,
+    players: &[AIType; 2],
+    mcts_ai: &HybridAI,
+) -> InputResult<()> {
+    let you = original.player;
+    // A human opponent is stood in for by the smart AI
+    let opponent = match &players[you.opposite() as usize] {
+        AIType::Human => &AIType::Smart,
+        ai => ai,
+    };
+    let game_winner = history.main_line().last().map(|&id| history.node(id).position)
+        .and_then(|end| end.status().winner());
+
+    let mut game = history.node(from).position;
+    // Turns passed on a zero roll leave no node, so the side to move may need fixing up
+    if game.current_player() != you {
+        game.pass_turn();
+    }
+    let mut node = from;
+    let mut turn = original.turn;
+    let mut roll = original.roll;
+    let mut first_move = true;
+
+    loop {
+        if let Some(winner) = game.status().winner() {
+            redraw_board(&game, "", &[]);
+            print_score(&game, None);
+            outln!("🔀 What-if over: {} wins.", winner.name());
+            if let Some(game_winner) = game_winner {
+                outln!("   In the game as played, {} won.", game_winner.name());
+            }
+            prompt_line("Press ENTER to return to the replay... ")?;
+            return Ok(());
+        }
+
+        if !first_move {
+            roll = FastGameState::roll_dice(game.current_player());
+            turn += 1;
+        }
+        let mover = game.current_player();
+        let moves = game.generate_moves(roll);
+        if moves.is_empty() {
+            outln!("🎲 {} rolled {}: no moves, turn passes.", mover.name(), roll);
+            game.pass_turn();
+            first_move = false;
+            continue;
+        }
+
+        let piece_idx = if mover == you {
+            redraw_board(&game, "", &[]);
+            print_score(&game, None);
+            outln!("🔀 What-if: you play {} against the {}", you.name(), opponent.display_name());
+            outln!("🎲 Rolled: {} {}", roll, crate::dice::model().render(roll));
+            if first_move {
+                outln!("   (in the game, {})", describe_move(&original));
+            }
+            print_legal_moves(&game, &moves, roll);
+            match choose_index(moves.len())? {
+                Some(idx) => moves[idx],
+                None => return Ok(()),
+            }
+        } else {
+            opponent.choose_move(&game, &moves, roll, mcts_ai)
+        };
+
+        let Ok(info) = game.make_move(piece_idx, roll) else {
+            continue;
+        };
+        let record = MoveRecord { turn, player: mover, roll, info };
+        node = history.add_move(node, record, game);
+        if mover != you {
+            outln!("🎲 {}", describe_move(&record));
+            thread::sleep(Duration::from_millis(800));
+        }
+        first_move = false;
+    }
+}
+
+/// Read a move index; None if the player abandons the what-if
+fn choose_index(count: usize) -> InputResult<Option<usize>> {
+    let last = count - 1;
+    loop {
+        let input = prompt_line(&format!("Choose move index [0..{}] (q to stop exploring): ", last))?;
+        if input.eq_ignore_ascii_case("q") {
+            return Ok(None);
+        }
+        match input.parse::<usize>() {
+            Ok(idx) if idx <= last => return Ok(Some(idx)),
+            _ => outln!("❌ '{}' is not a move index, try again.", input),
+        }
+    }
+}
+
+/// One line describing a recorded move, e.g. "Player 2 rolled 3: piece 4 → grid (1, 5), capturing"
+fn describe_move(record: &MoveRecord) -> String {
+    let info = &record.info;
+    let action = if FastGameState::is_on_board(info.to_pos) {
+        let (r, c) = global_to_coord(FastGameState::path_to_global(record.player, info.to_pos - 1));
+        let verb = if info.from_pos == 0 { "enters at" } else { "→" };
+        format!("piece {} {} grid ({}, {})", info.piece_idx, verb, r, c)
+    } else {
+        format!("piece {} exits", info.piece_idx)
+    };
+    let mut notes = String::new();
+    if info.captured_piece.is_some() {
+        notes.push_str(", capturing");
+    }
+    if info.extra_turn {
+        notes.push_str(", extra roll");
+    }
+    format!("{} rolled {}: {}{}", record.player.name(), record.roll, action, notes)
+}
@@ -0,0 +1,90 @@
+//! CPU and memory use of this process, sampled during long runs so a
+//! dashboard can show whether the threads are keeping the machine busy, and
+//! so memory-hungry searches can stop before exhausting it. Read from
+//! `/proc/self`; elsewhere the numbers are simply unavailable.
+
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Clock ticks per second in `/proc/self/stat` (USER_HZ, fixed at 100 in the
+/// Linux ABI whatever the kernel's own tick rate)
+const TICKS_PER_SEC: f64 = 100.0;
+/// Shortest interval a CPU reading is averaged over; shorter ones are mostly tick noise
+const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// CPU time this process has used, user and system
+fn cpu_time() -> Option<Duration> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesized command name, starting with the state (field 3)
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(Duration::from_secs_f64((utime + stime) as f64 / TICKS_PER_SEC))
+}
+
+/// Resident memory of this process in bytes
+pub fn resident_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Bytes as a short human-readable size, e.g. "41.2 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 { format!("{:.2} GB", mb / 1024.0) } else { format!("{:.1} MB", mb) }
+}
+
+/// One reading of the process's resource use
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    /// CPU time per wall-clock time since the previous reading: 1.0 is one core kept busy
+    cores_busy: Option<f64>,
+    resident: Option<u64>,
+}
+
+/// Samples resource use, averaging CPU over the time between readings
+pub struct ResourceMeter {
+    cores: usize,
+    last: Option<(Instant, Duration)>,
+    usage: Usage,
+}
+
+impl ResourceMeter {
+    pub fn new() -> Self {
+        ResourceMeter {
+            cores: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            last: cpu_time().map(|cpu| (Instant::now(), cpu)),
+            usage: Usage::default(),
+        }
+    }
+
+    /// Current use; the CPU figure only moves once enough time has passed to average over
+    fn sample(&mut self) -> Usage {
+        self.usage.resident = resident_bytes();
+        let now = Instant::now();
+        if let (Some((then, cpu_then)), Some(cpu_now)) = (self.last, cpu_time()) {
+            let wall = now - then;
+            if wall >= MIN_INTERVAL {
+                self.usage.cores_busy = Some((cpu_now - cpu_then).as_secs_f64() / wall.as_secs_f64());
+                self.last = Some((now, cpu_now));
+            }
+        }
+        self.usage
+    }
+
+    /// Sample and sum up current use in a line, e.g. "CPU 387% of 4 cores (97%) | memory 41.2 MB"
+    pub fn summary(&mut self) -> String {
+        let usage = self.sample();
+        let cpu = match usage.cores_busy {
+            _ if self.last.is_none() => "CPU n/a".to_string(),
+            Some(busy) => format!("CPU {:.0}% of {} core{} ({:.0}%)", busy * 100.0, self.cores,
+                                  if self.cores == 1 { "" } else { "s" }, busy / self.cores as f64 * 100.0),
+            None => "CPU measuring...".to_string(),
+        };
+        let memory = usage.resident.map_or("n/a".to_string(), format_bytes);
+        format!("{} | memory {}", cpu, memory)
+    }
+}
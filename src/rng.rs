@@ -0,0 +1,176 @@
+//! Dice randomness. Every game rolls from a `GameRng`, built from a seed with
+//! the generator chosen once at startup (`--rng`): xoshiro (the default, and
+//! the fastest, for simulations), PCG, ChaCha (a cryptographic generator, so
+//! the rolls cannot be worked out from the ones before) or the operating
+//! system's entropy source, which has no seed and so cannot be replayed.
+//! Fresh seeds are drawn from the operating system.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use rand::rngs::{OsRng, SmallRng, StdRng};
+use rand::{RngCore, SeedableRng, TryRngCore};
+use rand::rand_core::UnwrapErr;
+use rand_pcg::Pcg64;
+
+use crate::args::Flags;
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+/// Generator behind the dice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RngAlgorithm {
+    /// Xoshiro256++, the generator every seed was rolled with before the others
+    #[default]
+    Xoshiro,
+    Pcg,
+    /// ChaCha12
+    ChaCha,
+    /// The operating system's entropy source, read on every roll
+    Os,
+}
+
+impl RngAlgorithm {
+    pub const ALL: [RngAlgorithm; 4] = [RngAlgorithm::Xoshiro, RngAlgorithm::Pcg, RngAlgorithm::ChaCha, RngAlgorithm::Os];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RngAlgorithm::Xoshiro => "xoshiro",
+            RngAlgorithm::Pcg => "pcg",
+            RngAlgorithm::ChaCha => "chacha",
+            RngAlgorithm::Os => "os",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.label() == label)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            RngAlgorithm::Xoshiro => "xoshiro256++, fast",
+            RngAlgorithm::Pcg => "PCG 64",
+            RngAlgorithm::ChaCha => "ChaCha12, cryptographic",
+            RngAlgorithm::Os => "OS entropy, no seed",
+        }
+    }
+
+    /// Whether the same seed rolls the same dice
+    pub fn is_seeded(self) -> bool {
+        self != RngAlgorithm::Os
+    }
+}
+
+impl FromStr for RngAlgorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::from_label(s).ok_or(())
+    }
+}
+
+static SELECTED: OnceLock<RngAlgorithm> = OnceLock::new();
+
+/// Choose the generator for this run; must happen before any game is created.
+/// Returns false if a different one was already chosen.
+pub fn select(algorithm: RngAlgorithm) -> bool {
+    *SELECTED.get_or_init(|| algorithm) == algorithm
+}
+
+/// The generator chosen for this run (xoshiro unless `select` said otherwise)
+pub fn algorithm() -> RngAlgorithm {
+    SELECTED.get().copied().unwrap_or_default()
+}
+
+/// The generator named by `--rng`, if given
+pub fn from_flags(flags: &Flags) -> Result<Option<RngAlgorithm>, String> {
+    flags.get_opt("--rng")
+}
+
+enum Source {
+    Xoshiro(SmallRng),
+    Pcg(Box<Pcg64>),
+    ChaCha(Box<StdRng>),
+    Os(UnwrapErr<OsRng>),
+}
+
+impl RngCore for Source {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Source::Xoshiro(rng) => rng.next_u32(),
+            Source::Pcg(rng) => rng.next_u32(),
+            Source::ChaCha(rng) => rng.next_u32(),
+            Source::Os(rng) => rng.next_u32(),
+        }
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Source::Xoshiro(rng) => rng.next_u64(),
+            Source::Pcg(rng) => rng.next_u64(),
+            Source::ChaCha(rng) => rng.next_u64(),
+            Source::Os(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            Source::Xoshiro(rng) => rng.fill_bytes(dst),
+            Source::Pcg(rng) => rng.fill_bytes(dst),
+            Source::ChaCha(rng) => rng.fill_bytes(dst),
+            Source::Os(rng) => rng.fill_bytes(dst),
+        }
+    }
+}
+
+/// Seedable source of dice rolls for one game.
+/// Two GameRngs built from the same seed produce the same roll sequence,
+/// which lets experiments replay identical dice with different players.
+pub struct GameRng {
+    seed: u64,
+    algorithm: RngAlgorithm,
+    rng: Source,
+}
+
+impl GameRng {
+    /// Rolls from `seed` with the generator chosen for this run
+    pub fn from_seed(seed: u64) -> Self {
+        Self::with_algorithm(algorithm(), seed)
+    }
+
+    fn with_algorithm(algorithm: RngAlgorithm, seed: u64) -> Self {
+        let rng = match algorithm {
+            RngAlgorithm::Xoshiro => Source::Xoshiro(SmallRng::seed_from_u64(seed)),
+            RngAlgorithm::Pcg => Source::Pcg(Box::new(Pcg64::seed_from_u64(seed))),
+            RngAlgorithm::ChaCha => Source::ChaCha(Box::new(StdRng::seed_from_u64(seed))),
+            RngAlgorithm::Os => Source::Os(OsRng.unwrap_err()),
+        };
+        GameRng { seed, algorithm, rng }
+    }
+
+    /// Fresh random seed drawn from the operating system
+    pub fn random() -> Self {
+        Self::from_seed(OsRng.unwrap_err().next_u64())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn algorithm(&self) -> RngAlgorithm {
+        self.algorithm
+    }
+
+    /// A seed for another game, so a whole run of games follows from this one's seed
+    pub fn next_seed(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    /// Roll `player`'s dice
+    pub fn roll_dice(&mut self, player: FastPlayer) -> u8 {
+        FastGameState::roll_dice_with(player, &mut self.rng)
+    }
+}
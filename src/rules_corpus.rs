@@ -0,0 +1,116 @@
+//! Data-driven rule tests: every case in testdata/rules_corpus.txt is checked
+//! against each game state encoding, so the corpus is the contract any engine
+//! refactor or new encoding has to keep.
+
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::{ArrayGameState, GameStateRepr};
+
+const CORPUS: &str = include_str!("../testdata/rules_corpus.txt");
+
+struct Case {
+    name: String,
+    start: FastGameState,
+    roll: u8,
+    moves: Vec<u8>,
+    play: Option<(u8, FastGameState)>,
+}
+
+fn parse_positions(values: &[&str]) -> [u8; 7] {
+    let positions: Vec<u8> = values.iter().map(|v| v.parse().expect("piece position")).collect();
+    positions.try_into().expect("seven piece positions")
+}
+
+fn parse_player(values: &[&str]) -> FastPlayer {
+    match values {
+        ["1"] => FastPlayer::One,
+        ["2"] => FastPlayer::Two,
+        _ => panic!("side must be 1 or 2"),
+    }
+}
+
+fn build(p1: Option<[u8; 7]>, p2: Option<[u8; 7]>, turn: Option<FastPlayer>, name: &str) -> FastGameState {
+    FastGameState::from_piece_positions(p1.unwrap(), p2.unwrap(), turn.unwrap())
+        .unwrap_or_else(|e| panic!("{}: {}", name, e))
+}
+
+fn parse_corpus() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    for block in CORPUS.split("\n\n") {
+        let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#')).collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        let mut name = String::new();
+        let (mut turn, mut pieces1, mut pieces2, mut roll, mut moves, mut play) = (None, None, None, None, None, None);
+        let (mut after_turn, mut after1, mut after2) = (None, None, None);
+
+        for line in lines {
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap();
+            let values: Vec<&str> = fields.collect();
+            match key {
+                "case" => name = values.join(" "),
+                "turn" => turn = Some(parse_player(&values)),
+                "pieces1" => pieces1 = Some(parse_positions(&values)),
+                "pieces2" => pieces2 = Some(parse_positions(&values)),
+                "roll" => roll = Some(values[0].parse().unwrap()),
+                "moves" => moves = Some(values.iter().map(|v| v.parse().unwrap()).collect()),
+                "play" => play = Some(values[0].parse().unwrap()),
+                "after_turn" => after_turn = Some(parse_player(&values)),
+                "after1" => after1 = Some(parse_positions(&values)),
+                "after2" => after2 = Some(parse_positions(&values)),
+                _ => panic!("{}: unknown key '{}'", name, key),
+            }
+        }
+
+        let start = build(pieces1, pieces2, turn, &name);
+        let play = play.map(|piece| (piece, build(after1, after2, after_turn, &name)));
+        cases.push(Case {
+            roll: roll.unwrap_or_else(|| panic!("{}: missing roll", name)),
+            moves: moves.unwrap_or_else(|| panic!("{}: missing moves", name)),
+            name,
+            start,
+            play,
+        });
+    }
+
+    cases
+}
+
+fn check_case<S: GameStateRepr + PartialEq + std::fmt::Debug>(case: &Case, encoding: &str) {
+    let context = format!("{} [{}]", case.name, encoding);
+    let mut state = S::from_fast(&case.start);
+
+    assert_eq!(&*state.generate_move_list(case.roll), case.moves.as_slice(), "{}: legal moves", context);
+
+    if let Some((piece, expected)) = case.play {
+        let before = state;
+        let player = state.current_player();
+        let info = state.make_move(piece, case.roll).unwrap_or_else(|e| panic!("{}: move rejected: {}", context, e));
+        assert_eq!(state, S::from_fast(&expected), "{}: position after move", context);
+
+        state.unmake_move(player, &info);
+        assert_eq!(state, before, "{}: unmake", context);
+    }
+}
+
+#[test]
+fn corpus_is_not_empty() {
+    assert!(parse_corpus().len() >= 10);
+}
+
+#[test]
+fn fast_game_state_follows_the_corpus() {
+    for case in parse_corpus() {
+        check_case::<FastGameState>(&case, "FastGameState");
+    }
+}
+
+#[test]
+fn array_game_state_follows_the_corpus() {
+    for case in parse_corpus() {
+        check_case::<ArrayGameState>(&case, "ArrayGameState");
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+
+use crate::board::{self, BoardVariant};
+use crate::error::UrError;
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+/// Default file used when the player does not name one
+pub const DEFAULT_SAVE_FILE: &str = "ur_save.txt";
+
+const SAVE_HEADER: &str = "# Royal Game of Ur saved game";
+const SAVE_VERSION: u32 = 1;
+
+/// A saved game: the position plus a label for who controls each side
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub game: FastGameState,
+    pub players: [String; 2],
+}
+
+impl SavedGame {
+    /// Serialize to the plain-text save format
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(SAVE_HEADER);
+        out.push('\n');
+        out.push_str(&format!("version {}\n", SAVE_VERSION));
+        out.push_str(&format!("players {} {}\n", self.players[0], self.players[1]));
+        // Standard-board saves stay readable by builds without board variants
+        if board::variant() != BoardVariant::Standard {
+            out.push_str(&format!("board {}\n", board::variant().label()));
+        }
+        let turn = match self.game.current_player() {
+            FastPlayer::One => 1,
+            FastPlayer::Two => 2,
+        };
+        out.push_str(&format!("turn {}\n", turn));
+        for (key, player) in [("pieces1", FastPlayer::One), ("pieces2", FastPlayer::Two)] {
+            out.push_str(key);
+            for piece_idx in 0..7 {
+                out.push_str(&format!(" {}", self.game.get_piece_pos(player, piece_idx)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse the plain-text save format, rejecting anything malformed
+    pub fn from_text(text: &str) -> Result<Self, UrError> {
+        let mut version = None;
+        let mut players = None;
+        let mut turn = None;
+        let mut pieces: [Option<[u8; 7]>; 2] = [None, None];
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let err = |message: String| UrError::ParseError { line: line_no, message };
+
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+            let values: Vec<&str> = fields.collect();
+
+            match key {
+                "version" => {
+                    let v: u32 = single_value(&values)
+                        .and_then(|v| v.parse().ok())
+                        .ok_or_else(|| err("expected a version number".to_string()))?;
+                    if v != SAVE_VERSION {
+                        return Err(err(format!("unsupported save version {}", v)));
+                    }
+                    version = Some(v);
+                }
+                "players" => {
+                    if values.len() != 2 {
+                        return Err(err("expected two player labels".to_string()));
+                    }
+                    players = Some([values[0].to_string(), values[1].to_string()]);
+                }
+                "board" => {
+                    let variant = single_value(&values)
+                        .and_then(BoardVariant::from_label)
+                        .ok_or_else(|| err("unknown board".to_string()))?;
+                    if variant != board::variant() {
+                        return Err(err(format!("saved on the {} board; restart with --board {}",
+                                               variant.label(), variant.label())));
+                    }
+                }
+                "turn" => {
+                    turn = match single_value(&values) {
+                        Some("1") => Some(FastPlayer::One),
+                        Some("2") => Some(FastPlayer::Two),
+                        _ => return Err(err("turn must be 1 or 2".to_string())),
+                    };
+                }
+                "pieces1" | "pieces2" => {
+                    if values.len() != 7 {
+                        return Err(err(format!("{} needs 7 positions", key)));
+                    }
+                    let mut positions = [0u8; 7];
+                    for (slot, value) in positions.iter_mut().zip(&values) {
+                        *slot = value
+                            .parse()
+                            .ok()
+                            .filter(|&p| p <= FastGameState::finished_pos())
+                            .ok_or_else(|| err(format!("invalid piece position '{}'", value)))?;
+                    }
+                    let side = if key == "pieces1" { 0 } else { 1 };
+                    pieces[side] = Some(positions);
+                }
+                _ => return Err(err(format!("unknown key '{}'", key))),
+            }
+        }
+
+        let missing = |what: &str| UrError::parse(format!("missing '{}'", what));
+        version.ok_or_else(|| missing("version"))?;
+        let players = players.ok_or_else(|| missing("players"))?;
+        let turn = turn.ok_or_else(|| missing("turn"))?;
+        let p1 = pieces[0].ok_or_else(|| missing("pieces1"))?;
+        let p2 = pieces[1].ok_or_else(|| missing("pieces2"))?;
+
+        let game = FastGameState::from_piece_positions(p1, p2, turn)?;
+
+        Ok(SavedGame { game, players })
+    }
+}
+
+fn single_value<'a>(values: &[&'a str]) -> Option<&'a str> {
+    match values {
+        [v] => Some(v),
+        _ => None,
+    }
+}
+
+pub fn save_game(path: &str, saved: &SavedGame) -> Result<(), UrError> {
+    fs::write(path, saved.to_text())?;
+    Ok(())
+}
+
+pub fn load_game(path: &str) -> Result<SavedGame, UrError> {
+    let text = fs::read_to_string(path)?;
+    SavedGame::from_text(&text)
+}
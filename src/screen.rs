@@ -0,0 +1,387 @@
+//! Off-screen frame buffers for the terminal UI. A `Screen` is built line by
+//! line from colored spans and widgets that size themselves (boxed panels,
+//! progress bars, tables), so no caller pads by hand. It is then printed where
+//! the cursor is, or presented at the top of the terminal, where only the cells
+//! that changed since the last presented screen are rewritten.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use unicode_width::UnicodeWidthStr;
+
+use crossterm::{
+    cursor::{self, MoveTo},
+    execute, queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::transcript;
+
+/// One character on screen, with any zero-width characters that follow it
+/// (variation selectors, combining accents), and its colors
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    text: String,
+    /// Columns the character takes up: 2 for emoji and other wide glyphs
+    width: usize,
+    fg: Color,
+    bg: Color,
+}
+
+/// The screen as `Screen::present` last left it at the top of the terminal;
+/// None once the terminal has been cleared or the screen may have scrolled away
+static PRESENTED: Mutex<Option<Vec<Vec<Cell>>>> = Mutex::new(None);
+/// Set when the terminal does not report the cursor position, which in-place
+/// drawing needs, so every screen is presented in full
+static NO_CURSOR_REPORTS: AtomicBool = AtomicBool::new(false);
+
+/// Clear the terminal and forget what was presented on it
+pub fn clear() {
+    let _ = execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
+    *PRESENTED.lock().unwrap() = None;
+}
+
+/// Columns `text` takes up on a terminal, counting emoji and other wide
+/// glyphs as two
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// A run of text in one color
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    text: String,
+    fg: Color,
+    bg: Color,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Span::colored(text, Color::Reset)
+    }
+
+    pub fn colored(text: impl Into<String>, fg: Color) -> Self {
+        Span { text: text.into(), fg, bg: Color::Reset }
+    }
+
+    /// The same text on a `bg` background
+    pub fn on(self, bg: Color) -> Self {
+        Span { bg, ..self }
+    }
+
+    fn width(&self) -> usize {
+        width(&self.text)
+    }
+}
+
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(Span::width).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// `spans` padded with spaces to `width` columns
+fn aligned(mut spans: Vec<Span>, align: Align, width: usize) -> Vec<Span> {
+    let slack = width.saturating_sub(spans_width(&spans));
+    let left = match align {
+        Align::Left => 0,
+        Align::Center => slack / 2,
+        Align::Right => slack,
+    };
+    if left > 0 {
+        spans.insert(0, Span::plain(" ".repeat(left)));
+    }
+    if slack > left {
+        spans.push(Span::plain(" ".repeat(slack - left)));
+    }
+    spans
+}
+
+/// A horizontal bar `width` columns wide, filled in green for `fraction` of it
+pub fn progress_bar(fraction: f64, width: usize) -> Vec<Span> {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64) as usize).min(width);
+    vec![
+        Span::plain("["),
+        Span::colored("█".repeat(filled), Color::Green),
+        Span::plain(" ".repeat(width - filled)),
+        Span::plain("]"),
+    ]
+}
+
+/// Rows of cells laid out in columns as wide as their widest cell
+#[derive(Debug, Clone)]
+pub struct Table {
+    align: Vec<Align>,
+    /// Spaces between columns
+    gap: usize,
+    rows: Vec<Vec<Span>>,
+}
+
+impl Table {
+    /// A table with one column per entry of `align`, `gap` spaces apart
+    pub fn new(align: &[Align], gap: usize) -> Self {
+        Table { align: align.to_vec(), gap, rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, cells: Vec<Span>) {
+        self.rows.push(cells);
+    }
+
+    /// The rows as lines
+    fn lines(&self) -> Vec<Vec<Span>> {
+        let widths: Vec<usize> = (0..self.align.len())
+            .map(|col| self.rows.iter().filter_map(|row| row.get(col)).map(Span::width).max().unwrap_or(0))
+            .collect();
+        self.rows.iter().map(|row| {
+            let mut line = Vec::new();
+            for (col, cell) in row.iter().enumerate() {
+                if col > 0 {
+                    line.push(Span::plain(" ".repeat(self.gap)));
+                }
+                line.extend(aligned(vec![cell.clone()], self.align[col], widths[col]));
+            }
+            line
+        }).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Border {
+    Double,
+    Single,
+}
+
+impl Border {
+    /// Corners, edges and the joints of a rule, in the order ┌ ┐ └ ┘ ─ │ ├ ┤
+    fn glyphs(self) -> [char; 8] {
+        match self {
+            Border::Double => ['╔', '╗', '╚', '╝', '═', '║', '╠', '╣'],
+            Border::Single => ['┌', '┐', '└', '┘', '─', '│', '├', '┤'],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PanelRow {
+    Line(Vec<Span>, Align),
+    Rule,
+}
+
+/// A box around lines of text, at least `width` columns wide inside and wider
+/// if a line needs it
+#[derive(Debug, Clone)]
+pub struct Panel {
+    border: Border,
+    width: usize,
+    rows: Vec<PanelRow>,
+}
+
+impl Panel {
+    pub fn new(border: Border, width: usize) -> Self {
+        Panel { border, width, rows: Vec::new() }
+    }
+
+    /// A line starting one space in from the left edge
+    pub fn line(&mut self, mut spans: Vec<Span>) {
+        spans.insert(0, Span::plain(" "));
+        self.rows.push(PanelRow::Line(spans, Align::Left));
+    }
+
+    pub fn centered(&mut self, spans: Vec<Span>) {
+        self.rows.push(PanelRow::Line(spans, Align::Center));
+    }
+
+    pub fn blank(&mut self) {
+        self.rows.push(PanelRow::Line(Vec::new(), Align::Left));
+    }
+
+    /// A horizontal line across the panel
+    pub fn rule(&mut self) {
+        self.rows.push(PanelRow::Rule);
+    }
+
+    pub fn table(&mut self, table: &Table) {
+        for line in table.lines() {
+            self.line(line);
+        }
+    }
+
+    /// The panel as lines of spans, borders included
+    fn lines(&self) -> Vec<Vec<Span>> {
+        let inner = self.rows.iter()
+            .map(|row| match row {
+                PanelRow::Line(spans, _) => spans_width(spans) + 1,
+                PanelRow::Rule => 0,
+            })
+            .fold(self.width, usize::max);
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical, left_joint, right_joint] =
+            self.border.glyphs();
+        let edge = |left: char, right: char| {
+            vec![Span::plain(format!("{}{}{}", left, horizontal.to_string().repeat(inner), right))]
+        };
+
+        let mut lines = vec![edge(top_left, top_right)];
+        for row in &self.rows {
+            match row {
+                PanelRow::Line(spans, align) => {
+                    let mut line = vec![Span::plain(vertical.to_string())];
+                    line.extend(aligned(spans.clone(), *align, inner));
+                    line.push(Span::plain(vertical.to_string()));
+                    lines.push(line);
+                }
+                PanelRow::Rule => lines.push(edge(left_joint, right_joint)),
+            }
+        }
+        lines.push(edge(bottom_left, bottom_right));
+        lines
+    }
+}
+
+/// A frame buffer: lines of cells, top to bottom
+#[derive(Debug, Clone, Default)]
+pub struct Screen {
+    lines: Vec<Vec<Cell>>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn line(&mut self, spans: Vec<Span>) {
+        let mut cells: Vec<Cell> = Vec::new();
+        for span in &spans {
+            for ch in span.text.chars() {
+                let mut buf = [0; 4];
+                let ch = ch.encode_utf8(&mut buf);
+                match cells.last_mut() {
+                    Some(cell) if width(ch) == 0 => {
+                        cell.text.push_str(ch);
+                        cell.width = width(&cell.text);
+                    }
+                    _ => cells.push(Cell { text: ch.to_string(), width: width(ch), fg: span.fg, bg: span.bg }),
+                }
+            }
+        }
+        self.lines.push(cells);
+    }
+
+    pub fn blank(&mut self) {
+        self.lines.push(Vec::new());
+    }
+
+    /// Add `table`'s rows, `indent` columns in from the left
+    pub fn table(&mut self, table: &Table, indent: usize) {
+        for mut line in table.lines() {
+            line.insert(0, Span::plain(" ".repeat(indent)));
+            self.line(line);
+        }
+    }
+
+    pub fn panel(&mut self, panel: &Panel) {
+        for line in panel.lines() {
+            self.line(line);
+        }
+    }
+
+    /// Print the screen at the cursor, like `outln!` for each line
+    pub fn print(&self) {
+        self.print_to(&mut transcript::console());
+    }
+
+    fn print_to(&self, out: &mut impl Write) {
+        for line in &self.lines {
+            let _ = queue_cells(out, line);
+            let _ = queue!(out, Print("\n"));
+        }
+        let _ = out.flush();
+    }
+
+    /// The screen's text without colors, for the transcript
+    fn text(&self) -> String {
+        self.lines.iter()
+            .map(|line| line.iter().map(|cell| cell.text.as_str()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    /// Draw the screen at the top of the terminal and clear everything below
+    /// it. When the last presented screen is still in place only the cells that
+    /// changed are rewritten; otherwise the terminal is cleared and the screen
+    /// drawn in full.
+    pub fn present(self) {
+        transcript::record(&self.text());
+        let previous = PRESENTED.lock().unwrap().take();
+        let Some(previous) = previous.filter(|_| io::stdout().is_terminal() && still_in_place()) else {
+            clear();
+            self.print_to(&mut io::stdout());
+            *PRESENTED.lock().unwrap() = Some(self.lines);
+            return;
+        };
+
+        let mut out = io::stdout();
+        for (row, line) in self.lines.iter().enumerate() {
+            let old = previous.get(row).map(Vec::as_slice).unwrap_or_default();
+            if old == line.as_slice() {
+                continue;
+            }
+            let row = row as u16;
+            if same_columns(old, line) {
+                let mut col = 0;
+                for (cell, old_cell) in line.iter().zip(old) {
+                    if cell != old_cell {
+                        let _ = queue!(out, MoveTo(col as u16, row));
+                        let _ = queue_cells(&mut out, std::slice::from_ref(cell));
+                    }
+                    col += cell.width;
+                }
+            } else {
+                let _ = queue!(out, MoveTo(0, row), Clear(ClearType::CurrentLine));
+                let _ = queue_cells(&mut out, line);
+            }
+        }
+        let _ = queue!(out, MoveTo(0, self.lines.len() as u16), Clear(ClearType::FromCursorDown));
+        let _ = out.flush();
+        *PRESENTED.lock().unwrap() = Some(self.lines);
+    }
+}
+
+/// Queue `cells` for output at the cursor
+fn queue_cells(out: &mut impl Write, cells: &[Cell]) -> io::Result<()> {
+    for cell in cells {
+        if cell.fg == Color::Reset && cell.bg == Color::Reset {
+            queue!(out, Print(&cell.text))?;
+        } else {
+            queue!(out, SetForegroundColor(cell.fg), SetBackgroundColor(cell.bg), Print(&cell.text), ResetColor)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `new` can be drawn over `old` cell by cell: each cell starts in the
+/// same column in both lines
+fn same_columns(old: &[Cell], new: &[Cell]) -> bool {
+    old.len() == new.len() && old.iter().zip(new).all(|(a, b)| a.width == b.width)
+}
+
+/// Whether the screen presented last is still where it was. Output only
+/// scrolls the terminal once it reaches the bottom row, so while the cursor is
+/// above it nothing has moved.
+fn still_in_place() -> bool {
+    if NO_CURSOR_REPORTS.load(Ordering::Relaxed) {
+        return false;
+    }
+    match (cursor::position(), terminal::size()) {
+        (Ok((_, row)), Ok((_, rows))) => row + 1 < rows,
+        _ => {
+            NO_CURSOR_REPORTS.store(true, Ordering::Relaxed);
+            false
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Script AIs: a Rhai script that defines `fn choose_move(state, moves, roll)`
+//! plays as an AI in interactive play, stats mode and the demo when passed with
+//! `--script FILE`. The script sees the game only through a read-only `State`
+//! value and returns one of the piece indices in `moves`. Scripts need the
+//! `scripting` feature; without it `--script` reports how to enable it.
+//! `ur match` also plays scripts, through the referee, which forfeits a script
+//! that fails instead of standing in for it.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+static SCRIPT: OnceLock<ScriptAI> = OnceLock::new();
+
+/// A compiled strategy script
+pub struct ScriptAI {
+    /// File name without its extension, used in labels
+    pub name: String,
+    #[cfg(feature = "scripting")]
+    engine: rhai::Engine,
+    #[cfg(feature = "scripting")]
+    ast: rhai::AST,
+}
+
+/// Compile the script at `path` and make it the script AI for this run
+pub fn load(path: &Path) -> Result<(), String> {
+    let script = ScriptAI::from_file(path)?;
+    SCRIPT.set(script).map_err(|_| "a script AI is already loaded".to_string())
+}
+
+/// The script AI, if `--script` loaded one
+pub fn loaded() -> Option<&'static ScriptAI> {
+    SCRIPT.get()
+}
+
+impl ScriptAI {
+    /// Name of this script on the leaderboard
+    pub fn label(&self) -> String {
+        format!("script-{}", self.name)
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptAI {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        Err(format!("cannot load {}: this build has no script support (rebuild with `--features scripting`)", path.display()))
+    }
+
+    pub fn choose_move(&self, _game: &crate::optimized_game::FastGameState, _moves: &[u8], _roll: u8) -> u8 {
+        unreachable!("script AIs cannot be loaded without the scripting feature")
+    }
+
+    pub fn try_choose_move(&self, _game: &crate::optimized_game::FastGameState, _moves: &[u8], _roll: u8) -> Result<i64, String> {
+        unreachable!("script AIs cannot be loaded without the scripting feature")
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+
+    use super::ScriptAI;
+    use crate::ai_helpers::choose_smart_move_fast;
+    use crate::board;
+    use crate::optimized_game::{FastGameState, FastPlayer, MoveInfo};
+
+    /// Operations one call may run, so a runaway loop fails instead of hanging the game
+    const MAX_OPERATIONS: u64 = 1_000_000;
+    const MAX_CALL_LEVELS: usize = 32;
+    /// Nesting allowed in expressions and function bodies (Rhai's debug-build defaults are much lower)
+    const MAX_EXPR_DEPTH: usize = 64;
+
+    /// Only the first failing call is reported; after that the Smart AI quietly stands in
+    static REPORTED_ERROR: AtomicBool = AtomicBool::new(false);
+
+    type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+    /// The read-only view of a position a script receives. Players are 1 and 2;
+    /// piece positions count along the owner's path, 0 waiting to enter,
+    /// 1..=path_length on the board and path_length + 1 finished.
+    #[derive(Clone)]
+    struct State(FastGameState);
+
+    fn player(number: i64) -> ScriptResult<FastPlayer> {
+        match number {
+            1 => Ok(FastPlayer::One),
+            2 => Ok(FastPlayer::Two),
+            _ => Err(format!("there is no player {} (players are 1 and 2)", number).into()),
+        }
+    }
+
+    fn number(player: FastPlayer) -> i64 {
+        player as i64 + 1
+    }
+
+    /// Board square of `player`'s path position `pos`, if it is on the board
+    fn square(player: FastPlayer, pos: i64) -> Option<u8> {
+        u8::try_from(pos).ok()
+            .filter(|&pos| FastGameState::is_on_board(pos))
+            .map(|pos| FastGameState::path_to_global(player, pos - 1))
+    }
+
+    impl State {
+        /// The position after `piece` moves `roll`, or an error if it cannot
+        fn play(&self, piece: i64, roll: i64) -> ScriptResult<(FastGameState, MoveInfo)> {
+            let (Ok(piece), Ok(roll)) = (u8::try_from(piece), u8::try_from(roll)) else {
+                return Err(format!("piece {} cannot move {}", piece, roll).into());
+            };
+            let mut game = self.0;
+            match game.play(piece, roll) {
+                Ok(info) => Ok((game, info)),
+                Err(e) => Err(e.to_string().into()),
+            }
+        }
+    }
+
+    fn build_engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+
+        engine.register_type_with_name::<State>("State")
+            .register_get("player", |s: &mut State| number(s.0.current_player()))
+            .register_get("opponent", |s: &mut State| number(s.0.current_player().opposite()))
+            .register_get("path_length", |_: &mut State| FastGameState::path_len() as i64)
+            .register_get("winner", |s: &mut State| {
+                s.0.status().winner().map_or(0, number)
+            })
+            .register_fn("score", |s: &mut State, p: i64| -> ScriptResult<i64> {
+                Ok(s.0.get_score(player(p)?) as i64)
+            })
+            .register_fn("pieces", |s: &mut State, p: i64| -> ScriptResult<Array> {
+                let p = player(p)?;
+                Ok((0..7).map(|idx| Dynamic::from(s.0.get_piece_pos(p, idx) as i64)).collect())
+            })
+            .register_fn("is_rosette", |_: &mut State, p: i64, pos: i64| -> ScriptResult<bool> {
+                Ok(square(player(p)?, pos).is_some_and(FastGameState::is_rosette))
+            })
+            .register_fn("is_safe", |_: &mut State, p: i64, pos: i64| -> ScriptResult<bool> {
+                Ok(square(player(p)?, pos).is_some_and(FastGameState::is_safe))
+            })
+            .register_fn("in_shared_lane", |_: &mut State, p: i64, pos: i64| -> ScriptResult<bool> {
+                let paths = board::layout().paths;
+                Ok(square(player(p)?, pos).is_some_and(|sq| paths.iter().all(|path| path.contains(&sq))))
+            })
+            // Player (1 or 2) with a piece on `p`'s path position `pos`, or 0
+            .register_fn("occupant", |s: &mut State, p: i64, pos: i64| -> ScriptResult<i64> {
+                Ok(square(player(p)?, pos).and_then(|sq| s.0.get_occupant(sq)).map_or(0, number))
+            })
+            .register_fn("legal_moves", |s: &mut State, roll: i64| -> Array {
+                u8::try_from(roll).map_or_else(|_| Array::new(), |roll| {
+                    s.0.generate_moves(roll).into_iter().map(|piece| Dynamic::from(piece as i64)).collect()
+                })
+            })
+            // What moving `piece` by `roll` would do, as a map
+            .register_fn("preview", |s: &mut State, piece: i64, roll: i64| -> ScriptResult<Map> {
+                let (_, info) = s.play(piece, roll)?;
+                let mut map = Map::new();
+                map.insert("from".into(), Dynamic::from(info.from_pos as i64));
+                map.insert("to".into(), Dynamic::from(info.to_pos as i64));
+                map.insert("captures".into(), Dynamic::from(info.captured_piece.is_some()));
+                map.insert("extra_turn".into(), Dynamic::from(info.extra_turn));
+                map.insert("finishes".into(), Dynamic::from(info.to_pos == FastGameState::finished_pos()));
+                Ok(map)
+            })
+            // The position after moving `piece` by `roll`; the original is unchanged
+            .register_fn("after", |s: &mut State, piece: i64, roll: i64| -> ScriptResult<State> {
+                s.play(piece, roll).map(|(game, _)| State(game))
+            });
+        engine
+    }
+
+    impl ScriptAI {
+        /// Compile the script at `path`
+        pub fn from_file(path: &Path) -> Result<Self, String> {
+            let engine = build_engine();
+            let ast = engine.compile_file(path.to_path_buf())
+                .map_err(|e| format!("cannot load {}: {}", path.display(), e))?;
+            if !ast.iter_functions().any(|f| f.name == "choose_move" && f.params.len() == 3) {
+                return Err(format!("{} does not define fn choose_move(state, moves, roll)", path.display()));
+            }
+            let name = path.file_stem().map_or_else(|| "script".to_string(), |stem| stem.to_string_lossy().into_owned());
+            Ok(ScriptAI { name, engine, ast })
+        }
+
+        /// Whatever the script's `choose_move` returns, unchecked, or its error
+        pub fn try_choose_move(&self, game: &FastGameState, moves: &[u8], roll: u8) -> Result<i64, String> {
+            let choices: Array = moves.iter().map(|&piece| Dynamic::from(piece as i64)).collect();
+            self.engine
+                .call_fn::<i64>(&mut Scope::new(), &self.ast, "choose_move", (State(*game), choices, roll as i64))
+                .map_err(|e| e.to_string())
+        }
+
+        /// The script's move; if the script fails or returns something other
+        /// than one of `moves`, the Smart AI's move is played instead
+        pub fn choose_move(&self, game: &FastGameState, moves: &[u8], roll: u8) -> u8 {
+            let result = self.try_choose_move(game, moves, roll)
+                .and_then(|piece| match moves.iter().find(|&&m| m as i64 == piece) {
+                    Some(&piece) => Ok(piece),
+                    None => Err(format!("choose_move returned {}, which is not one of {:?}", piece, moves)),
+                });
+            result.unwrap_or_else(|e| {
+                if !REPORTED_ERROR.swap(true, Ordering::Relaxed) {
+                    eprintln!("⚠️  Script {} failed ({}); the Smart AI plays its moves when it does", self.name, e);
+                }
+                choose_smart_move_fast(game, game.current_player(), moves, roll)
+            })
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Scoreboard for a run of games between the same two participants.
+//! Participants keep their index even when they swap seats between games.
+
+use crate::difficulty::Difficulty;
+use crate::luck::Ledger;
+use crate::optimized_game::FastPlayer;
+use crate::screen::{Align, Screen, Span, Table};
+use crate::stats::ExtraRolls;
+use crate::transcript::outln;
+
+/// Turns, captures, extra rolls, luck and skill so far in one game, indexed by seat
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameTally {
+    pub turns: usize,
+    pub captures: [usize; 2],
+    pub extra_rolls: ExtraRolls,
+    pub luck: Ledger,
+}
+
+/// How a finished game went, by seat
+#[derive(Debug, Clone, Copy)]
+pub struct GameSummary {
+    /// None for a draw
+    pub winner: Option<FastPlayer>,
+    pub tally: GameTally,
+    /// Pieces each seat bore off
+    pub scores: [u8; 2],
+    /// Each seat's efficiency, if the game was rated and the seat had a choice
+    pub efficiency: [Option<f64>; 2],
+    /// Both sides asked for a rematch during the game
+    pub rematch: bool,
+}
+
+/// One finished game, by participant
+struct SessionGame {
+    winner: Option<usize>,
+    difficulty: Option<Difficulty>,
+}
+
+pub struct Session {
+    names: [String; 2],
+    wins: [usize; 2],
+    captures: [usize; 2],
+    /// Sum and count of the efficiencies of rated games
+    efficiency: [(f64, usize); 2],
+    total_turns: usize,
+    games: Vec<SessionGame>,
+}
+
+impl Session {
+    pub fn new(names: [String; 2]) -> Self {
+        Session {
+            names,
+            wins: [0; 2],
+            captures: [0; 2],
+            efficiency: [(0.0, 0); 2],
+            total_turns: 0,
+            games: Vec::new(),
+        }
+    }
+
+    /// Record a finished game; `swapped` means participant 1 sat in seat 1
+    pub fn record(&mut self, summary: &GameSummary, swapped: bool, difficulty: Option<Difficulty>) {
+        let participant = |seat: usize| if swapped { 1 - seat } else { seat };
+
+        let winner = summary.winner.map(|winner| participant(winner as usize));
+        if let Some(winner) = winner {
+            self.wins[winner] += 1;
+        }
+        for seat in 0..2 {
+            self.captures[participant(seat)] += summary.tally.captures[seat];
+            if let Some(efficiency) = summary.efficiency[seat] {
+                let (sum, count) = &mut self.efficiency[participant(seat)];
+                *sum += efficiency;
+                *count += 1;
+            }
+        }
+        self.total_turns += summary.tally.turns;
+        self.games.push(SessionGame { winner, difficulty });
+    }
+
+    /// Consecutive results for `participant` ending with the latest game:
+    /// positive for wins, negative for losses, and 0 after a draw
+    pub fn streak(&self, participant: usize) -> i32 {
+        let Some(last_winner) = self.games.last().and_then(|last| last.winner) else {
+            return 0;
+        };
+        let run = self.games.iter().rev().take_while(|g| g.winner == Some(last_winner)).count() as i32;
+        if last_winner == participant { run } else { -run }
+    }
+
+    pub fn display(&self) {
+        let games = self.games.len().max(1) as f64;
+        let draws = self.games.iter().filter(|g| g.winner.is_none()).count();
+        let drawn = if draws > 0 { format!(", {} drawn", draws) } else { String::new() };
+        outln!("=== SESSION SCOREBOARD ({} game{}{}) ===", self.games.len(), if self.games.len() == 1 { "" } else { "s" }, drawn);
+        let mut table = Table::new(&[Align::Left, Align::Left], 2);
+        for i in 0..2 {
+            let (sum, count) = self.efficiency[i];
+            let efficiency = if count > 0 { format!(" | efficiency {:.1}", sum / count as f64) } else { String::new() };
+            table.row(vec![
+                Span::plain(self.names[i].as_str()),
+                Span::plain(format!("{} win{} | {} captures ({:.1} per game){}",
+                    self.wins[i], if self.wins[i] == 1 { "" } else { "s" },
+                    self.captures[i], self.captures[i] as f64 / games, efficiency)),
+            ]);
+        }
+        let mut screen = Screen::new();
+        screen.table(&table, 2);
+        screen.print();
+        outln!("  Average game length: {:.1} turns", self.total_turns as f64 / games);
+
+        let levels: Vec<String> = Difficulty::ALL.iter().filter_map(|&level| {
+            let at_level = self.games.iter().filter(|g| g.difficulty == Some(level));
+            let wins = at_level.filter_map(|g| g.winner).fold([0; 2], |mut wins, winner| {
+                wins[winner] += 1;
+                wins
+            });
+            (wins[0] + wins[1] > 0).then(|| format!("{} {}-{}", level.name(), wins[0], wins[1]))
+        }).collect();
+        if !levels.is_empty() {
+            outln!("  By difficulty ({} vs {}): {}", self.names[0], self.names[1], levels.join(" | "));
+        }
+    }
+}
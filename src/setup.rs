@@ -0,0 +1,228 @@
+//! The setup wizard for a new interactive game. Each player's type, the rule
+//! variant, the time control and the dice seed are chosen one step at a time,
+//! so a new AI type only adds a line to a list, and everything chosen is shown
+//! together for confirmation before the game starts.
+
+use crate::autotune;
+use crate::board::BoardVariant;
+use crate::dice::DiceVariant;
+use crate::input::{prompt_in_range, prompt_line, prompt_parse, prompt_yes_no, InputResult};
+use crate::opponents;
+use crate::rng::{self, GameRng};
+use crate::screen::{Align, Border, Panel, Screen, Span, Table};
+use crate::scripting;
+use crate::stats::{StatsAgent, TunedAI};
+use crate::subprocess_bot;
+use crate::transcript::outln;
+use crate::turn_clock::{TimeoutAction, TurnClock};
+use crate::{AIType, PlayOptions};
+
+/// Everything chosen for a new game
+#[derive(Debug, Clone)]
+pub struct GameSetup {
+    pub players: [AIType; 2],
+    pub board: BoardVariant,
+    pub dice: DiceVariant,
+    /// Time limit for each human turn
+    pub clock: Option<TurnClock>,
+    /// Seed for the dice; each game of a session follows from it
+    pub seed: u64,
+}
+
+/// Walk through the setup steps until the summary is confirmed. Rules fixed on
+/// the command line (`--board`, `--dice`, or `--position`, which is read on
+/// the standard board unless `--board` says otherwise) are shown, not asked.
+pub fn choose(options: &PlayOptions) -> InputResult<GameSetup> {
+    loop {
+        outln!("Step 1/5: Player 1");
+        let player1 = choose_player(AIType::Human)?;
+        outln!("\nStep 2/5: Player 2");
+        let player2 = choose_player(AIType::Smart)?;
+        let players = [player1, player2];
+
+        outln!("\nStep 3/5: Rules");
+        let board = match options.board.or(options.position.as_ref().map(|_| BoardVariant::default())) {
+            Some(board) => {
+                outln!("Board: {} (set on the command line)", board.label());
+                board
+            }
+            None => choose_board()?,
+        };
+        let dice = match options.dice {
+            Some(dice) => {
+                outln!("Dice: {} (set on the command line)", dice.label());
+                dice
+            }
+            None => choose_dice()?,
+        };
+
+        outln!("\nStep 4/5: Time control");
+        let clock = if players.contains(&AIType::Human) {
+            choose_clock(options.clock)?
+        } else {
+            outln!("No human players, so there is no move clock.");
+            None
+        };
+
+        outln!("\nStep 5/5: Seed");
+        let random = GameRng::random().seed();
+        let seed = if rng::algorithm().is_seeded() {
+            prompt_parse(&format!("Dice seed, to replay the same rolls (default {}, chosen at random): ", random), random)?
+        } else {
+            outln!("The dice come straight from the operating system (--rng os), so there is no seed to choose.");
+            random
+        };
+
+        let setup = GameSetup { players, board, dice, clock, seed };
+        outln!();
+        setup.summary().print();
+        if prompt_yes_no("Start the game? [Y/n]: ", true)? {
+            outln!();
+            return Ok(setup);
+        }
+        outln!("\nStarting over.\n");
+    }
+}
+
+/// A choice on the player menu; a tuned AI's settings and an external bot's
+/// command are asked next
+#[derive(Clone, PartialEq)]
+enum Pick {
+    Player(AIType),
+    Tuned,
+    External,
+}
+
+/// Ask what plays one side; `default` is chosen on ENTER
+fn choose_player(default: AIType) -> InputResult<AIType> {
+    let mut choices: Vec<(String, Pick)> = vec![
+        ("Human".to_string(), Pick::Player(AIType::Human)),
+        ("Random AI: picks any legal move".to_string(), Pick::Player(AIType::Random)),
+        ("Smart AI: fast heuristic play".to_string(), Pick::Player(AIType::Smart)),
+        ("Expectimax AI: heuristic play that weighs the replies each roll allows".to_string(), Pick::Player(AIType::Expectimax)),
+        ("MCTS AI: Monte Carlo Tree Search".to_string(), Pick::Player(AIType::MCTS)),
+        ("Adaptive AI: MCTS that matches your skill over a session".to_string(), Pick::Player(AIType::Adaptive)),
+    ];
+    for opponent in &opponents::ROSTER {
+        choices.push((format!("{}: {}", opponent.name, opponent.style), Pick::Player(AIType::Opponent(opponent))));
+    }
+    if let Some(script) = scripting::loaded() {
+        choices.push((format!("Script AI '{}'", script.name), Pick::Player(AIType::Script)));
+    }
+    choices.push(("Tuned AI: your own settings, e.g. smart-vulnerability200, expectimax-3 or mcts-5000".to_string(), Pick::Tuned));
+    choices.push(("External bot: a program speaking the `ur match` protocol".to_string(), Pick::External));
+
+    let default_choice = choices.iter().position(|(_, pick)| *pick == Pick::Player(default.clone())).unwrap_or(0) + 1;
+    for (i, (label, _)) in choices.iter().enumerate() {
+        outln!("  {}: {}", i + 1, label);
+    }
+    let choice = prompt_in_range(
+        &format!("Choose [1-{}] (default {}): ", choices.len(), default_choice),
+        1..=choices.len(),
+        default_choice,
+    )?;
+    match &choices[choice - 1].1 {
+        Pick::Player(ai) => Ok(ai.clone()),
+        Pick::Tuned => loop {
+            let spec = prompt_line("Player spec (as in `ur match`, e.g. mcts-5000-risk+0.50): ")?;
+            match StatsAgent::from_spec(&spec, autotune::threads()) {
+                Ok(agent) => return Ok(AIType::Tuned(TunedAI::new(agent))),
+                Err(e) if !spec.is_empty() => outln!("❌ {}", e),
+                Err(_) => {}
+            }
+        },
+        Pick::External => loop {
+            let command = prompt_line("Bot command (e.g. python3 bots/mine.py): ")?;
+            if !command.is_empty() {
+                return Ok(AIType::External(subprocess_bot::seat(&command)));
+            }
+        },
+    }
+}
+
+fn choose_board() -> InputResult<BoardVariant> {
+    outln!("Board:");
+    for (i, board) in BoardVariant::ALL.iter().enumerate() {
+        let layout = board.layout();
+        outln!("  {}: {}, {} ({} squares, {}-square path)",
+                 i + 1, board.label(), board.description(), layout.coords.len(), layout.paths[0].len());
+    }
+    let choice = prompt_in_range(&format!("Choose [1-{}] (default 1): ", BoardVariant::ALL.len()), 1..=BoardVariant::ALL.len(), 1)?;
+    Ok(BoardVariant::ALL[choice - 1])
+}
+
+fn choose_dice() -> InputResult<DiceVariant> {
+    outln!("Dice:");
+    for (i, dice) in DiceVariant::ALL.iter().enumerate() {
+        outln!("  {}: {}, {}", i + 1, dice.label(), dice.model().description());
+    }
+    let choice = prompt_in_range(&format!("Choose [1-{}] (default 1): ", DiceVariant::ALL.len()), 1..=DiceVariant::ALL.len(), 1)?;
+    Ok(DiceVariant::ALL[choice - 1])
+}
+
+/// Ask for a move clock, defaulting to the one from `--move-time`
+fn choose_clock(default: Option<TurnClock>) -> InputResult<Option<TurnClock>> {
+    let default_seconds = default.map_or(0.0, |clock| clock.per_move.as_secs_f64());
+    loop {
+        let seconds: f64 = prompt_parse(
+            &format!("Seconds per human turn, 0 for no limit (default {}): ", default_seconds),
+            default_seconds,
+        )?;
+        if seconds == 0.0 {
+            return Ok(None);
+        }
+        let clock = match TurnClock::from_seconds(seconds, TimeoutAction::default()) {
+            Ok(clock) => clock,
+            Err(e) => {
+                outln!("❌ {}", e);
+                continue;
+            }
+        };
+        outln!("When a turn runs out of time:");
+        outln!("  1: The smart AI moves for the player");
+        outln!("  2: The player forfeits the game");
+        let forfeit_by_default = default.is_some_and(|clock| clock.on_timeout == TimeoutAction::Forfeit);
+        let default_choice = if forfeit_by_default { 2 } else { 1 };
+        let on_timeout = match prompt_in_range(&format!("Choose [1-2] (default {}): ", default_choice), 1..=2, default_choice)? {
+            1 => TimeoutAction::AutoMove,
+            _ => TimeoutAction::Forfeit,
+        };
+        return Ok(Some(TurnClock { on_timeout, ..clock }));
+    }
+}
+
+impl GameSetup {
+    /// The choices, boxed, for confirmation
+    fn summary(&self) -> Screen {
+        let mut table = Table::new(&[Align::Left, Align::Left], 2);
+        for (i, player) in self.players.iter().enumerate() {
+            table.row(vec![Span::plain(format!("Player {}", i + 1)), Span::plain(player_description(player))]);
+        }
+        let layout = self.board.layout();
+        table.row(vec![Span::plain("Board"), Span::plain(format!("{} ({}-square path)", self.board.label(), layout.paths[0].len()))]);
+        table.row(vec![Span::plain("Dice"), Span::plain(format!("{} ({})", self.dice.label(), self.dice.model().description()))]);
+        table.row(vec![Span::plain("Move clock"), Span::plain(self.clock.map_or("none".to_string(), |clock| clock.to_string()))]);
+        if rng::algorithm().is_seeded() {
+            table.row(vec![Span::plain("Seed"), Span::plain(format!("{} ({})", self.seed, rng::algorithm().label()))]);
+        } else {
+            table.row(vec![Span::plain("Seed"), Span::plain("none (os)")]);
+        }
+
+        let mut panel = Panel::new(Border::Single, 37);
+        panel.centered(vec![Span::plain("Game setup")]);
+        panel.rule();
+        panel.table(&table);
+        let mut screen = Screen::new();
+        screen.panel(&panel);
+        screen
+    }
+}
+
+fn player_description(player: &AIType) -> String {
+    match player {
+        AIType::Script => format!("Script AI '{}'", scripting::loaded().map_or("?", |script| script.name.as_str())),
+        AIType::External(idx) => format!("External bot ({})", subprocess_bot::seated_label(*idx)),
+        AIType::Tuned(tuned) => format!("Tuned AI ({})", tuned.label()),
+        _ => player.display_name().to_string(),
+    }
+}
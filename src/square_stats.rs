@@ -0,0 +1,201 @@
+//! Per-square tracking for stats mode: how long each square is occupied and by
+//! whom, and how the central rosette is fought over.
+
+use crate::board::{self, MAX_SQUARES};
+use crate::optimized_game::{FastGameState, FastPlayer};
+
+/// Square-level record of one game, fed once per turn
+#[derive(Debug)]
+pub struct SquareTracker {
+    /// Turns each player spent on each square
+    occupancy: [[usize; MAX_SQUARES as usize]; 2],
+    turns: usize,
+    central: Option<u8>,
+    /// Piece currently on the central rosette and how many turns it has been there
+    holder: Option<(FastPlayer, u8)>,
+    stint: usize,
+    /// Completed stints on the central rosette, in turns
+    stints: Vec<usize>,
+    /// Turns the side to move rolled exactly what it needed to land on an
+    /// opponent-held central rosette
+    contested: usize,
+}
+
+impl SquareTracker {
+    pub fn new() -> Self {
+        SquareTracker {
+            occupancy: [[0; MAX_SQUARES as usize]; 2],
+            turns: 0,
+            central: board::layout().central_rosette(),
+            holder: None,
+            stint: 0,
+            stints: Vec::new(),
+            contested: 0,
+        }
+    }
+
+    /// Record the position at the start of a turn
+    pub fn observe(&mut self, game: &FastGameState) {
+        self.turns += 1;
+        let mut holder = None;
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            for piece_idx in 0..7 {
+                let pos = game.get_piece_pos(player, piece_idx);
+                if !FastGameState::is_on_board(pos) {
+                    continue;
+                }
+                let square = FastGameState::path_to_global(player, pos - 1);
+                self.occupancy[player as usize][square as usize] += 1;
+                if Some(square) == self.central {
+                    holder = Some((player, piece_idx));
+                }
+            }
+        }
+
+        if holder.is_some() && holder == self.holder {
+            self.stint += 1;
+        } else {
+            self.end_stint();
+            self.holder = holder;
+            self.stint = usize::from(holder.is_some());
+        }
+    }
+
+    /// Record the roll of the side to move; a roll that would land on an
+    /// opponent-held central rosette counts as a contest
+    pub fn observe_roll(&mut self, game: &FastGameState, roll: u8) {
+        let (Some(central), Some((holder, _))) = (self.central, self.holder) else {
+            return;
+        };
+        let mover = game.current_player();
+        if roll == 0 || holder == mover {
+            return;
+        }
+        let blocked = (0..7).any(|piece_idx| {
+            let pos = game.get_piece_pos(mover, piece_idx);
+            FastGameState::is_on_board(pos)
+                && pos - 1 + roll < FastGameState::path_len()
+                && FastGameState::path_to_global(mover, pos - 1 + roll) == central
+        });
+        if blocked {
+            self.contested += 1;
+        }
+    }
+
+    fn end_stint(&mut self) {
+        if self.holder.is_some() {
+            self.stints.push(self.stint);
+        }
+    }
+
+    /// Turns each player held the central rosette
+    fn central_turns(&self) -> [usize; 2] {
+        match self.central {
+            Some(square) => [self.occupancy[0][square as usize], self.occupancy[1][square as usize]],
+            None => [0, 0],
+        }
+    }
+}
+
+/// Square statistics summed over many games
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SquareStats {
+    occupancy: [[usize; MAX_SQUARES as usize]; 2],
+    turns: usize,
+    games: usize,
+    central_turns: [usize; 2],
+    stint_count: usize,
+    stint_total: usize,
+    longest_stint: usize,
+    contested: usize,
+    /// Games where one side held the central rosette longer, and how many that side won
+    decided_by_holding: usize,
+    longer_holder_won: usize,
+}
+
+impl SquareStats {
+    pub fn new() -> Self {
+        SquareStats {
+            occupancy: [[0; MAX_SQUARES as usize]; 2],
+            turns: 0,
+            games: 0,
+            central_turns: [0; 2],
+            stint_count: 0,
+            stint_total: 0,
+            longest_stint: 0,
+            contested: 0,
+            decided_by_holding: 0,
+            longer_holder_won: 0,
+        }
+    }
+
+    pub fn add_game(&mut self, mut tracker: SquareTracker, winner: Option<FastPlayer>) {
+        tracker.end_stint();
+
+        for (total, game) in self.occupancy.iter_mut().zip(&tracker.occupancy) {
+            for (t, g) in total.iter_mut().zip(game) {
+                *t += g;
+            }
+        }
+        self.turns += tracker.turns;
+        self.games += 1;
+        self.contested += tracker.contested;
+        self.stint_count += tracker.stints.len();
+        self.stint_total += tracker.stints.iter().sum::<usize>();
+        self.longest_stint = self.longest_stint.max(tracker.stints.iter().copied().max().unwrap_or(0));
+
+        let held = tracker.central_turns();
+        self.central_turns[0] += held[0];
+        self.central_turns[1] += held[1];
+        if held[0] != held[1] {
+            self.decided_by_holding += 1;
+            let longer = if held[0] > held[1] { FastPlayer::One } else { FastPlayer::Two };
+            if Some(longer) == winner {
+                self.longer_holder_won += 1;
+            }
+        }
+    }
+
+    pub fn display(&self, p1_desc: &str, p2_desc: &str) {
+        if self.games == 0 || self.turns == 0 {
+            return;
+        }
+        let layout = board::layout();
+        let share = |turns: usize| turns as f64 / self.turns as f64 * 100.0;
+
+        println!();
+        println!("BOARD CONTROL (% of turns each square is occupied):");
+        for row in 0..layout.rows {
+            print!("  ");
+            for col in 0..layout.cols {
+                match layout.coords.iter().position(|&coord| coord == (row, col)) {
+                    Some(square) => print!("{:>4.0}", share(self.occupancy[0][square] + self.occupancy[1][square])),
+                    None => print!("    "),
+                }
+            }
+            println!();
+        }
+
+        let Some(central) = layout.central_rosette() else {
+            return;
+        };
+        let (r, c) = layout.coords[central as usize];
+        let empty = self.turns - self.central_turns[0] - self.central_turns[1];
+        println!();
+        println!("CENTRAL ROSETTE (grid ({}, {})):", r, c);
+        println!("  Held by {} ({}): {:.1}% of turns", FastPlayer::One.name(), p1_desc, share(self.central_turns[0]));
+        println!("  Held by {} ({}): {:.1}% of turns", FastPlayer::Two.name(), p2_desc, share(self.central_turns[1]));
+        println!("  Empty: {:.1}% of turns", share(empty));
+        println!("  Contested: {:.1} times per game (a roll that would have landed on it while the opponent held it)",
+                 self.contested as f64 / self.games as f64);
+        if self.stint_count > 0 {
+            println!("  Camping: {:.1} turns per stay on average, longest {} turns",
+                     self.stint_total as f64 / self.stint_count as f64, self.longest_stint);
+        }
+        if self.decided_by_holding > 0 {
+            println!("  The side that held it longer won {:.1}% of {} games",
+                     self.longer_holder_won as f64 / self.decided_by_holding as f64 * 100.0, self.decided_by_holding);
+        }
+    }
+}
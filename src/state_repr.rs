@@ -0,0 +1,310 @@
+//! Game state encodings the AI can search over.
+//! `GameStateRepr` is the interface MCTS playouts and the move heuristics use, so
+//! an experimental encoding only has to implement it to be searched; the
+//! `array-state` feature swaps the packed `FastGameState` for `ArrayGameState`
+//! at compile time. Board geometry (paths, rosettes, safe squares) comes from
+//! the selected board layout through `FastGameState`'s helpers.
+
+use crate::board::MAX_SQUARES;
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus, MoveError, MoveInfo, MoveList};
+
+/// Encoding the MCTS search runs on, picked at compile time
+#[cfg(not(feature = "array-state"))]
+pub type SearchState = FastGameState;
+#[cfg(feature = "array-state")]
+pub type SearchState = ArrayGameState;
+
+/// A game state encoding.
+/// Implementors provide storage primitives; the rules are provided on top of them
+/// and may be overridden with faster encoding-specific versions.
+/// Piece positions use the `FastGameState` convention: 0=OffBoard, 1-N=OnBoard(0-(N-1)), N+1=Finished.
+pub trait GameStateRepr: Copy + Send + 'static {
+    /// Start of a game: all pieces off the board, Player 1 to move
+    fn new_game() -> Self;
+
+    fn current_player(&self) -> FastPlayer;
+    /// Hand the turn to the other player
+    fn pass_turn(&mut self);
+
+    fn get_score(&self, player: FastPlayer) -> u8;
+    fn set_score(&mut self, player: FastPlayer, score: u8);
+
+    fn get_piece_pos(&self, player: FastPlayer, piece_idx: u8) -> u8;
+    fn set_piece_pos(&mut self, player: FastPlayer, piece_idx: u8, pos: u8);
+
+    fn get_occupant(&self, square: u8) -> Option<FastPlayer>;
+    fn set_occupant(&mut self, square: u8, occupant: Option<FastPlayer>);
+
+    /// Copy of `state` in this encoding
+    fn from_fast(state: &FastGameState) -> Self {
+        let mut repr = Self::new_game();
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            for piece_idx in 0..7 {
+                let pos = state.get_piece_pos(player, piece_idx);
+                repr.set_piece_pos(player, piece_idx, pos);
+                if FastGameState::is_on_board(pos) {
+                    repr.set_occupant(FastGameState::path_to_global(player, pos - 1), Some(player));
+                }
+            }
+            repr.set_score(player, state.get_score(player));
+        }
+        if state.current_player() != repr.current_player() {
+            repr.pass_turn();
+        }
+        repr
+    }
+
+    fn is_winner(&self, player: FastPlayer) -> bool {
+        self.get_score(player) >= 7
+    }
+
+    /// Won by whoever has brought every piece home, else in progress
+    fn status(&self) -> GameStatus {
+        if self.is_winner(FastPlayer::One) {
+            GameStatus::Won(FastPlayer::One)
+        } else if self.is_winner(FastPlayer::Two) {
+            GameStatus::Won(FastPlayer::Two)
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    fn generate_move_list(&self, roll: u8) -> MoveList {
+        let mut moves = MoveList::new();
+        if roll == 0 {
+            return moves;
+        }
+
+        let player = self.current_player();
+        let path_len = FastGameState::path_len();
+        for piece_idx in 0..7 {
+            let target = match self.get_piece_pos(player, piece_idx) {
+                0 => 0,
+                pos if FastGameState::is_on_board(pos) => pos - 1 + roll,
+                _ => continue,
+            };
+            if target == path_len {
+                moves.push(piece_idx);
+            } else if target < path_len {
+                let square = FastGameState::path_to_global(player, target);
+                let open = match self.get_occupant(square) {
+                    None => true,
+                    Some(occupant) => occupant != player && !FastGameState::is_safe(square),
+                };
+                if open {
+                    moves.push(piece_idx);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Make a move and return undo information, or why the move is illegal
+    fn make_move(&mut self, piece_idx: u8, roll: u8) -> Result<MoveInfo, MoveError> {
+        if roll == 0 {
+            return Err(MoveError::NoRoll);
+        }
+        if piece_idx >= 7 {
+            return Err(MoveError::NoSuchPiece);
+        }
+        let player = self.current_player();
+        let from_pos = self.get_piece_pos(player, piece_idx);
+        let to_pos = match from_pos {
+            0 => 1,
+            _ if !FastGameState::is_on_board(from_pos) => return Err(MoveError::Finished),
+            _ if from_pos + roll > FastGameState::finished_pos() => return Err(MoveError::Overshoot),
+            _ => from_pos + roll,
+        };
+
+        let mut captured_piece = None;
+        let mut extra_turn = false;
+        if FastGameState::is_on_board(to_pos) {
+            let square = FastGameState::path_to_global(player, to_pos - 1);
+            match self.get_occupant(square) {
+                Some(occupant) if occupant == player => return Err(MoveError::OwnPiece),
+                Some(_) if FastGameState::is_safe(square) => return Err(MoveError::Protected),
+                Some(opponent) => {
+                    captured_piece = (0..7).find(|&i| {
+                        let pos = self.get_piece_pos(opponent, i);
+                        FastGameState::is_on_board(pos) && FastGameState::path_to_global(opponent, pos - 1) == square
+                    });
+                    if let Some(captured) = captured_piece {
+                        self.set_piece_pos(opponent, captured, 0);
+                    }
+                }
+                None => {}
+            }
+            self.set_occupant(square, Some(player));
+            extra_turn = FastGameState::is_rosette(square);
+        } else {
+            self.set_score(player, self.get_score(player) + 1);
+        }
+
+        if FastGameState::is_on_board(from_pos) {
+            self.set_occupant(FastGameState::path_to_global(player, from_pos - 1), None);
+        }
+        self.set_piece_pos(player, piece_idx, to_pos);
+        if !extra_turn {
+            self.pass_turn();
+        }
+
+        Ok(MoveInfo { piece_idx, from_pos, to_pos, captured_piece, extra_turn })
+    }
+
+    /// Undo a move made by `player`
+    fn unmake_move(&mut self, player: FastPlayer, move_info: &MoveInfo) {
+        if FastGameState::is_on_board(move_info.to_pos) {
+            let square = FastGameState::path_to_global(player, move_info.to_pos - 1);
+            self.set_occupant(square, None);
+            if let Some(captured) = move_info.captured_piece {
+                let opponent = player.opposite();
+                let path_idx = (0..FastGameState::path_len()).find(|&i| FastGameState::path_to_global(opponent, i) == square).unwrap_or(0);
+                self.set_piece_pos(opponent, captured, path_idx + 1);
+                self.set_occupant(square, Some(opponent));
+            }
+        } else {
+            self.set_score(player, self.get_score(player) - 1);
+        }
+
+        self.set_piece_pos(player, move_info.piece_idx, move_info.from_pos);
+        if FastGameState::is_on_board(move_info.from_pos) {
+            self.set_occupant(FastGameState::path_to_global(player, move_info.from_pos - 1), Some(player));
+        }
+        if !move_info.extra_turn {
+            self.pass_turn();
+        }
+    }
+}
+
+/// The packed bitboard encoding; rules use its own optimized implementations
+impl GameStateRepr for FastGameState {
+    fn new_game() -> Self {
+        FastGameState::new()
+    }
+
+    #[inline]
+    fn current_player(&self) -> FastPlayer {
+        FastGameState::current_player(*self)
+    }
+
+    #[inline]
+    fn pass_turn(&mut self) {
+        self.scores_and_turn ^= 1 << 6;
+    }
+
+    #[inline]
+    fn get_score(&self, player: FastPlayer) -> u8 {
+        FastGameState::get_score(*self, player)
+    }
+
+    #[inline]
+    fn set_score(&mut self, player: FastPlayer, score: u8) {
+        FastGameState::set_score(self, player, score)
+    }
+
+    #[inline]
+    fn get_piece_pos(&self, player: FastPlayer, piece_idx: u8) -> u8 {
+        FastGameState::get_piece_pos(*self, player, piece_idx)
+    }
+
+    #[inline]
+    fn set_piece_pos(&mut self, player: FastPlayer, piece_idx: u8, pos: u8) {
+        FastGameState::set_piece_pos(self, player, piece_idx, pos)
+    }
+
+    #[inline]
+    fn get_occupant(&self, square: u8) -> Option<FastPlayer> {
+        FastGameState::get_occupant(*self, square)
+    }
+
+    #[inline]
+    fn set_occupant(&mut self, square: u8, occupant: Option<FastPlayer>) {
+        self.occupied_squares &= !((1u64 << square) | (1u64 << (square + MAX_SQUARES)));
+        match occupant {
+            Some(FastPlayer::One) => self.occupied_squares |= 1u64 << square,
+            Some(FastPlayer::Two) => self.occupied_squares |= 1u64 << (square + MAX_SQUARES),
+            None => {}
+        }
+    }
+
+    fn from_fast(state: &FastGameState) -> Self {
+        *state
+    }
+
+    #[inline]
+    fn is_winner(&self, player: FastPlayer) -> bool {
+        FastGameState::is_winner(*self, player)
+    }
+
+    #[inline]
+    fn generate_move_list(&self, roll: u8) -> MoveList {
+        FastGameState::generate_move_list(*self, roll)
+    }
+
+    #[inline]
+    fn make_move(&mut self, piece_idx: u8, roll: u8) -> Result<MoveInfo, MoveError> {
+        FastGameState::make_move(self, piece_idx, roll)
+    }
+
+    #[inline]
+    fn unmake_move(&mut self, player: FastPlayer, move_info: &MoveInfo) {
+        FastGameState::unmake_move(self, player, move_info)
+    }
+}
+
+/// Plain-array encoding: one byte per piece and per square, no packing limits.
+/// Slower to copy than `FastGameState`, but a starting point for encodings
+/// that need more squares or per-piece data than 4-bit fields allow.
+#[cfg_attr(not(feature = "array-state"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArrayGameState {
+    pieces: [[u8; 7]; 2],
+    board: [Option<FastPlayer>; MAX_SQUARES as usize],
+    scores: [u8; 2],
+    turn: FastPlayer,
+}
+
+impl GameStateRepr for ArrayGameState {
+    fn new_game() -> Self {
+        ArrayGameState {
+            pieces: [[0; 7]; 2],
+            board: [None; MAX_SQUARES as usize],
+            scores: [0; 2],
+            turn: FastPlayer::One,
+        }
+    }
+
+    fn current_player(&self) -> FastPlayer {
+        self.turn
+    }
+
+    fn pass_turn(&mut self) {
+        self.turn = self.turn.opposite();
+    }
+
+    fn get_score(&self, player: FastPlayer) -> u8 {
+        self.scores[player as usize]
+    }
+
+    fn set_score(&mut self, player: FastPlayer, score: u8) {
+        self.scores[player as usize] = score;
+    }
+
+    fn get_piece_pos(&self, player: FastPlayer, piece_idx: u8) -> u8 {
+        self.pieces[player as usize][piece_idx as usize]
+    }
+
+    fn set_piece_pos(&mut self, player: FastPlayer, piece_idx: u8, pos: u8) {
+        self.pieces[player as usize][piece_idx as usize] = pos;
+    }
+
+    fn get_occupant(&self, square: u8) -> Option<FastPlayer> {
+        self.board[square as usize]
+    }
+
+    fn set_occupant(&mut self, square: u8, occupant: Option<FastPlayer>) {
+        self.board[square as usize] = occupant;
+    }
+}
+
@@ -1,32 +1,339 @@
-use std::io::{self, Write};
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use crossterm::{
     execute,
-    terminal::{Clear, ClearType},
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    cursor::{MoveTo, Hide, Show},
+    cursor::{Hide, Show},
 };
 
-use crate::optimized_game::{FastGameState, FastPlayer};
-use crate::ai::HybridAI;
-use crate::ai_helpers::{choose_random_move_fast, choose_smart_move_fast};
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::archive::Turn;
+use crate::ai::{HybridAI, PlayoutPolicy, SearchInfo};
+use crate::cancel::CancelToken;
+use crate::ai_helpers::{choose_random_move_fast, choose_smart_move_fast, choose_weighted_move, EvalWeights};
+use crate::expectimax::{self, choose_expectimax_move};
+use crate::fast_stats::{run_fast_stats, FastAgent};
+use crate::input::{prompt_in_range, prompt_line, prompt_parse, prompt_yes_no, InputResult};
+use crate::leaderboard::{record_match, DEFAULT_LEADERBOARD_FILE};
+use crate::luck::{self, Ledger};
+use crate::opponents::{self, Opponent};
+use crate::presets::{self, Preset, STATS_PRESET};
+use crate::resources::ResourceMeter;
+use crate::rng::GameRng;
+use crate::screen::{self, progress_bar, Align, Border, Panel, Screen, Span, Table};
+use crate::scripting::{self, ScriptAI};
+use crate::square_stats::{SquareStats, SquareTracker};
+use crate::state_repr::GameStateRepr;
+use crate::style;
 
 #[derive(Debug, Clone, Copy)]
 pub enum StatsAIType {
     Random,
     Smart,
     MCTS,
+    /// The Rhai script loaded with --script
+    Script,
+}
+
+impl StatsAIType {
+    /// The equivalent agent on the allocation-free fast path, if there is one
+    pub fn fast_agent(self) -> Option<FastAgent> {
+        match self {
+            StatsAIType::Random => Some(FastAgent::Random),
+            StatsAIType::Smart => Some(FastAgent::Smart),
+            StatsAIType::MCTS | StatsAIType::Script => None,
+        }
+    }
+
+    /// Playout policy that imitates this AI
+    pub fn playout_policy(self) -> PlayoutPolicy {
+        match self {
+            StatsAIType::Random => PlayoutPolicy::Random,
+            StatsAIType::Smart => PlayoutPolicy::Smart,
+            StatsAIType::MCTS | StatsAIType::Script => PlayoutPolicy::Mixed,
+        }
+    }
+}
+
+/// How stats mode configures its MCTS players
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MctsSettings {
+    /// Preset for AI A and AI B; None uses the stats preset
+    pub presets: [Option<&'static Preset>; 2],
+    /// Per-move think time instead of a fixed simulation count
+    pub time_budget: Option<Duration>,
+    /// Model the opponent's actual policy in playouts
+    pub model_opponent: bool,
+    /// Risk preference for AI A and AI B (ignored for non-MCTS AIs)
+    pub risk: [f64; 2],
+}
+
+/// A configured player for silent games; unlike StatsAIType it owns its AI settings
+pub enum StatsAgent {
+    Random,
+    Smart,
+    /// Smart-style one-ply search with custom evaluation weights
+    Weighted(EvalWeights),
+    /// Smart evaluation searched this many plies deep over the dice
+    Expectimax(u8),
+    MCTS(HybridAI),
+    Script(&'static ScriptAI),
+    Opponent(&'static Opponent),
+}
+
+impl StatsAgent {
+    /// Name of this configuration on the leaderboard
+    pub fn label(&self) -> String {
+        match self {
+            StatsAgent::Random => "random".to_string(),
+            StatsAgent::Smart => "smart".to_string(),
+            StatsAgent::Weighted(weights) => weights.label(),
+            StatsAgent::Expectimax(plies) if *plies == expectimax::DEFAULT_PLIES => "expectimax".to_string(),
+            StatsAgent::Expectimax(plies) => format!("expectimax-{}", plies),
+            StatsAgent::MCTS(mcts_ai) => mcts_ai.config_label(),
+            StatsAgent::Script(script) => script.label(),
+            StatsAgent::Opponent(opponent) => opponent.key.to_string(),
+        }
+    }
+
+    /// The agent a player spec names: `random`, `smart`, `expectimax`, a named
+    /// opponent or an MCTS preset, or a tuned AI by its leaderboard name, such
+    /// as `smart-rosette0-vulnerability200`, `expectimax-3`, `mcts-5000-risk+0.50`
+    /// or `mcts-250ms-model-smart`. MCTS searches on `threads` threads.
+    pub fn from_spec(spec: &str, threads: usize) -> Result<StatsAgent, String> {
+        if let Some(opponent) = opponents::find(spec) {
+            return Ok(StatsAgent::Opponent(opponent));
+        }
+        if let Some(preset) = presets::find(spec) {
+            return Ok(StatsAgent::MCTS(preset.build(threads)));
+        }
+        Ok(match spec.split_once('-').map_or(spec, |(kind, _)| kind) {
+            "random" if spec == "random" => StatsAgent::Random,
+            "smart" => match EvalWeights::from_label(spec)? {
+                weights if weights == EvalWeights::DEFAULT => StatsAgent::Smart,
+                weights => StatsAgent::Weighted(weights),
+            },
+            "expectimax" => match spec.strip_prefix("expectimax-") {
+                None => StatsAgent::Expectimax(expectimax::DEFAULT_PLIES),
+                Some(plies) => match plies.parse() {
+                    Ok(plies @ 1..=expectimax::MAX_PLIES) => StatsAgent::Expectimax(plies),
+                    _ => return Err(format!("'{}' needs 1 to {} plies, e.g. expectimax-3", spec, expectimax::MAX_PLIES)),
+                },
+            },
+            "mcts" => StatsAgent::MCTS(HybridAI::from_label(spec, threads)?),
+            _ => return Err(format!("unknown player '{}' (use random, smart, expectimax, an opponent, a preset, \
+                                     or a tuned AI such as smart-vulnerability200, expectimax-3 or mcts-5000)", spec)),
+        })
+    }
+
+    /// Pick a move for the current player and report the search effort; `moves` must be non-empty
+    pub fn choose_move_with_info(&self, game: &FastGameState, moves: &[u8], roll: u8) -> (u8, SearchInfo) {
+        self.choose_move_cancellable(game, moves, roll, &CancelToken::new())
+    }
+
+    /// Like `choose_move_with_info`; cancelling `cancel` ends an MCTS search early with its best move so far
+    pub fn choose_move_cancellable(&self, game: &FastGameState, moves: &[u8], roll: u8, cancel: &CancelToken) -> (u8, SearchInfo) {
+        let start = Instant::now();
+        let player = game.current_player();
+        let (piece_idx, nodes) = match self {
+            StatsAgent::Random => (choose_random_move_fast(moves), 0),
+            StatsAgent::Smart => (choose_smart_move_fast(game, player, moves, roll), moves.len()),
+            StatsAgent::Weighted(weights) => (choose_weighted_move(game, player, moves, roll, weights), moves.len()),
+            StatsAgent::Expectimax(plies) => choose_expectimax_move(game, player, moves, roll, *plies),
+            StatsAgent::MCTS(mcts_ai) => match mcts_ai.choose_move_cancellable(game, player, roll, cancel) {
+                Some(result) => return result,
+                None => (choose_random_move_fast(moves), 0),
+            },
+            StatsAgent::Script(script) => (script.choose_move(game, moves, roll), 0),
+            StatsAgent::Opponent(opponent) => return opponent.choose_move_cancellable(game, moves, roll, cancel),
+        };
+        (piece_idx, SearchInfo::new(nodes, start.elapsed()))
+    }
+}
+
+/// A tuned AI playing in interactive games and demos, with its name on the
+/// leaderboard. Copies share the one AI, which games also lend to their
+/// search threads.
+#[derive(Clone)]
+pub struct TunedAI(Arc<(String, StatsAgent)>);
+
+impl TunedAI {
+    pub fn new(agent: StatsAgent) -> Self {
+        TunedAI(Arc::new((agent.label(), agent)))
+    }
+
+    pub fn label(&self) -> &str {
+        &self.0.0
+    }
+
+    pub fn agent(&self) -> &StatsAgent {
+        &self.0.1
+    }
+}
+
+impl PartialEq for TunedAI {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for TunedAI {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TunedAI").field(&self.label()).finish()
+    }
+}
+
+/// Think times and search effort for every move one AI made
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThinkStats {
+    times: Vec<Duration>,
+    nodes: usize,
+}
+
+impl ThinkStats {
+    pub fn record(&mut self, info: &SearchInfo) {
+        self.times.push(info.elapsed);
+        self.nodes += info.nodes;
+    }
+
+    pub fn merge(&mut self, other: &ThinkStats) {
+        self.times.extend_from_slice(&other.times);
+        self.nodes += other.nodes;
+    }
+
+    pub fn moves(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.times.iter().sum()
+    }
+
+    pub fn average(&self) -> Duration {
+        self.total_time() / self.moves().max(1) as u32
+    }
+
+    /// Think time at percentile `p` (0-100), nearest-rank
+    pub fn percentile(&self, p: f64) -> Duration {
+        let mut sorted = self.times.clone();
+        sorted.sort_unstable();
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+    }
+
+    pub fn nodes_per_move(&self) -> f64 {
+        self.nodes as f64 / self.moves().max(1) as f64
+    }
+
+    pub fn nodes_per_sec(&self) -> f64 {
+        self.nodes as f64 / self.total_time().as_secs_f64().max(1e-9)
+    }
+}
+
+/// Chain lengths told apart in the statistics; longer chains count as the longest
+pub const CHAIN_LENGTHS: usize = 4;
+
+/// Extra rolls each seat earned from rosettes in a game, and the most it had
+/// in a row
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtraRolls {
+    pub total: [usize; 2],
+    pub longest_chain: [usize; 2],
+    /// Chains of 1, 2, 3 and `CHAIN_LENGTHS` or more extra rolls in a row
+    pub chains: [[usize; CHAIN_LENGTHS]; 2],
+    /// Who took the last turn, and how many extra rolls in a row that was
+    last: Option<FastPlayer>,
+    chain: usize,
+}
+
+impl ExtraRolls {
+    /// Count a turn by `player`, lost or not: an extra roll when the last
+    /// turn was theirs too
+    pub fn turn(&mut self, player: FastPlayer) {
+        if self.last == Some(player) {
+            let seat = player as usize;
+            let bucket = |chain: usize| chain.min(CHAIN_LENGTHS) - 1;
+            // The chain so far moves up a length
+            if self.chain > 0 {
+                self.chains[seat][bucket(self.chain)] -= 1;
+            }
+            self.chain += 1;
+            self.chains[seat][bucket(self.chain)] += 1;
+            self.total[seat] += 1;
+            self.longest_chain[seat] = self.longest_chain[seat].max(self.chain);
+        } else {
+            self.chain = 0;
+        }
+        self.last = Some(player);
+    }
+
+    /// Extra rolls in a row the last turn's player is on
+    pub fn chain(&self) -> usize {
+        self.chain
+    }
+
+    pub fn from_log(log: &[Turn]) -> Self {
+        let mut extra = ExtraRolls::default();
+        for turn in log {
+            extra.turn(turn.player);
+        }
+        extra
+    }
+}
+
+/// Outcome of one game played without display
+pub struct SilentGame {
+    pub status: GameStatus,
+    pub turns: usize,
+    pub captures_p1: usize,
+    pub captures_p2: usize,
+    /// Per-seat think times
+    pub think: [ThinkStats; 2],
+    pub squares: SquareTracker,
+    /// Every turn in order, lost ones included
+    pub log: Vec<Turn>,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameStatistics {
     player1_wins: usize,
     player2_wins: usize,
+    /// Games drawn, and games won other than by bringing every piece home
+    draws: usize,
+    adjudicated: usize,
     total_games: usize,
     total_turns: usize,
     shortest_game: usize,
     longest_game: usize,
     total_captures_p1: usize,
     total_captures_p2: usize,
+    /// Extra rolls by seat, the longest chain of them in any game and chains
+    /// by length
+    total_extra_rolls: [usize; 2],
+    longest_chain: [usize; 2],
+    chains: [[usize; CHAIN_LENGTHS]; 2],
+    /// Games with a winner and a difference in extra rolls, and how many the
+    /// seat with more extra rolls won
+    extra_roll_edges: usize,
+    extra_roll_edge_wins: usize,
+    /// Sums over games with a winner of x, the difference in extra rolls
+    /// (seat 1 less seat 2), and y, 1 if seat 1 won: n, Σx, Σy, Σx², Σy², Σxy
+    extra_roll_sums: [f64; 6],
+    /// Mirrored-dice pairs: (AI A won both, AI B won both, split)
+    pairs_a_swept: usize,
+    pairs_b_swept: usize,
+    pairs_split: usize,
+    /// Think times per AI (AI A, AI B), so seats swapped in paired mode stay with their AI
+    think: [ThinkStats; 2],
+    /// Square occupancy and central rosette control by seat (silent games only)
+    squares: SquareStats,
+    /// Luck and skill summed per AI (AI A, AI B) over the games measured
+    /// (silent games only), and how many those were
+    luck: Ledger,
+    luck_games: usize,
 }
 
 impl GameStatistics {
@@ -34,19 +341,126 @@ impl GameStatistics {
         GameStatistics {
             player1_wins: 0,
             player2_wins: 0,
+            draws: 0,
+            adjudicated: 0,
             total_games: 0,
             total_turns: 0,
             shortest_game: usize::MAX,
             longest_game: 0,
             total_captures_p1: 0,
             total_captures_p2: 0,
+            total_extra_rolls: [0; 2],
+            longest_chain: [0; 2],
+            chains: [[0; CHAIN_LENGTHS]; 2],
+            extra_roll_edges: 0,
+            extra_roll_edge_wins: 0,
+            extra_roll_sums: [0.0; 6],
+            pairs_a_swept: 0,
+            pairs_b_swept: 0,
+            pairs_split: 0,
+            think: [ThinkStats::default(), ThinkStats::default()],
+            squares: SquareStats::new(),
+            luck: Ledger::default(),
+            luck_games: 0,
         }
     }
 
-    pub fn add_game(&mut self, winner: FastPlayer, turns: usize, captures_p1: usize, captures_p2: usize) {
-        match winner {
-            FastPlayer::One => self.player1_wins += 1,
-            FastPlayer::Two => self.player2_wins += 1,
+    pub fn add_square_stats(&mut self, tracker: SquareTracker, winner: Option<FastPlayer>) {
+        self.squares.add_game(tracker, winner);
+    }
+
+    pub fn display_board_control(&self, p1_desc: &str, p2_desc: &str) {
+        self.squares.display(p1_desc, p2_desc);
+    }
+
+    /// Add one game's think times; `swapped` means AI B sat in seat 1
+    pub fn add_think_stats(&mut self, think: &[ThinkStats; 2], swapped: bool) {
+        let (a, b) = if swapped { (&think[1], &think[0]) } else { (&think[0], &think[1]) };
+        self.think[0].merge(a);
+        self.think[1].merge(b);
+    }
+
+    pub fn display_think_times(&self, a_desc: &str, b_desc: &str) {
+        if self.think.iter().all(|t| t.moves() == 0) {
+            return;
+        }
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        println!();
+        println!("THINK TIME PER MOVE:");
+        for (desc, think) in [(a_desc, &self.think[0]), (b_desc, &self.think[1])] {
+            println!("  {}: {} moves, avg {:.3} ms | p50 {:.3} ms | p90 {:.3} ms | p99 {:.3} ms",
+                     desc, think.moves(), ms(think.average()),
+                     ms(think.percentile(50.0)), ms(think.percentile(90.0)), ms(think.percentile(99.0)));
+            println!("  {}  {:.0} nodes/move, {:.0} nodes/s",
+                     " ".repeat(desc.chars().count()), think.nodes_per_move(), think.nodes_per_sec());
+        }
+    }
+
+    /// Add one game's luck and skill; `swapped` means AI B sat in seat 1
+    pub fn add_luck(&mut self, ledger: &Ledger, swapped: bool) {
+        let (a, b) = if swapped { (1, 0) } else { (0, 1) };
+        for (ai, seat) in [(0, a), (1, b)] {
+            self.luck.luck[ai] += ledger.luck[seat];
+            self.luck.skill[ai] += ledger.skill[seat];
+        }
+        self.luck_games += 1;
+    }
+
+    pub fn display_luck(&self, a_desc: &str, b_desc: &str) {
+        if self.luck_games == 0 {
+            return;
+        }
+
+        let per_game = |total: f64| total / self.luck_games as f64 * 100.0;
+        println!();
+        println!("LUCK AND SKILL (win chance per game from rolls and from moves, in points):");
+        for (ai, desc) in [a_desc, b_desc].into_iter().enumerate() {
+            let (luck, skill) = (per_game(self.luck.luck[ai]), per_game(self.luck.skill[ai]));
+            println!("  {}: luck {:+.1} | skill {:+.1} | together {:+.1}", desc, luck, skill, luck + skill);
+        }
+    }
+
+    /// Record the outcome of a mirrored pair: AI A played seat 1 in the first game
+    /// and seat 2 in the second, with the same dice sequence in both
+    pub fn add_pair(&mut self, a_won_first: bool, a_won_second: bool) {
+        match (a_won_first, a_won_second) {
+            (true, true) => self.pairs_a_swept += 1,
+            (false, false) => self.pairs_b_swept += 1,
+            _ => self.pairs_split += 1,
+        }
+    }
+
+    pub fn display_pairs(&self, a_desc: &str, b_desc: &str) {
+        let pairs = self.pairs_a_swept + self.pairs_b_swept + self.pairs_split;
+        if pairs == 0 {
+            return;
+        }
+
+        // Per-pair score for A is 1, 0.5 or 0; its spread gives the paired standard error
+        let mean = (self.pairs_a_swept as f64 + 0.5 * self.pairs_split as f64) / pairs as f64;
+        let mean_sq = (self.pairs_a_swept as f64 + 0.25 * self.pairs_split as f64) / pairs as f64;
+        let paired_se = ((mean_sq - mean * mean).max(0.0) / pairs as f64).sqrt();
+        // What the same number of independent games would give
+        let unpaired_se = (mean * (1.0 - mean) / (2 * pairs) as f64).sqrt();
+
+        println!();
+        println!("PAIRED RESULTS (mirrored dice, {} pairs):", pairs);
+        println!("  {} won both games: {}", a_desc, self.pairs_a_swept);
+        println!("  {} won both games: {}", b_desc, self.pairs_b_swept);
+        println!("  Split pairs: {}", self.pairs_split);
+        println!("  {} score: {:.1}% ± {:.1}% (unpaired estimate ± {:.1}%)",
+                 a_desc, mean * 100.0, paired_se * 100.0, unpaired_se * 100.0);
+    }
+
+    pub fn add_game(&mut self, status: GameStatus, turns: usize, captures_p1: usize, captures_p2: usize) {
+        match status.winner() {
+            Some(FastPlayer::One) => self.player1_wins += 1,
+            Some(FastPlayer::Two) => self.player2_wins += 1,
+            None => self.draws += 1,
+        }
+        if matches!(status, GameStatus::Adjudicated(..)) {
+            self.adjudicated += 1;
         }
         self.total_games += 1;
         self.total_turns += turns;
@@ -56,6 +470,60 @@ impl GameStatistics {
         self.total_captures_p2 += captures_p2;
     }
 
+    /// Add one game's extra rolls, and how they went with the result
+    pub fn add_extra_rolls(&mut self, extra: &ExtraRolls, winner: Option<FastPlayer>) {
+        for seat in 0..2 {
+            self.total_extra_rolls[seat] += extra.total[seat];
+            self.longest_chain[seat] = self.longest_chain[seat].max(extra.longest_chain[seat]);
+            for (total, count) in self.chains[seat].iter_mut().zip(extra.chains[seat]) {
+                *total += count;
+            }
+        }
+        let Some(winner) = winner else {
+            return;
+        };
+        let [p1, p2] = extra.total;
+        if p1 != p2 {
+            self.extra_roll_edges += 1;
+            if (p1 > p2) == (winner == FastPlayer::One) {
+                self.extra_roll_edge_wins += 1;
+            }
+        }
+        let x = p1 as f64 - p2 as f64;
+        let y = if winner == FastPlayer::One { 1.0 } else { 0.0 };
+        for (sum, term) in self.extra_roll_sums.iter_mut().zip([1.0, x, y, x * x, y * y, x * y]) {
+            *sum += term;
+        }
+    }
+
+    /// Pearson correlation between seat 1's lead in extra rolls and seat 1
+    /// winning, if both vary
+    fn extra_roll_correlation(&self) -> Option<f64> {
+        let [n, x, y, xx, yy, xy] = self.extra_roll_sums;
+        let spread = ((n * xx - x * x) * (n * yy - y * y)).sqrt();
+        (spread > 0.0).then(|| (n * xy - x * y) / spread)
+    }
+
+    /// Search nodes (MCTS playouts or heuristic evaluations) of both AIs so far
+    pub fn nodes(&self) -> usize {
+        self.think.iter().map(|think| think.nodes).sum()
+    }
+
+    pub fn total_games(&self) -> usize {
+        self.total_games
+    }
+
+    pub fn draws(&self) -> usize {
+        self.draws
+    }
+
+    pub fn wins(&self, player: FastPlayer) -> usize {
+        match player {
+            FastPlayer::One => self.player1_wins,
+            FastPlayer::Two => self.player2_wins,
+        }
+    }
+
     pub fn display(&self, p1_desc: &str, p2_desc: &str) {
         println!("\n=== GAME STATISTICS ===");
         println!("Total games played: {}", self.total_games);
@@ -68,6 +536,12 @@ impl GameStatistics {
         println!("  {} ({}): {} ({:.1}%)",
                  FastPlayer::Two.name(), p2_desc, self.player2_wins,
                  (self.player2_wins as f64 / self.total_games as f64) * 100.0);
+        if self.draws > 0 {
+            println!("  Draws: {} ({:.1}%)", self.draws, (self.draws as f64 / self.total_games as f64) * 100.0);
+        }
+        if self.adjudicated > 0 {
+            println!("  Decided at the move limit or by forfeit: {}", self.adjudicated);
+        }
         println!();
 
         println!("GAME LENGTH:");
@@ -83,97 +557,89 @@ impl GameStatistics {
         println!("  {} total captures: {} (avg: {:.1} per game)",
                  FastPlayer::Two.name(), self.total_captures_p2,
                  self.total_captures_p2 as f64 / self.total_games as f64);
-    }
-}
+        println!();
 
-pub fn display_running_stats(stats: &GameStatistics, current_game: usize, total_games: usize, p1_desc: &str, p2_desc: &str) {
-    // Clear multiple lines to ensure we overwrite previous display
-    for _ in 0..15 {
-        print!("\r{}", " ".repeat(80));
-        print!("\n");
+        println!("EXTRA ROLLS (from rosettes):");
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            let seat = player as usize;
+            println!("  {}: {:.1} per game | longest chain {} in a row",
+                     player.name(), self.total_extra_rolls[seat] as f64 / self.total_games as f64, self.longest_chain[seat]);
+        }
+        println!("  Chains per game by length (1 / 2 / 3 / {}+ in a row):", CHAIN_LENGTHS);
+        for player in [FastPlayer::One, FastPlayer::Two] {
+            let per_game: Vec<String> = self.chains[player as usize].iter()
+                .map(|&count| format!("{:.2}", count as f64 / self.total_games as f64))
+                .collect();
+            println!("    {}: {}", player.name(), per_game.join(" / "));
+        }
+        if self.extra_roll_edges > 0 {
+            println!("  The player with more extra rolls won {:.1}% of the {} games where the counts differed",
+                     self.extra_roll_edge_wins as f64 / self.extra_roll_edges as f64 * 100.0, self.extra_roll_edges);
+        }
+        if let Some(r) = self.extra_roll_correlation() {
+            println!("  Correlation of {}'s lead in extra rolls with {} winning: r = {:+.2}",
+                     FastPlayer::One.name(), FastPlayer::One.name(), r);
+        }
     }
+}
 
-    // Move back to start
-    let _ = execute!(io::stdout(), MoveTo(0, 0));
-
-    let progress = (current_game as f64 / total_games as f64) * 100.0;
-    let progress_bar_width = 40;
-    let filled_width = ((progress / 100.0) * progress_bar_width as f64) as usize;
+/// `load` is a line of machine load (CPU, memory, search rate) shown under the figures
+pub fn display_running_stats(stats: &GameStatistics, current_game: usize, total_games: usize, p1_desc: &str, p2_desc: &str, load: &str) {
+    let progress = current_game as f64 / total_games as f64;
 
     // Header with progress
-    println!("╔═══════════════════════════════════════════════════════════════════════════════╗");
-    println!("║                           🎮 LIVE GAME STATISTICS 🎮                          ║");
-    println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
-
-    // Progress bar
-    print!("║ Progress: [");
-    for i in 0..progress_bar_width {
-        if i < filled_width {
-            let _ = execute!(io::stdout(), SetForegroundColor(Color::Green), Print("█"), ResetColor);
-        } else {
-            print!(" ");
-        }
-    }
-    println!("] {:.1}% ({}/{}) ║", progress, current_game, total_games);
-    println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
+    let mut panel = Panel::new(Border::Double, 79);
+    panel.centered(vec![Span::plain("🎮 LIVE GAME STATISTICS 🎮")]);
+    panel.rule();
+    let mut progress_line = vec![Span::plain("Progress: ")];
+    progress_line.extend(progress_bar(progress, 40));
+    progress_line.push(Span::plain(format!(" {:.1}% ({}/{})", progress * 100.0, current_game, total_games)));
+    panel.line(progress_line);
+    panel.rule();
 
     if stats.total_games > 0 {
         // Win statistics
-        let p1_win_pct = (stats.player1_wins as f64 / stats.total_games as f64) * 100.0;
-        let p2_win_pct = (stats.player2_wins as f64 / stats.total_games as f64) * 100.0;
-
-        print!("║ ");
-        let _ = execute!(io::stdout(), SetForegroundColor(Color::Blue), Print("🔵"), ResetColor);
-        print!(" {} wins: {} ({:.1}%)", p1_desc, stats.player1_wins, p1_win_pct);
-
-        // Pad to align
-        let line_len = format!(" {} wins: {} ({:.1}%)", p1_desc, stats.player1_wins, p1_win_pct).len();
-        let padding = 77 - line_len;
-        for _ in 0..padding {
-            print!(" ");
+        let mut wins = Table::new(&[Align::Left, Align::Left, Align::Right, Align::Right], 1);
+        for (player, desc, count) in [
+            (FastPlayer::One, p1_desc, stats.player1_wins),
+            (FastPlayer::Two, p2_desc, stats.player2_wins),
+        ] {
+            let style = style::of(player);
+            wins.row(vec![
+                Span::colored(style.marker, style.color),
+                Span::plain(format!("{} wins:", desc)),
+                Span::plain(count.to_string()),
+                Span::plain(format!("({:.1}%)", count as f64 / stats.total_games as f64 * 100.0)),
+            ]);
         }
-        println!("║");
-
-        print!("║ ");
-        let _ = execute!(io::stdout(), SetForegroundColor(Color::Red), Print("🔴"), ResetColor);
-        print!(" {} wins: {} ({:.1}%)", p2_desc, stats.player2_wins, p2_win_pct);
-
-        // Pad to align
-        let line_len = format!(" {} wins: {} ({:.1}%)", p2_desc, stats.player2_wins, p2_win_pct).len();
-        let padding = 77 - line_len;
-        for _ in 0..padding {
-            print!(" ");
-        }
-        println!("║");
-
-        println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
+        panel.table(&wins);
+        panel.rule();
 
         // Game length statistics
         let avg_turns = stats.total_turns as f64 / stats.total_games as f64;
         let avg_captures_p1 = stats.total_captures_p1 as f64 / stats.total_games as f64;
         let avg_captures_p2 = stats.total_captures_p2 as f64 / stats.total_games as f64;
 
-        println!("║ 📊 Avg game length: {:.1} turns | Shortest: {} | Longest: {}{}║",
-                avg_turns,
-                if stats.shortest_game == usize::MAX { 0 } else { stats.shortest_game },
-                stats.longest_game,
-                " ".repeat(25));
-
-        println!("║ ⚔️  Avg captures per game: {:.1} vs {:.1}{}║",
-                avg_captures_p1, avg_captures_p2, " ".repeat(42));
+        panel.line(vec![Span::plain(format!("📊 Avg game length: {:.1} turns | Shortest: {} | Longest: {}",
+            avg_turns,
+            if stats.shortest_game == usize::MAX { 0 } else { stats.shortest_game },
+            stats.longest_game))]);
+        panel.line(vec![Span::plain(format!("⚔️  Avg captures per game: {:.1} vs {:.1}", avg_captures_p1, avg_captures_p2))]);
     } else {
-        println!("║ Waiting for first game to complete...{}║", " ".repeat(45));
-        println!("║{}║", " ".repeat(79));
-        println!("║{}║", " ".repeat(79));
-        println!("║{}║", " ".repeat(79));
+        panel.line(vec![Span::plain("Waiting for first game to complete...")]);
+        panel.blank();
+        panel.blank();
+        panel.blank();
     }
+    panel.rule();
+    panel.line(vec![Span::plain(format!("🖥️  {}", load))]);
 
-    println!("╚═══════════════════════════════════════════════════════════════════════════════╝");
-
-    io::stdout().flush().unwrap();
+    let mut screen = Screen::new();
+    screen.panel(&panel);
+    screen.present();
 }
 
-pub fn run_statistics_menu() {
+pub fn run_statistics_menu() -> InputResult<()> {
     println!("\n=== STATISTICS MENU ===");
     println!("Choose AI matchup:");
     println!("  1: Random AI vs Random AI");
@@ -185,12 +651,21 @@ pub fn run_statistics_menu() {
     println!("  7: MCTS AI vs Random AI");
     println!("  8: MCTS AI vs Smart AI");
     println!("  9: MCTS AI vs MCTS AI");
-    print!("Enter choice [1-9]: ");
-    io::stdout().flush().unwrap();
-
-    let mut buf = String::new();
-    io::stdin().read_line(&mut buf).unwrap();
-    let matchup: usize = buf.trim().parse().unwrap_or(5);
+    let last_matchup = match scripting::loaded() {
+        Some(script) => {
+            println!(" 10: Script AI '{}' vs Random AI", script.name);
+            println!(" 11: Script AI '{}' vs Smart AI", script.name);
+            println!(" 12: Script AI '{}' vs MCTS AI", script.name);
+            12
+        }
+        None => 9,
+    };
+    let tuned_matchup = last_matchup + 1;
+    println!(" {}: Tuned AI vs Tuned AI: your own settings for each, as in `ur match`", tuned_matchup);
+    let matchup = prompt_in_range(&format!("Enter choice [1-{}]: ", tuned_matchup), 1..=tuned_matchup, 5)?;
+    if matchup == tuned_matchup {
+        return run_tuned_matchup();
+    }
 
     let (p1_type, p2_type, p1_desc, p2_desc) = match matchup {
         1 => (StatsAIType::Random, StatsAIType::Random, "Random AI", "Random AI"),
@@ -202,48 +677,179 @@ pub fn run_statistics_menu() {
         7 => (StatsAIType::MCTS, StatsAIType::Random, "MCTS AI", "Random AI"),
         8 => (StatsAIType::MCTS, StatsAIType::Smart, "MCTS AI", "Smart AI"),
         9 => (StatsAIType::MCTS, StatsAIType::MCTS, "MCTS AI", "MCTS AI"),
+        10 => (StatsAIType::Script, StatsAIType::Random, "Script AI", "Random AI"),
+        11 => (StatsAIType::Script, StatsAIType::Smart, "Script AI", "Smart AI"),
+        12 => (StatsAIType::Script, StatsAIType::MCTS, "Script AI", "MCTS AI"),
         _ => (StatsAIType::Smart, StatsAIType::Smart, "Smart AI", "Smart AI"),
     };
 
+    // Random and Smart agents can use the allocation-free fast path
+    let fast_agents = match (p1_type.fast_agent(), p2_type.fast_agent()) {
+        (Some(p1), Some(p2)) => Some((p1, p2)),
+        _ => None,
+    };
+    if let Some((p1_agent, p2_agent)) = fast_agents {
+        println!();
+        if prompt_yes_no("Use the fast stats path (no live display, up to 10M games)? [y/N]: ", false)? {
+            let num_games = prompt_in_range(
+                "Enter number of games to simulate [1-10000000]: ", 1..=10_000_000, 100_000)?;
+            println!("\nRunning {} games on the fast path: {} vs {}...", num_games, p1_desc, p2_desc);
+            let run = run_fast_stats(p1_agent, p2_agent, num_games);
+            println!("\n✅ Simulation complete!");
+            run.stats.display(p1_desc, p2_desc);
+            run.display_throughput();
+            if record_match(p1_agent.label(), p2_agent.label(), num_games - run.stats.draws(), run.stats.wins(FastPlayer::One)) {
+                println!();
+                println!("Results added to {} (see `ur leaderboard`)", DEFAULT_LEADERBOARD_FILE);
+            }
+            return Ok(());
+        }
+    }
+
     println!();
-    print!("Enter number of games to simulate [1-10000]: ");
-    io::stdout().flush().unwrap();
+    let mut num_games = prompt_in_range("Enter number of games to simulate [1-10000]: ", 1..=10000, 100)?;
+    let paired = prompt_yes_no("Play paired games with mirrored dice and swapped seats? [y/N]: ", false)?;
+    if paired {
+        // Every pair is two games
+        num_games += num_games % 2;
+    }
 
-    buf.clear();
-    io::stdin().read_line(&mut buf).unwrap();
-    let num_games: usize = buf.trim().parse().unwrap_or(100).min(10000).max(1);
+    let has_mcts = matches!(p1_type, StatsAIType::MCTS) || matches!(p2_type, StatsAIType::MCTS);
+    let mut mcts_settings = MctsSettings::default();
+    for (i, (ai_type, seat)) in [(p1_type, FastPlayer::One), (p2_type, FastPlayer::Two)].into_iter().enumerate() {
+        if matches!(ai_type, StatsAIType::MCTS) {
+            let prompt = format!("MCTS presets for {}:", seat.name());
+            mcts_settings.presets[i] = Some(presets::choose(&prompt, STATS_PRESET)?);
+        }
+    }
+    if has_mcts && prompt_yes_no("Give each AI the same think time per move instead of a fixed simulation count? [y/N]: ", false)? {
+        let millis = prompt_in_range("Think time per move in ms [1-10000]: ", 1..=10000, 50)?;
+        mcts_settings.time_budget = Some(Duration::from_millis(millis as u64));
+    }
+    if has_mcts && !matches!((p1_type, p2_type), (StatsAIType::MCTS, StatsAIType::MCTS)) {
+        mcts_settings.model_opponent =
+            prompt_yes_no("Let MCTS model its opponent's play in playouts? [y/N]: ", false)?;
+    }
+    if has_mcts && prompt_yes_no("Set MCTS risk preferences? [y/N]: ", false)? {
+        for (i, (ai_type, seat)) in [(p1_type, FastPlayer::One), (p2_type, FastPlayer::Two)].into_iter().enumerate() {
+            if matches!(ai_type, StatsAIType::MCTS) {
+                let prompt = format!("Risk for {} MCTS AI [-1 cautious .. 1 bold] (default 0): ", seat.name());
+                mcts_settings.risk[i] = prompt_parse::<f64>(&prompt, 0.0)?.clamp(-1.0, 1.0);
+            }
+        }
+    }
 
     println!("\nRunning {} games: {} vs {}...", num_games, p1_desc, p2_desc);
 
     // Show MCTS configuration if using MCTS AI
-    if matches!(p1_type, StatsAIType::MCTS) || matches!(p2_type, StatsAIType::MCTS) {
-        let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
-        let mcts_info_ai = HybridAI::new_with_threads(num_cpus * 500, num_cpus); // Fewer sims for stats
-        println!("MCTS Configuration: {}", mcts_info_ai.get_info());
+    if has_mcts {
+        for (i, ai_type) in [p1_type, p2_type].into_iter().enumerate() {
+            if matches!(ai_type, StatsAIType::MCTS) {
+                println!("MCTS Configuration: {}", stats_mcts_ai(&mcts_settings, i, None).get_info());
+            }
+        }
+        if mcts_settings.model_opponent {
+            println!("MCTS playouts model the opponent as {}.", if matches!(p1_type, StatsAIType::MCTS) { p2_desc } else { p1_desc });
+        }
+        if mcts_settings.time_budget.is_some() {
+            println!("Random and Smart AIs answer instantly and cannot use the extra time.");
+        }
     }
 
     println!();
+    let agent_a = build_agent(p1_type, p2_type, &mcts_settings, 0);
+    let agent_b = build_agent(p2_type, p1_type, &mcts_settings, 1);
+    run_matchup(&agent_a, &agent_b, p1_desc, p2_desc, num_games, paired)
+}
+
+/// Ask for a player spec for each AI, then run their games as for any matchup
+fn run_tuned_matchup() -> InputResult<()> {
+    let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let ask = |name: &str| -> InputResult<StatsAgent> {
+        loop {
+            let spec = prompt_line(&format!("Player spec for {} (e.g. smart-vulnerability200, expectimax-3 or mcts-5000): ", name))?;
+            match StatsAgent::from_spec(&spec, num_cpus) {
+                Ok(agent) => return Ok(agent),
+                Err(e) if !spec.is_empty() => println!("❌ {}", e),
+                Err(_) => {}
+            }
+        }
+    };
+    let (agent_a, agent_b) = (ask("AI A")?, ask("AI B")?);
+
+    println!();
+    let mut num_games = prompt_in_range("Enter number of games to simulate [1-10000]: ", 1..=10000, 100)?;
+    let paired = prompt_yes_no("Play paired games with mirrored dice and swapped seats? [y/N]: ", false)?;
+    if paired {
+        num_games += num_games % 2;
+    }
+    let (p1_desc, p2_desc) = (agent_a.label(), agent_b.label());
+    println!("\nRunning {} games: {} vs {}...", num_games, p1_desc, p2_desc);
+    for agent in [&agent_a, &agent_b] {
+        if let StatsAgent::MCTS(mcts_ai) = agent {
+            println!("MCTS Configuration: {}", mcts_ai.get_info());
+        }
+    }
+    println!();
+    run_matchup(&agent_a, &agent_b, &p1_desc, &p2_desc, num_games, paired)
+}
 
+/// Play `num_games` silent games between AI A and AI B with the live display,
+/// then show and record the results
+fn run_matchup(agent_a: &StatsAgent, agent_b: &StatsAgent, p1_desc: &str, p2_desc: &str, num_games: usize, paired: bool) -> InputResult<()> {
     let mut stats = GameStatistics::new();
 
     // Hide cursor for cleaner display
     let _ = execute!(io::stdout(), Hide);
 
-    // Clear screen and move to top for our display area
-    let _ = execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
-    let start_row = 0;
+    // The live display is drawn over itself at the top of the screen
+    screen::clear();
+
+    // With pairing the seats alternate between AIs, so seat labels replace AI names
+    let (seat1_desc, seat2_desc) = if paired { ("Seat 1", "Seat 2") } else { (p1_desc, p2_desc) };
+    let mut pair_seed = 0;
+    let mut first_of_pair_won_by_a = false;
+    let mut a_wins = 0;
+    let mut meter = ResourceMeter::new();
+    let run_start = Instant::now();
 
     for game_num in 1..=num_games {
-        let (winner, turns, captures_p1, captures_p2) = run_silent_game(p1_type, p2_type);
-        stats.add_game(winner, turns, captures_p1, captures_p2);
+        let second_of_pair = paired && game_num % 2 == 0;
+        if !second_of_pair {
+            pair_seed = GameRng::random().seed();
+        }
+        let mut dice = GameRng::from_seed(pair_seed);
+
+        let result = if second_of_pair {
+            play_silent_game(agent_b, agent_a, &mut dice)
+        } else {
+            play_silent_game(agent_a, agent_b, &mut dice)
+        };
+        let winner = result.status.winner();
+        if winner == Some(if second_of_pair { FastPlayer::Two } else { FastPlayer::One }) {
+            a_wins += 1;
+        }
+        stats.add_game(result.status, result.turns, result.captures_p1, result.captures_p2);
+        stats.add_extra_rolls(&ExtraRolls::from_log(&result.log), winner);
+        stats.add_think_stats(&result.think, second_of_pair);
+        stats.add_luck(&luck::assess(FastGameState::new(), &result.log), second_of_pair);
+        stats.add_square_stats(result.squares, winner);
+
+        if paired {
+            if second_of_pair {
+                stats.add_pair(first_of_pair_won_by_a, winner == Some(FastPlayer::Two));
+            } else {
+                first_of_pair_won_by_a = winner == Some(FastPlayer::One);
+            }
+        }
 
         // Update display every 10 games, or for the first few games, or at the end
         let should_update = game_num % 10 == 0 || game_num <= 5 || game_num == num_games;
 
         if should_update {
-            // Clear the display area and show current stats
-            let _ = execute!(io::stdout(), MoveTo(0, start_row));
-            display_running_stats(&stats, game_num, num_games, p1_desc, p2_desc);
+            let nodes_per_sec = stats.nodes() as f64 / run_start.elapsed().as_secs_f64().max(1e-9);
+            let load = format!("{} | {:.0} nodes/s", meter.summary(), nodes_per_sec);
+            display_running_stats(&stats, game_num, num_games, seat1_desc, seat2_desc, &load);
         }
     }
 
@@ -251,60 +857,104 @@ pub fn run_statistics_menu() {
     let _ = execute!(io::stdout(), Show);
 
     println!("\n✅ Simulation complete!");
-    stats.display(p1_desc, p2_desc);
+    stats.display(seat1_desc, seat2_desc);
+    stats.display_pairs(p1_desc, p2_desc);
+    stats.display_think_times(p1_desc, p2_desc);
+    stats.display_luck(p1_desc, p2_desc);
+    stats.display_board_control(seat1_desc, seat2_desc);
+    if record_match(&agent_a.label(), &agent_b.label(), num_games - stats.draws(), a_wins) {
+        println!();
+        println!("Results added to {} (see `ur leaderboard`)", DEFAULT_LEADERBOARD_FILE);
+    }
+    Ok(())
+}
+
+/// MCTS AI for AI A (`side` 0) or AI B in stats mode: its preset, with the
+/// menu's time budget, risk and opponent modeling applied on top
+fn stats_mcts_ai(settings: &MctsSettings, side: usize, opponent: Option<StatsAIType>) -> HybridAI {
+    let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let preset = settings.presets[side].unwrap_or_else(|| presets::find(STATS_PRESET).expect("built-in preset"));
+    let mut ai = preset.build(num_cpus);
+    if let Some(budget) = settings.time_budget {
+        ai.mcts.time_budget = Some(budget);
+        ai.name = None;
+    }
+    if settings.risk[side] != 0.0 {
+        ai = ai.with_risk(settings.risk[side]);
+        ai.name = None;
+    }
+    match opponent {
+        Some(opponent) if settings.model_opponent => {
+            ai.name = None;
+            ai.with_opponent_model(opponent.playout_policy())
+        }
+        _ => ai,
+    }
+}
+
+/// Configure a stats-mode agent for `ai_type` (AI A if `side` is 0) playing against `opponent`
+fn build_agent(ai_type: StatsAIType, opponent: StatsAIType, settings: &MctsSettings, side: usize) -> StatsAgent {
+    match ai_type {
+        StatsAIType::Random => StatsAgent::Random,
+        StatsAIType::Smart => StatsAgent::Smart,
+        StatsAIType::MCTS => StatsAgent::MCTS(stats_mcts_ai(settings, side, Some(opponent))),
+        StatsAIType::Script => StatsAgent::Script(scripting::loaded().expect("script players need a loaded script")),
+    }
+}
+
+/// Play one game between two configured agents without any display
+pub fn play_silent_game(p1: &StatsAgent, p2: &StatsAgent, dice: &mut GameRng) -> SilentGame {
+    play_silent_game_from(FastGameState::new(), p1, p2, dice)
 }
 
-pub fn run_silent_game(p1_type: StatsAIType, p2_type: StatsAIType) -> (FastPlayer, usize, usize, usize) {
-    let mut game = FastGameState::new();
+/// Play one game on from `start` without any display
+pub fn play_silent_game_from(start: FastGameState, p1: &StatsAgent, p2: &StatsAgent, dice: &mut GameRng) -> SilentGame {
+    let mut game = start;
     let mut turn_count = 0;
     let mut captures_p1 = 0;
     let mut captures_p2 = 0;
-
-    // Create MCTS AI for stats (fewer simulations for speed)
-    let num_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
-    let mcts_ai = HybridAI::new_with_threads(num_cpus * 400, num_cpus); // Fast MCTS for stats
+    let mut think = [ThinkStats::default(), ThinkStats::default()];
+    let mut squares = SquareTracker::new();
+    let mut log = Vec::new();
 
     loop {
         turn_count += 1;
+        squares.observe(&game);
 
         // Count pieces before move for capture detection
         let p1_pieces_before = count_on_board_pieces(&game, FastPlayer::One);
         let p2_pieces_before = count_on_board_pieces(&game, FastPlayer::Two);
 
-        let roll = FastGameState::roll_dice();
+        let roll = dice.roll_dice(game.current_player());
+        squares.observe_roll(&game, roll);
+        let lost_turn = Turn { player: game.current_player(), roll, piece: None };
 
         if roll == 0 {
             // Switch turn manually since we don't have a move to make
-            game.scores_and_turn ^= 1 << 6;
+            log.push(lost_turn);
+            game.pass_turn();
             continue;
         }
 
         let moves = game.generate_moves(roll);
         if moves.is_empty() {
             // Switch turn manually
-            game.scores_and_turn ^= 1 << 6;
+            log.push(lost_turn);
+            game.pass_turn();
             continue;
         }
 
         let current_player = game.current_player();
-        let current_ai_type = match current_player {
-            FastPlayer::One => p1_type,
-            FastPlayer::Two => p2_type,
+        let current_agent = match current_player {
+            FastPlayer::One => p1,
+            FastPlayer::Two => p2,
         };
 
-        let chosen_piece = match current_ai_type {
-            StatsAIType::Random => choose_random_move_fast(&moves),
-            StatsAIType::Smart => choose_smart_move_fast(&game, current_player, &moves, roll),
-            StatsAIType::MCTS => {
-                if let Some(piece_idx) = mcts_ai.choose_move(&game, current_player, roll) {
-                    piece_idx
-                } else {
-                    choose_random_move_fast(&moves)
-                }
-            }
-        };
+        let (chosen_piece, search_info) = current_agent.choose_move_with_info(&game, &moves, roll);
+        think[current_player as usize].record(&search_info);
+        log.push(Turn { piece: Some(chosen_piece), ..lost_turn });
 
-        if let Some(_move_info) = game.make_move(chosen_piece, roll) {
+        if let Ok(_move_info) = game.make_move(chosen_piece, roll) {
             // Count pieces after move to detect captures
             let p1_pieces_after = count_on_board_pieces(&game, FastPlayer::One);
             let p2_pieces_after = count_on_board_pieces(&game, FastPlayer::Two);
@@ -324,7 +974,7 @@ pub fn run_silent_game(p1_type: StatsAIType, p2_type: StatsAIType) -> (FastPlaye
             }
 
             if game.is_winner(current_player) {
-                return (current_player, turn_count, captures_p1, captures_p2);
+                return SilentGame { status: GameStatus::Won(current_player), turns: turn_count, captures_p1, captures_p2, think, squares, log };
             }
 
             // Note: Turn switching is handled automatically by make_move() if no extra turn
@@ -332,14 +982,7 @@ pub fn run_silent_game(p1_type: StatsAIType, p2_type: StatsAIType) -> (FastPlaye
 
         // Safety valve to prevent infinite games
         if turn_count > 1000 {
-            let winner = if game.get_score(FastPlayer::One) > game.get_score(FastPlayer::Two) {
-                FastPlayer::One
-            } else if game.get_score(FastPlayer::Two) > game.get_score(FastPlayer::One) {
-                FastPlayer::Two
-            } else {
-                FastPlayer::One
-            };
-            return (winner, turn_count, captures_p1, captures_p2);
+            return SilentGame { status: game.status_at_move_limit(), turns: turn_count, captures_p1, captures_p2, think, squares, log };
         }
     }
 }
@@ -348,7 +991,7 @@ pub fn count_on_board_pieces(game: &FastGameState, player: FastPlayer) -> usize
     let mut count = 0;
     for piece_idx in 0..7 {
         let pos = game.get_piece_pos(player, piece_idx);
-        if pos >= 1 && pos <= 14 {
+        if FastGameState::is_on_board(pos) {
             count += 1;
         }
     }
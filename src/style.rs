@@ -0,0 +1,221 @@
+//! How each player looks on screen: the color of their pieces and UI accents,
+//! the marker shown beside their name, the glyphs drawn for their pieces and
+//! whose side of the board is drawn nearest the viewer. Chosen for the run
+//! with `--p1-color`, `--p2-color`, `--pieces` and `--view`; display code asks
+//! `style::of` and `style::viewer` instead of hard-coding them.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crossterm::style::Color;
+
+use crate::args::Flags;
+use crate::optimized_game::FastPlayer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerColor {
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+
+impl PlayerColor {
+    pub const ALL: [PlayerColor; 6] = [
+        PlayerColor::Blue,
+        PlayerColor::Red,
+        PlayerColor::Green,
+        PlayerColor::Yellow,
+        PlayerColor::Purple,
+        PlayerColor::White,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlayerColor::Blue => "blue",
+            PlayerColor::Red => "red",
+            PlayerColor::Green => "green",
+            PlayerColor::Yellow => "yellow",
+            PlayerColor::Purple => "purple",
+            PlayerColor::White => "white",
+        }
+    }
+
+    fn terminal_color(self) -> Color {
+        match self {
+            PlayerColor::Blue => Color::Blue,
+            PlayerColor::Red => Color::Red,
+            PlayerColor::Green => Color::Green,
+            PlayerColor::Yellow => Color::Yellow,
+            PlayerColor::Purple => Color::Magenta,
+            PlayerColor::White => Color::White,
+        }
+    }
+
+    fn marker(self) -> &'static str {
+        match self {
+            PlayerColor::Blue => "🔵",
+            PlayerColor::Red => "🔴",
+            PlayerColor::Green => "🟢",
+            PlayerColor::Yellow => "🟡",
+            PlayerColor::Purple => "🟣",
+            PlayerColor::White => "⚪",
+        }
+    }
+}
+
+impl FromStr for PlayerColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|color| color.label() == s).ok_or_else(|| {
+            let labels: Vec<&str> = Self::ALL.iter().map(|color| color.label()).collect();
+            format!("unknown color '{}' (expected one of {})", s, labels.join(", "))
+        })
+    }
+}
+
+/// Glyphs drawn for pieces on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PieceSet {
+    /// ● for both players, told apart by color
+    #[default]
+    Dots,
+    /// ● for Player 1 and ▲ for Player 2, told apart without color
+    Shapes,
+    /// Each piece's index, 0 to 6, as used when choosing moves
+    Numbers,
+}
+
+impl FromStr for PieceSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dots" => Ok(PieceSet::Dots),
+            "shapes" => Ok(PieceSet::Shapes),
+            "numbers" => Ok(PieceSet::Numbers),
+            _ => Err(format!("unknown piece set '{}' (expected dots, shapes or numbers)", s)),
+        }
+    }
+}
+
+/// Whose side of the board is drawn nearest the viewer, at the bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Player 1's home row at the bottom
+    Player1,
+    /// Player 2's home row at the bottom, the classic layout
+    Player2,
+    /// The home row of whoever moves next at the bottom
+    Auto,
+}
+
+impl FromStr for View {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "p1" => Ok(View::Player1),
+            "p2" => Ok(View::Player2),
+            "auto" => Ok(View::Auto),
+            _ => Err(format!("unknown view '{}' (expected p1, p2 or auto)", s)),
+        }
+    }
+}
+
+/// The look chosen for this run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Styles {
+    pub colors: [PlayerColor; 2],
+    pub pieces: PieceSet,
+    /// The board's orientation, if `--view` chose one
+    pub view: Option<View>,
+}
+
+impl Default for Styles {
+    fn default() -> Self {
+        Styles { colors: [PlayerColor::Blue, PlayerColor::Red], pieces: PieceSet::Dots, view: None }
+    }
+}
+
+impl Styles {
+    /// Styles with the given colors, which must differ
+    pub fn new(colors: [PlayerColor; 2], pieces: PieceSet, view: Option<View>) -> Result<Self, String> {
+        if colors[0] == colors[1] {
+            return Err(format!("both players cannot be {}", colors[0].label()));
+        }
+        Ok(Styles { colors, pieces, view })
+    }
+
+    /// Styles from `--p1-color`, `--p2-color`, `--pieces` and `--view`
+    pub fn from_flags(flags: &Flags) -> Result<Self, String> {
+        let default = Styles::default();
+        Styles::new(
+            [flags.get("--p1-color", default.colors[0])?, flags.get("--p2-color", default.colors[1])?],
+            flags.get("--pieces", default.pieces)?,
+            flags.get_opt("--view")?,
+        )
+    }
+}
+
+/// How one player is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerStyle {
+    /// Color of the player's pieces, name and other accents
+    pub color: Color,
+    /// Colored marker shown beside the player's name
+    pub marker: &'static str,
+    player: FastPlayer,
+    pieces: PieceSet,
+}
+
+impl PlayerStyle {
+    /// Glyph for the player's piece `piece_idx` on the board
+    pub fn glyph(&self, piece_idx: u8) -> char {
+        match (self.pieces, self.player) {
+            (PieceSet::Dots, _) | (PieceSet::Shapes, FastPlayer::One) => '●',
+            (PieceSet::Shapes, FastPlayer::Two) => '▲',
+            (PieceSet::Numbers, _) => char::from(b'0' + piece_idx),
+        }
+    }
+}
+
+static SELECTED: OnceLock<Styles> = OnceLock::new();
+
+/// Choose the look for this run. Returns false if a different one was
+/// already chosen.
+pub fn select(styles: Styles) -> bool {
+    *SELECTED.get_or_init(|| styles) == styles
+}
+
+fn selected() -> Styles {
+    SELECTED.get().copied().unwrap_or_default()
+}
+
+/// How `player` is drawn in this run
+pub fn of(player: FastPlayer) -> PlayerStyle {
+    let styles = selected();
+    let color = styles.colors[player as usize];
+    PlayerStyle { color: color.terminal_color(), marker: color.marker(), player, pieces: styles.pieces }
+}
+
+/// The `--view` chosen for this run, if any
+pub fn view() -> Option<View> {
+    selected().view
+}
+
+/// The player whose home row is drawn at the bottom when `to_move` moves
+/// next. Without `--view`, hot-seat games turn the board to face whoever's
+/// turn it is and other games keep the classic layout.
+pub fn viewer(to_move: FastPlayer, hot_seat: bool) -> FastPlayer {
+    match view() {
+        Some(View::Player1) => FastPlayer::One,
+        Some(View::Player2) => FastPlayer::Two,
+        Some(View::Auto) => to_move,
+        None if hot_seat => to_move,
+        None => FastPlayer::Two,
+    }
+}
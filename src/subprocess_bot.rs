@@ -0,0 +1,385 @@
+//! Bots written in any language, run as subprocesses. A bot is launched for each
+//! game and talks JSON lines over stdin/stdout:
+//!   → `{"type":"start","seat":1,"board":"standard","dice":"tetrahedra","path_length":14,"time_ms":1000,"session":"..."}`
+//!   → `{"type":"move","seat":1,"roll":3,"moves":[0,2],"pieces":[[...],[...]],"scores":[0,1],"position":"UR:...","time_ms":1000}`
+//!   ← `{"move":2}` (or just `2`)
+//!   → `{"type":"end","winner":2,"reason":"..."}`
+//! With fair dice, each roll is preceded by a commit-reveal exchange:
+//!   → `{"type":"commit","number":1}` ← `{"commit":"<SHA-256 of the nonce, hex>"}`
+//!   → `{"type":"reveal","number":1,"commitments":["...","..."]}` ← `{"nonce":"<64 hex digits>"}`
+//!   → `{"type":"roll","number":1,"seat":1,"nonces":["...","..."],"roll":3}`
+//! A bot that drops out of a match may be started again and brought up to date:
+//!   → `{"type":"resume",<the start fields>,"history":[[1,3,0],[2,0,null],...],"position":"UR:...","hash":"..."}`
+//!   ← `{"hash":"<SHA-256 of the history array as sent, hex>"}`
+//! Between replies a bot seated in an interactive game may chat, offer a draw
+//! or ask for a rematch, and hears the same from the other side:
+//!   ← `{"type":"chat","text":"..."}`, `{"type":"draw"}`, `{"type":"rematch"}`
+//!   → `{"type":"chat","seat":2,"text":"..."}`, `{"type":"draw","seat":2}`, `{"type":"rematch","seat":2}`
+//! This module speaks the protocol; in matches the referee checks moves and
+//! clocks and decides what a late or broken reply costs. Bots can also be
+//! seated in interactive games (`seat`), where only legal moves are accepted,
+//! and clients of `ur lobby` speak it over a network connection.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::board;
+use crate::chat::Said;
+use crate::dice;
+use crate::error::UrError;
+use crate::fair_dice::{self, Commitment, FairRoll, Nonce};
+use crate::json::{self, Json};
+use crate::optimized_game::{FastGameState, FastPlayer, GameStatus};
+use crate::position_code;
+use crate::referee::{Clock, Contestant, Resync, Violation, STARTUP_GRACE};
+
+/// Chat messages from a bot held until they are read; more are dropped
+const CHAT_BACKLOG: usize = 16;
+
+/// Where a bot's messages are written, shared between its requests and its chat
+pub type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// A running bot process, or a lobby client at the other end of a connection
+pub struct SubprocessBot {
+    /// None for a lobby client
+    child: Option<Child>,
+    /// Shared with the bot's `BotChat`, which writes between requests
+    writer: SharedWriter,
+    /// Lines the bot has written, read on a separate thread so replies can time out
+    replies: Receiver<String>,
+    /// Chat lines, kept apart from the replies; None once handed to a `BotChat`
+    heard: Option<Receiver<Said>>,
+}
+
+impl SubprocessBot {
+    /// Start `command`: a program followed by its arguments, separated by spaces
+    pub fn launch(command: &str) -> io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("cannot start '{}': {}", command, e)))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (sender, replies) = mpsc::channel();
+        let (chat, heard) = mpsc::sync_channel(CHAT_BACKLOG);
+        let reader = thread::Builder::new().spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(said) = Said::from_bot(&line) {
+                    let _ = chat.try_send(said);
+                } else if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        if let Err(e) = reader {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(e.kind(), format!("cannot read from '{}': {}", command, e)));
+        }
+        let writer: Box<dyn Write + Send> = Box::new(stdin);
+        Ok(SubprocessBot { child: Some(child), writer: Arc::new(Mutex::new(writer)), replies, heard: Some(heard) })
+    }
+
+    /// A bot whose replies arrive on `replies` from a reader elsewhere, such
+    /// as a lobby client's connection
+    pub fn attach(writer: SharedWriter, replies: Receiver<String>) -> Self {
+        SubprocessBot { child: None, writer, replies, heard: None }
+    }
+
+    fn send(&mut self, message: &str) -> Result<(), Violation> {
+        write_line(&self.writer, message).map_err(|_| Violation::Failed("bot exited".to_string()))
+    }
+
+    /// The bot's side of the chat, usable while it works on a move
+    pub fn take_chat(&mut self) -> Option<BotChat> {
+        Some(BotChat { writer: Arc::clone(&self.writer), heard: self.heard.take()? })
+    }
+
+    pub fn start_game(&mut self, seat: FastPlayer, clock: &Clock, session: &str) -> Result<(), Violation> {
+        self.send(&format!(r#"{{"type":"start",{}}}"#, start_fields(seat, clock.per_move, clock.bank, session)))
+    }
+
+    /// Bring a bot started mid-game up to date and wait for it to confirm the
+    /// history by its hash
+    pub fn resume(&mut self, resync: &Resync, allowed: Duration) -> Result<(), Violation> {
+        let fields = start_fields(resync.seat, resync.clock.per_move, resync.bank, resync.session);
+        let hash = resync.history_hash();
+        let message = format!(r#"{{"type":"resume",{},"history":{},"position":"{}","hash":"{}"}}"#,
+                              fields, resync.history(), position_code::encode(resync.game), hash);
+        let echoed = self.request_hex(&message, "hash", allowed)?;
+        if fair_dice::to_hex(&echoed) != hash {
+            return Err(Violation::BadReply(format!("history hash {} does not match", fair_dice::to_hex(&echoed))));
+        }
+        Ok(())
+    }
+
+    /// Ask for a move and wait up to `allowed` for the reply; the piece is not checked here
+    pub fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation> {
+        let pieces = [FastPlayer::One, FastPlayer::Two].map(|player| {
+            let positions: Vec<String> = (0..7).map(|idx| game.get_piece_pos(player, idx).to_string()).collect();
+            format!("[{}]", positions.join(","))
+        });
+        let move_list: Vec<String> = moves.iter().map(u8::to_string).collect();
+        self.send(&format!(
+            r#"{{"type":"move","seat":{},"roll":{},"moves":[{}],"pieces":[{}],"scores":[{},{}],"position":"{}","time_ms":{}}}"#,
+            game.current_player() as usize + 1, roll, move_list.join(","), pieces.join(","),
+            game.get_score(FastPlayer::One), game.get_score(FastPlayer::Two), position_code::encode(game), allowed.as_millis(),
+        ))?;
+        let reply = self.reply(allowed)?;
+        parse_reply(&reply).map_err(|e| Violation::BadReply(e.to_string()))
+    }
+
+    /// The bot's next line, if it comes within `allowed`
+    fn reply(&mut self, allowed: Duration) -> Result<String, Violation> {
+        match self.replies.recv_timeout(allowed) {
+            Ok(reply) => Ok(reply),
+            Err(RecvTimeoutError::Timeout) => Err(Violation::Timeout(allowed)),
+            Err(RecvTimeoutError::Disconnected) => Err(Violation::Failed("bot exited".to_string())),
+        }
+    }
+
+    /// The 32 bytes of hex the bot answers `message` with, under `key`
+    fn request_hex(&mut self, message: &str, key: &str, allowed: Duration) -> Result<[u8; 32], Violation> {
+        self.send(message)?;
+        let reply = self.reply(allowed)?;
+        hex_field(&reply, key).as_deref().and_then(fair_dice::from_hex)
+            .ok_or_else(|| UrError::ProtocolError(format!("unreadable reply '{}', expected 64 hex digits under '{}'", reply.trim(), key)))
+            .map_err(|e| Violation::BadReply(e.to_string()))
+    }
+
+    pub fn commit_dice(&mut self, number: u64, allowed: Duration) -> Result<Commitment, Violation> {
+        self.request_hex(&format!(r#"{{"type":"commit","number":{}}}"#, number), "commit", allowed)
+    }
+
+    pub fn reveal_dice(&mut self, number: u64, commitments: &[Commitment; 2], allowed: Duration) -> Result<Nonce, Violation> {
+        let message = format!(r#"{{"type":"reveal","number":{},"commitments":["{}","{}"]}}"#,
+                              number, fair_dice::to_hex(&commitments[0]), fair_dice::to_hex(&commitments[1]));
+        self.request_hex(&message, "nonce", allowed)
+    }
+
+    /// Show the bot a roll and the nonces behind it; a bot that has exited
+    /// finds out at its next request
+    pub fn show_roll(&mut self, roll: &FairRoll) {
+        let _ = self.send(&format!(r#"{{"type":"roll","number":{},"seat":{},"nonces":["{}","{}"],"roll":{}}}"#,
+                                   roll.number, roll.player as usize + 1,
+                                   fair_dice::to_hex(&roll.nonces[0]), fair_dice::to_hex(&roll.nonces[1]), roll.roll));
+    }
+
+    /// Tell the bot how the game ended, with the winner 0 for a draw; it may
+    /// have exited already, which is fine
+    pub fn end_game(&mut self, status: GameStatus, reason: &str) {
+        let _ = self.send(&end_message(status, reason));
+    }
+}
+
+/// Write `message` to a bot as one line
+pub fn write_line(writer: &Mutex<Box<dyn Write + Send>>, message: &str) -> io::Result<()> {
+    let mut writer = writer.lock().unwrap();
+    writeln!(writer, "{}", message).and_then(|_| writer.flush())
+}
+
+/// Chat with a running bot, apart from its moves
+pub struct BotChat {
+    writer: SharedWriter,
+    heard: Receiver<Said>,
+}
+
+impl BotChat {
+    /// Tell the bot what the player at `seat` said; a bot that has exited
+    /// is not told
+    pub fn tell(&self, seat: FastPlayer, said: &Said) {
+        let _ = write_line(&self.writer, &said.to_json(seat));
+    }
+
+    /// The next thing the bot said, if there is one
+    pub fn hear(&self) -> Option<Said> {
+        self.heard.try_recv().ok()
+    }
+}
+
+impl Drop for SubprocessBot {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// The fields a bot is started with, also sent when it is resumed
+fn start_fields(seat: FastPlayer, per_move: Duration, bank: Option<Duration>, session: &str) -> String {
+    let bank = bank.map_or(String::new(), |bank| format!(r#","bank_ms":{}"#, bank.as_millis()));
+    format!(r#""seat":{},"board":"{}","dice":"{}","path_length":{},"time_ms":{}{},"session":"{}""#,
+            seat as usize + 1, board::variant().label(), dice::variant().label(), FastGameState::path_len(),
+            per_move.as_millis(), bank, session)
+}
+
+/// The `end` message; the reason may quote a bot's unreadable reply, so it is escaped
+fn end_message(status: GameStatus, reason: &str) -> String {
+    let winner = status.winner().map_or(0, |winner| winner as usize + 1);
+    format!(r#"{{"type":"end","winner":{},"reason":{}}}"#, winner, json::json_string(reason))
+}
+
+/// The piece in a reply: a bare number or an object with a "move" field
+fn parse_reply(reply: &str) -> Result<i64, UrError> {
+    let reply = reply.trim();
+    let piece = match json::parse(reply) {
+        Ok(Json::Number(piece)) => Some(piece),
+        Ok(object) => object.get("move").and_then(Json::as_f64),
+        Err(_) => None,
+    };
+    piece.filter(|piece| piece.fract() == 0.0 && piece.abs() < 1e15).map(|piece| piece as i64)
+        .ok_or_else(|| UrError::ProtocolError(format!("unreadable reply '{}'", reply)))
+}
+
+/// The string value of `key` in a one-line JSON object
+pub fn string_field(line: &str, key: &str) -> Option<String> {
+    json::parse(line).ok()?.get(key)?.as_str().map(str::to_string)
+}
+
+/// The number value of `key` in a one-line JSON object
+pub fn number_field(line: &str, key: &str) -> Option<f64> {
+    json::parse(line).ok()?.get(key)?.as_f64()
+}
+
+/// A string in a reply: the `key` field of an object, a JSON string, or bare
+fn hex_field(reply: &str, key: &str) -> Option<String> {
+    let reply = reply.trim();
+    match json::parse(reply) {
+        Ok(object @ Json::Object(_)) => object.get(key)?.as_str().map(str::to_string),
+        Ok(Json::String(text)) => Some(text),
+        _ => Some(reply.to_string()),
+    }
+}
+
+/// An external bot: the command to run, and the process while a game is on
+pub struct ExternalBot {
+    command: String,
+    running: Option<SubprocessBot>,
+}
+
+impl ExternalBot {
+    pub fn new(command: &str) -> Self {
+        ExternalBot { command: command.to_string(), running: None }
+    }
+
+    fn bot(&mut self) -> Result<&mut SubprocessBot, Violation> {
+        self.running.as_mut().ok_or_else(|| Violation::Failed("bot is not running".to_string()))
+    }
+}
+
+impl Contestant for ExternalBot {
+    /// Named after the bot's program, or the script it runs (`python3 bots/mine.py` is `bot-mine`)
+    fn label(&self) -> String {
+        let mut words = self.command.split_whitespace();
+        let first = words.next().unwrap_or("bot");
+        let program = words.chain([first]).find(|w| w.contains(['/', '.']) && !w.starts_with('-')).unwrap_or(first);
+        let stem = Path::new(program).file_stem().map_or_else(|| program.to_string(), |s| s.to_string_lossy().into_owned());
+        format!("bot-{}", stem)
+    }
+
+    fn start_game(&mut self, seat: FastPlayer, clock: &Clock, session: &str) -> Result<(), Violation> {
+        let mut bot = SubprocessBot::launch(&self.command).map_err(|e| Violation::Failed(e.to_string()))?;
+        let started = bot.start_game(seat, clock, session);
+        self.running = Some(bot);
+        started
+    }
+
+    fn request_move(&mut self, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<i64, Violation> {
+        self.bot()?.request_move(game, moves, roll, allowed)
+    }
+
+    /// Tell the bot the result and stop it
+    fn end_game(&mut self, status: GameStatus, reason: &str) {
+        if let Some(mut bot) = self.running.take() {
+            bot.end_game(status, reason);
+        }
+    }
+
+    fn commit_dice(&mut self, number: u64, allowed: Duration) -> Result<Option<Commitment>, Violation> {
+        self.bot()?.commit_dice(number, allowed).map(Some)
+    }
+
+    fn reveal_dice(&mut self, number: u64, commitments: &[Commitment; 2], allowed: Duration) -> Result<Nonce, Violation> {
+        self.bot()?.reveal_dice(number, commitments, allowed)
+    }
+
+    fn show_roll(&mut self, roll: &FairRoll) {
+        if let Ok(bot) = self.bot() {
+            bot.show_roll(roll);
+        }
+    }
+
+    /// Stop what is left of the old process and start the command again,
+    /// with the start-up grace to confirm the history
+    fn resume(&mut self, resync: &Resync) -> Result<(), Violation> {
+        self.running = None;
+        let mut bot = SubprocessBot::launch(&self.command).map_err(|e| Violation::Failed(e.to_string()))?;
+        let resumed = bot.resume(resync, resync.clock.per_move + STARTUP_GRACE);
+        self.running = Some(bot);
+        resumed
+    }
+}
+
+/// External bots playing in interactive games, indexed by `AIType::External`
+static SEATED: Mutex<Vec<ExternalBot>> = Mutex::new(Vec::new());
+
+/// Add a bot running `command` to interactive play; returns its index
+pub fn seat(command: &str) -> usize {
+    let mut seated = SEATED.lock().unwrap();
+    seated.push(ExternalBot::new(command));
+    seated.len() - 1
+}
+
+/// Name of seated bot `idx`, as on the leaderboard
+pub fn seated_label(idx: usize) -> String {
+    SEATED.lock().unwrap()[idx].label()
+}
+
+/// Launch seated bot `idx` for a game in which it plays `seat`; returns the
+/// bot's side of the chat
+pub fn start_seated(idx: usize, seat: FastPlayer, clock: &Clock) -> Result<BotChat, Violation> {
+    let mut seated = SEATED.lock().unwrap();
+    let bot = &mut seated[idx];
+    bot.start_game(seat, clock, &fair_dice::to_hex(&fair_dice::fresh_nonce()))?;
+    bot.bot()?.take_chat().ok_or_else(|| Violation::Failed("chat already taken".to_string()))
+}
+
+/// The piece seated bot `idx` moves, which must be one of `moves`
+pub fn seated_move(idx: usize, game: &FastGameState, moves: &[u8], roll: u8, allowed: Duration) -> Result<u8, Violation> {
+    let piece = SEATED.lock().unwrap()[idx].request_move(game, moves, roll, allowed)?;
+    u8::try_from(piece).ok().filter(|piece| moves.contains(piece)).ok_or_else(|| Violation::illegal_move(game, piece, roll))
+}
+
+/// Stop seated bot `idx` after a game, telling it how the game ended unless
+/// a human quit
+pub fn end_seated(idx: usize, status: Option<GameStatus>) {
+    let mut seated = SEATED.lock().unwrap();
+    match status {
+        Some(status) => seated[idx].end_game(status, &status.describe()),
+        None => seated[idx].running = None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_messages_carry_any_reason_as_json() {
+        let reason = "unreadable reply 'a\\b \"c\"\nd'";
+        let message = json::parse(&end_message(GameStatus::Won(FastPlayer::Two), reason)).unwrap();
+        assert_eq!(message.get("reason").and_then(Json::as_str), Some(reason));
+        assert_eq!(message.get("winner").and_then(Json::as_f64), Some(2.0));
+    }
+}
@@ -0,0 +1,144 @@
+//! Player-swap symmetry. On every board both players' paths have the same
+//! shape: the same squares are shared, rosettes and safe squares fall at the
+//! same steps, and positions are stored as steps along a path. Swapping the
+//! two players' pieces, scores and turn therefore gives a position that plays
+//! exactly like the original with the roles reversed, and is worth the same to
+//! the side to move. Tables of positions (the reachability search, and any
+//! transposition table, opening book or solver) need only store positions with
+//! Player 1 to move, about half of them.
+
+use crate::board::BoardLayout;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::GameStateRepr;
+
+impl BoardLayout {
+    /// Whether step `i` of both paths is the same kind of square at every `i`:
+    /// shared by both or private to each, and equally a rosette or safe. Every
+    /// built-in board is.
+    pub fn is_mirror_symmetric(&self) -> bool {
+        let [one, two] = self.paths;
+        let has = |mask: u32, square: u8| (mask >> square) & 1 != 0;
+        one.len() == two.len()
+            && one.iter().zip(two).all(|(&a, &b)| {
+                let shared = |square| one.contains(&square) && two.contains(&square);
+                shared(a) == shared(b)
+                    && (!shared(a) || a == b)
+                    && has(self.rosettes, a) == has(self.rosettes, b)
+                    && has(self.safe, a) == has(self.safe, b)
+            })
+    }
+}
+
+/// `game` with the players swapped: Player 1's pieces become Player 2's at the
+/// same steps of their path, and the other side moves
+pub fn mirror(game: &FastGameState) -> FastGameState {
+    let pieces = |player| std::array::from_fn(|idx| game.get_piece_pos(player, idx as u8));
+    FastGameState::from_piece_positions(pieces(FastPlayer::Two), pieces(FastPlayer::One), game.current_player().opposite())
+        .expect("the mirror of a legal position is legal")
+}
+
+/// `game` seen from the side to move, as a position with Player 1 to move, and
+/// whether it had to be mirrored to get there
+pub fn canonical(game: &FastGameState) -> (FastGameState, bool) {
+    match game.current_player() {
+        FastPlayer::One => (*game, false),
+        FastPlayer::Two => (mirror(game), true),
+    }
+}
+
+/// A key shared by a position and its mirror image, with piece identities
+/// dropped as in `FastGameState::canonical_key`
+pub fn symmetric_key(game: &FastGameState) -> u64 {
+    canonical(game).0.canonical_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_helpers::{choose_smart_move_fast, evaluate_move_weighted, EvalWeights};
+    use crate::board::BoardVariant;
+    use crate::rng::GameRng;
+
+    /// Every position of a few Smart AI self-play games on the standard board
+    fn sample_positions() -> Vec<FastGameState> {
+        let mut positions = Vec::new();
+        for seed in 1..=5 {
+            let mut dice = GameRng::from_seed(seed);
+            let mut game = FastGameState::new();
+            while !game.status().is_over() {
+                positions.push(game);
+                let roll = dice.roll_dice(game.current_player());
+                let moves = game.generate_moves(roll);
+                if moves.is_empty() {
+                    game.pass_turn();
+                    continue;
+                }
+                let piece = choose_smart_move_fast(&game, game.current_player(), &moves, roll);
+                game.make_move(piece, roll).expect("generated moves are legal");
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn every_board_is_mirror_symmetric() {
+        for variant in BoardVariant::ALL {
+            assert!(variant.layout().is_mirror_symmetric(), "{}", variant.label());
+        }
+    }
+
+    #[test]
+    fn mirroring_twice_is_the_identity() {
+        for game in sample_positions() {
+            let mirrored = mirror(&game);
+            assert_ne!(mirrored.current_player(), game.current_player());
+            assert_eq!(mirror(&mirrored), game);
+        }
+    }
+
+    #[test]
+    fn a_position_and_its_mirror_share_a_key() {
+        for game in sample_positions() {
+            assert_eq!(symmetric_key(&game), symmetric_key(&mirror(&game)));
+            let (canonical, mirrored) = canonical(&game);
+            assert_eq!(canonical.current_player(), FastPlayer::One);
+            assert_eq!(mirrored, game.current_player() == FastPlayer::Two);
+        }
+    }
+
+    #[test]
+    fn moves_commute_with_mirroring() {
+        for game in sample_positions() {
+            let mirrored = mirror(&game);
+            for roll in 1..=4 {
+                let moves = game.generate_moves(roll);
+                assert_eq!(mirrored.generate_moves(roll), moves);
+                for piece in moves {
+                    let (mut after, mut mirrored_after) = (game, mirrored);
+                    let info = after.make_move(piece, roll).unwrap();
+                    let mirrored_info = mirrored_after.make_move(piece, roll).unwrap();
+                    assert_eq!(mirror(&after), mirrored_after);
+                    assert_eq!((info.captured_piece, info.extra_turn), (mirrored_info.captured_piece, mirrored_info.extra_turn));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evaluation_is_invariant_under_mirroring() {
+        for weights in [EvalWeights::DEFAULT, EvalWeights::COACH] {
+            for game in sample_positions() {
+                let player = game.current_player();
+                let mirrored = mirror(&game);
+                for roll in 1..=4 {
+                    for piece in game.generate_moves(roll) {
+                        assert_eq!(
+                            evaluate_move_weighted(&game, player, piece, roll, &weights),
+                            evaluate_move_weighted(&mirrored, player.opposite(), piece, roll, &weights),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crossterm::{
+    cursor::Show,
+    execute,
+    style::ResetColor,
+    terminal::disable_raw_mode,
+};
+
+/// Put the terminal back into a sane state: visible cursor, default colors, cooked mode
+pub fn restore_terminal() {
+    let _ = disable_raw_mode();
+    // Output to a file or a log collector is left free of escape codes
+    if io::stdout().is_terminal() {
+        let _ = execute!(io::stdout(), ResetColor, Show);
+    }
+    let _ = io::stdout().flush();
+}
+
+/// Called instead of exiting on the first interrupt, once set
+static ON_INTERRUPT: OnceLock<Box<dyn Fn() + Send + Sync>> = OnceLock::new();
+
+/// Restore the terminal when the program panics or is interrupted with
+/// ctrl-C or a termination signal
+pub fn install_restore_handlers() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+
+    let interrupted = AtomicBool::new(false);
+    let result = ctrlc::set_handler(move || {
+        if let Some(hook) = ON_INTERRUPT.get()
+            && !interrupted.swap(true, Ordering::Relaxed)
+        {
+            return hook();
+        }
+        restore_terminal();
+        if io::stdout().is_terminal() {
+            println!();
+        }
+        // Conventional exit status for SIGINT
+        std::process::exit(130);
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: could not install ctrl-C handler: {}", e);
+    }
+}
+
+/// Have the first interrupt call `hook`, for a server to wind down, rather
+/// than exit; a second interrupt still exits at once
+pub fn on_interrupt(hook: impl Fn() + Send + Sync + 'static) {
+    let _ = ON_INTERRUPT.set(Box::new(hook));
+}
@@ -0,0 +1,265 @@
+//! Importer for games written in tile coordinates, the board notation used by
+//! RoyalUr.net: lanes A (light's side), B (the shared middle lane) and C (dark's
+//! side), squares numbered 1-8 along each lane, so light's path is A4 A3 A2 A1,
+//! B1 … B8, A8 A7. Light is Player 1 and moves first.
+//!
+//! A game is a list of `[Tag "value"]` lines followed by moves, one token per
+//! turn in playing order:
+//!   `start-A4`  enter a piece        `A3-B1`  move a piece
+//!   `A7-end`    bear a piece off     `pass`   a zero roll or no legal move
+//! Move numbers (`12.`) and `#` comment lines are ignored. The roll is the
+//! distance moved. Pieces here always enter on the first square of their path,
+//! so a game that enters further along (the roll-based entry some rule sets
+//! use) cannot be imported.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::args::Flags;
+use crate::board;
+use crate::dice;
+use crate::error::UrError;
+use crate::game_record::{GameRecord, RecordedMove};
+use crate::rng::RngAlgorithm;
+use crate::optimized_game::{FastGameState, FastPlayer};
+use crate::state_repr::GameStateRepr;
+
+/// Options for `ur import --file FILE [--dir DIR]`
+pub struct ImportOptions {
+    pub file: PathBuf,
+    /// Game record directory the import is written to
+    pub dir: PathBuf,
+}
+
+impl ImportOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let flags = Flags::parse(args, &["--file", "--dir"], &[])?;
+        Ok(ImportOptions {
+            file: PathBuf::from(flags.get_str("--file").ok_or("import needs --file FILE")?),
+            dir: PathBuf::from(flags.get_str("--dir").unwrap_or("games")),
+        })
+    }
+}
+
+/// Convert a tile-notation game into a game record in `options.dir`
+pub fn run_import(options: &ImportOptions) -> Result<(), UrError> {
+    let record = parse_game(&fs::read_to_string(&options.file)?)?;
+    let path = record.write_to_dir(&options.dir)?;
+
+    let mut end = FastGameState::new();
+    for &(position, mv) in &record.positions()? {
+        end = position;
+        end.make_move(mv.piece, mv.roll).expect("recorded moves are checked");
+    }
+    println!("Imported {} ({} vs {}, {} moves) to {}",
+             options.file.display(), record.players[0], record.players[1], record.moves.len(), path.display());
+    println!("Score: {} {} - {} {}", record.players[0], end.get_score(FastPlayer::One),
+             end.get_score(FastPlayer::Two), record.players[1]);
+    println!("Analyze it with: ur analyze-db --dir {}", options.dir.display());
+    Ok(())
+}
+
+/// Square on the current board for a tile such as "B5"
+pub fn tile_to_square(tile: &str) -> Option<u8> {
+    let mut chars = tile.chars();
+    let row = match chars.next()?.to_ascii_uppercase() {
+        'A' => 0,
+        'B' => 1,
+        'C' => 2,
+        _ => return None,
+    };
+    let number: usize = chars.as_str().parse().ok()?;
+    let col = number.checked_sub(1)?;
+    board::layout().coords.iter().position(|&coord| coord == (row, col)).map(|square| square as u8)
+}
+
+pub fn square_to_tile(square: u8) -> String {
+    let (row, col) = board::layout().coords[square as usize];
+    format!("{}{}", ['A', 'B', 'C'][row], col + 1)
+}
+
+/// Path index of `square` for `player`, if it is on their path
+fn path_index(player: FastPlayer, square: u8) -> Option<u8> {
+    (0..FastGameState::path_len()).find(|&idx| FastGameState::path_to_global(player, idx) == square)
+}
+
+/// Parse a tile-notation game into a game record on the current board and dice
+pub fn parse_game(text: &str) -> Result<GameRecord, UrError> {
+    let mut names = [String::from("light"), String::from("dark")];
+    let mut game = FastGameState::new();
+    let mut moves = Vec::new();
+
+    for (idx, raw) in text.lines().enumerate() {
+        let err = |message: String| UrError::ParseError { line: idx + 1, message };
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(tag) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            let (key, value) = tag.split_once(' ').ok_or_else(|| err(format!("malformed tag '{}'", line)))?;
+            let value = value.trim().trim_matches('"');
+            // Record labels are single words
+            let label: String = value.split_whitespace().collect::<Vec<_>>().join("_");
+            match key {
+                "Light" if !label.is_empty() => names[0] = label,
+                "Dark" if !label.is_empty() => names[1] = label,
+                _ => {}
+            }
+            continue;
+        }
+
+        for token in line.split_whitespace() {
+            if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            if token.eq_ignore_ascii_case("pass") {
+                game.pass_turn();
+                continue;
+            }
+            let mv = parse_move(&game, token).map_err(err)?;
+            if let Err(e) = game.play(mv.piece, mv.roll) {
+                return Err(err(format!("'{}': {}", token, e)));
+            }
+            moves.push(mv);
+        }
+    }
+
+    Ok(GameRecord {
+        players: names,
+        board: board::variant(),
+        dice: dice::variant(),
+        start: FastGameState::new(),
+        moves,
+        result: Some(game.status()).filter(|status| status.is_over()),
+        unassisted: false,
+        rng: RngAlgorithm::default(),
+        seed: None,
+        hashes: Vec::new(),
+    })
+}
+
+/// Work out which piece a `FROM-TO` token moves and what it rolled
+fn parse_move(game: &FastGameState, token: &str) -> Result<RecordedMove, String> {
+    let player = game.current_player();
+    let (from, to) = token.split_once('-').ok_or_else(|| format!("'{}' is not a move", token))?;
+    let tile_index = |tile: &str| {
+        let square = tile_to_square(tile).ok_or_else(|| format!("'{}' is not a tile", tile))?;
+        path_index(player, square).ok_or_else(|| format!("{} is not on {}'s path", tile, player.name()))
+    };
+
+    let (piece, roll) = if from.eq_ignore_ascii_case("start") {
+        let to_idx = tile_index(to)?;
+        if to_idx != 0 {
+            return Err(format!("'{}' enters {} squares along the path; pieces here always enter on {}",
+                               token, to_idx + 1, square_to_tile(FastGameState::path_to_global(player, 0))));
+        }
+        let piece = (0..7).find(|&p| game.get_piece_pos(player, p) == 0)
+            .ok_or_else(|| format!("'{}': {} has no piece waiting to enter", token, player.name()))?;
+        (piece, 1)
+    } else {
+        let from_idx = tile_index(from)?;
+        let piece = (0..7).find(|&p| game.get_piece_pos(player, p) == from_idx + 1)
+            .ok_or_else(|| format!("'{}': {} has no piece on {}", token, player.name(), from))?;
+        let to_idx = if to.eq_ignore_ascii_case("end") { FastGameState::path_len() } else { tile_index(to)? };
+        if to_idx <= from_idx {
+            return Err(format!("'{}' does not move forward", token));
+        }
+        (piece, to_idx - from_idx)
+    };
+
+    if dice::model().probability(roll) == 0.0 {
+        return Err(format!("'{}' needs a roll of {}, which the dice cannot make", token, roll));
+    }
+    if !game.generate_move_list(roll).contains(&piece) {
+        return Err(format!("'{}' is not a legal move", token));
+    }
+    Ok(RecordedMove { player, roll, piece })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_follow_each_players_path() {
+        let light: Vec<String> = (0..FastGameState::path_len())
+            .map(|idx| square_to_tile(FastGameState::path_to_global(FastPlayer::One, idx)))
+            .collect();
+        assert_eq!(light, ["A4", "A3", "A2", "A1", "B1", "B2", "B3", "B4", "B5", "B6", "B7", "B8", "A8", "A7"]);
+
+        let dark: Vec<String> = (0..FastGameState::path_len())
+            .map(|idx| square_to_tile(FastGameState::path_to_global(FastPlayer::Two, idx)))
+            .collect();
+        assert_eq!(dark, ["C4", "C3", "C2", "C1", "B1", "B2", "B3", "B4", "B5", "B6", "B7", "B8", "C8", "C7"]);
+    }
+
+    #[test]
+    fn tiles_round_trip() {
+        for square in 0..board::layout().coords.len() as u8 {
+            assert_eq!(tile_to_square(&square_to_tile(square)), Some(square));
+        }
+        assert_eq!(tile_to_square("b4"), tile_to_square("B4"));
+        // The gap between the two blocks of the standard board
+        assert_eq!(tile_to_square("A5"), None);
+        assert_eq!(tile_to_square("D1"), None);
+        assert_eq!(tile_to_square("B0"), None);
+    }
+
+    #[test]
+    fn imports_moves_with_rolls_and_captures() {
+        let text = "\
+[Light \"Ann Lee\"]
+[Dark \"bot\"]
+1. start-A4 start-C4
+2. A4-A1 C4-C2
+3. A1-B2 pass
+4. B2-B3 C2-B1
+5. B3-B5 B1-B5
+";
+        let record = parse_game(text).unwrap();
+        assert_eq!(record.players, ["Ann_Lee".to_string(), "bot".to_string()]);
+        let rolls: Vec<u8> = record.moves.iter().map(|mv| mv.roll).collect();
+        assert_eq!(rolls, [1, 1, 3, 2, 2, 1, 2, 2, 4]);
+
+        let positions = record.positions().unwrap();
+        let (before_last, last) = positions.last().copied().unwrap();
+        assert_eq!(last.player, FastPlayer::Two);
+        let mut end = before_last;
+        let info = end.make_move(last.piece, last.roll).unwrap();
+        assert_eq!(info.captured_piece, Some(0), "dark lands on light's piece on B5");
+    }
+
+    #[test]
+    fn rosette_landing_keeps_the_turn() {
+        // B4 is the central rosette: light moves again without a pass in between
+        let record = parse_game("start-A4 pass A4-A1 pass A1-B4 B4-B5").unwrap();
+        assert!(record.moves.iter().all(|mv| mv.player == FastPlayer::One));
+        assert!(parse_game("start-A4 pass A4-A1 pass A1-B3 B3-B5").is_err());
+    }
+
+    #[test]
+    fn bears_off_with_the_exact_roll() {
+        // A7, the last square, is a rosette, so light bears off straight away
+        let record = parse_game("start-A4 pass A4-B1 pass B1-B5 pass B5-A8 pass A8-A7 A7-end").unwrap();
+        let last = record.moves.last().copied().unwrap();
+        assert_eq!((last.player, last.roll), (FastPlayer::One, 1));
+        assert!(parse_game("start-A4 pass A4-B1 pass B1-B5 pass B5-A8 pass A8-end").is_ok());
+    }
+
+    #[test]
+    fn rejects_what_this_engine_cannot_play() {
+        let entry = parse_game("start-A3").unwrap_err().to_string();
+        assert!(entry.contains("always enter on A4"), "{}", entry);
+
+        let too_far = parse_game("start-A4 pass A4-B4").unwrap_err().to_string();
+        assert!(too_far.contains("roll of 7"), "{}", too_far);
+
+        let wrong_side = parse_game("start-C4").unwrap_err().to_string();
+        assert!(wrong_side.contains("not on Player 1's path"), "{}", wrong_side);
+
+        let missing = parse_game("start-A4 B1-B2").unwrap_err().to_string();
+        assert!(missing.starts_with("line 1:"), "{}", missing);
+        assert!(missing.contains("no piece on B1"), "{}", missing);
+    }
+}
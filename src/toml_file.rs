@@ -0,0 +1,52 @@
+//! The TOML files the game reads, layout files and the lobby's access file,
+//! with the line each value is on, so a problem with a value is reported
+//! at its line as the line-based formats' are.
+
+use toml::Spanned;
+use toml::de::{DeTable, DeValue};
+
+use crate::error::UrError;
+
+/// A parsed TOML file
+pub struct TomlFile<'t> {
+    text: &'t str,
+    spans: Spanned<DeTable<'t>>,
+    pub table: toml::Table,
+}
+
+impl<'t> TomlFile<'t> {
+    pub fn parse(text: &'t str) -> Result<Self, UrError> {
+        let error = |e: toml::de::Error| UrError::ParseError {
+            line: e.span().map_or(0, |span| line_at(text, span.start)),
+            message: e.message().trim_end().to_string(),
+        };
+        let spans = DeTable::parse(text).map_err(error)?;
+        let table = text.parse().map_err(error)?;
+        Ok(TomlFile { text, spans, table })
+    }
+
+    /// The line the value at `path` is on: keys joined by dots, with a
+    /// number for an entry of a list, as in `client.2.name`; 0 if the file
+    /// does not have it
+    pub fn line(&self, path: &str) -> usize {
+        let mut steps = path.split('.');
+        let Some(first) = steps.next().and_then(|key| self.spans.get_ref().get(key)) else {
+            return 0;
+        };
+        let value = steps.try_fold(first, |value, step| match step.parse::<usize>() {
+            Ok(idx) => value.get_ref().get(idx),
+            Err(_) => value.get_ref().get(step),
+        });
+        value.map_or(0, |value: &Spanned<DeValue>| line_at(self.text, value.span().start))
+    }
+
+    /// A problem with the value at `path`, reported at its line
+    pub fn error(&self, path: &str, message: impl Into<String>) -> UrError {
+        UrError::ParseError { line: self.line(path), message: message.into() }
+    }
+}
+
+/// The line, counted from 1, that byte `offset` of `text` is on
+pub fn line_at(text: &str, offset: usize) -> usize {
+    text.as_bytes()[..offset.min(text.len())].iter().filter(|&&b| b == b'\n').count() + 1
+}
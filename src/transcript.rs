@@ -0,0 +1,122 @@
+//! `--transcript FILE`: a plain-text copy of what the interactive game shows
+//! (boards, rolls, moves, prompts and what was typed at them, results), to
+//! review a game later or attach to a bug report. Output goes through
+//! `console()`, usually by way of `outln!` and `out!`, instead of straight to
+//! stdout: the terminal gets it unchanged and the transcript gets it with the
+//! colors and cursor movement stripped. Output that is redrawn in place, like
+//! the thinking spinner and the countdown in front of a prompt, stays on
+//! stdout only, and so out of the transcript.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Where the transcript is in a terminal escape sequence, which can be split
+/// across writes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Escape {
+    None,
+    /// ESC seen
+    Started,
+    /// Control sequence (ESC [), ended by a byte in '@'..='~'
+    Control,
+    /// Operating system command (ESC ]), ended by BEL or ESC \
+    System,
+}
+
+struct Transcript {
+    file: File,
+    escape: Escape,
+}
+
+impl Transcript {
+    /// Write `bytes` without escape sequences or carriage returns
+    fn write_plain(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut plain = Vec::with_capacity(bytes.len());
+        for &byte in bytes {
+            self.escape = match (self.escape, byte) {
+                (Escape::None, 0x1b) => Escape::Started,
+                (Escape::None, b'\r') => Escape::None,
+                (Escape::None, _) => {
+                    plain.push(byte);
+                    Escape::None
+                }
+                (Escape::Started, b'[') => Escape::Control,
+                (Escape::Started, b']') => Escape::System,
+                (Escape::Started, _) => Escape::None,
+                (Escape::Control, b'@'..=b'~') => Escape::None,
+                (Escape::Control, _) => Escape::Control,
+                (Escape::System, 0x07) => Escape::None,
+                (Escape::System, 0x1b) => Escape::Started,
+                (Escape::System, _) => Escape::System,
+            };
+        }
+        self.file.write_all(&plain)
+    }
+}
+
+/// The transcript being kept, if any
+static TRANSCRIPT: Mutex<Option<Transcript>> = Mutex::new(None);
+
+fn transcript() -> MutexGuard<'static, Option<Transcript>> {
+    TRANSCRIPT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start copying output to a new transcript at `path`
+pub fn start(path: &Path) -> io::Result<()> {
+    let file = File::create(path)?;
+    *transcript() = Some(Transcript { file, escape: Escape::None });
+    Ok(())
+}
+
+/// Add `text` to the transcript, if one is being kept, without showing it.
+/// A transcript that cannot be written is given up with a warning.
+pub fn record(text: &str) {
+    copy(text.as_bytes());
+}
+
+fn copy(bytes: &[u8]) {
+    let mut transcript = transcript();
+    if let Some(Err(e)) = transcript.as_mut().map(|t| t.write_plain(bytes)) {
+        *transcript = None;
+        eprintln!("⚠️  Could not write the transcript ({}); it stops here", e);
+    }
+}
+
+/// Standard output, copied into the transcript
+pub struct Console(io::Stdout);
+
+pub fn console() -> Console {
+    Console(io::stdout())
+}
+
+impl Write for Console {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.0.write(buf)?;
+        copy(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// `println!` through `console()`
+macro_rules! outln {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        let _ = writeln!($crate::transcript::console(), $($arg)*);
+    }};
+}
+
+/// `print!` through `console()`
+macro_rules! out {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        let _ = write!($crate::transcript::console(), $($arg)*);
+    }};
+}
+
+pub(crate) use {out, outln};
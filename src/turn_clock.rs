@@ -0,0 +1,62 @@
+//! Move clocks for human players. With `--move-time SECS` each human turn, from
+//! the roll prompt to the chosen move, has to be finished in time; when the clock
+//! runs out the Smart AI plays the turn instead, or with `--on-timeout forfeit`
+//! the game is lost.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// What happens to a human who runs out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeoutAction {
+    /// The Smart AI rolls and moves for them
+    #[default]
+    AutoMove,
+    /// The opponent wins the game
+    Forfeit,
+}
+
+impl FromStr for TimeoutAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "move" => Ok(TimeoutAction::AutoMove),
+            "forfeit" => Ok(TimeoutAction::Forfeit),
+            _ => Err(format!("unknown timeout action '{}' (expected move or forfeit)", s)),
+        }
+    }
+}
+
+/// Time allowed for each human turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurnClock {
+    pub per_move: Duration,
+    pub on_timeout: TimeoutAction,
+}
+
+impl TurnClock {
+    /// Clock for `--move-time SECS`, which must be a positive number of seconds
+    pub fn from_seconds(seconds: f64, on_timeout: TimeoutAction) -> Result<Self, String> {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            return Err(format!("--move-time must be a positive number of seconds, not {}", seconds));
+        }
+        Ok(TurnClock { per_move: Duration::from_secs_f64(seconds), on_timeout })
+    }
+
+    /// When a turn starting now runs out of time
+    pub fn deadline(&self) -> Instant {
+        Instant::now() + self.per_move
+    }
+}
+
+impl fmt::Display for TurnClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.on_timeout {
+            TimeoutAction::AutoMove => "the Smart AI moves for you",
+            TimeoutAction::Forfeit => "you forfeit the game",
+        };
+        write!(f, "{}s per turn; when it runs out {}", self.per_move.as_secs_f64(), action)
+    }
+}
@@ -0,0 +1,66 @@
+//! Starting worker threads without panicking when the system will not give us
+//! any more (containers with a low thread limit). Work that cannot get a thread
+//! of its own runs on the calling thread instead, so a search or study only
+//! gets slower, and a worker that panics costs its own results, not the run.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Scope, ScopedJoinHandle};
+
+/// Set once the fallback has been announced, so a long run warns only once
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+fn warn_once(what: &str, error: &io::Error) {
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!("⚠️  Could not start a thread for {} ({}); carrying on with fewer threads", what, error);
+    }
+}
+
+/// Start `work` on a new thread in `scope`. If no thread can be started the
+/// warning is printed and `work` is handed back to be run some other way.
+pub fn spawn_scoped<'scope, 'env, T, F>(scope: &'scope Scope<'scope, 'env>, what: &str, work: F) -> Result<ScopedJoinHandle<'scope, T>, F>
+where
+    T: Send + 'scope,
+    F: FnOnce() -> T + Send + 'scope,
+{
+    // The thread takes the work out of the slot; if it never starts, the work is still there
+    let slot = Arc::new(Mutex::new(Some(work)));
+    let theirs = Arc::clone(&slot);
+    let started = thread::Builder::new().spawn_scoped(scope, move || {
+        let work = theirs.lock().unwrap_or_else(|e| e.into_inner()).take().expect("work is taken once");
+        work()
+    });
+    started.map_err(|e| {
+        warn_once(what, &e);
+        slot.lock().unwrap_or_else(|e| e.into_inner()).take().expect("a thread that never started left its work")
+    })
+}
+
+/// The result of a worker, or None with a warning if it panicked
+pub fn join<T>(handle: ScopedJoinHandle<'_, T>, what: &str) -> Option<T> {
+    handle.join().map_err(|_| eprintln!("⚠️  A {} thread failed; its results are left out", what)).ok()
+}
+
+/// Run every job, each on its own thread where one can be started and on the
+/// calling thread where not, and collect the results of those that finish
+pub fn run_parallel<T, F>(what: &str, jobs: impl IntoIterator<Item = F>) -> Vec<T>
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    thread::scope(|scope| {
+        let mut handles = Vec::new();
+        let mut stranded = Vec::new();
+        for job in jobs {
+            match spawn_scoped(scope, what, job) {
+                Ok(handle) => handles.push(handle),
+                Err(job) => stranded.push(job),
+            }
+        }
+        // Jobs without a thread run here while the others carry on
+        let mut results: Vec<T> = stranded.into_iter().map(|job| job()).collect();
+        results.extend(handles.into_iter().filter_map(|handle| join(handle, what)));
+        results
+    })
+}